@@ -0,0 +1,308 @@
+use serde::{Deserialize, Serialize};
+
+use crate::colors::*;
+
+use crate::combat::strike;
+use crate::crafting::craft;
+use crate::dialogue::{choose, trade, upgrade};
+use crate::food::eat;
+
+use crate::furniture::{dip, drink_from_fountain};
+use crate::game::{is_blocked, level_up, next_level, Game, Terrain, PLAYER};
+use crate::hireling::give;
+use crate::item::{diggable, drop_item, pick_item, use_item};
+use crate::lang::tr;
+use crate::object::{find_handle, handle_of, player_move_attack, Object};
+use crate::overworld::{roam, travelling, TRAVEL_TURNS};
+use crate::perk::Perk;
+use crate::piety::{sacrifice, Offering};
+use crate::push::pushable_at;
+use crate::render::Context;
+use crate::status::action_time;
+
+const REGENERATION_HEAL: i32 = 3;
+// Time of a turn, in the hundredths the actions are measured in
+pub const TURN: i32 = 100;
+const EAT_TURNS: i32 = 3;
+const DIG_TURNS: i32 = 5;
+
+// Everything the player can do to change the game,
+// decided before it's performed so that it can be recorded and replayed
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum Action {
+    // Move by the offset, attacking whatever is in the way
+    Move(i32, i32),
+    // Hit the enemy on the tile, farther than next to the player with a polearm
+    Strike(i32, i32),
+    Rest,
+    // Let the turn pass without doing anything, when the player waits too long in the ultra mode
+    Idle,
+    PickUp,
+    // Start dragging the heavy object next to the player, or let go of it
+    Drag,
+    // Start or stop sneaking
+    Sneak,
+    // Eat the corpse the player stands on
+    Eat,
+    // Use the inventory item, aimed at the tile if the item needs it
+    UseItem {
+        index: usize,
+        target: Option<(i32, i32)>,
+    },
+    Drop(usize),
+    // Pay the npc smith to improve the inventory item
+    Upgrade {
+        npc: usize,
+        item: usize,
+    },
+    // Drink from the fountain the player stands on
+    Drink,
+    // Dip the inventory item into the fountain the player stands on
+    Dip(usize),
+    // Give something to the deity of the altar the player stands on
+    Sacrifice(Offering),
+    // Hand the inventory item to the hireling to wear
+    Give {
+        hireling: usize,
+        item: usize,
+    },
+    // Mix the two inventory items with the alchemy kit
+    Craft {
+        first: usize,
+        second: usize,
+    },
+    Descend,
+    // Give the answer of the npc's dialogue that does something
+    Choose {
+        npc: usize,
+        node: usize,
+        choice: usize,
+    },
+    // Swap an inventory item for one of the npc's goods
+    Trade {
+        npc: usize,
+        good: usize,
+        give: usize,
+    },
+    // Raise the stat chosen on leveling up, or take one of the perks offered after the stats
+    LevelUp(usize),
+}
+
+// Effect that happens some turns after the action that started it
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum Delayed {
+    // Teleport the player back to the anchor, or to where they arrived on the level
+    Return,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct DelayedAction {
+    pub turns: u32,
+    pub effect: Delayed,
+    // Whether getting hurt cancels it
+    pub interruptible: bool,
+}
+
+impl Action {
+    // How many turns the monsters get after the player does it, the quick actions
+    // leave the rest of the turn to be used by the next ones
+    pub fn turns(self, game: &mut Game, objects: &[Object]) -> u32 {
+        let time = match self {
+            Action::LevelUp(_) => 0,
+            // The weapon decides how long an attack takes
+            Action::Move(dx, dy) if attacks(dx, dy, objects) => objects[PLAYER].attack_delay(game),
+            Action::Strike(_, _) => objects[PLAYER].attack_delay(game),
+            // Digging through the rock is slow work
+            Action::Move(dx, dy) if digs(dx, dy, game, objects) => DIG_TURNS * TURN,
+            // Every step on the surface covers a lot of ground
+            Action::Move(_, _) if travelling(game, objects) => TRAVEL_TURNS * TURN,
+            // Dragging or pushing something heavy, sneaking or climbing over rubble
+            // slows the player down
+            Action::Move(dx, dy)
+                if game.dragging.is_some()
+                    || game.sneaking
+                    || onto_rubble(dx, dy, game, objects)
+                    || pushable_at(objects[PLAYER].x + dx, objects[PLAYER].y + dy, objects)
+                        .is_some() =>
+            {
+                2 * TURN
+            }
+            // Eating a whole corpse takes a while
+            Action::Eat => EAT_TURNS * TURN,
+            _ => TURN,
+        };
+        game.time += action_time(&objects[PLAYER], time);
+        let turns = game.time / TURN;
+        game.time %= TURN;
+        turns as u32
+    }
+}
+
+// Whether moving that way attacks someone rather than walking
+fn attacks(dx: i32, dy: i32, objects: &[Object]) -> bool {
+    let player = &objects[PLAYER];
+    let (x, y) = (player.x + dx, player.y + dy);
+    objects.iter().any(|object| {
+        object.fighter.is_some() && object.pos() == (x, y) && object.faction != player.faction
+    })
+}
+
+// Whether moving that way digs into the rock
+fn digs(dx: i32, dy: i32, game: &mut Game, objects: &[Object]) -> bool {
+    let player = &objects[PLAYER];
+    diggable(player.x + dx, player.y + dy, game) && player.digs(game)
+}
+
+// Whether moving that way climbs onto rubble
+fn onto_rubble(dx: i32, dy: i32, game: &Game, objects: &[Object]) -> bool {
+    let (x, y) = (objects[PLAYER].x + dx, objects[PLAYER].y + dy);
+    game.map
+        .get(x, y)
+        .is_some_and(|tile| tile.terrain == Terrain::Rubble)
+}
+
+pub fn perform_action(
+    action: Action,
+    ctx: &mut Context,
+    game: &mut Game,
+    objects: &mut Vec<Object>,
+) {
+    match action {
+        Action::Move(dx, dy) => {
+            player_move_attack(dx, dy, game, objects);
+            roam(game, objects);
+        }
+        Action::Strike(x, y) => strike(x, y, game, objects),
+        Action::Rest => {
+            game.messages.add(tr("action.you_rest", &[]), VIOLET);
+            let amount = if objects[PLAYER].perks.contains(&Perk::Regeneration) {
+                REGENERATION_HEAL
+            } else {
+                1
+            };
+            objects[PLAYER].heal(amount, game);
+        }
+        Action::Idle => {}
+        Action::PickUp => {
+            // Look for an item under the player
+            let item = objects
+                .iter()
+                .position(|o| o.pos() == objects[PLAYER].pos() && o.item.is_some());
+            if let Some(id) = item {
+                pick_item(id, game, objects);
+            }
+        }
+        Action::Drag => toggle_drag(game, objects),
+        Action::Eat => eat(game, objects),
+        Action::Sneak => {
+            game.sneaking = !game.sneaking;
+            if game.sneaking {
+                game.messages
+                    .add(tr("action.you_start_sneaking", &[]), LIGHT_GREY);
+            } else {
+                game.messages
+                    .add(tr("action.you_stop_sneaking", &[]), LIGHT_GREY);
+            }
+        }
+        Action::UseItem { index, target } => use_item(index, target, ctx, game, objects),
+        Action::Drop(index) => drop_item(index, game, objects),
+        Action::Craft { first, second } => craft(first, second, game),
+        Action::Sacrifice(offering) => sacrifice(offering, game, objects),
+        Action::Drink => drink_from_fountain(game, objects),
+        Action::Dip(index) => dip(index, game, objects),
+        Action::Descend => {
+            // Go down stairs or into the dungeon entrance, if the player is on them
+            let here = objects[PLAYER].pos();
+            let on_stairs = objects
+                .iter()
+                .any(|object| object.pos() == here && object.name == "stairs");
+            let entrance = objects
+                .iter()
+                .filter(|object| object.pos() == here)
+                .find_map(|object| object.entrance);
+            if let Some(branch) = entrance {
+                game.branch = branch;
+            }
+            if on_stairs || entrance.is_some() {
+                next_level(ctx, game, objects);
+            }
+        }
+        Action::Choose { npc, node, choice } => choose(npc, node, choice, game, objects),
+        Action::Trade { npc, good, give } => trade(npc, good, give, game, objects),
+        Action::Upgrade { npc, item } => upgrade(npc, item, game, objects),
+        Action::Give { hireling, item } => give(hireling, item, game, objects),
+        Action::LevelUp(stat) => level_up(game, objects, stat),
+    }
+}
+
+fn toggle_drag(game: &mut Game, objects: &mut [Object]) {
+    if let Some(id) = game
+        .dragging
+        .take()
+        .and_then(|handle| find_handle(handle, objects))
+    {
+        game.messages.add(
+            tr("action.you_let_go_of", &[("object", &objects[id].name)]),
+            LIGHT_GREY,
+        );
+        return;
+    }
+
+    let heavy = objects
+        .iter()
+        .position(|object| object.heavy && object.distance_to(&objects[PLAYER]) < 2.0);
+    match heavy {
+        Some(id) => {
+            game.messages.add(
+                tr(
+                    "action.you_start_dragging",
+                    &[("object", &objects[id].name)],
+                ),
+                LIGHT_GREY,
+            );
+            game.dragging = Some(handle_of(id, game, objects));
+        }
+        None => game
+            .messages
+            .add(tr("action.there_is_nothing_to_drag_here", &[]), LIGHT_GREY),
+    }
+}
+
+// Count down the delayed actions, performing the ones whose turn came
+pub fn tick_delayed(game: &mut Game, objects: &mut [Object]) {
+    let mut ready = vec![];
+    game.delayed.retain_mut(|delayed| {
+        delayed.turns = delayed.turns.saturating_sub(1);
+        if delayed.turns == 0 {
+            ready.push(delayed.effect);
+        }
+        delayed.turns > 0
+    });
+
+    for effect in ready {
+        match effect {
+            Delayed::Return => {
+                let (x, y) = game.anchor.unwrap_or(game.entrance);
+                if objects[PLAYER].pos() != (x, y) && is_blocked(x, y, &game.map, objects) {
+                    game.messages
+                        .add(tr("action.something_blocks_the_way_back", &[]), LIGHT_GREY);
+                } else {
+                    game.messages
+                        .add(tr("action.you_are_pulled_back", &[]), LIGHT_BLUE);
+                    objects[PLAYER].set_pos(x, y);
+                    // Whatever was dragged stays where it was
+                    game.dragging = None;
+                }
+            }
+        }
+    }
+}
+
+pub fn interrupt_delayed(game: &mut Game) {
+    let before = game.delayed.len();
+    game.delayed.retain(|delayed| !delayed.interruptible);
+    if game.delayed.len() < before {
+        game.messages
+            .add(tr("action.your_concentration_breaks", &[]), LIGHT_RED);
+    }
+}
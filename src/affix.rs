@@ -0,0 +1,190 @@
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::colors::*;
+
+use crate::combat::Brand;
+use crate::object::Object;
+use crate::rng::GameRng;
+use crate::status::{Effect, Status};
+
+// Chance in percent of equipment found on the level being magical, and rare,
+// the deeper the more likely
+const MAGIC_CHANCE: i32 = 15;
+const MAGIC_CHANCE_PER_LEVEL: i32 = 2;
+const RARE_CHANCE: i32 = 3;
+const RARE_CHANCE_PER_LEVEL: i32 = 1;
+
+// How special the item is, magic items have one affix and rare ones both
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Rarity {
+    #[default]
+    Common,
+    Magic,
+    Rare,
+}
+
+impl Rarity {
+    pub fn color(self) -> Option<Color> {
+        match self {
+            Rarity::Common => None,
+            Rarity::Magic => Some(LIGHT_BLUE),
+            Rarity::Rare => Some(LIGHT_ORANGE),
+        }
+    }
+}
+
+// Word added to the name of the equipment, with what it gives
+pub struct Affix {
+    pub name: &'static str,
+    pub power: i32,
+    pub defense: i32,
+    pub max_hp: i32,
+    // Damage that no armor stops
+    pub fire_damage: i32,
+    pub on_hit: Option<Effect>,
+    pub brand: Option<Brand>,
+}
+
+const NO_AFFIX: Affix = Affix {
+    name: "",
+    power: 0,
+    defense: 0,
+    max_hp: 0,
+    fire_damage: 0,
+    on_hit: None,
+    brand: None,
+};
+
+pub const PREFIXES: &[Affix] = &[
+    Affix {
+        name: "flaming",
+        fire_damage: 3,
+        ..NO_AFFIX
+    },
+    Affix {
+        name: "sharp",
+        power: 2,
+        ..NO_AFFIX
+    },
+    Affix {
+        name: "sturdy",
+        defense: 2,
+        ..NO_AFFIX
+    },
+    Affix {
+        name: "numbing",
+        on_hit: Some(Effect {
+            status: Status::Slow,
+            turns: 3,
+        }),
+        ..NO_AFFIX
+    },
+    Affix {
+        name: "venomous",
+        brand: Some(Brand::Venomous(5)),
+        ..NO_AFFIX
+    },
+    Affix {
+        name: "vampiric",
+        brand: Some(Brand::Vampiric(25)),
+        ..NO_AFFIX
+    },
+];
+
+pub const SUFFIXES: &[Affix] = &[
+    Affix {
+        name: "of the bear",
+        max_hp: 10,
+        ..NO_AFFIX
+    },
+    Affix {
+        name: "of the wolf",
+        power: 1,
+        max_hp: 4,
+        ..NO_AFFIX
+    },
+    Affix {
+        name: "of the turtle",
+        defense: 1,
+        max_hp: 4,
+        ..NO_AFFIX
+    },
+    Affix {
+        name: "of embers",
+        fire_damage: 2,
+        ..NO_AFFIX
+    },
+];
+
+// Maybe make the equipment magical or rare, the deeper the level the likelier
+pub fn enchant(item: &mut Object, level: u32, rng: &mut GameRng) {
+    if item.equipment.is_none() {
+        return;
+    }
+    let level = level as i32;
+    let roll = rng.gen_range(0, 100);
+    if roll < RARE_CHANCE + level * RARE_CHANCE_PER_LEVEL {
+        let prefix = &PREFIXES[rng.gen_range(0, PREFIXES.len())];
+        let suffix = &SUFFIXES[rng.gen_range(0, SUFFIXES.len())];
+        add_affix(item, prefix, true);
+        add_affix(item, suffix, false);
+        item.rarity = Rarity::Rare;
+    } else if roll < MAGIC_CHANCE + level * MAGIC_CHANCE_PER_LEVEL {
+        if rng.gen_range(0, 2) == 0 {
+            add_affix(item, &PREFIXES[rng.gen_range(0, PREFIXES.len())], true);
+        } else {
+            add_affix(item, &SUFFIXES[rng.gen_range(0, SUFFIXES.len())], false);
+        }
+        item.rarity = Rarity::Magic;
+    } else {
+        return;
+    }
+    if let Some(color) = item.rarity.color() {
+        item.color = color;
+    }
+}
+
+// Put the affix of that name on the equipment, making it more special,
+// returns whether it could be put
+pub fn imbue(item: &mut Object, name: &str) -> bool {
+    let prefix = PREFIXES.iter().find(|affix| affix.name == name);
+    let suffix = SUFFIXES.iter().find(|affix| affix.name == name);
+    let (affix, is_prefix) = match (prefix, suffix) {
+        (Some(affix), _) => (affix, true),
+        (None, Some(affix)) => (affix, false),
+        (None, None) => return false,
+    };
+    if item.equipment.is_none() || item.name.contains(name) {
+        return false;
+    }
+    add_affix(item, affix, is_prefix);
+    item.rarity = match item.rarity {
+        Rarity::Common => Rarity::Magic,
+        _ => Rarity::Rare,
+    };
+    if let Some(color) = item.rarity.color() {
+        item.color = color;
+    }
+    true
+}
+
+fn add_affix(item: &mut Object, affix: &Affix, prefix: bool) {
+    item.name = if prefix {
+        format!("{} {}", affix.name, item.name)
+    } else {
+        format!("{} {}", item.name, affix.name)
+    };
+    if let Some(equipment) = item.equipment.as_mut() {
+        equipment.power_bonus += affix.power;
+        equipment.defense_bonus += affix.defense;
+        equipment.max_hp_bonus += affix.max_hp;
+        equipment.fire_damage += affix.fire_damage;
+        if affix.on_hit.is_some() {
+            equipment.on_hit = affix.on_hit;
+        }
+        if affix.brand.is_some() {
+            equipment.brand = affix.brand;
+        }
+    }
+}
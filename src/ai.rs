@@ -1,70 +1,383 @@
 use serde::{Deserialize, Serialize};
 
-use tcod::colors::*;
+use crate::colors::*;
 
 use rand::Rng;
 
-use crate::game::{mut_two, Game, PLAYER};
-use crate::object::{move_by, move_towards, Object};
-use crate::render::Tcod;
+use crate::action::TURN;
+use crate::behavior::run_behavior;
+use crate::combat::in_reach;
+use crate::dijkstra::{distance_map, downhill};
+use crate::faction::{hostile, Faction};
+use crate::fighter::MAX_MORALE;
+use crate::fov::line_of_sight;
+use crate::game::{cuts_corner, is_blocked, mut_two, Game, Terrain, MAP_HEIGHT, MAP_WIDTH, PLAYER};
+use crate::grid::Grid;
+use crate::lang::tr;
+use crate::object::{find_handle, handle_of, move_by, move_towards, Object};
+use crate::render::Context;
+use crate::status::moves_on_turn;
+
+// How far apart the members of a pack can be
+const PACK_RADIUS: f32 = 8.0;
+
+// How far monsters see, and for how many turns the alert shows over the ones that spotted the player
+const MONSTER_SIGHT: i32 = 8;
+const ALERT_TURNS: u32 = 2;
+// Turns the scent of the player lasts, and the monsters follow it
+const SCENT_STRENGTH: u32 = 10;
+const SCENT_TRACK_TURNS: u32 = 10;
+// Monsters flee below this part of their hp, in percent
+const FLEE_HP_PERCENT: i32 = 25;
+// Morale lost when an ally dies close by, and regained every turn out of the player's sight
+const ALLY_DEATH_MORALE: i32 = 40;
+const MORALE_RADIUS: f32 = 6.0;
+const MORALE_RECOVERY: i32 = 5;
 
 // artificial intelligence for npcs
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum Ai {
-    Basic,
+    // Following the behavior tree of the monster kind from the data file
+    Tree(String),
+    // Doing nothing until a noise wakes it up
+    Sleeping {
+        previous_ai: Box<Ai>,
+    },
+    // Running away from the player until it calms down
+    Fleeing {
+        previous_ai: Box<Ai>,
+    },
     Confused {
         previous_ai: Box<Ai>,
         num_turns: i32,
     },
+    // Fighting back against another monster that hurt it, until one of them dies
+    Infighting {
+        // Handle of the attacker
+        target: u32,
+        previous_ai: Box<Ai>,
+    },
+}
+
+// How many times the monster acts this turn, with its speed and where it stands
+pub fn moves_this_turn(monster: &Object, game: &Game) -> u32 {
+    // Climbing over rubble takes every other turn
+    let rubble = game.map[(monster.x, monster.y)].terrain == Terrain::Rubble;
+    if rubble && game.stats.turns % 2 == 1 {
+        0
+    } else {
+        moves_on_turn(monster, game.stats.turns)
+    }
 }
 
-pub fn ai_take_turn(monster_id: usize, tcod: &Tcod, game: &mut Game, objects: &mut [Object]) {
+// The behavior tree the monster runs, under whatever it's going through now
+pub fn tree_kind(ai: &Ai) -> Option<&str> {
+    match ai {
+        Ai::Tree(kind) => Some(kind),
+        Ai::Sleeping { previous_ai }
+        | Ai::Fleeing { previous_ai }
+        | Ai::Confused { previous_ai, .. }
+        | Ai::Infighting { previous_ai, .. } => tree_kind(previous_ai),
+    }
+}
+
+// Let every monster act as many times as its speed allows this turn
+pub fn monsters_take_turns(ctx: &Context, game: &mut Game, objects: &mut [Object]) {
+    for id in 0..objects.len() {
+        let mut moves = moves_this_turn(&objects[id], game);
+        while moves > 0 {
+            moves -= 1;
+            if objects[id].ai.is_none() {
+                continue;
+            }
+            // A slow blow takes up the moves after it, quick ones add up to another move
+            if objects[id].attack_time >= TURN {
+                objects[id].attack_time -= TURN;
+                continue;
+            }
+            ai_take_turn(id, ctx, game, objects);
+            if objects[id].attack_time <= -TURN {
+                objects[id].attack_time += TURN;
+                moves += 1;
+            }
+        }
+    }
+}
+
+// Hit the target, taking as long as the monster's weapon swings
+pub fn monster_attack(monster: &mut Object, target: &mut Object, game: &mut Game) {
+    monster.attack(target, game);
+    monster.attack_time += monster.attack_delay(game) - TURN;
+}
+
+pub fn ai_take_turn(monster_id: usize, ctx: &Context, game: &mut Game, objects: &mut [Object]) {
     if let Some(ai) = objects[monster_id].ai.take() {
+        let ai = panic_check(monster_id, game, objects, ai);
+        if !matches!(ai, Ai::Sleeping { .. }) {
+            watch_player(monster_id, ctx, game, objects);
+        }
         let new_ai = match ai {
-            Ai::Basic => ai_basic(monster_id, tcod, game, objects),
+            Ai::Tree(kind) => {
+                run_behavior(monster_id, &kind, game, objects);
+                Ai::Tree(kind)
+            }
+            Ai::Sleeping { previous_ai } => Ai::Sleeping { previous_ai },
+            Ai::Fleeing { previous_ai } => ai_fleeing(monster_id, game, objects, previous_ai),
             Ai::Confused {
                 previous_ai,
                 num_turns,
-            } => ai_confused(monster_id, tcod, game, objects, previous_ai, num_turns),
+            } => ai_confused(monster_id, ctx, game, objects, previous_ai, num_turns),
+            Ai::Infighting {
+                target,
+                previous_ai,
+            } => ai_infighting(monster_id, game, objects, target, previous_ai),
         };
         objects[monster_id].ai = Some(new_ai);
     }
 }
 
-fn ai_basic(monster_id: usize, tcod: &Tcod, game: &mut Game, objects: &mut [Object]) -> Ai {
-    let (monster_x, monster_y) = objects[monster_id].pos();
+// Go where the player was last seen, then sniff after them for a while,
+// returns whether the monster is still after the player
+pub fn track_player(monster_id: usize, game: &Game, objects: &mut [Object]) -> bool {
+    let (x, y) = objects[monster_id].pos();
+    if let Some((last_x, last_y)) = objects[monster_id].last_seen {
+        if (x, y) == (last_x, last_y) {
+            objects[monster_id].last_seen = None;
+            objects[monster_id].tracking = SCENT_TRACK_TURNS;
+        } else {
+            move_towards(monster_id, last_x, last_y, game, objects);
+        }
+        return true;
+    }
+
+    if objects[monster_id].tracking == 0 || game.scent.is_empty() {
+        return false;
+    }
+    objects[monster_id].tracking -= 1;
+    let here = game.scent[(x, y)];
+    let stronger = (-1..=1)
+        .flat_map(|dx| (-1..=1).map(move |dy| (x + dx, y + dy)))
+        .filter(|&(nx, ny)| {
+            !is_blocked(nx, ny, &game.map, objects) && !cuts_corner(x, y, nx - x, ny - y, game)
+        })
+        .max_by_key(|&(nx, ny)| game.scent[(nx, ny)]);
+    match stronger {
+        Some((nx, ny)) if game.scent[(nx, ny)] > here => {
+            move_towards(monster_id, nx, ny, game, objects)
+        }
+        // The trail went cold
+        _ => objects[monster_id].tracking = 0,
+    }
+    true
+}
+
+// Let the scent fade everywhere, and leave a fresh one where the player stands
+pub fn update_scent(game: &mut Game, objects: &[Object]) {
+    if game.scent.is_empty() {
+        game.scent = Grid::new(MAP_WIDTH, MAP_HEIGHT, 0);
+    }
+    for scent in game.scent.iter_mut() {
+        *scent = scent.saturating_sub(1);
+    }
+    let (x, y) = objects[PLAYER].pos();
+    game.scent[(x, y)] = SCENT_STRENGTH;
+}
 
-    if tcod.fov.is_in_fov(monster_x, monster_y) {
-        if objects[monster_id].distance_to(&objects[PLAYER]) >= 2.0 {
-            // Move towards the player
-            let (player_x, player_y) = objects[PLAYER].pos();
-            move_towards(monster_id, player_x, player_y, &game.map, objects);
-        } else if objects[PLAYER].fighter.map_or(false, |f| f.hp > 0) {
-            // Attack the player if he's alive
-            let (monster, player) = mut_two(monster_id, PLAYER, objects);
-            monster.attack(player, game);
+// Ids of the monsters of the kind hunting in a pack with the monster, itself included, lowest first
+pub fn packmates(monster_id: usize, kind: &str, objects: &[Object]) -> Vec<usize> {
+    let monster = &objects[monster_id];
+    objects
+        .iter()
+        .enumerate()
+        .filter(|&(id, object)| {
+            id == monster_id
+                || (object.alive
+                    && matches!(&object.ai, Some(Ai::Tree(other)) if other == kind)
+                    && object.faction == monster.faction
+                    && monster.distance_to(object) <= PACK_RADIUS)
+        })
+        .map(|(id, _)| id)
+        .collect()
+}
+
+// Start fleeing when badly hurt or out of morale
+fn panic_check(monster_id: usize, game: &mut Game, objects: &[Object], ai: Ai) -> Ai {
+    let monster = &objects[monster_id];
+    let fighter = match monster.fighter {
+        Some(fighter) => fighter,
+        None => return ai,
+    };
+    let fearless = monster.faction.is_some_and(Faction::fearless);
+    let hurt = fighter.hp * 100 < fighter.base_max_hp * FLEE_HP_PERCENT;
+    match ai {
+        Ai::Tree(_) if !fearless && (hurt || fighter.morale <= 0) => {
+            game.messages.add(
+                tr("ai.shrieks_and_runs_away", &[("monster", &monster.name)]),
+                LIGHT_RED,
+            );
+            Ai::Fleeing {
+                previous_ai: Box::new(ai),
+            }
         }
+        ai => ai,
     }
-    Ai::Basic
+}
+
+fn ai_fleeing(
+    monster_id: usize,
+    game: &mut Game,
+    objects: &mut [Object],
+    previous_ai: Box<Ai>,
+) -> Ai {
+    let (player_x, player_y) = objects[PLAYER].pos();
+    let sees_player = can_see(&objects[monster_id], player_x, player_y, game);
+    if let Some(fighter) = objects[monster_id].fighter.as_mut() {
+        // Calm down once out of sight
+        if !sees_player {
+            fighter.morale = (fighter.morale + MORALE_RECOVERY).min(MAX_MORALE);
+        }
+        let hurt = fighter.hp * 100 < fighter.base_max_hp * FLEE_HP_PERCENT;
+        if fighter.morale >= MAX_MORALE / 2 && !hurt {
+            return *previous_ai;
+        }
+    }
+
+    match downhill(&game.dijkstra.from_player, monster_id, game, objects) {
+        Some((nx, ny)) => objects[monster_id].set_pos(nx, ny),
+        None => {
+            // Cornered, fight back
+            if objects[PLAYER].alive && in_reach(monster_id, PLAYER, game, objects) {
+                let (monster, player) = mut_two(monster_id, PLAYER, objects);
+                monster_attack(monster, player, game);
+            }
+        }
+    }
+    Ai::Fleeing { previous_ai }
+}
+
+// Wake up the sleeping monsters close enough to hear the noises made this turn
+pub fn wake_monsters(ctx: &Context, game: &mut Game, objects: &mut [Object]) {
+    let noises = std::mem::take(&mut game.noises);
+    for (x, y, loudness) in noises {
+        let distances = distance_map(&[(x, y)], game);
+        for object in objects.iter_mut() {
+            if distances[(object.x, object.y)] > loudness {
+                continue;
+            }
+            if let Some(Ai::Sleeping { previous_ai }) = &object.ai {
+                if ctx.fov.is_in_fov(object.x, object.y) {
+                    game.messages
+                        .add(tr("ai.wakes_up", &[("monster", &object.name)]), LIGHT_GREY);
+                }
+                object.ai = Some((**previous_ai).clone());
+            }
+        }
+    }
+}
+
+// Scare the allies of the monsters that died this turn
+pub fn lose_morale(game: &mut Game, objects: &mut [Object]) {
+    for (x, y, faction) in game.deaths.drain(..) {
+        for object in objects.iter_mut() {
+            if object.faction.is_some()
+                && object.faction == faction
+                && object.distance(x, y) <= MORALE_RADIUS
+            {
+                if let Some(fighter) = object.fighter.as_mut() {
+                    fighter.morale -= ALLY_DEATH_MORALE;
+                }
+            }
+        }
+    }
+}
+
+// Notice the player coming into the monster's view, showing the alert over its head
+fn watch_player(monster_id: usize, ctx: &Context, game: &mut Game, objects: &mut [Object]) {
+    let (player_x, player_y) = objects[PLAYER].pos();
+    let monster = &objects[monster_id];
+    let spotted = objects[PLAYER].alive
+        && hostile(monster.faction, objects[PLAYER].faction)
+        && can_see(monster, player_x, player_y, game);
+
+    let monster = &mut objects[monster_id];
+    monster.alert = monster.alert.saturating_sub(1);
+    if spotted {
+        monster.last_seen = Some((player_x, player_y));
+    }
+    if spotted && !monster.seen_player {
+        monster.alert = ALERT_TURNS;
+        if ctx.fov.is_in_fov(monster.x, monster.y) {
+            game.messages.add(
+                tr("ai.notices_you", &[("monster", &monster.name)]),
+                LIGHT_RED,
+            );
+        }
+    }
+    monster.seen_player = spotted;
+}
+
+// Whether the monster sees the tile: close by, in front of it and with nothing in the way
+pub fn can_see(monster: &Object, x: i32, y: i32, game: &Game) -> bool {
+    let (dx, dy) = (x - monster.x, y - monster.y);
+    let distance_squared = dx * dx + dy * dy;
+    if distance_squared > MONSTER_SIGHT * MONSTER_SIGHT {
+        return false;
+    }
+    // Anything right next to the monster is noticed, whichever way it looks
+    let (facing_x, facing_y) = monster.facing;
+    let in_front = dx * facing_x + dy * facing_y >= 0;
+    if distance_squared > 2 * 2 && !in_front {
+        return false;
+    }
+    line_of_sight(&game.map, monster.pos(), (x, y))
+}
+
+// The nearest fighter the monster sees of a faction hostile to its own, the player first on a tie
+pub fn closest_enemy(monster_id: usize, game: &Game, objects: &[Object]) -> Option<usize> {
+    let monster = &objects[monster_id];
+    objects
+        .iter()
+        .enumerate()
+        .filter(|&(id, object)| {
+            id != monster_id
+                && object.alive
+                && object.fighter.is_some()
+                && hostile(monster.faction, object.faction)
+                && can_see(monster, object.x, object.y, game)
+        })
+        .min_by(|(_, a), (_, b)| {
+            monster
+                .distance_to(a)
+                .partial_cmp(&monster.distance_to(b))
+                .unwrap()
+        })
+        .map(|(id, _)| id)
 }
 
 fn ai_confused(
     monster_id: usize,
-    _tcod: &Tcod,
+    _ctx: &Context,
     game: &mut Game,
     objects: &mut [Object],
     previous_ai: Box<Ai>,
     num_turns: i32,
 ) -> Ai {
     if num_turns >= 0 {
-        // Move around confused
-        move_by(
-            monster_id,
-            rand::thread_rng().gen_range(-1, 2),
-            rand::thread_rng().gen_range(-1, 2),
-            &game.map,
-            objects,
-        );
+        // Move around confused, hitting whoever is in the way
+        let dx = game.rng.gen_range(-1, 2);
+        let dy = game.rng.gen_range(-1, 2);
+        let (x, y) = objects[monster_id].pos();
+        let victim = objects.iter().enumerate().position(|(id, object)| {
+            id != monster_id && object.fighter.is_some() && object.pos() == (x + dx, y + dy)
+        });
+        match victim {
+            Some(victim_id) => {
+                let (monster, victim) = mut_two(monster_id, victim_id, objects);
+                monster_attack(monster, victim, game);
+                provoke(victim_id, monster_id, game, objects);
+            }
+            None => move_by(monster_id, dx, dy, game, objects),
+        }
 
         Ai::Confused {
             previous_ai: previous_ai,
@@ -72,9 +385,67 @@ fn ai_confused(
         }
     } else {
         game.messages.add(
-            format!("{} is no longer confused", objects[monster_id].name),
+            tr(
+                "ai.is_no_longer_confused",
+                &[("monster", &objects[monster_id].name)],
+            ),
             WHITE,
         );
         *previous_ai
     }
 }
+
+fn ai_infighting(
+    monster_id: usize,
+    game: &mut Game,
+    objects: &mut [Object],
+    handle: u32,
+    previous_ai: Box<Ai>,
+) -> Ai {
+    // The target might be dead, or gone from the level
+    let target = match find_handle(handle, objects) {
+        Some(target)
+            if target != monster_id
+                && objects[target].alive
+                && objects[target].fighter.is_some() =>
+        {
+            target
+        }
+        _ => return *previous_ai,
+    };
+
+    if !in_reach(monster_id, target, game, objects) {
+        let (target_x, target_y) = objects[target].pos();
+        move_towards(monster_id, target_x, target_y, game, objects);
+    } else {
+        let (monster, enemy) = mut_two(monster_id, target, objects);
+        monster_attack(monster, enemy, game);
+        provoke(target, monster_id, game, objects);
+    }
+    Ai::Infighting {
+        target: handle,
+        previous_ai,
+    }
+}
+
+// Turn a monster hurt by another monster against its attacker
+pub fn provoke(victim_id: usize, attacker_id: usize, game: &mut Game, objects: &mut [Object]) {
+    if victim_id == PLAYER || attacker_id == PLAYER || victim_id == attacker_id {
+        return;
+    }
+    if !objects[victim_id].alive {
+        return;
+    }
+    let attacker = handle_of(attacker_id, game, objects);
+    let victim = &mut objects[victim_id];
+    if let Some(ai) = victim.ai.take() {
+        let previous_ai = match ai {
+            Ai::Infighting { previous_ai, .. } => previous_ai,
+            ai => Box::new(ai),
+        };
+        victim.ai = Some(Ai::Infighting {
+            target: attacker,
+            previous_ai,
+        });
+    }
+}
@@ -0,0 +1,159 @@
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use crate::event::GameEvent;
+use crate::game::{Game, PLAYER};
+use crate::object::Object;
+use crate::plan::enemy_in_sight;
+use crate::render::Context;
+use crate::theme::Theme;
+
+// Loudest a category can be set to, in steps of the options screen
+pub const MAX_VOLUME: u8 = 10;
+
+// Something the game makes a noise for
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Sound {
+    Hit,
+    Death,
+    Pickup,
+    LevelUp,
+    Spell,
+}
+
+// Sounds sharing a volume setting
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SoundCategory {
+    Combat,
+    Items,
+    Magic,
+    Character,
+}
+
+impl Sound {
+    pub fn category(self) -> SoundCategory {
+        match self {
+            Sound::Hit | Sound::Death => SoundCategory::Combat,
+            Sound::Pickup => SoundCategory::Items,
+            Sound::Spell => SoundCategory::Magic,
+            Sound::LevelUp => SoundCategory::Character,
+        }
+    }
+
+    // Pitch in hertz and length in milliseconds of the tone played for the sound
+    pub fn tone(self) -> (f32, u64) {
+        match self {
+            Sound::Hit => (220.0, 60),
+            Sound::Death => (110.0, 300),
+            Sound::Pickup => (880.0, 80),
+            Sound::LevelUp => (660.0, 400),
+            Sound::Spell => (1320.0, 200),
+        }
+    }
+}
+
+// What should be playing: the track of the place the player is in, whether its
+// fight layer is heard over it, and how loud, from 0 to 1
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Music {
+    pub track: &'static str,
+    pub combat: bool,
+    pub volume: f32,
+}
+
+// Name of the music files of the theme, the track and the fight layer played along
+// with it being music/<name>.ogg and music/<name>_combat.ogg
+fn track(theme: Theme) -> &'static str {
+    match theme {
+        Theme::Surface => "surface",
+        Theme::Catacombs => "catacombs",
+        Theme::FungalCaverns => "fungal_caverns",
+        Theme::Forge => "forge",
+    }
+}
+
+// Volume of every category, from 0 to MAX_VOLUME, all of them scaled by the master volume
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct VolumeConfig {
+    pub master: u8,
+    pub combat: u8,
+    pub items: u8,
+    pub magic: u8,
+    pub character: u8,
+    pub music: u8,
+}
+
+impl Default for VolumeConfig {
+    fn default() -> Self {
+        VolumeConfig {
+            master: MAX_VOLUME,
+            combat: MAX_VOLUME,
+            items: MAX_VOLUME,
+            magic: MAX_VOLUME,
+            character: MAX_VOLUME,
+            music: MAX_VOLUME,
+        }
+    }
+}
+
+impl VolumeConfig {
+    // The category's volume scaled by the master volume, from 0 to 1
+    fn scaled(&self, category: u8) -> f32 {
+        let max = f32::from(MAX_VOLUME);
+        f32::from(self.master.min(MAX_VOLUME)) / max * f32::from(category.min(MAX_VOLUME)) / max
+    }
+
+    // How loud the sound plays
+    pub fn of(&self, sound: Sound) -> f32 {
+        self.scaled(match sound.category() {
+            SoundCategory::Combat => self.combat,
+            SoundCategory::Items => self.items,
+            SoundCategory::Magic => self.magic,
+            SoundCategory::Character => self.character,
+        })
+    }
+
+    pub fn of_music(&self) -> f32 {
+        self.scaled(self.music)
+    }
+}
+
+// Sound the player hears for the event, if any
+fn sound_of(event: &GameEvent) -> Option<Sound> {
+    match *event {
+        GameEvent::Damaged {
+            by_player,
+            to_player,
+            ..
+        } if by_player || to_player => Some(Sound::Hit),
+        GameEvent::Died {
+            by_player, player, ..
+        } if by_player || player => Some(Sound::Death),
+        GameEvent::ItemPickedUp { .. } => Some(Sound::Pickup),
+        GameEvent::LeveledUp { .. } => Some(Sound::LevelUp),
+        GameEvent::SpellCast { .. } => Some(Sound::Spell),
+        _ => None,
+    }
+}
+
+pub fn play_sound(event: &GameEvent, ctx: &mut Context) {
+    if let Some(sound) = sound_of(event) {
+        let volume = ctx.options.volume.of(sound);
+        if volume > 0.0 {
+            ctx.audio.play(sound, volume);
+        }
+    }
+}
+
+// Ask for the music fitting the place and the danger the player is in, the audio
+// fades between what was playing and that over the time since the last frame
+pub fn update_music(delta: Duration, ctx: &mut Context, game: &Game, objects: &[Object]) {
+    let music = Music {
+        track: track(Theme::at(game.branch, game.dungeon_level)),
+        combat: objects[PLAYER].alive && enemy_in_sight(ctx, objects),
+        volume: ctx.options.volume.of_music(),
+    };
+    ctx.audio.play_music(music, delta);
+}
@@ -0,0 +1,158 @@
+use std::time::Duration;
+
+use crate::audio::{Music, Sound};
+use crate::colors::Color;
+use crate::render::{SCREEN_HEIGHT, SCREEN_WIDTH};
+use crate::tileset::TileMapping;
+
+pub const FPS_LIMIT: i32 = 60;
+
+// Keys the game knows about, independent of the backend
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum KeyCode {
+    #[default]
+    NoKey,
+    Escape,
+    Backspace,
+    Tab,
+    Enter,
+    Spacebar,
+    Up,
+    Down,
+    Left,
+    Right,
+    Home,
+    End,
+    PageUp,
+    PageDown,
+    NumPad0,
+    NumPad1,
+    NumPad2,
+    NumPad3,
+    NumPad4,
+    NumPad5,
+    NumPad6,
+    NumPad7,
+    NumPad8,
+    NumPad9,
+    F1,
+    F2,
+    F3,
+    F4,
+    F5,
+    F6,
+    F7,
+    F8,
+    F9,
+    F10,
+    F11,
+    F12,
+    // A key producing a character, `printable` holds it
+    Char,
+    // A typed character, `printable` holds it
+    Text,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Key {
+    pub code: KeyCode,
+    pub printable: char,
+    pub alt: bool,
+    pub ctrl: bool,
+    pub shift: bool,
+}
+
+// Mouse state in console cells
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Mouse {
+    pub cx: i32,
+    pub cy: i32,
+    pub lbutton_pressed: bool,
+    pub rbutton_pressed: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Event {
+    Key(Key),
+    Mouse(Mouse),
+    // The window changed its size, everything has to be laid out again
+    Resize,
+}
+
+// Everything the game needs to draw the screen
+pub trait Renderer {
+    // Erase the whole screen
+    fn clear(&mut self);
+
+    // Draw a glyph, keeping the background of the cell if none is given
+    fn put_char(
+        &mut self,
+        x: i32,
+        y: i32,
+        glyph: char,
+        foreground: Color,
+        background: Option<Color>,
+    );
+
+    // Change the background of a cell, keeping its glyph
+    fn set_background(&mut self, x: i32, y: i32, color: Color);
+
+    // Print a single line of text
+    fn print(&mut self, x: i32, y: i32, text: &str, color: Color);
+
+    // Show everything drawn since the last flush
+    fn flush(&mut self);
+
+    fn window_closed(&self) -> bool;
+
+    // Size of the screen in cells
+    fn size(&self) -> (i32, i32) {
+        (SCREEN_WIDTH, SCREEN_HEIGHT)
+    }
+
+    fn is_fullscreen(&self) -> bool {
+        false
+    }
+
+    fn set_fullscreen(&mut self, _fullscreen: bool) {}
+
+    // Limit how many frames are shown a second
+    fn set_fps(&mut self, _fps: i32) {}
+
+    // Fill the screen with an image, if the backend can show one
+    fn draw_image(&mut self, _path: &str) {}
+
+    // Draw the text in another font, its glyphs scaled up by the factor,
+    // if the backend draws its own glyphs
+    fn set_font(&mut self, _font: &str, _scale: u32) {}
+}
+
+// Everything the game needs to read the player's input
+pub trait InputSource {
+    // The next pending event, if there is one
+    fn check_for_event(&mut self) -> Option<Event>;
+
+    // Drop pending events and block until a key is pressed
+    fn wait_for_keypress(&mut self) -> Key;
+}
+
+// Everything the game needs to make a noise
+pub trait AudioSink {
+    // Play the sound once, at a volume from 0 to 1
+    fn play(&mut self, sound: Sound, volume: f32);
+
+    // Keep the music going, fading towards what's asked for over the time given
+    fn play_music(&mut self, _music: Music, _delta: Duration) {}
+
+    fn stop_music(&mut self) {}
+}
+
+// Stands in when there is no audio compiled in or no device to play it on
+pub struct Silence;
+
+impl AudioSink for Silence {
+    fn play(&mut self, _sound: Sound, _volume: f32) {}
+}
+
+// A drawing target, an input source and the sprites the target was set up with
+pub type Backend = (Box<dyn Renderer>, Box<dyn InputSource>, Option<TileMapping>);
@@ -0,0 +1,369 @@
+use std::collections::HashMap;
+use std::error::Error;
+
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::colors::*;
+
+use crate::ai::{closest_enemy, monster_attack, packmates, track_player, Ai};
+use crate::combat::in_reach;
+use crate::cover::misses_in_cover;
+use crate::dijkstra::{downhill, CostMap};
+use crate::fov::line_of_sight;
+use crate::game::{cuts_corner, is_blocked, mut_two, Game, PLAYER};
+use crate::lang::tr;
+use crate::mods::read_data;
+use crate::object::{move_towards, Object};
+use crate::room::make_monster;
+use crate::script::run_script;
+use crate::spawn::summon;
+
+const BEHAVIORS_FILE: &str = "data/behaviors.json";
+
+// A blow lands on a target closer than that, next to the monster
+pub const MELEE_REACH: f32 = 2.0;
+// How close the monsters on the player's side keep
+const FOLLOW_DISTANCE: f32 = 3.0;
+// How many monsters of the kind a summoner keeps around itself
+const MAX_SUMMONS: usize = 3;
+const SUMMON_RADIUS: f32 = 8.0;
+
+// A node of a behavior tree, which either succeeds or fails when the monster runs it
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum Behavior {
+    // Run the children in order until one fails
+    Sequence(Vec<Behavior>),
+    // Run the children in order until one succeeds
+    Selector(Vec<Behavior>),
+    // Succeed when the child fails and the other way around
+    Inverter(Box<Behavior>),
+    Condition(Condition),
+    Task(Task),
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum Condition {
+    // The target is closer than the distance
+    TargetWithin(f32),
+    // Nothing blocks the view of the target
+    TargetInSight,
+    // The monster's weapon reaches the target, over the tile between for a polearm
+    TargetInReach,
+    // Enough of the pack is no farther from the target than the distance,
+    // or the whole pack if it's smaller
+    PackReady { members: usize, distance: f32 },
+    // Passes the roll of the chance in percent
+    Chance(i32),
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum Task {
+    // Pick the closest enemy the monster sees as the target
+    FindEnemy,
+    // Pick the target of the pack leader, or the monster's own if the leader has none
+    FindPackEnemy,
+    // Hit the target next to the monster
+    Attack,
+    // Hit the target from afar
+    Shoot,
+    Approach,
+    // Come at the target from the free side closest to the monster
+    Flank,
+    // Step away from the target, failing if there is nowhere to go
+    Retreat,
+    // Follow the player out of sight, failing if the monster lost them
+    Track,
+    // Head for the closest stairs, failing once on them
+    GoToStairs,
+    // Keep close to the player, for the monsters on their side
+    Follow,
+    // Call a monster of the kind to fight alongside, failing if too many already answered
+    Summon(String),
+    // Run the script of the name on the target, failing if the script says so
+    Script(String),
+    Wait,
+}
+
+// What the nodes of the tree share while it runs
+struct Blackboard<'a> {
+    kind: &'a str,
+    target: Option<usize>,
+}
+
+impl Behavior {
+    // Walk towards the closest enemy and attack it, follow the player out of sight
+    pub fn basic() -> Self {
+        use Behavior::*;
+        Selector(vec![
+            Sequence(vec![
+                Task(self::Task::FindEnemy),
+                Selector(vec![
+                    Sequence(vec![
+                        Condition(self::Condition::TargetInReach),
+                        Task(self::Task::Attack),
+                    ]),
+                    Task(self::Task::Approach),
+                ]),
+            ]),
+            Task(self::Task::Track),
+        ])
+    }
+
+    // How far the monster can hit from, the distance the target has to be closer than:
+    // as far as its weapon reaches for a blow, as far as the conditions before the shot
+    // let it shoot
+    pub fn reach(&self, melee: f32) -> f32 {
+        self.reach_within(f32::INFINITY, melee)
+    }
+
+    fn reach_within(&self, within: f32, melee: f32) -> f32 {
+        use Behavior::*;
+        match self {
+            Sequence(children) => {
+                let mut within = within;
+                let mut reach: f32 = 0.0;
+                for child in children {
+                    if let Condition(self::Condition::TargetWithin(distance)) = child {
+                        within = within.min(*distance);
+                    }
+                    reach = reach.max(child.reach_within(within, melee));
+                }
+                reach
+            }
+            Selector(children) => children
+                .iter()
+                .map(|child| child.reach_within(within, melee))
+                .fold(0.0, f32::max),
+            Task(self::Task::Attack) => melee.min(within),
+            Task(self::Task::Shoot) => within,
+            Inverter(_) | Condition(_) | Task(_) => 0.0,
+        }
+    }
+}
+
+pub fn load_behaviors() -> Result<HashMap<String, Behavior>, Box<dyn Error>> {
+    let result = serde_json::from_value::<HashMap<String, Behavior>>(read_data(BEHAVIORS_FILE)?)?;
+    Ok(result)
+}
+
+// Run the behavior tree of the monster kind, the basic one if the data file has none
+pub fn run_behavior(monster_id: usize, kind: &str, game: &mut Game, objects: &mut [Object]) {
+    if game.behaviors.is_empty() {
+        game.behaviors = load_behaviors().unwrap_or_default();
+    }
+    let tree = game
+        .behaviors
+        .get(kind)
+        .cloned()
+        .unwrap_or_else(Behavior::basic);
+    let mut board = Blackboard { kind, target: None };
+    run(&tree, monster_id, &mut board, game, objects);
+}
+
+fn run(
+    node: &Behavior,
+    monster_id: usize,
+    board: &mut Blackboard,
+    game: &mut Game,
+    objects: &mut [Object],
+) -> bool {
+    match node {
+        Behavior::Sequence(children) => children
+            .iter()
+            .all(|child| run(child, monster_id, board, game, objects)),
+        Behavior::Selector(children) => children
+            .iter()
+            .any(|child| run(child, monster_id, board, game, objects)),
+        Behavior::Inverter(child) => !run(child, monster_id, board, game, objects),
+        Behavior::Condition(condition) => check(condition, monster_id, board, game, objects),
+        Behavior::Task(task) => perform(task, monster_id, board, game, objects),
+    }
+}
+
+fn check(
+    condition: &Condition,
+    monster_id: usize,
+    board: &Blackboard,
+    game: &mut Game,
+    objects: &[Object],
+) -> bool {
+    if let Condition::Chance(percent) = *condition {
+        return game.rng.gen_range(0, 100) < percent;
+    }
+    let target = match board.target {
+        Some(target) => target,
+        None => return false,
+    };
+    let monster = &objects[monster_id];
+    match *condition {
+        Condition::TargetWithin(distance) => monster.distance_to(&objects[target]) < distance,
+        Condition::TargetInSight => line_of_sight(&game.map, monster.pos(), objects[target].pos()),
+        Condition::TargetInReach => in_reach(monster_id, target, game, objects),
+        Condition::PackReady { members, distance } => {
+            let pack = packmates(monster_id, board.kind, objects);
+            let in_position = pack
+                .iter()
+                .filter(|&&id| objects[id].distance_to(&objects[target]) <= distance)
+                .count();
+            in_position >= members.min(pack.len())
+        }
+        Condition::Chance(_) => unreachable!(),
+    }
+}
+
+fn perform(
+    task: &Task,
+    monster_id: usize,
+    board: &mut Blackboard,
+    game: &mut Game,
+    objects: &mut [Object],
+) -> bool {
+    match task {
+        Task::FindEnemy => {
+            board.target = closest_enemy(monster_id, game, objects);
+            return board.target.is_some();
+        }
+        Task::FindPackEnemy => {
+            let leader = packmates(monster_id, board.kind, objects)[0];
+            board.target = closest_enemy(leader, game, objects)
+                .or_else(|| closest_enemy(monster_id, game, objects));
+            return board.target.is_some();
+        }
+        Task::Track => return track_player(monster_id, game, objects),
+        Task::GoToStairs => {
+            return step_downhill(&game.dijkstra.to_stairs, monster_id, game, objects)
+        }
+        Task::Follow => {
+            if objects[monster_id].distance_to(&objects[PLAYER]) < FOLLOW_DISTANCE {
+                return false;
+            }
+            return step_downhill(&game.dijkstra.to_player, monster_id, game, objects);
+        }
+        Task::Summon(kind) => {
+            let (x, y) = objects[monster_id].pos();
+            let answered = objects
+                .iter()
+                .filter(|object| {
+                    object.alive
+                        && object.faction == objects[monster_id].faction
+                        && object.distance(x, y) < SUMMON_RADIUS
+                        && matches!(&object.ai, Some(Ai::Tree(other)) if other == kind)
+                })
+                .count();
+            if answered >= MAX_SUMMONS {
+                return false;
+            }
+            return match make_monster(kind, x, y) {
+                Some(monster) => summon(monster, monster_id, game, objects),
+                None => false,
+            };
+        }
+        Task::Wait => return true,
+        _ => {}
+    }
+
+    let target = match board.target {
+        Some(target) => target,
+        None => return false,
+    };
+    let (x, y) = objects[monster_id].pos();
+    let (target_x, target_y) = objects[target].pos();
+    match task {
+        Task::Attack => {
+            let alive = objects[target].fighter.is_some_and(|f| f.hp > 0);
+            if !alive || !in_reach(monster_id, target, game, objects) {
+                return false;
+            }
+            let (monster, enemy) = mut_two(monster_id, target, objects);
+            monster_attack(monster, enemy, game);
+        }
+        Task::Shoot => {
+            game.messages.add(
+                tr(
+                    "behavior.shoots_an_arrow_at",
+                    &[
+                        ("monster", &objects[monster_id].name),
+                        ("target", &objects[target].name),
+                    ],
+                ),
+                LIGHT_GREY,
+            );
+            if misses_in_cover((x, y), (target_x, target_y), game, objects) {
+                game.messages.add(
+                    tr(
+                        "behavior.the_arrow_hits_the_cover_of",
+                        &[("target", &objects[target].name)],
+                    ),
+                    LIGHT_GREY,
+                );
+                return true;
+            }
+            let (monster, enemy) = mut_two(monster_id, target, objects);
+            monster_attack(monster, enemy, game);
+        }
+        // The player is the common target, already mapped for everyone
+        Task::Approach if target == PLAYER => {
+            if !step_downhill(&game.dijkstra.to_player, monster_id, game, objects) {
+                move_towards(monster_id, target_x, target_y, game, objects);
+            }
+        }
+        Task::Approach => move_towards(monster_id, target_x, target_y, game, objects),
+        Task::Script(name) => return run_script(name, monster_id, Some(target), game, objects),
+        Task::Flank => {
+            let flank = (-1..=1)
+                .flat_map(|dx| (-1..=1).map(move |dy| (target_x + dx, target_y + dy)))
+                .filter(|&(fx, fy)| {
+                    (fx, fy) != (target_x, target_y) && !is_blocked(fx, fy, &game.map, objects)
+                })
+                .min_by(|&(ax, ay), &(bx, by)| {
+                    objects[monster_id]
+                        .distance(ax, ay)
+                        .partial_cmp(&objects[monster_id].distance(bx, by))
+                        .unwrap()
+                });
+            let (flank_x, flank_y) = flank.unwrap_or((target_x, target_y));
+            move_towards(monster_id, flank_x, flank_y, game, objects);
+        }
+        Task::Retreat if target == PLAYER => {
+            return step_downhill(&game.dijkstra.from_player, monster_id, game, objects);
+        }
+        Task::Retreat => {
+            let distance = objects[monster_id].distance_to(&objects[target]);
+            let back = (-1..=1)
+                .flat_map(|dx| (-1..=1).map(move |dy| (x + dx, y + dy)))
+                .filter(|&(bx, by)| {
+                    !is_blocked(bx, by, &game.map, objects)
+                        && !cuts_corner(x, y, bx - x, by - y, game)
+                })
+                .max_by_key(|&(bx, by)| (bx - target_x).pow(2) + (by - target_y).pow(2));
+            match back {
+                Some((bx, by)) if objects[target].distance(bx, by) > distance => {
+                    objects[monster_id].set_pos(bx, by)
+                }
+                _ => return false,
+            }
+        }
+        Task::FindEnemy
+        | Task::FindPackEnemy
+        | Task::Track
+        | Task::GoToStairs
+        | Task::Follow
+        | Task::Summon(_)
+        | Task::Wait => {
+            unreachable!()
+        }
+    }
+    true
+}
+
+// Take a step down the cost map, returns whether there was one to take
+fn step_downhill(costs: &CostMap, monster_id: usize, game: &Game, objects: &mut [Object]) -> bool {
+    match downhill(costs, monster_id, game, objects) {
+        Some((x, y)) => {
+            objects[monster_id].set_pos(x, y);
+            true
+        }
+        None => false,
+    }
+}
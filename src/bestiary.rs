@@ -0,0 +1,171 @@
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::fs::File;
+
+use std::io::{Read, Write};
+
+use serde::{Deserialize, Serialize};
+
+use crate::event::GameEvent;
+use crate::faction::hostile;
+use crate::game::{Game, PLAYER};
+use crate::lang::tr;
+use crate::object::Object;
+use crate::paths::data_file;
+use crate::render::{menu, msgbox, Context, MAX_MENU_OPTIONS};
+
+const BESTIARY_FILE: &str = "bestiary.json";
+const BESTIARY_SCREEN_WIDTH: i32 = 40;
+
+// What the player learned about a monster, kept across the runs
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Entry {
+    // Kind of the monster the lore is about, for the uniques with names of their own
+    #[serde(default)]
+    pub kind: String,
+    pub glyph: char,
+    pub max_hp: i32,
+    pub power: i32,
+    pub defense: i32,
+    pub killed: u32,
+}
+
+// Every monster the player has ever seen, by name
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Bestiary {
+    pub entries: BTreeMap<String, Entry>,
+}
+
+impl Bestiary {
+    pub fn record_kill(&mut self, name: &str) {
+        if let Some(entry) = self.entries.get_mut(name) {
+            entry.killed += 1;
+        }
+    }
+}
+
+// Count the monsters the player kills
+pub fn record_bestiary(event: &GameEvent, game: &mut Game) {
+    if let GameEvent::Died {
+        name,
+        by_player: true,
+        player: false,
+    } = event
+    {
+        game.bestiary.record_kill(name);
+    }
+}
+
+pub fn save_bestiary(bestiary: &Bestiary) -> Result<(), Box<dyn Error>> {
+    let bestiary_data = serde_json::to_string(bestiary)?;
+    let mut file = File::create(data_file(BESTIARY_FILE))?;
+    file.write_all(bestiary_data.as_bytes())?;
+    Ok(())
+}
+
+pub fn load_bestiary() -> Result<Bestiary, Box<dyn Error>> {
+    let mut json_bestiary = String::new();
+    let mut file = File::open(data_file(BESTIARY_FILE))?;
+    file.read_to_string(&mut json_bestiary)?;
+    let result = serde_json::from_str::<Bestiary>(&json_bestiary)?;
+    Ok(result)
+}
+
+// Note down the monsters in sight, keeping the kill counts of the ones already known
+pub fn note_monsters(ctx: &Context, game: &mut Game, objects: &[Object]) {
+    for (id, object) in objects.iter().enumerate() {
+        let visible = ctx.fov.is_in_fov(object.x, object.y);
+        let monster = object.alive && hostile(object.faction, objects[PLAYER].faction);
+        if id == PLAYER || !monster || !visible {
+            continue;
+        }
+        let entry = Entry {
+            kind: object.kind().into(),
+            glyph: object.char,
+            max_hp: object.max_hp(game),
+            power: object.power(game),
+            defense: object.defense(game),
+            killed: 0,
+        };
+        game.bestiary
+            .entries
+            .entry(object.name.clone())
+            .and_modify(|known| {
+                known.max_hp = entry.max_hp;
+                known.power = entry.power;
+                known.defense = entry.defense;
+            })
+            .or_insert(entry);
+    }
+}
+
+// A line or two of lore about the monster
+pub fn flavor(name: &str) -> &'static str {
+    match name {
+        "goblin" => "Small, cowardly and never alone. Where there is one, the pack is near.",
+        "orc" => "A brute that fights alone and hits hard.",
+        "goblin archer" => "Keeps its distance and shoots whoever comes close.",
+        "wolf" => "Hunts in packs and circles its prey.",
+        "skeleton" => "The dead, walking again. It knows no fear.",
+        "ghoul" => "Feeds on the dead. Its touch numbs the limbs of the living.",
+        "necromancer" => "Raises the dead to fight for it and stays behind them.",
+        "goblin king" => "The ruler of the goblins, never without his guards.",
+        "warlord" => "A veteran of a hundred battles who grows angrier as he bleeds.",
+        "lich" => "A sorcerer who cheated death and feeds on the life of others.",
+        _ => "Little is known about it.",
+    }
+}
+
+pub fn bestiary_screen(ctx: &mut Context, game: &Game) {
+    if game.bestiary.entries.is_empty() {
+        msgbox(
+            &tr("bestiary.no_monsters_met", &[]),
+            BESTIARY_SCREEN_WIDTH,
+            ctx,
+        );
+        return;
+    }
+    let entries = game
+        .bestiary
+        .entries
+        .iter()
+        .take(MAX_MENU_OPTIONS)
+        .collect::<Vec<_>>();
+    let options = entries
+        .iter()
+        .map(|(name, entry)| {
+            tr(
+                "bestiary.entry",
+                &[
+                    ("glyph", &entry.glyph),
+                    ("name", name),
+                    ("killed", &entry.killed),
+                ],
+            )
+        })
+        .collect::<Vec<_>>();
+    let header = tr("bestiary.header", &[]);
+    if let Some(choice) = menu(&header, &options, BESTIARY_SCREEN_WIDTH, ctx) {
+        let (name, entry) = entries[choice];
+        let text = tr(
+            "bestiary.details",
+            &[
+                ("glyph", &entry.glyph),
+                ("name", name),
+                (
+                    "flavor",
+                    &flavor(if entry.kind.is_empty() {
+                        name
+                    } else {
+                        &entry.kind
+                    }),
+                ),
+                ("max_hp", &entry.max_hp),
+                ("power", &entry.power),
+                ("defense", &entry.defense),
+                ("killed", &entry.killed),
+            ],
+        );
+        msgbox(&text, BESTIARY_SCREEN_WIDTH, ctx);
+    }
+}
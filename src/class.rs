@@ -0,0 +1,176 @@
+use serde::{Deserialize, Serialize};
+
+use crate::colors::*;
+
+use crate::equipment::{Equipment, Slot};
+use crate::fighter::{DeathCallback, Fighter, MAX_MORALE};
+use crate::item::Item;
+use crate::lang::tr;
+use crate::object::Object;
+use crate::paths::file_stem;
+use crate::race::Race;
+use crate::render::{confirm, menu, text_input, Context};
+use crate::room::make_item;
+use crate::save::saved_games;
+
+const CLASS_SCREEN_WIDTH: i32 = 50;
+const MAX_NAME_LENGTH: usize = 20;
+
+// What the player starts the game as
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum Class {
+    Warrior,
+    Rogue,
+    Mage,
+}
+
+// The player's choices on the creation screen
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Character {
+    pub name: String,
+    // Older replays were recorded before the races
+    #[serde(default)]
+    pub race: Race,
+    pub class: Class,
+}
+
+impl Class {
+    pub const ALL: [Class; 3] = [Class::Warrior, Class::Rogue, Class::Mage];
+
+    pub fn name(self) -> String {
+        match self {
+            Class::Warrior => tr("class.warrior", &[]),
+            Class::Rogue => tr("class.rogue", &[]),
+            Class::Mage => tr("class.mage", &[]),
+        }
+    }
+
+    pub fn description(self) -> String {
+        match self {
+            Class::Warrior => tr("class.warrior_description", &[]),
+            Class::Rogue => tr("class.rogue_description", &[]),
+            Class::Mage => tr("class.mage_description", &[]),
+        }
+    }
+
+    // Starting stats
+    pub fn fighter(self) -> Fighter {
+        let (hp, defense, power) = match self {
+            Class::Warrior => (120, 1, 4),
+            Class::Rogue => (100, 0, 5),
+            Class::Mage => (70, 0, 3),
+        };
+        Fighter {
+            base_max_hp: hp,
+            hp,
+            base_defense: defense,
+            base_power: power,
+            xp: 0,
+            on_death: DeathCallback::Player,
+            morale: MAX_MORALE,
+        }
+    }
+
+    // Starting equipment, already equipped, and the spells known as scrolls
+    pub fn starting_inventory(self) -> Vec<Object> {
+        match self {
+            Class::Warrior => vec![weapon("sword", '/', Slot::RightHand, 5, 0, 100), shield()],
+            Class::Rogue => vec![
+                weapon("dagger", '-', Slot::LeftHand, 3, 1, 60),
+                make_item(Item::Heal, 0, 0),
+                make_item(Item::Heal, 0, 0),
+            ],
+            Class::Mage => vec![
+                weapon("staff", '/', Slot::RightHand, 2, 0, 100),
+                make_item(Item::Lightning, 0, 0),
+                make_item(Item::Lightning, 0, 0),
+                make_item(Item::Confusion, 0, 0),
+                make_item(Item::Fireball, 0, 0),
+                make_item(Item::AlchemyKit, 0, 0),
+            ],
+        }
+    }
+}
+
+fn weapon(
+    name: &str,
+    glyph: char,
+    slot: Slot,
+    power: i32,
+    defense: i32,
+    attack_delay: i32,
+) -> Object {
+    let mut weapon = Object::new(0, 0, glyph, name, SKY, false);
+    weapon.item = Some(Item::Sword);
+    weapon.equipment = Some(Equipment {
+        equipped: true,
+        slot,
+        max_hp_bonus: 0,
+        power_bonus: power,
+        defense_bonus: defense,
+        evasion_bonus: 0,
+        block_chance: 0,
+        attack_delay,
+        reach: 0,
+        fire_damage: 0,
+        on_hit: None,
+        brand: None,
+        enchantment: 0,
+        digging: false,
+        light_radius: 0,
+    });
+    weapon
+}
+
+fn shield() -> Object {
+    let mut shield = make_item(Item::Shield, 0, 0);
+    if let Some(equipment) = shield.equipment.as_mut() {
+        equipment.equipped = true;
+        equipment.defense_bonus = 2;
+    }
+    shield
+}
+
+// Ask for the name, the race and the class of the new character, None if the player backs out
+pub fn create_character(ctx: &mut Context) -> Option<Character> {
+    // The save is named after the character, a run of the same name would be lost
+    let name = loop {
+        let name = text_input(&tr("class.what_is_your_name", &[]), MAX_NAME_LENGTH, ctx)?;
+        let taken = saved_games().contains(&file_stem(&name));
+        ctx.renderer.clear();
+        if !taken || confirm(&tr("class.overwrite_the_save", &[("name", &name)]), ctx) {
+            break name;
+        }
+        ctx.renderer.clear();
+    };
+
+    ctx.renderer.clear();
+    let options = Race::ALL
+        .iter()
+        .map(|race| format!("{}: {}", race.name(), race.description()))
+        .collect::<Vec<_>>();
+    let race = menu(
+        &tr("class.choose_the_race", &[("name", &name)]),
+        &options,
+        CLASS_SCREEN_WIDTH,
+        ctx,
+    )?;
+
+    ctx.renderer.clear();
+    let options = Class::ALL
+        .iter()
+        .map(|class| format!("{}: {}", class.name(), class.description()))
+        .collect::<Vec<_>>();
+    let choice = menu(
+        &tr("class.choose_the_class", &[("name", &name)]),
+        &options,
+        CLASS_SCREEN_WIDTH,
+        ctx,
+    )?;
+
+    Some(Character {
+        name,
+        race: Race::ALL[race],
+        class: Class::ALL[choice],
+    })
+}
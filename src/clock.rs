@@ -0,0 +1,89 @@
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+
+// How real time affects the game
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum ClockMode {
+    // Only turns count, the time played is shown to the second
+    Off,
+    // A clock runs alongside the turns and is shown to the tenth of a second
+    Speedrun,
+    // Like speedrun, and the monsters act on their own if the player waits too long
+    Ultra,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ClockConfig {
+    pub mode: ClockMode,
+    // Real time the monsters give the player to act in the ultra mode
+    pub ultra_turn_seconds: f32,
+}
+
+impl Default for ClockConfig {
+    fn default() -> Self {
+        ClockConfig {
+            mode: ClockMode::Off,
+            ultra_turn_seconds: 1.0,
+        }
+    }
+}
+
+impl ClockConfig {
+    // How long the player can idle before the monsters act, if they ever do
+    pub fn idle_turn(&self) -> Option<Duration> {
+        match self.mode {
+            ClockMode::Ultra => Some(Duration::from_secs_f32(self.ultra_turn_seconds.max(0.1))),
+            _ => None,
+        }
+    }
+}
+
+// Show milliseconds as minutes, seconds and tenths
+pub fn format_time(millis: u64) -> String {
+    let minutes = millis / 60_000;
+    let seconds = millis / 1000 % 60;
+    let tenths = millis / 100 % 10;
+    format!("{:02}:{:02}.{}", minutes, seconds, tenths)
+}
+
+// Show milliseconds as minutes and seconds
+pub fn format_seconds(millis: u64) -> String {
+    format!("{:02}:{:02}", millis / 60_000, millis / 1000 % 60)
+}
+
+// The time played as the panel shows it, to the tenth of a second when racing the clock
+pub fn format_play_time(millis: u64, mode: ClockMode) -> String {
+    match mode {
+        ClockMode::Off => format_seconds(millis),
+        _ => format_time(millis),
+    }
+}
+
+// Measures the real time between the frames, for whatever runs on real time
+pub struct FrameClock {
+    last_frame: Instant,
+}
+
+impl Default for FrameClock {
+    fn default() -> Self {
+        FrameClock {
+            last_frame: Instant::now(),
+        }
+    }
+}
+
+impl FrameClock {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    // Time since the last frame
+    pub fn tick(&mut self) -> Duration {
+        let now = Instant::now();
+        let delta = now.duration_since(self.last_frame);
+        self.last_frame = now;
+        delta
+    }
+}
@@ -0,0 +1,220 @@
+// The full palette is kept even while only part of it is in use
+#![allow(dead_code)]
+
+use serde::{Deserialize, Serialize};
+
+// An RGB color, independent of the backend drawing it
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct Color {
+    pub r: u8,
+    pub g: u8,
+    pub b: u8,
+}
+
+impl Color {
+    pub const fn new(r: u8, g: u8, b: u8) -> Self {
+        Color { r, g, b }
+    }
+}
+
+// Interpolate between two colors, coefficient 0.0 being `from` and 1.0 being `to`
+pub fn lerp(from: Color, to: Color, coefficient: f32) -> Color {
+    let channel = |a: u8, b: u8| (a as f32 + (b as f32 - a as f32) * coefficient) as u8;
+    Color {
+        r: channel(from.r, to.r),
+        g: channel(from.g, to.g),
+        b: channel(from.b, to.b),
+    }
+}
+
+// The standard palette
+pub const BLACK: Color = Color::new(0, 0, 0);
+pub const DARKEST_GREY: Color = Color::new(31, 31, 31);
+pub const DARKER_GREY: Color = Color::new(63, 63, 63);
+pub const DARK_GREY: Color = Color::new(95, 95, 95);
+pub const GREY: Color = Color::new(127, 127, 127);
+pub const LIGHT_GREY: Color = Color::new(159, 159, 159);
+pub const LIGHTER_GREY: Color = Color::new(191, 191, 191);
+pub const LIGHTEST_GREY: Color = Color::new(223, 223, 223);
+pub const WHITE: Color = Color::new(255, 255, 255);
+pub const DARKEST_SEPIA: Color = Color::new(31, 24, 15);
+pub const DARKER_SEPIA: Color = Color::new(63, 50, 31);
+pub const DARK_SEPIA: Color = Color::new(94, 75, 47);
+pub const SEPIA: Color = Color::new(127, 101, 63);
+pub const LIGHT_SEPIA: Color = Color::new(158, 134, 100);
+pub const LIGHTER_SEPIA: Color = Color::new(191, 171, 143);
+pub const LIGHTEST_SEPIA: Color = Color::new(222, 211, 195);
+pub const DESATURATED_RED: Color = Color::new(127, 63, 63);
+pub const DESATURATED_FLAME: Color = Color::new(127, 79, 63);
+pub const DESATURATED_ORANGE: Color = Color::new(127, 95, 63);
+pub const DESATURATED_AMBER: Color = Color::new(127, 111, 63);
+pub const DESATURATED_YELLOW: Color = Color::new(127, 127, 63);
+pub const DESATURATED_LIME: Color = Color::new(111, 127, 63);
+pub const DESATURATED_CHARTREUSE: Color = Color::new(95, 127, 63);
+pub const DESATURATED_GREEN: Color = Color::new(63, 127, 63);
+pub const DESATURATED_SEA: Color = Color::new(63, 127, 95);
+pub const DESATURATED_TURQUOISE: Color = Color::new(63, 127, 111);
+pub const DESATURATED_CYAN: Color = Color::new(63, 127, 127);
+pub const DESATURATED_SKY: Color = Color::new(63, 111, 127);
+pub const DESATURATED_AZURE: Color = Color::new(63, 95, 127);
+pub const DESATURATED_BLUE: Color = Color::new(63, 63, 127);
+pub const DESATURATED_HAN: Color = Color::new(79, 63, 127);
+pub const DESATURATED_VIOLET: Color = Color::new(95, 63, 127);
+pub const DESATURATED_PURPLE: Color = Color::new(111, 63, 127);
+pub const DESATURATED_FUCHSIA: Color = Color::new(127, 63, 127);
+pub const DESATURATED_MAGENTA: Color = Color::new(127, 63, 111);
+pub const DESATURATED_PINK: Color = Color::new(127, 63, 95);
+pub const DESATURATED_CRIMSON: Color = Color::new(127, 63, 79);
+pub const LIGHTEST_RED: Color = Color::new(255, 191, 191);
+pub const LIGHTEST_FLAME: Color = Color::new(255, 207, 191);
+pub const LIGHTEST_ORANGE: Color = Color::new(255, 223, 191);
+pub const LIGHTEST_AMBER: Color = Color::new(255, 239, 191);
+pub const LIGHTEST_YELLOW: Color = Color::new(255, 255, 191);
+pub const LIGHTEST_LIME: Color = Color::new(239, 255, 191);
+pub const LIGHTEST_CHARTREUSE: Color = Color::new(223, 255, 191);
+pub const LIGHTEST_GREEN: Color = Color::new(191, 255, 191);
+pub const LIGHTEST_SEA: Color = Color::new(191, 255, 223);
+pub const LIGHTEST_TURQUOISE: Color = Color::new(191, 255, 239);
+pub const LIGHTEST_CYAN: Color = Color::new(191, 255, 255);
+pub const LIGHTEST_SKY: Color = Color::new(191, 239, 255);
+pub const LIGHTEST_AZURE: Color = Color::new(191, 223, 255);
+pub const LIGHTEST_BLUE: Color = Color::new(191, 191, 255);
+pub const LIGHTEST_HAN: Color = Color::new(207, 191, 255);
+pub const LIGHTEST_VIOLET: Color = Color::new(223, 191, 255);
+pub const LIGHTEST_PURPLE: Color = Color::new(239, 191, 255);
+pub const LIGHTEST_FUCHSIA: Color = Color::new(255, 191, 255);
+pub const LIGHTEST_MAGENTA: Color = Color::new(255, 191, 239);
+pub const LIGHTEST_PINK: Color = Color::new(255, 191, 223);
+pub const LIGHTEST_CRIMSON: Color = Color::new(255, 191, 207);
+pub const LIGHTER_RED: Color = Color::new(255, 127, 127);
+pub const LIGHTER_FLAME: Color = Color::new(255, 159, 127);
+pub const LIGHTER_ORANGE: Color = Color::new(255, 191, 127);
+pub const LIGHTER_AMBER: Color = Color::new(255, 223, 127);
+pub const LIGHTER_YELLOW: Color = Color::new(255, 255, 127);
+pub const LIGHTER_LIME: Color = Color::new(223, 255, 127);
+pub const LIGHTER_CHARTREUSE: Color = Color::new(191, 255, 127);
+pub const LIGHTER_GREEN: Color = Color::new(127, 255, 127);
+pub const LIGHTER_SEA: Color = Color::new(127, 255, 191);
+pub const LIGHTER_TURQUOISE: Color = Color::new(127, 255, 223);
+pub const LIGHTER_CYAN: Color = Color::new(127, 255, 255);
+pub const LIGHTER_SKY: Color = Color::new(127, 223, 255);
+pub const LIGHTER_AZURE: Color = Color::new(127, 191, 255);
+pub const LIGHTER_BLUE: Color = Color::new(127, 127, 255);
+pub const LIGHTER_HAN: Color = Color::new(159, 127, 255);
+pub const LIGHTER_VIOLET: Color = Color::new(191, 127, 255);
+pub const LIGHTER_PURPLE: Color = Color::new(223, 127, 255);
+pub const LIGHTER_FUCHSIA: Color = Color::new(255, 127, 255);
+pub const LIGHTER_MAGENTA: Color = Color::new(255, 127, 223);
+pub const LIGHTER_PINK: Color = Color::new(255, 127, 191);
+pub const LIGHTER_CRIMSON: Color = Color::new(255, 127, 159);
+pub const LIGHT_RED: Color = Color::new(255, 63, 63);
+pub const LIGHT_FLAME: Color = Color::new(255, 111, 63);
+pub const LIGHT_ORANGE: Color = Color::new(255, 159, 63);
+pub const LIGHT_AMBER: Color = Color::new(255, 207, 63);
+pub const LIGHT_YELLOW: Color = Color::new(255, 255, 63);
+pub const LIGHT_LIME: Color = Color::new(207, 255, 63);
+pub const LIGHT_CHARTREUSE: Color = Color::new(159, 255, 63);
+pub const LIGHT_GREEN: Color = Color::new(63, 255, 63);
+pub const LIGHT_SEA: Color = Color::new(63, 255, 159);
+pub const LIGHT_TURQUOISE: Color = Color::new(63, 255, 207);
+pub const LIGHT_CYAN: Color = Color::new(63, 255, 255);
+pub const LIGHT_SKY: Color = Color::new(63, 207, 255);
+pub const LIGHT_AZURE: Color = Color::new(63, 159, 255);
+pub const LIGHT_BLUE: Color = Color::new(63, 63, 255);
+pub const LIGHT_HAN: Color = Color::new(111, 63, 255);
+pub const LIGHT_VIOLET: Color = Color::new(159, 63, 255);
+pub const LIGHT_PURPLE: Color = Color::new(207, 63, 255);
+pub const LIGHT_FUCHSIA: Color = Color::new(255, 63, 255);
+pub const LIGHT_MAGENTA: Color = Color::new(255, 63, 207);
+pub const LIGHT_PINK: Color = Color::new(255, 63, 159);
+pub const LIGHT_CRIMSON: Color = Color::new(255, 63, 111);
+pub const RED: Color = Color::new(255, 0, 0);
+pub const FLAME: Color = Color::new(255, 63, 0);
+pub const ORANGE: Color = Color::new(255, 127, 0);
+pub const AMBER: Color = Color::new(255, 191, 0);
+pub const YELLOW: Color = Color::new(255, 255, 0);
+pub const LIME: Color = Color::new(191, 255, 0);
+pub const CHARTREUSE: Color = Color::new(127, 255, 0);
+pub const GREEN: Color = Color::new(0, 255, 0);
+pub const SEA: Color = Color::new(0, 255, 127);
+pub const TURQUOISE: Color = Color::new(0, 255, 191);
+pub const CYAN: Color = Color::new(0, 255, 255);
+pub const SKY: Color = Color::new(0, 191, 255);
+pub const AZURE: Color = Color::new(0, 127, 255);
+pub const BLUE: Color = Color::new(0, 0, 255);
+pub const HAN: Color = Color::new(63, 0, 255);
+pub const VIOLET: Color = Color::new(127, 0, 255);
+pub const PURPLE: Color = Color::new(191, 0, 255);
+pub const FUCHSIA: Color = Color::new(255, 0, 255);
+pub const MAGENTA: Color = Color::new(255, 0, 191);
+pub const PINK: Color = Color::new(255, 0, 127);
+pub const CRIMSON: Color = Color::new(255, 0, 63);
+pub const DARK_RED: Color = Color::new(191, 0, 0);
+pub const DARK_FLAME: Color = Color::new(191, 47, 0);
+pub const DARK_ORANGE: Color = Color::new(191, 95, 0);
+pub const DARK_AMBER: Color = Color::new(191, 143, 0);
+pub const DARK_YELLOW: Color = Color::new(191, 191, 0);
+pub const DARK_LIME: Color = Color::new(143, 191, 0);
+pub const DARK_CHARTREUSE: Color = Color::new(95, 191, 0);
+pub const DARK_GREEN: Color = Color::new(0, 191, 0);
+pub const DARK_SEA: Color = Color::new(0, 191, 95);
+pub const DARK_TURQUOISE: Color = Color::new(0, 191, 143);
+pub const DARK_CYAN: Color = Color::new(0, 191, 191);
+pub const DARK_SKY: Color = Color::new(0, 143, 191);
+pub const DARK_AZURE: Color = Color::new(0, 95, 191);
+pub const DARK_BLUE: Color = Color::new(0, 0, 191);
+pub const DARK_HAN: Color = Color::new(47, 0, 191);
+pub const DARK_VIOLET: Color = Color::new(95, 0, 191);
+pub const DARK_PURPLE: Color = Color::new(143, 0, 191);
+pub const DARK_FUCHSIA: Color = Color::new(191, 0, 191);
+pub const DARK_MAGENTA: Color = Color::new(191, 0, 143);
+pub const DARK_PINK: Color = Color::new(191, 0, 95);
+pub const DARK_CRIMSON: Color = Color::new(191, 0, 47);
+pub const DARKER_RED: Color = Color::new(127, 0, 0);
+pub const DARKER_FLAME: Color = Color::new(127, 31, 0);
+pub const DARKER_ORANGE: Color = Color::new(127, 63, 0);
+pub const DARKER_AMBER: Color = Color::new(127, 95, 0);
+pub const DARKER_YELLOW: Color = Color::new(127, 127, 0);
+pub const DARKER_LIME: Color = Color::new(95, 127, 0);
+pub const DARKER_CHARTREUSE: Color = Color::new(63, 127, 0);
+pub const DARKER_GREEN: Color = Color::new(0, 127, 0);
+pub const DARKER_SEA: Color = Color::new(0, 127, 63);
+pub const DARKER_TURQUOISE: Color = Color::new(0, 127, 95);
+pub const DARKER_CYAN: Color = Color::new(0, 127, 127);
+pub const DARKER_SKY: Color = Color::new(0, 95, 127);
+pub const DARKER_AZURE: Color = Color::new(0, 63, 127);
+pub const DARKER_BLUE: Color = Color::new(0, 0, 127);
+pub const DARKER_HAN: Color = Color::new(31, 0, 127);
+pub const DARKER_VIOLET: Color = Color::new(63, 0, 127);
+pub const DARKER_PURPLE: Color = Color::new(95, 0, 127);
+pub const DARKER_FUCHSIA: Color = Color::new(127, 0, 127);
+pub const DARKER_MAGENTA: Color = Color::new(127, 0, 95);
+pub const DARKER_PINK: Color = Color::new(127, 0, 63);
+pub const DARKER_CRIMSON: Color = Color::new(127, 0, 31);
+pub const DARKEST_RED: Color = Color::new(63, 0, 0);
+pub const DARKEST_FLAME: Color = Color::new(63, 15, 0);
+pub const DARKEST_ORANGE: Color = Color::new(63, 31, 0);
+pub const DARKEST_AMBER: Color = Color::new(63, 47, 0);
+pub const DARKEST_YELLOW: Color = Color::new(63, 63, 0);
+pub const DARKEST_LIME: Color = Color::new(47, 63, 0);
+pub const DARKEST_CHARTREUSE: Color = Color::new(31, 63, 0);
+pub const DARKEST_GREEN: Color = Color::new(0, 63, 0);
+pub const DARKEST_SEA: Color = Color::new(0, 63, 31);
+pub const DARKEST_TURQUOISE: Color = Color::new(0, 63, 47);
+pub const DARKEST_CYAN: Color = Color::new(0, 63, 63);
+pub const DARKEST_SKY: Color = Color::new(0, 47, 63);
+pub const DARKEST_AZURE: Color = Color::new(0, 31, 63);
+pub const DARKEST_BLUE: Color = Color::new(0, 0, 63);
+pub const DARKEST_HAN: Color = Color::new(15, 0, 63);
+pub const DARKEST_VIOLET: Color = Color::new(31, 0, 63);
+pub const DARKEST_PURPLE: Color = Color::new(47, 0, 63);
+pub const DARKEST_FUCHSIA: Color = Color::new(63, 0, 63);
+pub const DARKEST_MAGENTA: Color = Color::new(63, 0, 47);
+pub const DARKEST_PINK: Color = Color::new(63, 0, 31);
+pub const DARKEST_CRIMSON: Color = Color::new(63, 0, 15);
+pub const BRASS: Color = Color::new(191, 151, 96);
+pub const COPPER: Color = Color::new(197, 136, 124);
+pub const GOLD: Color = Color::new(229, 191, 0);
+pub const SILVER: Color = Color::new(203, 203, 203);
+pub const CELADON: Color = Color::new(172, 255, 175);
+pub const PEACH: Color = Color::new(255, 159, 127);
@@ -0,0 +1,254 @@
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::colors::*;
+
+use crate::action::interrupt_delayed;
+use crate::behavior::MELEE_REACH;
+use crate::faction::hostile;
+use crate::fov::line;
+use crate::game::{mut_two, Game, Severity, PLAYER};
+use crate::lang::tr;
+use crate::object::{handle_of, Object};
+use crate::perk::Perk;
+use crate::status::{apply_effect, Effect, Status};
+
+const BLOODLUST_HEAL: i32 = 5;
+// How much harder the player hits the monsters that don't see it coming
+const BACKSTAB_MULTIPLIER: i32 = 3;
+
+// What a weapon does to whoever it hits on top of the blow
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum Brand {
+    // Burns for the damage, which no armor stops
+    Flaming(i32),
+    // Poisons the target for the turns
+    Venomous(u32),
+    // Heals the attacker by the percent of the damage dealt
+    Vampiric(i32),
+}
+
+impl Brand {
+    pub fn name(self) -> String {
+        match self {
+            Brand::Flaming(damage) => tr("combat.flaming", &[("damage", &damage)]),
+            Brand::Venomous(turns) => tr("combat.venomous", &[("turns", &turns)]),
+            Brand::Vampiric(percent) => tr("combat.vampiric", &[("percent", &percent)]),
+        }
+    }
+
+    pub fn fire_damage(self) -> i32 {
+        match self {
+            Brand::Flaming(damage) => damage,
+            _ => 0,
+        }
+    }
+}
+
+// Distance the blows of the reach land closer than: the tiles next to the attacker
+// for a reach of 1, and a ring farther for every tile more
+pub fn reach_distance(reach: i32) -> f32 {
+    MELEE_REACH + (reach - 1) as f32
+}
+
+// Whether the attacker can hit the target from where it stands: next to it, or with
+// a polearm over tiles that are free or hold someone not hostile to the attacker
+pub fn in_reach(attacker: usize, target: usize, game: &Game, objects: &[Object]) -> bool {
+    let reach = reach_distance(objects[attacker].reach(game));
+    if objects[attacker].distance_to(&objects[target]) >= reach {
+        return false;
+    }
+    let to = objects[target].pos();
+    line(objects[attacker].pos(), to)
+        .into_iter()
+        .take_while(|&tile| tile != to)
+        .all(|(x, y)| {
+            let tile = &game.map[(x, y)];
+            !tile.blocked
+                && !tile.block_sight
+                && objects.iter().all(|object| {
+                    !object.blocks
+                        || object.pos() != (x, y)
+                        || object.fighter.is_some()
+                            && !hostile(object.faction, objects[attacker].faction)
+                })
+        })
+}
+
+// The player hits the enemy on the tile from afar, as long as it's still in reach
+pub fn strike(x: i32, y: i32, game: &mut Game, objects: &mut [Object]) {
+    let target = objects
+        .iter()
+        .enumerate()
+        .position(|(id, object)| id != PLAYER && object.alive && object.pos() == (x, y));
+    match target {
+        Some(id) if objects[id].fighter.is_some() && in_reach(PLAYER, id, game, objects) => {
+            game.last_target = Some(handle_of(id, game, objects));
+            let (monster, player) = mut_two(id, PLAYER, objects);
+            player.attack(monster, game);
+        }
+        _ => game
+            .messages
+            .add(tr("combat.out_of_reach", &[]), LIGHT_GREY),
+    }
+}
+
+// A blow as it's worked out before it lands
+struct Hit {
+    damage: i32,
+    backstab: bool,
+    // Statuses it puts on the target, from the weapons and the attacker itself
+    effects: Vec<Effect>,
+    // Percent of the damage the attacker gets back as health
+    drain: i32,
+}
+
+// The attacker hits the target, worked out in steps: the damage with everything
+// the weapons add to it, telling about it, and what it does once it lands
+pub fn resolve_attack(attacker: &mut Object, target: &mut Object, game: &mut Game) {
+    if attacker.race(game).is_some() {
+        // Whoever the player hits knows where they are from then on
+        target.last_seen = Some(attacker.pos());
+    }
+    // Nobody sees the blow coming when they're unaware of the attacker
+    if !(attacker.race(game).is_some() && target.unaware()) && avoided(attacker, target, game) {
+        return;
+    }
+    let hit = roll_hit(attacker, target, game);
+    if hit.damage <= 0 {
+        game.messages.report(
+            tr(
+                "object.failed_to_scratch",
+                &[("attacker", &attacker.name), ("target", &target.name)],
+            ),
+            Severity::Info,
+        );
+        return;
+    }
+    report_hit(attacker, target, &hit, game);
+    land_hit(attacker, target, hit, game);
+}
+
+// The target dodges the blow, or stops it with the shield, before the armor
+// takes anything off what gets through
+fn avoided(attacker: &Object, target: &Object, game: &mut Game) -> bool {
+    let evasion = target.evasion(game);
+    let block = target.block_chance(game);
+    let message = if evasion > 0 && game.rng.gen_range(0, 100) < evasion {
+        "combat.misses"
+    } else if block > 0 && game.rng.gen_range(0, 100) < block {
+        "combat.blocked"
+    } else {
+        return false;
+    };
+    // The player getting out of harm's way is good news
+    let severity = if target.race(game).is_some() {
+        Severity::Good
+    } else {
+        Severity::Info
+    };
+    game.messages.report(
+        tr(
+            message,
+            &[("attacker", &attacker.name), ("target", &target.name)],
+        ),
+        severity,
+    );
+    true
+}
+
+fn roll_hit(attacker: &Object, target: &Object, game: &mut Game) -> Hit {
+    let backstab = attacker.race(game).is_some() && target.unaware();
+    // Fire burns through the armor
+    let mut damage =
+        (attacker.power(game) - target.defense(game)).max(0) + attacker.fire_damage(game);
+    if backstab {
+        damage *= BACKSTAB_MULTIPLIER;
+    }
+
+    let equipped = attacker.get_all_equipped(game);
+    let mut effects = equipped
+        .iter()
+        .filter_map(|e| e.on_hit)
+        .chain(attacker.on_hit)
+        .collect::<Vec<_>>();
+    let mut drain = 0;
+    for brand in equipped.iter().filter_map(|e| e.brand) {
+        match brand {
+            // Counted in the fire damage already
+            Brand::Flaming(_) => {}
+            Brand::Venomous(turns) => effects.push(Effect {
+                status: Status::Poison,
+                turns,
+            }),
+            Brand::Vampiric(percent) => drain += percent,
+        }
+    }
+    Hit {
+        damage,
+        backstab,
+        effects,
+        drain,
+    }
+}
+
+fn report_hit(attacker: &Object, target: &Object, hit: &Hit, game: &mut Game) {
+    if hit.backstab {
+        game.messages.add(
+            tr(
+                "object.you_strike_unawares_for_damage",
+                &[("target", &target.name), ("damage", &hit.damage)],
+            ),
+            ORANGE,
+        );
+        return;
+    }
+    // The player getting hurt matters more than anyone else
+    let severity = if target.race(game).is_some() {
+        Severity::Danger
+    } else {
+        Severity::Info
+    };
+    game.messages.report(
+        tr(
+            "object.gets_damage_from",
+            &[
+                ("target", &target.name),
+                ("damage", &hit.damage),
+                ("attacker", &attacker.name),
+            ],
+        ),
+        severity,
+    );
+}
+
+fn land_hit(attacker: &mut Object, target: &mut Object, hit: Hit, game: &mut Game) {
+    let by_player = attacker.race(game).is_some();
+    if target.race(game).is_some() {
+        // Getting hurt breaks the player's concentration
+        interrupt_delayed(game);
+    }
+    for effect in hit.effects {
+        apply_effect(target, effect, game);
+    }
+    // Only the health the target had left can be drained
+    if hit.drain > 0 {
+        let hp = target.fighter.map_or(0, |f| f.hp);
+        let drained = (hit.damage.min(hp) * hit.drain / 100).max(1);
+        attacker.heal(drained, game);
+        game.messages.add(
+            tr(
+                "combat.drains_life",
+                &[("attacker", &attacker.name), ("target", &target.name)],
+            ),
+            DARK_RED,
+        );
+    }
+    if let Some(xp) = target.take_damage(hit.damage, by_player, game) {
+        // Reward killer with experience
+        attacker.fighter.as_mut().unwrap().xp += xp;
+        if attacker.perks.contains(&Perk::Bloodlust) {
+            attacker.heal(BLOODLUST_HEAL, game);
+        }
+    }
+}
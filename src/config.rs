@@ -0,0 +1,181 @@
+use std::error::Error;
+use std::fs::File;
+
+use std::io::{Read, Write};
+
+use serde::{Deserialize, Serialize};
+
+use crate::audio::VolumeConfig;
+use crate::backend::FPS_LIMIT;
+use crate::clock::ClockConfig;
+use crate::fonts::DEFAULT_FONT;
+use crate::glyphs::GlyphConfig;
+use crate::lang::{tr, DEFAULT_LANGUAGE};
+use crate::narrate::NarrationConfig;
+use crate::paths::config_file;
+use crate::scheme::ColorScheme;
+
+const CONFIG_FILE: &str = "config.json";
+
+// Which backend draws the game when more than one is compiled in
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum BackendKind {
+    Tcod,
+    Terminal,
+}
+
+// How the map and objects are presented
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum RenderMode {
+    Ascii,
+    Tiles,
+}
+
+// Sprite sheet used by the tiles render mode:
+// the text glyphs in the first 16 rows, sprites below them
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TilesetConfig {
+    pub path: String,
+    pub columns: i32,
+    pub rows: i32,
+}
+
+impl Default for TilesetConfig {
+    fn default() -> Self {
+        TilesetConfig {
+            path: "tileset.png".into(),
+            columns: 16,
+            rows: 32,
+        }
+    }
+}
+
+// Optional rules of the game, fixed for the whole run when it starts
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RulesConfig {
+    // Whether anyone can slip diagonally between two corners of the walls
+    pub corner_cutting: bool,
+}
+
+// How much the message log tells
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum Verbosity {
+    Full,
+    // Leaves out the flavour text: the ambience and the time of the day
+    Brief,
+}
+
+impl Verbosity {
+    pub fn name(self) -> String {
+        match self {
+            Verbosity::Full => tr("config.full", &[]),
+            Verbosity::Brief => tr("config.brief", &[]),
+        }
+    }
+
+    pub fn next(self) -> Self {
+        match self {
+            Verbosity::Full => Verbosity::Brief,
+            Verbosity::Brief => Verbosity::Full,
+        }
+    }
+}
+
+// Settings the player can change from the options screen at any time
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct OptionsConfig {
+    pub fullscreen: bool,
+    pub fps: i32,
+    pub verbosity: Verbosity,
+    // Walking onto an item picks it up
+    pub auto_pickup: bool,
+    // Ask before stepping onto a trap in sight
+    pub confirm: bool,
+    pub volume: VolumeConfig,
+    // Catalog the texts are taken from
+    pub language: String,
+    pub colors: ColorScheme,
+    // Font of the text, unless the tileset is drawn, and how many times it's scaled up
+    pub font: String,
+    pub font_scale: u32,
+    // Tint the tiles the enemies in sight could hit on their next turn
+    pub threats: bool,
+}
+
+impl Default for OptionsConfig {
+    fn default() -> Self {
+        OptionsConfig {
+            fullscreen: false,
+            fps: FPS_LIMIT,
+            verbosity: Verbosity::Full,
+            auto_pickup: false,
+            confirm: true,
+            volume: VolumeConfig::default(),
+            language: DEFAULT_LANGUAGE.into(),
+            colors: ColorScheme::Standard,
+            font: DEFAULT_FONT.into(),
+            font_scale: 1,
+            threats: false,
+        }
+    }
+}
+
+// User settings read from the config file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    pub backend: BackendKind,
+    pub render_mode: RenderMode,
+    pub tileset: TilesetConfig,
+    pub glyphs: GlyphConfig,
+    pub clock: ClockConfig,
+    pub rules: RulesConfig,
+    pub options: OptionsConfig,
+    pub narration: NarrationConfig,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            backend: BackendKind::Tcod,
+            render_mode: RenderMode::Ascii,
+            tileset: TilesetConfig::default(),
+            glyphs: GlyphConfig::default(),
+            clock: ClockConfig::default(),
+            rules: RulesConfig::default(),
+            options: OptionsConfig::default(),
+            narration: NarrationConfig::default(),
+        }
+    }
+}
+
+fn read_config() -> Result<Config, Box<dyn Error>> {
+    let mut json_config = String::new();
+    let mut file = File::open(config_file(CONFIG_FILE))?;
+    file.read_to_string(&mut json_config)?;
+    let result = serde_json::from_str::<Config>(&json_config)?;
+    Ok(result)
+}
+
+// Load the config file, falling back to the defaults if it's missing or broken
+pub fn load_config() -> Config {
+    read_config().unwrap_or_default()
+}
+
+// Keep the options in the config file, leaving the rest of it as it was.
+// A broken config file is left alone rather than overwritten with the defaults
+pub fn save_options(options: &OptionsConfig) -> Result<(), Box<dyn Error>> {
+    let mut config = match read_config() {
+        Ok(config) => config,
+        Err(_) if !config_file(CONFIG_FILE).exists() => Config::default(),
+        Err(e) => return Err(e),
+    };
+    config.options = options.clone();
+    let config_data = serde_json::to_string_pretty(&config)?;
+    let mut file = File::create(config_file(CONFIG_FILE))?;
+    file.write_all(config_data.as_bytes())?;
+    Ok(())
+}
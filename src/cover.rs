@@ -0,0 +1,38 @@
+use rand::Rng;
+
+use crate::fov::line;
+use crate::game::{Game, Terrain};
+use crate::object::Object;
+
+// Chance in percent of a shot or bolt missing a target in cover
+const COVER_MISS_CHANCE: i32 = 40;
+
+// Whether the target hides in the tall grass, or behind rubble or something solid
+// on the side the attack comes from
+pub fn in_cover(from: (i32, i32), target: (i32, i32), game: &Game, objects: &[Object]) -> bool {
+    let (x, y) = target;
+    if game.map[(x, y)].terrain == Terrain::Foliage {
+        return true;
+    }
+    match line(target, from).first() {
+        Some(&(cx, cy)) if (cx, cy) != from => {
+            let terrain = game.map[(cx, cy)].terrain;
+            terrain == Terrain::Rubble
+                || terrain == Terrain::Foliage
+                || objects
+                    .iter()
+                    .any(|o| o.blocks && o.fighter.is_none() && o.pos() == (cx, cy))
+        }
+        _ => false,
+    }
+}
+
+// Roll whether the ranged attack hits the cover rather than the target
+pub fn misses_in_cover(
+    from: (i32, i32),
+    target: (i32, i32),
+    game: &mut Game,
+    objects: &[Object],
+) -> bool {
+    in_cover(from, target, game, objects) && game.rng.gen_range(0, 100) < COVER_MISS_CHANCE
+}
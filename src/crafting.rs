@@ -0,0 +1,156 @@
+use std::error::Error;
+
+use serde::{Deserialize, Serialize};
+
+use crate::colors::*;
+
+use crate::action::Action;
+use crate::affix::imbue;
+use crate::game::Game;
+use crate::item::{add_to_inventory, Item};
+use crate::lang::tr;
+use crate::mods::read_data;
+use crate::render::{inventory_menu, Context};
+use crate::room::make_item;
+
+const RECIPES_FILE: &str = "data/recipes.json";
+
+// What comes out of mixing the ingredients
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum Outcome {
+    // A new item taking the place of both ingredients
+    Item(Item),
+    // The affix of that name put on the equipment, using up the other ingredient
+    Affix(String),
+}
+
+// Two items that make something together, in either order
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Recipe {
+    pub ingredients: (Item, Item),
+    pub outcome: Outcome,
+}
+
+impl Recipe {
+    fn matches(&self, first: &Item, second: &Item) -> bool {
+        let (a, b) = &self.ingredients;
+        (a == first && b == second) || (a == second && b == first)
+    }
+
+    fn describe(&self) -> String {
+        let (a, b) = &self.ingredients;
+        let result = match &self.outcome {
+            Outcome::Item(item) => make_item(item.clone(), 0, 0).name,
+            Outcome::Affix(affix) => format!("{} {}", affix, make_item(b.clone(), 0, 0).name),
+        };
+        format!(
+            "{} + {} = {}",
+            make_item(a.clone(), 0, 0).name,
+            make_item(b.clone(), 0, 0).name,
+            result
+        )
+    }
+}
+
+pub fn load_recipes() -> Result<Vec<Recipe>, Box<dyn Error>> {
+    let result = serde_json::from_value::<Vec<Recipe>>(read_data(RECIPES_FILE)?)?;
+    Ok(result)
+}
+
+// Ask for the two items to mix with the alchemy kit, showing the recipes found so far
+pub fn craft_menu(ctx: &mut Context, game: &mut Game) -> Option<Action> {
+    if game.recipes.is_empty() {
+        game.recipes = load_recipes().unwrap_or_default();
+    }
+    let mut header = tr("crafting.choose_the_first_ingredient", &[]);
+    for &known in &game.known_recipes {
+        header.push_str(&format!("{}\n", game.recipes[known].describe()));
+    }
+    let first = inventory_menu(&game.inventory, &header, ctx)?;
+    let header = tr(
+        "crafting.mix_with",
+        &[("item", &game.inventory[first].name)],
+    );
+    let second = inventory_menu(&game.inventory, &header, ctx)?;
+    if first == second {
+        game.messages
+            .add(tr("crafting.you_cant_mix_an_item_with", &[]), LIGHT_GREY);
+        return None;
+    }
+    Some(Action::Craft { first, second })
+}
+
+// Mix the two inventory items following the recipe for them, if there is one
+pub fn craft(first: usize, second: usize, game: &mut Game) {
+    if game.recipes.is_empty() {
+        game.recipes = load_recipes().unwrap_or_default();
+    }
+    let items = (
+        game.inventory[first].item.clone(),
+        game.inventory[second].item.clone(),
+    );
+    let recipe = match items {
+        (Some(a), Some(b)) => game.recipes.iter().position(|r| r.matches(&a, &b)),
+        _ => None,
+    };
+    let recipe = match recipe {
+        Some(recipe) => recipe,
+        None => {
+            game.messages
+                .add(tr("crafting.nothing_comes_of_mixing_them", &[]), LIGHT_GREY);
+            return;
+        }
+    };
+
+    match game.recipes[recipe].outcome.clone() {
+        Outcome::Item(item) => {
+            // The later one first, so that the other keeps its index
+            game.inventory.remove(first.max(second));
+            game.inventory.remove(first.min(second));
+            let result = make_item(item, 0, 0);
+            game.messages.add(
+                tr("crafting.you_brew", &[("result", &result.name)]),
+                LIGHT_GREEN,
+            );
+            add_to_inventory(result, game);
+        }
+        Outcome::Affix(affix) => {
+            // The equipment is whichever of them the recipe names second
+            let (_, base) = &game.recipes[recipe].ingredients;
+            let (base, used) = if game.inventory[second].item.as_ref() == Some(base) {
+                (second, first)
+            } else {
+                (first, second)
+            };
+            if !imbue(&mut game.inventory[base], &affix) {
+                game.messages.add(
+                    tr(
+                        "crafting.the_resists_the_essence",
+                        &[("item", &game.inventory[base].name)],
+                    ),
+                    LIGHT_GREY,
+                );
+                return;
+            }
+            game.messages.add(
+                tr(
+                    "crafting.you_make_the",
+                    &[("item", &game.inventory[base].name)],
+                ),
+                LIGHT_GREEN,
+            );
+            game.inventory.remove(used);
+        }
+    }
+
+    if !game.known_recipes.contains(&recipe) {
+        game.known_recipes.push(recipe);
+        game.messages.add(
+            tr(
+                "crafting.you_discovered_a_recipe",
+                &[("recipe", &game.recipes[recipe].describe())],
+            ),
+            YELLOW,
+        );
+    }
+}
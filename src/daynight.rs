@@ -0,0 +1,98 @@
+use crate::colors::*;
+
+use crate::config::Verbosity;
+use crate::game::Game;
+use crate::lang::tr;
+use crate::render::Context;
+
+// Turns a whole day takes, and where in it the run starts, a little after the dawn
+const DAY_LENGTH: u32 = 1000;
+const START_OF_RUN: u32 = 150;
+// Turns of the day the phases begin at
+const DAY_START: u32 = 100;
+const DUSK_START: u32 = 500;
+const NIGHT_START: u32 = 600;
+// How far the player sees on the surface at night without a light
+pub const NIGHT_SIGHT_RADIUS: i32 = 4;
+// How much more often the nocturnal monsters show up at night
+const NIGHT_SPAWN_FACTOR: u32 = 3;
+
+// Part of the day, as told by the clock that runs through the whole game
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Phase {
+    Dawn,
+    Day,
+    Dusk,
+    Night,
+}
+
+impl Phase {
+    // Part of the day the turn of the run falls on
+    pub fn of(turns: u32) -> Self {
+        match (turns + START_OF_RUN) % DAY_LENGTH {
+            time if time < DAY_START => Phase::Dawn,
+            time if time < DUSK_START => Phase::Day,
+            time if time < NIGHT_START => Phase::Dusk,
+            _ => Phase::Night,
+        }
+    }
+
+    pub fn name(self) -> String {
+        match self {
+            Phase::Dawn => tr("daynight.dawn", &[]),
+            Phase::Day => tr("daynight.day", &[]),
+            Phase::Dusk => tr("daynight.dusk", &[]),
+            Phase::Night => tr("daynight.night", &[]),
+        }
+    }
+
+    pub fn color(self) -> Color {
+        match self {
+            Phase::Dawn => LIGHT_PINK,
+            Phase::Day => LIGHT_YELLOW,
+            Phase::Dusk => LIGHT_ORANGE,
+            Phase::Night => LIGHT_BLUE,
+        }
+    }
+
+    // What the player is told when the phase begins
+    fn announcement(self) -> String {
+        match self {
+            Phase::Dawn => tr("daynight.dawn_breaks", &[]),
+            Phase::Day => tr("daynight.the_sun_is_up", &[]),
+            Phase::Dusk => tr("daynight.the_sun_is_setting", &[]),
+            Phase::Night => tr("daynight.night_falls", &[]),
+        }
+    }
+}
+
+pub fn phase(game: &Game) -> Phase {
+    Phase::of(game.stats.turns)
+}
+
+pub fn is_night(game: &Game) -> bool {
+    phase(game) == Phase::Night
+}
+
+// Monsters that come out when it's dark
+pub fn nocturnal(monster: &str) -> bool {
+    ["wolf", "skeleton", "ghoul", "necromancer"].contains(&monster)
+}
+
+// Raise the weight of the nocturnal monsters at night
+pub fn night_weight(monster: &str, weight: u32, night: bool) -> u32 {
+    if night && nocturnal(monster) {
+        weight * NIGHT_SPAWN_FACTOR
+    } else {
+        weight
+    }
+}
+
+// Tell the player when the next part of the day begins
+pub fn tick_clock(ctx: &Context, game: &mut Game) {
+    let now = phase(game);
+    let verbose = ctx.options.verbosity == Verbosity::Full;
+    if verbose && game.stats.turns > 0 && now != Phase::of(game.stats.turns - 1) {
+        game.messages.add(now.announcement(), now.color());
+    }
+}
@@ -0,0 +1,479 @@
+use std::error::Error;
+
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::colors::*;
+
+use crate::action::Action;
+use crate::daynight::is_night;
+use crate::errors::log_error;
+use crate::game::{is_blocked, Game, Map, PLAYER};
+use crate::hireling::hire;
+use crate::item::{add_to_inventory, category, Category, Item};
+use crate::lang::tr;
+use crate::mods::read_data;
+use crate::object::Object;
+use crate::render::{inventory_menu, menu, Context, MAX_MENU_OPTIONS};
+use crate::rng::GameRng;
+use crate::room::{make_item, Rect};
+
+const DIALOGUES_FILE: &str = "data/dialogues.json";
+const DIALOGUE_WIDTH: i32 = 50;
+// Gold for the first improvement of an item, growing with the square of the ones done,
+// which are safe until the item reaches the level, and then fail more and more often
+const UPGRADE_GOLD: u32 = 30;
+const SAFE_UPGRADES: i32 = 3;
+const FAILURE_PER_UPGRADE: i32 = 15;
+// Every point of liking takes that many percent off the price of the goods,
+// or puts it on for the npcs that dislike the player
+const DISCOUNT_PER_RELATIONSHIP: i32 = 10;
+const MAX_DISCOUNT: i32 = 50;
+// How much the npc likes the player for doing the quest
+const QUEST_RELATIONSHIP: i32 = 2;
+
+// A friendly character met on a dungeon level and everything they can say
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Dialogue {
+    pub npc: String,
+    pub glyph: char,
+    pub level: u32,
+    // The conversation starts at the first node
+    pub nodes: Vec<Node>,
+    // What the npc trades, if they have a shop
+    #[serde(default)]
+    pub goods: Vec<Item>,
+    // Won't trade or work between the dusk and the dawn
+    #[serde(default)]
+    pub closes_at_night: bool,
+    // The quests the npc gives, started by the choices of the same name
+    #[serde(default)]
+    pub quests: Vec<Quest>,
+}
+
+// Killing the monster of the name does the quest, for the gold
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Quest {
+    pub name: String,
+    pub slay: String,
+    #[serde(default)]
+    pub gold: u32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Node {
+    pub id: String,
+    pub text: String,
+    pub choices: Vec<Choice>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Choice {
+    pub text: String,
+    // Node the conversation goes on with, it ends without one
+    #[serde(default)]
+    pub next: Option<String>,
+    #[serde(default)]
+    pub hook: Option<Hook>,
+}
+
+// What choosing the answer does to the game, ending the conversation
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum Hook {
+    StartQuest(String),
+    OpenShop,
+    // Change how much the npc likes the player
+    Relationship(i32),
+    // Improve one of the player's items for gold and ore
+    Forge,
+    // Join the player for the price, asking for the wage every payday
+    Hire { price: u32, wage: u32 },
+}
+
+impl Dialogue {
+    pub fn spawn(&self, x: i32, y: i32) -> Object {
+        let mut npc = Object::new(x, y, self.glyph, &self.npc, LIGHT_AMBER, true);
+        npc.dialogue = Some(self.npc.clone());
+        npc.alive = true;
+        npc
+    }
+
+    fn node(&self, id: &str) -> Option<usize> {
+        self.nodes.iter().position(|node| node.id == id)
+    }
+}
+
+pub fn load_dialogues() -> Result<Vec<Dialogue>, Box<dyn Error>> {
+    let mut result = serde_json::from_value::<Vec<Dialogue>>(read_data(DIALOGUES_FILE)?)?;
+    // A menu has no more options than there are letters, the rest are left out
+    for dialogue in &mut result {
+        if dialogue.goods.len() > MAX_MENU_OPTIONS {
+            log_error(&format!(
+                "The {} has more than {} goods",
+                dialogue.npc, MAX_MENU_OPTIONS
+            ));
+            dialogue.goods.truncate(MAX_MENU_OPTIONS);
+        }
+        for node in &mut dialogue.nodes {
+            if node.choices.len() > MAX_MENU_OPTIONS {
+                log_error(&format!(
+                    "The {} node of the {} has more than {} choices",
+                    node.id, dialogue.npc, MAX_MENU_OPTIONS
+                ));
+                node.choices.truncate(MAX_MENU_OPTIONS);
+            }
+        }
+    }
+    Ok(result)
+}
+
+// The dialogues of every npc, read from the data file on first use
+fn dialogues(game: &mut Game) -> &[Dialogue] {
+    if game.dialogues.is_empty() {
+        game.dialogues = load_dialogues().unwrap_or_default();
+    }
+    &game.dialogues
+}
+
+fn find_dialogue(npc: &Object, game: &mut Game) -> Option<Dialogue> {
+    let name = npc.dialogue.as_ref()?;
+    dialogues(game)
+        .iter()
+        .find(|dialogue| &dialogue.npc == name)
+        .cloned()
+}
+
+// Put the npcs of the level somewhere in the rooms other than the first one
+pub fn place_npcs(
+    rooms: &[Rect],
+    map: &Map,
+    objects: &mut Vec<Object>,
+    level: u32,
+    rng: &mut GameRng,
+) {
+    if rooms.len() < 2 {
+        return;
+    }
+    // No npcs without the data file
+    let dialogues = load_dialogues().unwrap_or_default();
+    for dialogue in dialogues.iter().filter(|dialogue| dialogue.level == level) {
+        let room = rooms[rng.gen_range(1, rooms.len())];
+        let x = rng.gen_range(room.x1 + 1, room.x2);
+        let y = rng.gen_range(room.y1 + 1, room.y2);
+        if !is_blocked(x, y, map, objects) {
+            objects.push(dialogue.spawn(x, y));
+        }
+    }
+}
+
+// Talk to the npc until the conversation ends,
+// returning the action for the answer that changes something, if the player chose one
+pub fn talk(
+    npc_id: usize,
+    ctx: &mut Context,
+    game: &mut Game,
+    objects: &[Object],
+) -> Option<Action> {
+    let dialogue = find_dialogue(&objects[npc_id], game)?;
+    let mut node = 0;
+    loop {
+        let current = dialogue.nodes.get(node)?;
+        let options = current
+            .choices
+            .iter()
+            .map(|choice| choice.text.as_str())
+            .collect::<Vec<_>>();
+        let header = format!("{}: {}\n", dialogue.npc, current.text);
+        let choice = menu(&header, &options, DIALOGUE_WIDTH, ctx)?;
+        let chosen = &current.choices[choice];
+
+        let trading = chosen.hook == Some(Hook::OpenShop) || chosen.hook == Some(Hook::Forge);
+        if trading && dialogue.closes_at_night && is_night(game) {
+            game.messages.add(
+                tr(
+                    "dialogue.closed_for_the_night_come_back",
+                    &[("npc", &dialogue.npc)],
+                ),
+                LIGHT_GREY,
+            );
+            return None;
+        }
+        match &chosen.hook {
+            Some(Hook::OpenShop) => return shop(npc_id, &dialogue, ctx, game),
+            Some(Hook::Forge) => return forge(npc_id, &dialogue, ctx, game),
+            Some(_) => {
+                return Some(Action::Choose {
+                    npc: npc_id,
+                    node,
+                    choice,
+                })
+            }
+            None => {}
+        }
+        node = dialogue.node(chosen.next.as_ref()?)?;
+    }
+}
+
+// What the item is worth in a trade, the better the equipment and the more charges the more
+fn item_value(item: &Object) -> i32 {
+    let base = match category(item) {
+        Category::Weapon | Category::Armor => 10,
+        Category::Potion => 10,
+        Category::Scroll => 15,
+        Category::Wand => 20,
+        Category::Other => 5,
+    };
+    let charges = item.charges.unwrap_or(0) as i32 * 5;
+    let equipment = item.equipment.map_or(0, |e| {
+        3 * (e.power_bonus + e.defense_bonus)
+            + e.max_hp_bonus
+            + e.evasion_bonus
+            + e.block_chance / 2
+            + 10 * e.enchantment as i32
+    });
+    base + charges + equipment.max(0)
+}
+
+// What the npc wants for the good, cheaper the more they like the player
+fn price(good: &Item, npc: &str, game: &Game) -> i32 {
+    let liking = game.relationships.get(npc).copied().unwrap_or(0);
+    let discount = (liking * DISCOUNT_PER_RELATIONSHIP).clamp(-MAX_DISCOUNT, MAX_DISCOUNT);
+    item_value(&make_item(good.clone(), 0, 0)) * (100 - discount) / 100
+}
+
+// Whether the npc turns the item down as not worth the good, telling the player so
+fn not_worth(good: &Item, give: usize, npc: &str, game: &mut Game) -> bool {
+    let item = &game.inventory[give];
+    if item_value(item) >= price(good, npc, game) {
+        return false;
+    }
+    let message = tr(
+        "dialogue.not_worth_it",
+        &[("npc", &npc), ("item", &item.name)],
+    );
+    game.messages.add(message, LIGHT_GREY);
+    true
+}
+
+// Let the player swap one of their items for one of the npc's goods
+fn shop(npc_id: usize, dialogue: &Dialogue, ctx: &mut Context, game: &mut Game) -> Option<Action> {
+    let goods = dialogue
+        .goods
+        .iter()
+        .map(|good| {
+            tr(
+                "dialogue.good_for_the_price",
+                &[
+                    ("item", &make_item(good.clone(), 0, 0).name),
+                    ("price", &price(good, &dialogue.npc, game)),
+                ],
+            )
+        })
+        .collect::<Vec<_>>();
+    let header = tr("dialogue.offers_in_exchange", &[("npc", &dialogue.npc)]);
+    let good = menu(&header, &goods, DIALOGUE_WIDTH, ctx)?;
+    let give = inventory_menu(
+        &game.inventory,
+        &tr("dialogue.give_away_for", &[("item", &goods[good])]),
+        ctx,
+    )?;
+    if not_worth(&dialogue.goods[good], give, &dialogue.npc, game) {
+        return None;
+    }
+    Some(Action::Trade {
+        npc: npc_id,
+        good,
+        give,
+    })
+}
+
+// Gold and ore it takes to improve the equipment once more, the better it is the more
+fn upgrade_cost(enchantment: u32) -> (u32, usize) {
+    let next = enchantment + 1;
+    (UPGRADE_GOLD * next * next, next as usize)
+}
+
+// Chance in percent of the work going wrong, only for the well improved equipment
+fn upgrade_failure(enchantment: u32) -> i32 {
+    (enchantment as i32 + 1 - SAFE_UPGRADES) * FAILURE_PER_UPGRADE
+}
+
+// Let the player choose the equipment to improve, telling the price first
+fn forge(npc_id: usize, dialogue: &Dialogue, ctx: &mut Context, game: &mut Game) -> Option<Action> {
+    let header = tr(
+        "dialogue.what_to_work_on",
+        &[("npc", &dialogue.npc), ("gold", &game.gold)],
+    );
+    let item = inventory_menu(&game.inventory, &header, ctx)?;
+    let equipment = match game.inventory[item].equipment {
+        Some(equipment) => equipment,
+        None => {
+            game.messages.add(
+                tr(
+                    "dialogue.can_only_improve_equipment",
+                    &[("npc", &dialogue.npc)],
+                ),
+                LIGHT_GREY,
+            );
+            return None;
+        }
+    };
+    let (gold, ore) = upgrade_cost(equipment.enchantment);
+    let mut header = tr(
+        "dialogue.upgrade_cost",
+        &[
+            ("item", &game.inventory[item].name),
+            ("enchantment", &(equipment.enchantment + 1)),
+            ("gold", &gold),
+            ("ore", &ore),
+        ],
+    );
+    let failure = upgrade_failure(equipment.enchantment);
+    if failure > 0 {
+        header.push_str(&tr("dialogue.upgrade_risk", &[("failure", &failure)]));
+    }
+    header.push('\n');
+    let choices = [tr("dialogue.do_it", &[]), tr("dialogue.never_mind", &[])];
+    match menu(&header, &choices, DIALOGUE_WIDTH, ctx)? {
+        0 => Some(Action::Upgrade { npc: npc_id, item }),
+        _ => None,
+    }
+}
+
+// Pay for improving the equipment, which may go wrong for the well improved ones
+pub fn upgrade(npc_id: usize, item: usize, game: &mut Game, objects: &[Object]) {
+    let equipment = match game.inventory.get(item).and_then(|item| item.equipment) {
+        Some(equipment) => equipment,
+        None => return,
+    };
+    let name = &objects[npc_id].name;
+    let (gold, ore) = upgrade_cost(equipment.enchantment);
+    let carried_ore = game
+        .inventory
+        .iter()
+        .filter(|item| item.item == Some(Item::Ore))
+        .count();
+    if game.gold < gold || carried_ore < ore {
+        game.messages.add(
+            tr("dialogue.come_back_when_you_have_the", &[("npc", &name)]),
+            LIGHT_GREY,
+        );
+        return;
+    }
+
+    game.gold -= gold;
+    if game.rng.gen_range(0, 100) < upgrade_failure(equipment.enchantment) {
+        game.messages.add(
+            tr("dialogue.the_metal_didnt_take_it_sorry", &[("npc", &name)]),
+            LIGHT_RED,
+        );
+    } else if let Some(equipment) = game.inventory[item].equipment.as_mut() {
+        equipment.enchantment += 1;
+        // Weapons hit harder, the rest protects better
+        if equipment.power_bonus > 0 || equipment.attack_delay > 0 {
+            equipment.power_bonus += 1;
+        } else {
+            equipment.defense_bonus += 1;
+        }
+        let enchantment = equipment.enchantment;
+        let message = tr(
+            "dialogue.improves_your_item",
+            &[
+                ("npc", &name),
+                ("item", &game.inventory[item].name),
+                ("enchantment", &enchantment),
+            ],
+        );
+        game.messages.add(message, LIGHT_GREEN);
+    }
+    // Use up the ore from the end, leaving the upgraded item where it is
+    for _ in 0..ore {
+        if let Some(index) = game
+            .inventory
+            .iter()
+            .rposition(|item| item.item == Some(Item::Ore))
+        {
+            game.inventory.remove(index);
+        }
+    }
+}
+
+// Apply the hook of the dialogue choice
+pub fn choose(npc_id: usize, node: usize, choice: usize, game: &mut Game, objects: &mut [Object]) {
+    let hook = find_dialogue(&objects[npc_id], game)
+        .and_then(|dialogue| dialogue.nodes.get(node)?.choices.get(choice)?.hook.clone());
+    let name = &objects[npc_id].name;
+    match hook {
+        Some(Hook::StartQuest(quest)) => {
+            if !game.quests.contains(&quest) {
+                game.messages
+                    .add(tr("dialogue.new_quest", &[("quest", &quest)]), LIGHT_YELLOW);
+                game.quests.push(quest);
+            }
+        }
+        Some(Hook::Relationship(change)) => {
+            *game.relationships.entry(name.clone()).or_insert(0) += change;
+            let (message, color) = if change >= 0 {
+                ("dialogue.appreciates_that", LIGHT_GREEN)
+            } else {
+                ("dialogue.doesnt_like_that", LIGHT_RED)
+            };
+            game.messages.add(tr(message, &[("npc", name)]), color);
+        }
+        Some(Hook::Hire { price, wage }) => hire(npc_id, price, wage, game, objects),
+        Some(Hook::OpenShop) | Some(Hook::Forge) | None => {}
+    }
+}
+
+// Reward the player for the quests killing the monster of the name does
+pub fn complete_quests(slain: &str, game: &mut Game) {
+    if game.quests.is_empty() {
+        return;
+    }
+    for dialogue in dialogues(game).to_vec() {
+        for quest in &dialogue.quests {
+            if quest.slay != slain || !game.quests.contains(&quest.name) {
+                continue;
+            }
+            game.quests.retain(|name| name != &quest.name);
+            game.gold += quest.gold;
+            *game.relationships.entry(dialogue.npc.clone()).or_insert(0) += QUEST_RELATIONSHIP;
+            game.messages.add(
+                tr(
+                    "dialogue.quest_done",
+                    &[
+                        ("quest", &quest.name),
+                        ("npc", &dialogue.npc),
+                        ("gold", &quest.gold),
+                    ],
+                ),
+                LIGHT_YELLOW,
+            );
+        }
+    }
+}
+
+pub fn trade(npc_id: usize, good: usize, give: usize, game: &mut Game, objects: &[Object]) {
+    let good = match find_dialogue(&objects[npc_id], game).and_then(|d| d.goods.get(good).cloned())
+    {
+        Some(good) => good,
+        None => return,
+    };
+    if give >= game.inventory.len() {
+        return;
+    }
+    if not_worth(&good, give, &objects[npc_id].name, game) {
+        return;
+    }
+    let given = game.inventory.remove(give);
+    let (x, y) = objects[PLAYER].pos();
+    let received = make_item(good, x, y);
+    game.messages.add(
+        tr(
+            "dialogue.you_trade_your_for_a",
+            &[("given", &given.name), ("received", &received.name)],
+        ),
+        LIGHT_GREEN,
+    );
+    add_to_inventory(received, game);
+}
@@ -0,0 +1,121 @@
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, VecDeque};
+
+use crate::game::{cuts_corner, is_blocked, Game, MAP_HEIGHT, MAP_WIDTH, PLAYER};
+use crate::grid::Grid;
+use crate::object::Object;
+
+// Cost of the tiles nothing can walk to from the goals
+pub const UNREACHABLE: i32 = i32::MAX;
+
+// How much farther than the plain distance the fleeing monsters are willing to go,
+// in tenths, so that they run past the player out of dead ends
+const FLEE_FACTOR: i32 = 12;
+
+// Steps from every tile of the map to the closest goal
+pub type CostMap = Grid<i32>;
+
+// Cost maps of the whole level, built once a turn for every monster to read
+#[derive(Debug, Clone, Default)]
+pub struct DijkstraMaps {
+    pub to_player: CostMap,
+    pub to_stairs: CostMap,
+    pub from_player: CostMap,
+}
+
+// Steps from the closest of the origins to every tile, going around the walls
+pub fn distance_map(origins: &[(i32, i32)], game: &Game) -> CostMap {
+    let mut distances = Grid::new(MAP_WIDTH, MAP_HEIGHT, UNREACHABLE);
+    let mut frontier = VecDeque::new();
+    for &(x, y) in origins {
+        distances[(x, y)] = 0;
+        frontier.push_back((x, y));
+    }
+    while let Some((x, y)) = frontier.pop_front() {
+        let distance = distances[(x, y)];
+        for (nx, ny) in steps(x, y, game) {
+            if game.map[(nx, ny)].blocked || distances[(nx, ny)] != UNREACHABLE {
+                continue;
+            }
+            distances[(nx, ny)] = distance + 1;
+            frontier.push_back((nx, ny));
+        }
+    }
+    distances
+}
+
+// Turn the distances to the player inside out and smooth them again,
+// so that going downhill leads to the farthest places rather than the closest corner
+fn flee_map(to_player: &CostMap, game: &Game) -> CostMap {
+    let mut costs = to_player.clone();
+    let mut frontier = BinaryHeap::new();
+    for x in 0..MAP_WIDTH {
+        for y in 0..MAP_HEIGHT {
+            let cost = &mut costs[(x, y)];
+            if *cost != UNREACHABLE {
+                *cost = -*cost * FLEE_FACTOR / 10;
+                frontier.push(Reverse((*cost, x, y)));
+            }
+        }
+    }
+    while let Some(Reverse((cost, x, y))) = frontier.pop() {
+        if cost > costs[(x, y)] {
+            continue;
+        }
+        for (nx, ny) in steps(x, y, game) {
+            if game.map[(nx, ny)].blocked || costs[(nx, ny)] <= cost + 1 {
+                continue;
+            }
+            costs[(nx, ny)] = cost + 1;
+            frontier.push(Reverse((cost + 1, nx, ny)));
+        }
+    }
+    costs
+}
+
+fn neighbours(x: i32, y: i32) -> impl Iterator<Item = (i32, i32)> {
+    (-1..=1)
+        .flat_map(move |dx| (-1..=1).map(move |dy| (x + dx, y + dy)))
+        .filter(move |&(nx, ny)| {
+            (nx, ny) != (x, y) && nx >= 0 && ny >= 0 && nx < MAP_WIDTH && ny < MAP_HEIGHT
+        })
+}
+
+// Neighbours that can be stepped to from the tile under the rules of the game
+fn steps(x: i32, y: i32, game: &Game) -> impl Iterator<Item = (i32, i32)> + '_ {
+    neighbours(x, y).filter(move |&(nx, ny)| !cuts_corner(x, y, nx - x, ny - y, game))
+}
+
+// Rebuild the cost maps for where the player and the stairs are now
+pub fn update_dijkstra_maps(game: &mut Game, objects: &[Object]) {
+    let to_player = distance_map(&[objects[PLAYER].pos()], game);
+    let stairs = objects
+        .iter()
+        .filter(|object| object.name == "stairs")
+        .map(Object::pos)
+        .collect::<Vec<_>>();
+    game.dijkstra = DijkstraMaps {
+        from_player: flee_map(&to_player, game),
+        to_player,
+        to_stairs: distance_map(&stairs, game),
+    };
+}
+
+// Free tile next to the monster that is cheaper than where it stands, the cheapest one,
+// None when the monster is already at the bottom
+pub fn downhill(
+    costs: &CostMap,
+    monster_id: usize,
+    game: &Game,
+    objects: &[Object],
+) -> Option<(i32, i32)> {
+    if costs.is_empty() {
+        return None;
+    }
+    let (x, y) = objects[monster_id].pos();
+    let here = costs[(x, y)];
+    steps(x, y, game)
+        .filter(|&(nx, ny)| !is_blocked(nx, ny, &game.map, objects))
+        .min_by_key(|&(nx, ny)| costs[(nx, ny)])
+        .filter(|&(nx, ny)| costs[(nx, ny)] < here)
+}
@@ -0,0 +1,230 @@
+use std::error::Error;
+
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::colors::*;
+
+use crate::ai::Ai;
+use crate::combat::Brand;
+use crate::equipment::{Equipment, Slot};
+use crate::faction::Faction;
+use crate::fighter::{DeathCallback, Fighter, MAX_MORALE};
+use crate::game::{initialize_fov, is_blocked, set_tile, Game, Tile, MAP_HEIGHT, MAP_WIDTH};
+use crate::item::Item;
+use crate::lang::tr;
+use crate::mods::read_data;
+use crate::object::Object;
+use crate::render::Context;
+use crate::rng::GameRng;
+use crate::room::make_monster;
+use crate::spawn::free_tile_near;
+
+const ENCOUNTERS_FILE: &str = "data/encounters.json";
+
+// A boss waiting on a dungeon level, with the script of the fight against it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Encounter {
+    pub level: u32,
+    pub boss: Boss,
+    pub phases: Vec<Phase>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Boss {
+    pub name: String,
+    pub glyph: char,
+    pub hp: i32,
+    pub defense: i32,
+    pub power: i32,
+    pub xp: i32,
+    pub faction: Faction,
+    // One of them is rolled for the boss to carry, and dropped for sure when it dies
+    #[serde(default)]
+    pub relics: Vec<Relic>,
+}
+
+// Unique equipment only a boss carries
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Relic {
+    pub name: String,
+    pub glyph: char,
+    pub item: Item,
+    pub slot: Slot,
+    #[serde(default)]
+    pub power: i32,
+    #[serde(default)]
+    pub defense: i32,
+    #[serde(default)]
+    pub max_hp: i32,
+    #[serde(default)]
+    pub attack_delay: i32,
+    #[serde(default)]
+    pub brand: Option<Brand>,
+    #[serde(default)]
+    pub evasion: i32,
+    #[serde(default)]
+    pub block: i32,
+    #[serde(default)]
+    pub reach: i32,
+}
+
+// What happens once the boss is hurt down to the threshold
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Phase {
+    pub hp_percent: i32,
+    // Said by the boss
+    #[serde(default)]
+    pub lines: Vec<String>,
+    // Tiles around the boss turning into walls, and into lava
+    #[serde(default)]
+    pub walls: Vec<(i32, i32)>,
+    #[serde(default)]
+    pub lava: Vec<(i32, i32)>,
+    // Kinds of monsters called to help
+    #[serde(default)]
+    pub summons: Vec<String>,
+    // Part of its hp the boss heals back, in percent
+    #[serde(default)]
+    pub heal_percent: i32,
+    // Power the boss gains for the rest of the fight
+    #[serde(default)]
+    pub power: i32,
+}
+
+impl Encounter {
+    pub fn spawn(&self, x: i32, y: i32, rng: &mut GameRng) -> Object {
+        let boss = &self.boss;
+        let mut object = Object::new(x, y, boss.glyph, &boss.name, LIGHT_RED, true);
+        object.fighter = Some(Fighter {
+            base_max_hp: boss.hp,
+            hp: boss.hp,
+            base_defense: boss.defense,
+            base_power: boss.power,
+            xp: boss.xp,
+            on_death: DeathCallback::Monster,
+            morale: MAX_MORALE,
+        });
+        object.ai = Some(Ai::Tree(boss.name.clone()));
+        object.faction = Some(boss.faction);
+        object.alive = true;
+        object.level = self.level as i32;
+        object.phases = self.phases.clone();
+        object.boss = true;
+        if !boss.relics.is_empty() {
+            let relic = &boss.relics[rng.gen_range(0, boss.relics.len())];
+            object.inventory.push(relic.make());
+        }
+        object
+    }
+}
+
+impl Relic {
+    pub fn make(&self) -> Object {
+        let mut relic = Object::new(0, 0, self.glyph, &self.name, GOLD, false);
+        relic.item = Some(self.item.clone());
+        relic.equipment = Some(Equipment {
+            equipped: true,
+            slot: self.slot,
+            power_bonus: self.power,
+            defense_bonus: self.defense,
+            max_hp_bonus: self.max_hp,
+            evasion_bonus: self.evasion,
+            block_chance: self.block,
+            attack_delay: self.attack_delay,
+            reach: self.reach,
+            fire_damage: 0,
+            on_hit: None,
+            brand: self.brand,
+            enchantment: 0,
+            digging: false,
+            light_radius: 0,
+        });
+        relic
+    }
+}
+
+pub fn load_encounters() -> Result<Vec<Encounter>, Box<dyn Error>> {
+    let result = serde_json::from_value::<Vec<Encounter>>(read_data(ENCOUNTERS_FILE)?)?;
+    Ok(result)
+}
+
+// Run the phases of every boss hurt enough to reach them
+pub fn run_phases(ctx: &mut Context, game: &mut Game, objects: &mut Vec<Object>) {
+    let mut map_changed = false;
+    for id in 0..objects.len() {
+        let fighter = match objects[id].fighter {
+            Some(fighter) if !objects[id].phases.is_empty() => fighter,
+            _ => continue,
+        };
+        let hp_percent = fighter.hp * 100 / fighter.base_max_hp.max(1);
+
+        while objects[id]
+            .phases
+            .first()
+            .is_some_and(|phase| hp_percent <= phase.hp_percent)
+        {
+            let phase = objects[id].phases.remove(0);
+            map_changed |= run_phase(id, &phase, game, objects);
+        }
+    }
+
+    if map_changed {
+        initialize_fov(ctx, &game.map);
+    }
+}
+
+// Returns whether the map changed
+fn run_phase(boss_id: usize, phase: &Phase, game: &mut Game, objects: &mut Vec<Object>) -> bool {
+    let (boss_x, boss_y) = objects[boss_id].pos();
+
+    for line in &phase.lines {
+        game.messages.add(
+            tr(
+                "encounter.boss_says",
+                &[("boss", &objects[boss_id].name), ("line", &line)],
+            ),
+            LIGHT_RED,
+        );
+    }
+
+    if phase.heal_percent > 0 {
+        let amount = objects[boss_id].max_hp(game) * phase.heal_percent / 100;
+        objects[boss_id].heal(amount, game);
+    }
+    if let Some(fighter) = objects[boss_id].fighter.as_mut() {
+        fighter.base_power += phase.power;
+    }
+
+    let mut map_changed = false;
+    for &(dx, dy) in &phase.walls {
+        let (x, y) = (boss_x + dx, boss_y + dy);
+        // Never on the edges of the map, or on top of anyone
+        let inside = x > 0 && y > 0 && x < MAP_WIDTH - 1 && y < MAP_HEIGHT - 1;
+        if inside && !is_blocked(x, y, &game.map, objects) {
+            game.map[(x, y)] = Tile::wall();
+            map_changed = true;
+        }
+    }
+
+    for &(dx, dy) in &phase.lava {
+        let (x, y) = (boss_x + dx, boss_y + dy);
+        let inside = x > 0 && y > 0 && x < MAP_WIDTH - 1 && y < MAP_HEIGHT - 1;
+        if inside && !game.map[(x, y)].blocked {
+            set_tile(x, y, Tile::lava(), game);
+        }
+    }
+
+    for kind in &phase.summons {
+        if let Some((x, y)) = free_tile_near(boss_x, boss_y, game, objects) {
+            if let Some(monster) = make_monster(kind, x, y) {
+                game.messages.add(
+                    tr("encounter.answers_the_call", &[("monster", &monster.name)]),
+                    LIGHT_RED,
+                );
+                objects.push(monster);
+            }
+        }
+    }
+    map_changed
+}
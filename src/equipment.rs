@@ -1,5 +1,9 @@
 use serde::{Deserialize, Serialize};
 
+use crate::combat::Brand;
+use crate::lang::tr;
+use crate::status::Effect;
+
 // Equipment of the character
 #[derive(Copy, Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Equipment {
@@ -8,6 +12,35 @@ pub struct Equipment {
     pub power_bonus: i32,
     pub defense_bonus: i32,
     pub max_hp_bonus: i32,
+    // Chances in percent of dodging a blow, and of stopping it with a shield
+    #[serde(default)]
+    pub evasion_bonus: i32,
+    #[serde(default)]
+    pub block_chance: i32,
+    // Time a swing takes, in hundredths of a turn, 0 for what isn't swung
+    #[serde(default)]
+    pub attack_delay: i32,
+    // How many tiles away the blows land, over the tile between for a polearm,
+    // 0 for a weapon hitting next to the wielder
+    #[serde(default)]
+    pub reach: i32,
+    // Damage added to every hit that armor doesn't stop, and the status the hits put on
+    #[serde(default)]
+    pub fire_damage: i32,
+    #[serde(default)]
+    pub on_hit: Option<Effect>,
+    // What else the hits do, poisoning or draining the target
+    #[serde(default)]
+    pub brand: Option<Brand>,
+    // Times a smith improved it
+    #[serde(default)]
+    pub enchantment: u32,
+    // Lets the player dig through the walls walked into
+    #[serde(default)]
+    pub digging: bool,
+    // How far the lamp lights the dark levels while it has fuel
+    #[serde(default)]
+    pub light_radius: i32,
 }
 
 // Character slots
@@ -20,10 +53,11 @@ pub enum Slot {
 
 impl std::fmt::Display for Slot {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
-        match *self {
-            Slot::LeftHand => write!(f, "left hand"),
-            Slot::RightHand => write!(f, "right hand"),
-            Slot::Head => write!(f, "head"),
-        }
+        let id = match *self {
+            Slot::LeftHand => "equipment.left_hand",
+            Slot::RightHand => "equipment.right_hand",
+            Slot::Head => "equipment.head",
+        };
+        write!(f, "{}", tr(id, &[]))
     }
 }
@@ -0,0 +1,18 @@
+use std::fs::OpenOptions;
+use std::io::Write;
+
+use crate::paths::data_file;
+
+// Where the troubles met while playing are written down
+const ERRORS_FILE: &str = "errors.log";
+
+// Note what went wrong, the standard error being drawn over by the terminal backend
+// once the game is running
+pub fn log_error(text: &str) {
+    let file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(data_file(ERRORS_FILE));
+    // With nowhere to write it, there's nobody left to tell
+    let _ = file.and_then(|mut file| writeln!(file, "{}", text));
+}
@@ -0,0 +1,62 @@
+use crate::audio::play_sound;
+use crate::bestiary::record_bestiary;
+use crate::game::Game;
+use crate::render::Context;
+use crate::stats::record_stats;
+
+// Something that happened during the turn, for whoever wants to react to it
+#[derive(Debug, Clone, PartialEq)]
+pub enum GameEvent {
+    // Whether the player dealt the damage, and whether they took it
+    Damaged {
+        name: String,
+        damage: i32,
+        by_player: bool,
+        to_player: bool,
+    },
+    Died {
+        name: String,
+        by_player: bool,
+        player: bool,
+    },
+    ItemPickedUp {
+        name: String,
+    },
+    LevelChanged {
+        level: u32,
+    },
+    LeveledUp {
+        level: i32,
+    },
+    // A scroll read or a wand zapped
+    SpellCast {
+        name: String,
+    },
+}
+
+// Reacts to every event, in the order the events happened
+pub type Subscriber = fn(&GameEvent, &mut Game);
+
+// Everyone listening to the events, in the order they hear about them
+const SUBSCRIBERS: &[Subscriber] = &[record_stats, record_bestiary];
+
+// Lets the player know about the event outside of the game, through the context
+pub type Listener = fn(&GameEvent, &mut Context);
+
+const LISTENERS: &[Listener] = &[play_sound];
+
+pub fn emit(event: GameEvent, game: &mut Game) {
+    game.events.push(event);
+}
+
+// Let the subscribers react to the events of the turn, then the listeners
+pub fn dispatch_events(ctx: &mut Context, game: &mut Game) {
+    for event in std::mem::take(&mut game.events) {
+        for subscriber in SUBSCRIBERS {
+            subscriber(&event, game);
+        }
+        for listener in LISTENERS {
+            listener(&event, ctx);
+        }
+    }
+}
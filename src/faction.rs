@@ -0,0 +1,46 @@
+use serde::{Deserialize, Serialize};
+
+// Sides the fighters take, deciding who attacks whom
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Faction {
+    Player,
+    Goblins,
+    Undead,
+    Wildlife,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Relationship {
+    Allied,
+    Neutral,
+    Hostile,
+}
+
+impl Faction {
+    // Whether its fighters keep fighting no matter what
+    pub fn fearless(self) -> bool {
+        matches!(self, Faction::Player | Faction::Undead)
+    }
+
+    // How the faction feels about the other one, the same both ways
+    pub fn relationship(self, other: Faction) -> Relationship {
+        use Faction::*;
+        match (self, other) {
+            _ if self == other => Relationship::Allied,
+            (Player, _) | (_, Player) => Relationship::Hostile,
+            // The dead hate the living
+            (Undead, Goblins) | (Goblins, Undead) => Relationship::Hostile,
+            // Goblins hunt anything with meat on it
+            (Goblins, Wildlife) | (Wildlife, Goblins) => Relationship::Hostile,
+            _ => Relationship::Neutral,
+        }
+    }
+}
+
+// Whether fighters of the factions attack each other, nobody attacks those without one
+pub fn hostile(first: Option<Faction>, second: Option<Faction>) -> bool {
+    match (first, second) {
+        (Some(first), Some(second)) => first.relationship(second) == Relationship::Hostile,
+        _ => false,
+    }
+}
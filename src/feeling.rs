@@ -0,0 +1,74 @@
+use crate::colors::*;
+
+use crate::affix::Rarity;
+use crate::faction::Faction;
+use crate::game::{Game, PLAYER};
+use crate::lang::tr;
+use crate::object::Object;
+
+// Experience the monsters of an ordinary level are worth, growing with the depth
+const DANGER_BASE: i32 = 200;
+const DANGER_PER_LEVEL: i32 = 150;
+// Items lying around an ordinary level, weighted by their rarity
+const LOOT_BASE: i32 = 5;
+const LOOT_PER_LEVEL: i32 = 1;
+// Danger in percent of the ordinary, past which the level feels deadly or dangerous,
+// and below which it feels quiet
+const DEADLY: i32 = 180;
+const DANGEROUS: i32 = 130;
+const QUIET: i32 = 60;
+// Loot in percent of the ordinary the player is told about
+const RICH: i32 = 150;
+
+// How much the monsters on the level are worth in percent of an ordinary one
+fn danger(level: u32, objects: &[Object]) -> i32 {
+    let xp: i32 = objects
+        .iter()
+        .enumerate()
+        .filter(|&(id, object)| id != PLAYER && object.alive)
+        .filter(|(_, object)| object.faction != Some(Faction::Player))
+        .filter_map(|(_, object)| object.fighter.map(|fighter| fighter.xp))
+        .sum();
+    xp * 100 / (DANGER_BASE + DANGER_PER_LEVEL * level as i32)
+}
+
+// How much loot lies on the level in percent of an ordinary one
+fn loot(level: u32, objects: &[Object]) -> i32 {
+    let loot: i32 = objects
+        .iter()
+        .filter(|object| object.item.is_some())
+        .map(|object| match object.rarity {
+            Rarity::Common => 1,
+            Rarity::Magic => 3,
+            Rarity::Rare => 6,
+        })
+        .sum();
+    loot * 100 / (LOOT_BASE + LOOT_PER_LEVEL * level as i32)
+}
+
+// Color of the level readout on the panel, hinting at the danger
+pub fn danger_color(danger: i32) -> Color {
+    match danger {
+        danger if danger >= DEADLY => LIGHT_RED,
+        danger if danger >= DANGEROUS => LIGHT_YELLOW,
+        danger if danger < QUIET => LIGHT_GREEN,
+        _ => WHITE,
+    }
+}
+
+// Tell the player how the new level feels, judging by what it was made with
+pub fn level_feeling(game: &mut Game, objects: &[Object]) {
+    game.danger = danger(game.dungeon_level, objects);
+    let message = match game.danger {
+        danger if danger >= DEADLY => "feeling.you_sense_great_danger_here",
+        danger if danger >= DANGEROUS => "feeling.this_place_feels_dangerous",
+        danger if danger < QUIET => "feeling.this_level_seems_quiet",
+        _ => "feeling.you_feel_nothing_special",
+    };
+    game.messages
+        .add(tr(message, &[]), danger_color(game.danger));
+    if loot(game.dungeon_level, objects) >= RICH {
+        game.messages
+            .add(tr("feeling.you_catch_the_glint_of_treasure", &[]), GOLD);
+    }
+}
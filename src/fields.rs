@@ -0,0 +1,198 @@
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::colors::*;
+
+use crate::fov::line_of_sight;
+use crate::game::{set_tile, Game, Terrain, Tile, MAP_HEIGHT, MAP_WIDTH, PLAYER};
+use crate::lang::tr;
+use crate::object::Object;
+
+// Turns the fire burns on bare floor, and on the doors and brush it eats
+const FLAME_TURNS: u32 = 3;
+const BURN_TURNS: u32 = 6;
+// Chance in percent of the fire catching on every flammable tile next to it
+const FIRE_SPREAD_CHANCE: i32 = 30;
+const FIRE_DAMAGE: i32 = 4;
+const LAVA_DAMAGE: i32 = 6;
+// Gas thick enough to hide what is behind it, and how much thinner it gets spreading
+const GAS_OPAQUE: u32 = 4;
+const GAS_THINNING: u32 = 2;
+const GAS_DAMAGE: i32 = 2;
+
+// Something filling the tiles for a while, hurting whoever stands in it
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum FieldKind {
+    Gas,
+    Fire,
+}
+
+impl FieldKind {
+    pub fn color(self) -> Color {
+        match self {
+            FieldKind::Gas => LIGHT_CHARTREUSE,
+            FieldKind::Fire => FLAME,
+        }
+    }
+}
+
+// The field on a single tile, gone once its strength runs out
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct Field {
+    pub x: i32,
+    pub y: i32,
+    pub kind: FieldKind,
+    pub strength: u32,
+}
+
+pub fn field_at(x: i32, y: i32, game: &Game) -> Option<&Field> {
+    game.fields
+        .iter()
+        .find(|field| (field.x, field.y) == (x, y))
+}
+
+// Put the field on the tile, unless a stronger one is already there
+pub fn add_field(x: i32, y: i32, kind: FieldKind, strength: u32, game: &mut Game) {
+    match game
+        .fields
+        .iter_mut()
+        .find(|field| (field.x, field.y) == (x, y))
+    {
+        Some(field) if field.strength >= strength => {}
+        Some(field) => {
+            field.kind = kind;
+            field.strength = strength;
+        }
+        None => game.fields.push(Field {
+            x,
+            y,
+            kind,
+            strength,
+        }),
+    }
+    game.changed_tiles.push((x, y));
+}
+
+// Whether the tile hides what is behind it, the walls or thick gas
+pub fn blocks_sight(x: i32, y: i32, game: &Game) -> bool {
+    game.map[(x, y)].block_sight
+        || field_at(x, y, game)
+            .is_some_and(|field| field.kind == FieldKind::Gas && field.strength >= GAS_OPAQUE)
+}
+
+// Set fire to everything in the radius that the center can see,
+// burning down the doors and brush
+pub fn ignite(x: i32, y: i32, radius: i32, game: &mut Game) {
+    let mut burnt = false;
+    for tile_y in (y - radius).max(0)..=(y + radius).min(MAP_HEIGHT - 1) {
+        for tile_x in (x - radius).max(0)..=(x + radius).min(MAP_WIDTH - 1) {
+            let (dx, dy) = (tile_x - x, tile_y - y);
+            let tile = game.map[(tile_x, tile_y)];
+            if dx * dx + dy * dy > radius * radius
+                || tile.blocked
+                || !line_of_sight(&game.map, (x, y), (tile_x, tile_y))
+            {
+                continue;
+            }
+            if tile.terrain.flammable() {
+                set_tile(tile_x, tile_y, Tile::empty(), game);
+                add_field(tile_x, tile_y, FieldKind::Fire, BURN_TURNS, game);
+                burnt = true;
+            } else {
+                add_field(tile_x, tile_y, FieldKind::Fire, FLAME_TURNS, game);
+            }
+        }
+    }
+    if burnt {
+        game.messages
+            .add(tr("fields.the_flames_turn_the_doors_and", &[]), ORANGE);
+    }
+}
+
+// Burn whoever stands in the lava, the same as the fire does only worse
+pub fn burn_in_lava(game: &mut Game, objects: &mut [Object]) {
+    let burning = objects
+        .iter()
+        .enumerate()
+        .filter(|(_, object)| object.alive && object.fighter.is_some())
+        .filter(|(_, object)| game.map[(object.x, object.y)].terrain == Terrain::Lava)
+        .map(|(id, _)| id)
+        .collect::<Vec<_>>();
+    for id in burning {
+        let damage = objects[id]
+            .race(game)
+            .map_or(LAVA_DAMAGE, |race| race.resist_fire(LAVA_DAMAGE));
+        if id == PLAYER {
+            game.messages
+                .add(tr("fields.the_lava_burns_you", &[]), FLAME);
+        }
+        objects[id].take_damage(damage, false, game);
+    }
+}
+
+// Hurt whoever stands in the fields, then let the fields spread and die down
+pub fn tick_fields(game: &mut Game, objects: &mut [Object]) {
+    if game.fields.is_empty() {
+        return;
+    }
+
+    let burning = objects
+        .iter()
+        .enumerate()
+        .filter(|(_, object)| object.alive && object.fighter.is_some())
+        .filter_map(|(id, object)| field_at(object.x, object.y, game).map(|field| (id, *field)))
+        .collect::<Vec<_>>();
+    for (id, field) in burning {
+        let damage = match field.kind {
+            FieldKind::Gas => GAS_DAMAGE,
+            FieldKind::Fire => objects[id]
+                .race(game)
+                .map_or(FIRE_DAMAGE, |race| race.resist_fire(FIRE_DAMAGE)),
+        };
+        if id == PLAYER {
+            let message = match field.kind {
+                FieldKind::Gas => "fields.you_choke_on_the_poison_gas",
+                FieldKind::Fire => "fields.you_are_burning",
+            };
+            game.messages.add(tr(message, &[]), field.kind.color());
+        }
+        objects[id].take_damage(damage, false, game);
+    }
+
+    let fields = std::mem::take(&mut game.fields);
+    // The tiles they leave have to be seen through again
+    game.changed_tiles
+        .extend(fields.iter().map(|field| (field.x, field.y)));
+    for field in &fields {
+        let neighbours = [
+            (field.x + 1, field.y),
+            (field.x - 1, field.y),
+            (field.x, field.y + 1),
+            (field.x, field.y - 1),
+        ];
+        for &(x, y) in &neighbours {
+            if x < 0 || y < 0 || x >= MAP_WIDTH || y >= MAP_HEIGHT {
+                continue;
+            }
+            let tile = game.map[(x, y)];
+            match field.kind {
+                FieldKind::Gas if !tile.blocked && field.strength > GAS_THINNING => {
+                    add_field(x, y, FieldKind::Gas, field.strength - GAS_THINNING, game);
+                }
+                FieldKind::Fire
+                    if tile.terrain.flammable()
+                        && game.rng.gen_range(0, 100) < FIRE_SPREAD_CHANCE =>
+                {
+                    set_tile(x, y, Tile::empty(), game);
+                    add_field(x, y, FieldKind::Fire, BURN_TURNS, game);
+                }
+                _ => {}
+            }
+        }
+    }
+    for field in fields {
+        if field.strength > 1 {
+            add_field(field.x, field.y, field.kind, field.strength - 1, game);
+        }
+    }
+}
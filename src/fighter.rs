@@ -1,9 +1,14 @@
 use serde::{Deserialize, Serialize};
 
-use tcod::colors::*;
+use crate::colors::*;
 
-use crate::game::Game;
+use crate::dialogue::complete_quests;
+use crate::food::make_corpse;
+use crate::game::{Game, Severity};
+use crate::lang::tr;
 use crate::object::Object;
+use crate::room::monster_loot;
+use crate::spawn::spawn;
 
 // combat-related properties and functions
 #[derive(Debug, Clone, PartialEq, Copy, Serialize, Deserialize)]
@@ -14,6 +19,15 @@ pub struct Fighter {
     pub base_power: i32,
     pub xp: i32,
     pub on_death: DeathCallback,
+    // Courage left, the monster flees once it runs out
+    #[serde(default = "full_morale")]
+    pub morale: i32,
+}
+
+pub const MAX_MORALE: i32 = 100;
+
+fn full_morale() -> i32 {
+    MAX_MORALE
 }
 
 // Action to perform on fighter's death
@@ -35,19 +49,53 @@ impl DeathCallback {
 }
 
 pub fn player_death(player: &mut Object, game: &mut Game) {
-    game.messages.add("Your history ends here", RED);
+    game.messages
+        .report(tr("fighter.your_history_ends_here", &[]), Severity::Danger);
 
     player.char = '%';
     player.color = DARK_RED;
 }
 
 pub fn monster_death(monster: &mut Object, game: &mut Game) {
-    game.messages.add(format!("{} dies!", monster.name), RED);
+    game.messages
+        .add(tr("fighter.dies", &[("monster", &monster.name)]), RED);
 
     monster.char = '%';
     monster.color = DARK_RED;
     monster.blocks = false;
     monster.fighter = None;
     monster.ai = None;
-    monster.name = format!("remains of {}", monster.name);
+    monster.heavy = true;
+
+    game.deaths.push((monster.x, monster.y, monster.faction));
+    if monster.unique {
+        game.stats.uniques_slain.push(monster.name.clone());
+    }
+    complete_quests(&monster.name, game);
+    for mut item in monster.inventory.drain(..) {
+        game.messages.add(
+            tr(
+                "fighter.drops",
+                &[("monster", &monster.name), ("item", &item.name)],
+            ),
+            LIGHT_GREY,
+        );
+        item.set_pos(monster.x, monster.y);
+        if let Some(equipment) = item.equipment.as_mut() {
+            equipment.equipped = false;
+        }
+        spawn(item, game);
+    }
+    if let Some(item) = monster_loot(monster, game.dungeon_level, &mut game.rng) {
+        game.messages.add(
+            tr(
+                "fighter.drops",
+                &[("monster", &monster.name), ("item", &item.name)],
+            ),
+            LIGHT_GREY,
+        );
+        spawn(item, game);
+    }
+    make_corpse(monster);
+    monster.name = tr("fighter.remains_of", &[("monster", &monster.name)]);
 }
@@ -0,0 +1,42 @@
+use std::fs;
+use std::path::PathBuf;
+
+use crate::paths::{asset, data_dir};
+
+// Fonts are found in there, among the bundled files and in the data directory,
+// where the player can put fonts of their own
+const FONTS_DIR: &str = "fonts";
+pub const DEFAULT_FONT: &str = "arial10x10.png";
+// Whole factors the glyphs can be scaled up by, for the fonts to be readable on large screens
+pub const FONT_SCALES: [u32; 4] = [1, 2, 3, 4];
+
+fn font_names(dir: PathBuf) -> Vec<String> {
+    fs::read_dir(dir)
+        .map(|entries| {
+            entries
+                .filter_map(Result::ok)
+                .map(|entry| entry.file_name().to_string_lossy().into_owned())
+                .filter(|name| name.ends_with(".png"))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+// Fonts to choose from, sorted
+pub fn fonts() -> Vec<String> {
+    let mut fonts = font_names(asset(FONTS_DIR));
+    fonts.extend(font_names(data_dir(FONTS_DIR)));
+    fonts.sort();
+    fonts.dedup();
+    fonts
+}
+
+// The font of the name, the player's own winning over a bundled one
+pub fn font_file(name: &str) -> PathBuf {
+    let own = data_dir(FONTS_DIR).join(name);
+    if own.exists() {
+        own
+    } else {
+        asset(&format!("{}/{}", FONTS_DIR, name))
+    }
+}
@@ -0,0 +1,126 @@
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::colors::*;
+
+use crate::game::{remove_object, Game, PLAYER};
+use crate::lang::tr;
+use crate::object::Object;
+
+// Turns a corpse lies on the floor before it rots away, and from how many turns left it's rotten
+const CORPSE_ROT_TURNS: u32 = 400;
+const ROTTEN_TURNS: u32 = 150;
+// Chance in percent of getting sick from rotten meat, and how much being sick hurts
+const ROTTEN_SICK_CHANCE: i32 = 50;
+const SICK_DAMAGE: i32 = 8;
+
+// Turns without food after which the player gets hungry, weak, and then starves,
+// losing some health every few turns
+const HUNGRY_TURNS: i32 = 1000;
+const WEAK_TURNS: i32 = 1500;
+const STARVING_TURNS: i32 = 1800;
+const STARVE_DAMAGE_TURNS: i32 = 5;
+
+// What eating the corpse gives
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Food {
+    // Turns of hunger it takes away
+    pub nutrition: i32,
+    // Always makes the one who eats it sick
+    pub poisonous: bool,
+}
+
+// Meat left by the kind of monster, None for the ones that leave nothing to eat
+fn corpse_food(kind: &str) -> Option<Food> {
+    let (nutrition, poisonous) = match kind {
+        "skeleton" | "lich" => return None,
+        "goblin" | "goblin archer" => (300, false),
+        "orc" | "warlord" => (600, false),
+        "wolf" => (450, false),
+        // Tainted by the dark arts
+        "necromancer" | "ghoul" => (400, true),
+        _ => (400, false),
+    };
+    Some(Food {
+        nutrition,
+        poisonous,
+    })
+}
+
+// Leave what the monster was as a corpse that can be eaten until it rots away
+pub fn make_corpse(monster: &mut Object) {
+    monster.food = corpse_food(monster.kind());
+    if monster.food.is_some() {
+        monster.rot = Some(CORPSE_ROT_TURNS);
+    }
+}
+
+// How the player is doing without food, None when they're fine
+pub fn hunger_status(game: &Game) -> Option<(String, Color)> {
+    match game.hunger {
+        hunger if hunger >= STARVING_TURNS => Some((tr("food.starving", &[]), RED)),
+        hunger if hunger >= WEAK_TURNS => Some((tr("food.weak", &[]), ORANGE)),
+        hunger if hunger >= HUNGRY_TURNS => Some((tr("food.hungry", &[]), YELLOW)),
+        _ => None,
+    }
+}
+
+// Another turn without food, starving hurts
+pub fn get_hungry(game: &mut Game, objects: &mut [Object]) {
+    game.hunger += 1;
+    match game.hunger {
+        HUNGRY_TURNS => game
+            .messages
+            .add(tr("food.you_are_getting_hungry", &[]), YELLOW),
+        WEAK_TURNS => game
+            .messages
+            .add(tr("food.you_feel_weak_with_hunger", &[]), ORANGE),
+        STARVING_TURNS => game.messages.add(tr("food.you_are_starving", &[]), RED),
+        hunger if hunger > STARVING_TURNS && hunger % STARVE_DAMAGE_TURNS == 0 => {
+            objects[PLAYER].take_damage(1, false, game);
+        }
+        _ => {}
+    }
+}
+
+// Eat the corpse under the player
+pub fn eat(game: &mut Game, objects: &mut Vec<Object>) {
+    let corpse = objects
+        .iter()
+        .position(|object| object.food.is_some() && object.pos() == objects[PLAYER].pos());
+    let id = match corpse {
+        Some(id) => id,
+        None => {
+            game.messages
+                .add(tr("food.there_is_nothing_to_eat_here", &[]), LIGHT_GREY);
+            return;
+        }
+    };
+    let corpse = remove_object(id, game, objects);
+    let food = corpse.food.unwrap();
+    game.hunger = (game.hunger - food.nutrition).max(0);
+    game.messages.add(
+        tr("food.you_eat_the", &[("corpse", &corpse.name)]),
+        LIGHT_GREY,
+    );
+
+    let rotten = corpse.rot.is_some_and(|rot| rot <= ROTTEN_TURNS);
+    if food.poisonous || (rotten && game.rng.gen_range(0, 100) < ROTTEN_SICK_CHANCE) {
+        game.messages
+            .add(tr("food.you_feel_sick", &[]), LIGHT_GREEN);
+        objects[PLAYER].take_damage(SICK_DAMAGE, false, game);
+    }
+}
+
+// Let the corpses rot for the turns, taking away the ones that rotted away
+pub fn rot_corpses(turns: u32, game: &mut Game, objects: &mut Vec<Object>) {
+    // From the end, so that removing a corpse doesn't move the ones not checked yet
+    for id in (0..objects.len()).rev() {
+        if let Some(rot) = objects[id].rot.as_mut() {
+            *rot = rot.saturating_sub(turns);
+            if *rot == 0 {
+                remove_object(id, game, objects);
+            }
+        }
+    }
+}
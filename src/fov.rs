@@ -0,0 +1,201 @@
+use crate::game::Map;
+
+// Field of view computed by casting rays from the origin to the edge of the view radius
+pub struct FovMap {
+    width: i32,
+    height: i32,
+    transparent: Vec<bool>,
+    visible: Vec<bool>,
+    // Cells lit by the light sources, seen from any distance
+    lit: Vec<bool>,
+    // What the visible and the lit cells were computed from, kept until a cell
+    // changes its transparency, so that they're only computed again when needed
+    computed: Option<(i32, i32, i32, bool)>,
+    lights: Option<Vec<(i32, i32, i32)>>,
+}
+
+impl FovMap {
+    pub fn new(width: i32, height: i32) -> Self {
+        let size = (width * height) as usize;
+        FovMap {
+            width,
+            height,
+            transparent: vec![false; size],
+            visible: vec![false; size],
+            lit: vec![],
+            computed: None,
+            lights: None,
+        }
+    }
+
+    fn index(&self, x: i32, y: i32) -> Option<usize> {
+        if x >= 0 && y >= 0 && x < self.width && y < self.height {
+            Some((y * self.width + x) as usize)
+        } else {
+            None
+        }
+    }
+
+    pub fn set_transparent(&mut self, x: i32, y: i32, transparent: bool) {
+        if let Some(index) = self.index(x, y) {
+            if self.transparent[index] != transparent {
+                self.transparent[index] = transparent;
+                self.computed = None;
+                self.lights = None;
+            }
+        }
+    }
+
+    pub fn is_in_fov(&self, x: i32, y: i32) -> bool {
+        self.index(x, y).is_some_and(|index| self.visible[index])
+    }
+
+    // Light up the cells around the light sources, given as their position and radius
+    pub fn set_lights(&mut self, lights: &[(i32, i32, i32)]) {
+        if self.lights.as_deref() == Some(lights) {
+            return;
+        }
+        self.lights = Some(lights.to_vec());
+        self.lit = vec![];
+        let mut lit = vec![false; self.visible.len()];
+        for &(x, y, radius) in lights {
+            self.compute_fov(x, y, radius, true);
+            for (lit, &visible) in lit.iter_mut().zip(&self.visible) {
+                *lit |= visible;
+            }
+        }
+        // The cells visible to the player were overwritten, and the lit ones change them
+        self.computed = None;
+        if lights.is_empty() {
+            return;
+        }
+        self.lit = lit;
+    }
+
+    fn is_lit(&self, index: usize) -> bool {
+        self.lit.get(index).copied().unwrap_or(false)
+    }
+
+    // Recompute visible cells, a radius of 0 meaning unlimited sight,
+    // unless nothing changed since the last time
+    pub fn compute_fov(&mut self, origin_x: i32, origin_y: i32, radius: i32, light_walls: bool) {
+        let key = (origin_x, origin_y, radius, light_walls);
+        if self.computed == Some(key) {
+            return;
+        }
+        self.computed = Some(key);
+        for cell in self.visible.iter_mut() {
+            *cell = false;
+        }
+        if let Some(index) = self.index(origin_x, origin_y) {
+            self.visible[index] = true;
+        } else {
+            return;
+        }
+
+        // Lit cells can be seen past the radius
+        let (min_x, max_x, min_y, max_y) = if radius > 0 && self.lit.is_empty() {
+            (
+                origin_x - radius,
+                origin_x + radius,
+                origin_y - radius,
+                origin_y + radius,
+            )
+        } else {
+            (0, self.width - 1, 0, self.height - 1)
+        };
+
+        // Cast a ray to every cell on the border of the bounding square
+        for x in min_x..=max_x {
+            self.cast_ray(origin_x, origin_y, x, min_y, radius, light_walls);
+            self.cast_ray(origin_x, origin_y, x, max_y, radius, light_walls);
+        }
+        for y in min_y..=max_y {
+            self.cast_ray(origin_x, origin_y, min_x, y, radius, light_walls);
+            self.cast_ray(origin_x, origin_y, max_x, y, radius, light_walls);
+        }
+    }
+
+    fn cast_ray(
+        &mut self,
+        origin_x: i32,
+        origin_y: i32,
+        target_x: i32,
+        target_y: i32,
+        radius: i32,
+        light_walls: bool,
+    ) {
+        // Walk the Bresenham line until something opaque blocks the ray
+        let dx = (target_x - origin_x).abs();
+        let dy = -(target_y - origin_y).abs();
+        let step_x = if origin_x < target_x { 1 } else { -1 };
+        let step_y = if origin_y < target_y { 1 } else { -1 };
+        let mut error = dx + dy;
+        let (mut x, mut y) = (origin_x, origin_y);
+
+        while (x, y) != (target_x, target_y) {
+            let doubled = 2 * error;
+            if doubled >= dy {
+                error += dy;
+                x += step_x;
+            }
+            if doubled <= dx {
+                error += dx;
+                y += step_y;
+            }
+
+            let index = match self.index(x, y) {
+                Some(index) => index,
+                None => return,
+            };
+            let (distance_x, distance_y) = (x - origin_x, y - origin_y);
+            let in_radius =
+                radius == 0 || distance_x * distance_x + distance_y * distance_y <= radius * radius;
+            if !in_radius && self.lit.is_empty() {
+                return;
+            }
+            let seen = in_radius || self.is_lit(index);
+            if self.transparent[index] {
+                self.visible[index] |= seen;
+            } else {
+                if light_walls {
+                    self.visible[index] |= seen;
+                }
+                return;
+            }
+        }
+    }
+}
+
+// Tiles on the straight line between the points, without the first one
+pub fn line(from: (i32, i32), to: (i32, i32)) -> Vec<(i32, i32)> {
+    let dx = (to.0 - from.0).abs();
+    let dy = -(to.1 - from.1).abs();
+    let step_x = if from.0 < to.0 { 1 } else { -1 };
+    let step_y = if from.1 < to.1 { 1 } else { -1 };
+    let mut error = dx + dy;
+    let (mut x, mut y) = from;
+
+    let mut tiles = vec![];
+    while (x, y) != to {
+        let doubled = 2 * error;
+        if doubled >= dy {
+            error += dy;
+            x += step_x;
+        }
+        if doubled <= dx {
+            error += dx;
+            y += step_y;
+        }
+        tiles.push((x, y));
+    }
+    tiles
+}
+
+// Whether nothing opaque stands between the points
+pub fn line_of_sight(map: &Map, from: (i32, i32), to: (i32, i32)) -> bool {
+    line(from, to)
+        .into_iter()
+        .take_while(|&tile| tile != to)
+        .all(|(x, y)| !map[(x, y)].block_sight)
+}
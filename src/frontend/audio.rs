@@ -0,0 +1,128 @@
+use std::fs::File;
+use std::io::BufReader;
+use std::time::Duration;
+
+use rodio::source::{SineWave, Source};
+use rodio::{Decoder, OutputStream, OutputStreamHandle, Sink};
+
+use roguelike::audio::{Music, Sound};
+use roguelike::backend::{AudioSink, Silence};
+use roguelike::errors::log_error;
+use roguelike::paths::asset;
+
+// Seconds the music takes to fade in or out
+const FADE_SECONDS: f32 = 2.0;
+
+// A looping track and its fight layer, started together to play in step
+struct Track {
+    name: &'static str,
+    // Missing music files are left silent
+    base: Option<Sink>,
+    combat: Option<Sink>,
+    // How far the track and the fight layer have faded in, from 0 to 1
+    level: f32,
+    combat_level: f32,
+}
+
+// Stream the music file from the disk over and over, muted at first
+fn looping(name: &str, handle: &OutputStreamHandle) -> Option<Sink> {
+    let file = File::open(asset(&format!("music/{}.ogg", name))).ok()?;
+    let source = Decoder::new(BufReader::new(file)).ok()?.repeat_infinite();
+    let sink = Sink::try_new(handle).ok()?;
+    sink.set_volume(0.0);
+    sink.append(source);
+    Some(sink)
+}
+
+impl Track {
+    fn start(name: &'static str, handle: &OutputStreamHandle) -> Self {
+        Track {
+            name,
+            base: looping(name, handle),
+            combat: looping(&format!("{}_combat", name), handle),
+            level: 0.0,
+            combat_level: 0.0,
+        }
+    }
+
+    fn set_volume(&self, volume: f32) {
+        if let Some(base) = &self.base {
+            base.set_volume(self.level * volume);
+        }
+        if let Some(combat) = &self.combat {
+            combat.set_volume(self.level * self.combat_level * volume);
+        }
+    }
+}
+
+// Move the level towards the target by at most the step
+fn fade(level: f32, target: f32, step: f32) -> f32 {
+    if level < target {
+        (level + step).min(target)
+    } else {
+        (level - step).max(target)
+    }
+}
+
+// Plays a short tone for every sound and the music on the default output device
+struct RodioAudio {
+    // The sounds stop once the stream is dropped
+    _stream: OutputStream,
+    handle: OutputStreamHandle,
+    music: Option<Track>,
+    // Tracks fading out after the music changed, dropped once silent
+    fading: Vec<Track>,
+}
+
+impl AudioSink for RodioAudio {
+    fn play(&mut self, sound: Sound, volume: f32) {
+        let (frequency, millis) = sound.tone();
+        let source = SineWave::new(frequency)
+            .take_duration(Duration::from_millis(millis))
+            .amplify(volume);
+        // A sound that couldn't be played is only missed
+        let _ = self.handle.play_raw(source);
+    }
+
+    fn play_music(&mut self, music: Music, delta: Duration) {
+        let step = delta.as_secs_f32() / FADE_SECONDS;
+        // Cross-fade into the track of the new place
+        if self.music.as_ref().map(|track| track.name) != Some(music.track) {
+            self.fading.extend(self.music.take());
+            self.music = Some(Track::start(music.track, &self.handle));
+        }
+        if let Some(track) = &mut self.music {
+            track.level = fade(track.level, 1.0, step);
+            let combat = if music.combat { 1.0 } else { 0.0 };
+            track.combat_level = fade(track.combat_level, combat, step);
+            track.set_volume(music.volume);
+        }
+        for track in self.fading.iter_mut() {
+            track.level = fade(track.level, 0.0, step);
+            track.set_volume(music.volume);
+        }
+        // Dropping the sinks stops them
+        self.fading.retain(|track| track.level > 0.0);
+    }
+
+    fn stop_music(&mut self) {
+        self.music = None;
+        self.fading.clear();
+    }
+}
+
+// Fall back to silence if there is nothing to play the sounds on
+pub fn init() -> Box<dyn AudioSink> {
+    match OutputStream::try_default() {
+        Ok((stream, handle)) => Box::new(RodioAudio {
+            _stream: stream,
+            handle,
+            music: None,
+            fading: vec![],
+        }),
+        Err(e) => {
+            log_error(&format!("Playing without sound: {}", e));
+            Box::new(Silence)
+        }
+    }
+}
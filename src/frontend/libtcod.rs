@@ -0,0 +1,317 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::rc::Rc;
+
+use tcod::console::*;
+use tcod::image::{self, Image};
+use tcod::input;
+
+use roguelike::backend::{Backend, Event, InputSource, Key, KeyCode, Mouse, Renderer};
+use roguelike::colors::Color;
+use roguelike::config::{Config, RenderMode};
+use roguelike::fonts::font_file;
+use roguelike::glyphs::{cp437, fallback};
+use roguelike::paths::{asset, data_dir};
+use roguelike::render::{SCREEN_HEIGHT, SCREEN_WIDTH};
+use roguelike::tileset::{standard_mapping, FIRST_SPRITE_CODE};
+
+// Fonts scaled up are made once and kept in there
+const SCALED_FONTS_DIR: &str = "scaled_fonts";
+const TITLE: &str = "Rust and Steel";
+
+// Draws through libtcod, the renderer and the input share the root console
+struct TcodRenderer {
+    root: Rc<RefCell<Root>>,
+    images: HashMap<String, Image>,
+    ascii_only: bool,
+    // The glyphs come from the tileset, which the font doesn't replace
+    tiles: bool,
+}
+
+struct TcodInput {
+    root: Rc<RefCell<Root>>,
+}
+
+fn tcod_color(color: Color) -> tcod::Color {
+    tcod::Color::new(color.r, color.g, color.b)
+}
+
+impl TcodRenderer {
+    // The font only has the glyphs of code page 437, and the sprites past it
+    fn font_glyph(&self, glyph: char) -> char {
+        if glyph as u32 >= FIRST_SPRITE_CODE || glyph.is_ascii() {
+            return glyph;
+        }
+        match cp437(glyph) {
+            Some(code) if !self.ascii_only => code as char,
+            _ => fallback(glyph),
+        }
+    }
+}
+
+// How the glyphs are laid out in the font, told by the name the way libtcod names them,
+// the layout of its own fonts otherwise
+fn font_layout(font: &str) -> FontLayout {
+    if font.contains("_ro") {
+        FontLayout::AsciiInRow
+    } else if font.contains("_as") {
+        FontLayout::AsciiInCol
+    } else {
+        FontLayout::Tcod
+    }
+}
+
+// The font with every pixel made a square of the scale, the original if it can't be read
+fn scaled_font(font: &str, scale: u32) -> PathBuf {
+    let path = font_file(font);
+    if scale <= 1 {
+        return path;
+    }
+    let scaled = data_dir(SCALED_FONTS_DIR).join(format!("{}x_{}", scale, font));
+    if scaled.exists() {
+        return scaled;
+    }
+    let original = match Image::from_file(&path) {
+        Ok(original) => original,
+        Err(_) => {
+            eprintln!("Failed to read the font {}", path.display());
+            return path;
+        }
+    };
+    let scale = scale as i32;
+    let (width, height) = original.get_size();
+    let mut image = Image::new(width * scale, height * scale);
+    for y in 0..height * scale {
+        for x in 0..width * scale {
+            image.put_pixel(x, y, original.get_pixel(x / scale, y / scale));
+        }
+    }
+    image.save(&scaled);
+    scaled
+}
+
+fn in_bounds(x: i32, y: i32) -> bool {
+    x >= 0 && y >= 0 && x < SCREEN_WIDTH && y < SCREEN_HEIGHT
+}
+
+impl Renderer for TcodRenderer {
+    fn clear(&mut self) {
+        self.root.borrow_mut().clear();
+    }
+
+    fn put_char(
+        &mut self,
+        x: i32,
+        y: i32,
+        glyph: char,
+        foreground: Color,
+        background: Option<Color>,
+    ) {
+        if !in_bounds(x, y) {
+            return;
+        }
+        let glyph = self.font_glyph(glyph);
+        let mut root = self.root.borrow_mut();
+        match background {
+            Some(background) => {
+                root.put_char_ex(x, y, glyph, tcod_color(foreground), tcod_color(background))
+            }
+            None => {
+                root.set_default_foreground(tcod_color(foreground));
+                root.put_char(x, y, glyph, BackgroundFlag::None);
+            }
+        }
+    }
+
+    fn set_background(&mut self, x: i32, y: i32, color: Color) {
+        if in_bounds(x, y) {
+            self.root.borrow_mut().set_char_background(
+                x,
+                y,
+                tcod_color(color),
+                BackgroundFlag::Set,
+            );
+        }
+    }
+
+    fn print(&mut self, x: i32, y: i32, text: &str, color: Color) {
+        if !in_bounds(x, y) {
+            return;
+        }
+        let text: String = text.chars().map(|glyph| self.font_glyph(glyph)).collect();
+        let mut root = self.root.borrow_mut();
+        root.set_default_foreground(tcod_color(color));
+        root.print_ex(x, y, BackgroundFlag::None, TextAlignment::Left, text);
+    }
+
+    fn flush(&mut self) {
+        self.root.borrow_mut().flush();
+    }
+
+    fn window_closed(&self) -> bool {
+        self.root.borrow().window_closed()
+    }
+
+    fn is_fullscreen(&self) -> bool {
+        self.root.borrow().is_fullscreen()
+    }
+
+    fn set_fullscreen(&mut self, fullscreen: bool) {
+        self.root.borrow_mut().set_fullscreen(fullscreen);
+    }
+
+    fn set_fps(&mut self, fps: i32) {
+        tcod::system::set_fps(fps);
+    }
+
+    fn draw_image(&mut self, path: &str) {
+        let img = self
+            .images
+            .entry(path.into())
+            .or_insert_with(|| Image::from_file(path).expect("Background image not found"));
+        // Show the image at twice the regular console resolution
+        image::blit_2x(img, (0, 0), (-1, -1), &mut *self.root.borrow_mut(), (0, 0));
+    }
+
+    // The root console is made again with the font, the window taking its new size
+    fn set_font(&mut self, font: &str, scale: u32) {
+        if self.tiles {
+            return;
+        }
+        let fullscreen = self.is_fullscreen();
+        let mut root = self.root.borrow_mut();
+        *root = Root::initializer()
+            .size(SCREEN_WIDTH, SCREEN_HEIGHT)
+            .title(TITLE)
+            .fullscreen(fullscreen)
+            .font(scaled_font(font, scale), font_layout(font))
+            .font_type(FontType::Greyscale)
+            .init();
+    }
+}
+
+fn convert_key(key: input::Key) -> Key {
+    use tcod::input::KeyCode::*;
+
+    let code = match key.code {
+        Escape => KeyCode::Escape,
+        Backspace => KeyCode::Backspace,
+        Tab => KeyCode::Tab,
+        Enter | NumPadEnter => KeyCode::Enter,
+        Spacebar => KeyCode::Spacebar,
+        Up => KeyCode::Up,
+        Down => KeyCode::Down,
+        Left => KeyCode::Left,
+        Right => KeyCode::Right,
+        Home => KeyCode::Home,
+        End => KeyCode::End,
+        PageUp => KeyCode::PageUp,
+        PageDown => KeyCode::PageDown,
+        NumPad0 => KeyCode::NumPad0,
+        NumPad1 => KeyCode::NumPad1,
+        NumPad2 => KeyCode::NumPad2,
+        NumPad3 => KeyCode::NumPad3,
+        NumPad4 => KeyCode::NumPad4,
+        NumPad5 => KeyCode::NumPad5,
+        NumPad6 => KeyCode::NumPad6,
+        NumPad7 => KeyCode::NumPad7,
+        NumPad8 => KeyCode::NumPad8,
+        NumPad9 => KeyCode::NumPad9,
+        F1 => KeyCode::F1,
+        F2 => KeyCode::F2,
+        F3 => KeyCode::F3,
+        F4 => KeyCode::F4,
+        F5 => KeyCode::F5,
+        F6 => KeyCode::F6,
+        F7 => KeyCode::F7,
+        F8 => KeyCode::F8,
+        F9 => KeyCode::F9,
+        F10 => KeyCode::F10,
+        F11 => KeyCode::F11,
+        F12 => KeyCode::F12,
+        Char => KeyCode::Char,
+        Text => KeyCode::Text,
+        _ => KeyCode::NoKey,
+    };
+
+    // Text events carry the typed character in the text buffer
+    let printable = match key.code {
+        Text => key.text().chars().next().unwrap_or('\0'),
+        _ => key.printable,
+    };
+
+    Key {
+        code,
+        printable,
+        alt: key.alt,
+        ctrl: key.ctrl,
+        shift: key.shift,
+    }
+}
+
+impl InputSource for TcodInput {
+    fn check_for_event(&mut self) -> Option<Event> {
+        match input::check_for_event(input::MOUSE | input::KEY_PRESS) {
+            Some((_, input::Event::Mouse(m))) => Some(Event::Mouse(Mouse {
+                cx: m.cx as i32,
+                cy: m.cy as i32,
+                lbutton_pressed: m.lbutton_pressed,
+                rbutton_pressed: m.rbutton_pressed,
+            })),
+            Some((_, input::Event::Key(k))) => Some(Event::Key(convert_key(k))),
+            None => None,
+        }
+    }
+
+    fn wait_for_keypress(&mut self) -> Key {
+        convert_key(self.root.borrow_mut().wait_for_keypress(true))
+    }
+}
+
+pub fn init(config: &Config) -> Backend {
+    tcod::system::set_fps(config.options.fps);
+
+    // Fall back to ASCII if the sprite sheet is missing
+    let tiles = match config.render_mode {
+        RenderMode::Tiles if asset(&config.tileset.path).exists() => Some(standard_mapping()),
+        _ => None,
+    };
+
+    let mut initializer = Root::initializer();
+    initializer
+        .size(SCREEN_WIDTH, SCREEN_HEIGHT)
+        .title(TITLE)
+        .fullscreen(config.options.fullscreen);
+    if tiles.is_some() {
+        initializer
+            .font(asset(&config.tileset.path), FontLayout::AsciiInRow)
+            .font_type(FontType::Default)
+            .font_dimensions(config.tileset.columns, config.tileset.rows);
+    } else {
+        initializer
+            .font(
+                scaled_font(&config.options.font, config.options.font_scale),
+                font_layout(&config.options.font),
+            )
+            .font_type(FontType::Greyscale);
+    }
+    let mut root = initializer.init();
+
+    // Tell the console where to find the sprites in the sheet
+    if let Some(tiles) = &tiles {
+        for &(code, column, row) in tiles.sprites() {
+            root.map_ascii_code_to_font(code as i32, column, row);
+        }
+    }
+
+    let root = Rc::new(RefCell::new(root));
+    let renderer = TcodRenderer {
+        root: root.clone(),
+        images: HashMap::new(),
+        ascii_only: config.glyphs.ascii_only,
+        tiles: tiles.is_some(),
+    };
+    let input = TcodInput { root };
+    (Box::new(renderer), Box::new(input), tiles)
+}
@@ -0,0 +1,61 @@
+use roguelike::backend::{AudioSink, Backend};
+#[cfg(all(feature = "tcod-backend", feature = "terminal-backend"))]
+use roguelike::config::BackendKind;
+use roguelike::config::Config;
+
+#[cfg(feature = "audio")]
+mod audio;
+#[cfg(feature = "tcod-backend")]
+mod libtcod;
+#[cfg(feature = "terminal-backend")]
+mod terminal;
+
+#[cfg(not(any(feature = "tcod-backend", feature = "terminal-backend")))]
+compile_error!("Enable the tcod-backend or the terminal-backend feature");
+
+// Create the backend chosen in the config file
+#[cfg(all(feature = "tcod-backend", feature = "terminal-backend"))]
+pub fn init(config: &Config) -> Backend {
+    match config.backend {
+        BackendKind::Tcod => libtcod::init(config),
+        BackendKind::Terminal => terminal::init(config),
+    }
+}
+
+// Create the only backend compiled in
+#[cfg(all(feature = "tcod-backend", not(feature = "terminal-backend")))]
+pub fn init(config: &Config) -> Backend {
+    libtcod::init(config)
+}
+
+#[cfg(all(feature = "terminal-backend", not(feature = "tcod-backend")))]
+pub fn init(config: &Config) -> Backend {
+    terminal::init(config)
+}
+
+// Whether the backend draws the game on the standard output, leaving it to nobody else
+#[cfg(all(feature = "tcod-backend", feature = "terminal-backend"))]
+pub fn draws_on_stdout(config: &Config) -> bool {
+    config.backend == BackendKind::Terminal
+}
+
+#[cfg(all(feature = "tcod-backend", not(feature = "terminal-backend")))]
+pub fn draws_on_stdout(_config: &Config) -> bool {
+    false
+}
+
+#[cfg(all(feature = "terminal-backend", not(feature = "tcod-backend")))]
+pub fn draws_on_stdout(_config: &Config) -> bool {
+    true
+}
+
+// Sound output, whatever backend draws the game
+#[cfg(feature = "audio")]
+pub fn init_audio() -> Box<dyn AudioSink> {
+    audio::init()
+}
+
+#[cfg(not(feature = "audio"))]
+pub fn init_audio() -> Box<dyn AudioSink> {
+    Box::new(roguelike::backend::Silence)
+}
@@ -0,0 +1,308 @@
+use std::cell::Cell;
+use std::io::{stdout, Stdout, Write};
+use std::rc::Rc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crossterm::cursor::{Hide, MoveTo, Show};
+use crossterm::event::{
+    self, DisableMouseCapture, EnableMouseCapture, KeyEvent, KeyModifiers, MouseButton, MouseEvent,
+};
+use crossterm::style::{self, Print, SetBackgroundColor, SetForegroundColor};
+use crossterm::terminal::{self, Clear, ClearType, EnterAlternateScreen, LeaveAlternateScreen};
+use crossterm::{execute, queue};
+
+use roguelike::backend::{Backend, Event, InputSource, Key, KeyCode, Mouse, Renderer};
+use roguelike::colors::{Color, BLACK, WHITE};
+use roguelike::config::Config;
+use roguelike::glyphs::fallback;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct ScreenCell {
+    glyph: char,
+    foreground: Color,
+    background: Color,
+}
+
+const BLANK: ScreenCell = ScreenCell {
+    glyph: ' ',
+    foreground: WHITE,
+    background: BLACK,
+};
+
+// Draws into a terminal with crossterm, only sending the cells that changed since the last flush
+struct TerminalRenderer {
+    stdout: Stdout,
+    // Size of the cells, which is the size of the terminal when the screen was last cleared
+    width: i32,
+    height: i32,
+    cells: Vec<ScreenCell>,
+    shown: Vec<Option<ScreenCell>>,
+    closed: Rc<Cell<bool>>,
+    // Size of the terminal, the input hears of it changing
+    size: Rc<Cell<(i32, i32)>>,
+    last_flush: Instant,
+    // Shortest time a frame is shown for
+    frame: Duration,
+    ascii_only: bool,
+}
+
+// Ctrl+C closes the "window", as there is no window to close
+struct TerminalInput {
+    closed: Rc<Cell<bool>>,
+    size: Rc<Cell<(i32, i32)>>,
+}
+
+fn terminal_size() -> (i32, i32) {
+    let (width, height) = terminal::size().expect("Failed to set up the terminal");
+    (width as i32, height as i32)
+}
+
+impl TerminalRenderer {
+    fn index(&self, x: i32, y: i32) -> Option<usize> {
+        if x >= 0 && y >= 0 && x < self.width && y < self.height {
+            Some((y * self.width + x) as usize)
+        } else {
+            None
+        }
+    }
+}
+
+// How long a frame lasts at the frame rate
+fn frame_duration(fps: i32) -> Duration {
+    Duration::from_millis(1000 / fps.max(1) as u64)
+}
+
+fn terminal_color(color: Color) -> style::Color {
+    style::Color::Rgb {
+        r: color.r,
+        g: color.g,
+        b: color.b,
+    }
+}
+
+impl Renderer for TerminalRenderer {
+    fn clear(&mut self) {
+        // Lay the cells out for the terminal as big as it is now, drawing them all again
+        let (width, height) = self.size.get();
+        if (width, height) != (self.width, self.height) {
+            self.width = width;
+            self.height = height;
+            let size = (width * height) as usize;
+            self.shown = vec![None; size];
+            self.cells = vec![BLANK; size];
+            queue!(self.stdout, Clear(ClearType::All)).expect("Failed to draw to the terminal");
+        }
+        for cell in self.cells.iter_mut() {
+            *cell = BLANK;
+        }
+    }
+
+    fn put_char(
+        &mut self,
+        x: i32,
+        y: i32,
+        glyph: char,
+        foreground: Color,
+        background: Option<Color>,
+    ) {
+        if let Some(index) = self.index(x, y) {
+            let glyph = if self.ascii_only {
+                fallback(glyph)
+            } else {
+                glyph
+            };
+            let cell = &mut self.cells[index];
+            cell.glyph = glyph;
+            cell.foreground = foreground;
+            if let Some(background) = background {
+                cell.background = background;
+            }
+        }
+    }
+
+    fn set_background(&mut self, x: i32, y: i32, color: Color) {
+        if let Some(index) = self.index(x, y) {
+            self.cells[index].background = color;
+        }
+    }
+
+    fn print(&mut self, x: i32, y: i32, text: &str, color: Color) {
+        for (offset, glyph) in text.chars().enumerate() {
+            self.put_char(x + offset as i32, y, glyph, color, None);
+        }
+    }
+
+    fn flush(&mut self) {
+        for index in 0..self.cells.len() {
+            let cell = self.cells[index];
+            if self.shown[index] == Some(cell) {
+                continue;
+            }
+            let x = index as i32 % self.width;
+            let y = index as i32 / self.width;
+            queue!(
+                self.stdout,
+                MoveTo(x as u16, y as u16),
+                SetForegroundColor(terminal_color(cell.foreground)),
+                SetBackgroundColor(terminal_color(cell.background)),
+                Print(cell.glyph)
+            )
+            .expect("Failed to draw to the terminal");
+            self.shown[index] = Some(cell);
+        }
+        self.stdout.flush().expect("Failed to draw to the terminal");
+
+        // Keep to the frame limit, as there is no vsync to do it for us
+        let elapsed = self.last_flush.elapsed();
+        if elapsed < self.frame {
+            thread::sleep(self.frame - elapsed);
+        }
+        self.last_flush = Instant::now();
+    }
+
+    fn window_closed(&self) -> bool {
+        self.closed.get()
+    }
+
+    fn size(&self) -> (i32, i32) {
+        self.size.get()
+    }
+
+    fn set_fps(&mut self, fps: i32) {
+        self.frame = frame_duration(fps);
+    }
+}
+
+impl Drop for TerminalRenderer {
+    fn drop(&mut self) {
+        // Give the terminal back in the state we found it
+        let _ = execute!(self.stdout, Show, DisableMouseCapture, LeaveAlternateScreen);
+        let _ = terminal::disable_raw_mode();
+    }
+}
+
+impl TerminalInput {
+    fn convert_key(&self, key: KeyEvent) -> Key {
+        use crossterm::event::KeyCode::*;
+
+        let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+        let (code, printable) = match key.code {
+            Char('c') if ctrl => {
+                self.closed.set(true);
+                (KeyCode::NoKey, '\0')
+            }
+            Char(c) => (KeyCode::Text, c),
+            Esc => (KeyCode::Escape, '\0'),
+            Backspace => (KeyCode::Backspace, '\0'),
+            Tab => (KeyCode::Tab, '\0'),
+            Enter => (KeyCode::Enter, '\0'),
+            Up => (KeyCode::Up, '\0'),
+            Down => (KeyCode::Down, '\0'),
+            Left => (KeyCode::Left, '\0'),
+            Right => (KeyCode::Right, '\0'),
+            Home => (KeyCode::Home, '\0'),
+            End => (KeyCode::End, '\0'),
+            PageUp => (KeyCode::PageUp, '\0'),
+            PageDown => (KeyCode::PageDown, '\0'),
+            F(n) => {
+                let code = match n {
+                    1 => KeyCode::F1,
+                    2 => KeyCode::F2,
+                    3 => KeyCode::F3,
+                    4 => KeyCode::F4,
+                    5 => KeyCode::F5,
+                    6 => KeyCode::F6,
+                    7 => KeyCode::F7,
+                    8 => KeyCode::F8,
+                    9 => KeyCode::F9,
+                    10 => KeyCode::F10,
+                    11 => KeyCode::F11,
+                    12 => KeyCode::F12,
+                    _ => KeyCode::NoKey,
+                };
+                (code, '\0')
+            }
+            _ => (KeyCode::NoKey, '\0'),
+        };
+
+        Key {
+            code,
+            printable,
+            alt: key.modifiers.contains(KeyModifiers::ALT),
+            ctrl,
+            shift: key.modifiers.contains(KeyModifiers::SHIFT),
+        }
+    }
+}
+
+fn convert_mouse(mouse: MouseEvent) -> Option<Mouse> {
+    let (button, column, row) = match mouse {
+        MouseEvent::Down(button, column, row, _) => (Some(button), column, row),
+        MouseEvent::Up(_, column, row, _) | MouseEvent::Drag(_, column, row, _) => {
+            (None, column, row)
+        }
+        _ => return None,
+    };
+    Some(Mouse {
+        cx: column as i32,
+        cy: row as i32,
+        lbutton_pressed: button == Some(MouseButton::Left),
+        rbutton_pressed: button == Some(MouseButton::Right),
+    })
+}
+
+impl InputSource for TerminalInput {
+    fn check_for_event(&mut self) -> Option<Event> {
+        if !event::poll(Duration::from_millis(0)).unwrap_or(false) {
+            return None;
+        }
+        match event::read() {
+            Ok(event::Event::Key(key)) => Some(Event::Key(self.convert_key(key))),
+            Ok(event::Event::Mouse(mouse)) => convert_mouse(mouse).map(Event::Mouse),
+            Ok(event::Event::Resize(width, height)) => {
+                self.size.set((width as i32, height as i32));
+                Some(Event::Resize)
+            }
+            _ => None,
+        }
+    }
+
+    fn wait_for_keypress(&mut self) -> Key {
+        while self.check_for_event().is_some() {}
+        loop {
+            match event::read() {
+                Ok(event::Event::Key(key)) => return self.convert_key(key),
+                Ok(event::Event::Resize(width, height)) => {
+                    self.size.set((width as i32, height as i32))
+                }
+                _ => {}
+            }
+        }
+    }
+}
+
+pub fn init(config: &Config) -> Backend {
+    let mut stdout = stdout();
+    terminal::enable_raw_mode().expect("Failed to set up the terminal");
+    execute!(stdout, EnterAlternateScreen, Hide, EnableMouseCapture)
+        .expect("Failed to set up the terminal");
+
+    let (width, height) = terminal_size();
+    let size = Rc::new(Cell::new((width, height)));
+    let closed = Rc::new(Cell::new(false));
+    let renderer = TerminalRenderer {
+        stdout,
+        width,
+        height,
+        cells: vec![BLANK; (width * height) as usize],
+        shown: vec![None; (width * height) as usize],
+        closed: closed.clone(),
+        size: size.clone(),
+        last_flush: Instant::now(),
+        frame: frame_duration(config.options.fps),
+        ascii_only: config.glyphs.ascii_only,
+    };
+    let input = TerminalInput { closed, size };
+    (Box::new(renderer), Box::new(input), None)
+}
@@ -0,0 +1,200 @@
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::colors::*;
+
+use crate::affix::{imbue, PREFIXES, SUFFIXES};
+use crate::game::{Game, PLAYER};
+use crate::item::Item;
+use crate::lang::tr;
+use crate::object::Object;
+use crate::render::{inventory_menu, Context};
+use crate::rng::GameRng;
+use crate::room::{make_item, random_item};
+use crate::status::{apply_effect, Effect, Status};
+
+// How far the light of a brazier reaches
+const BRAZIER_RADIUS: i32 = 6;
+// Chance in percent of the fountain drying up after every use
+const DRY_CHANCE: i32 = 25;
+// What drinking from a fountain can do
+const FOUNTAIN_HEAL: i32 = 10;
+const FOUNTAIN_NUTRITION: i32 = 300;
+const FOUNTAIN_HASTE_TURNS: u32 = 10;
+const FOUNTAIN_POISON: i32 = 5;
+
+// Whoever the statues are made after
+const STATUES: &[&str] = &[
+    "furniture.warrior",
+    "furniture.king",
+    "furniture.dragon",
+    "furniture.sorceress",
+    "furniture.troll",
+];
+
+// Things standing in the rooms that the player can use or look at
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum Furniture {
+    Fountain,
+    Statue,
+    Brazier,
+}
+
+pub fn make_fountain(x: i32, y: i32) -> Object {
+    let mut fountain = Object::new(x, y, '{', &tr("furniture.fountain", &[]), LIGHT_BLUE, false);
+    fountain.furniture = Some(Furniture::Fountain);
+    fountain.always_visible = true;
+    fountain
+}
+
+// Statues are in the way, but can be seen past
+pub fn make_statue(x: i32, y: i32, rng: &mut GameRng) -> Object {
+    let what = tr(STATUES[rng.gen_range(0, STATUES.len())], &[]);
+    let name = tr("furniture.statue_of", &[("what", &what)]);
+    let mut statue = Object::new(x, y, '&', &name, LIGHT_GREY, true);
+    statue.furniture = Some(Furniture::Statue);
+    statue.always_visible = true;
+    statue
+}
+
+pub fn make_brazier(x: i32, y: i32) -> Object {
+    let mut brazier = Object::new(x, y, '^', "brazier", ORANGE, true);
+    brazier.furniture = Some(Furniture::Brazier);
+    brazier.light = Some(BRAZIER_RADIUS);
+    brazier.always_visible = true;
+    brazier
+}
+
+// Id of the fountain the player stands on
+fn fountain_under_player(objects: &[Object]) -> Option<usize> {
+    objects.iter().position(|object| {
+        object.furniture == Some(Furniture::Fountain) && object.pos() == objects[PLAYER].pos()
+    })
+}
+
+// Ask for the item to dip into the fountain the player stands on
+pub fn dip_menu(ctx: &mut Context, game: &mut Game, objects: &[Object]) -> Option<usize> {
+    if fountain_under_player(objects).is_none() {
+        game.messages
+            .add(tr("furniture.there_is_no_fountain_here", &[]), LIGHT_GREY);
+        return None;
+    }
+    let header = tr("furniture.dip_into_the_fountain", &[]);
+    inventory_menu(&game.inventory, &header, ctx)
+}
+
+pub fn drink_from_fountain(game: &mut Game, objects: &mut [Object]) {
+    let fountain = match fountain_under_player(objects) {
+        Some(fountain) => fountain,
+        None => {
+            game.messages
+                .add(tr("furniture.there_is_no_fountain_here", &[]), LIGHT_GREY);
+            return;
+        }
+    };
+    match game.rng.gen_range(0, 100) {
+        roll if roll < 30 => {
+            game.messages.add(
+                tr("furniture.the_cool_water_refreshes_you", &[]),
+                LIGHT_VIOLET,
+            );
+            objects[PLAYER].heal(FOUNTAIN_HEAL, game);
+        }
+        roll if roll < 45 => {
+            game.messages.add(
+                tr("furniture.the_water_is_surprisingly_filling", &[]),
+                LIGHT_GREY,
+            );
+            game.hunger = (game.hunger - FOUNTAIN_NUTRITION).max(0);
+        }
+        roll if roll < 55 => {
+            let haste = Effect {
+                status: Status::Haste,
+                turns: FOUNTAIN_HASTE_TURNS,
+            };
+            apply_effect(&mut objects[PLAYER], haste, game);
+        }
+        roll if roll < 70 => {
+            game.messages
+                .add(tr("furniture.the_water_is_foul", &[]), LIGHT_GREEN);
+            objects[PLAYER].take_damage(FOUNTAIN_POISON, false, game);
+        }
+        _ => game
+            .messages
+            .add(tr("furniture.the_water_tastes_of_nothing", &[]), LIGHT_GREY),
+    }
+    dry_up(&mut objects[fountain], game);
+}
+
+// Dip the inventory item into the fountain the player stands on
+pub fn dip(index: usize, game: &mut Game, objects: &mut [Object]) {
+    let fountain = match fountain_under_player(objects) {
+        Some(fountain) => fountain,
+        None => return,
+    };
+    if index >= game.inventory.len() {
+        return;
+    }
+    let name = game.inventory[index].name.clone();
+    match game.rng.gen_range(0, 100) {
+        roll if roll < 20 && game.inventory[index].equipment.is_some() => {
+            let count = PREFIXES.len() + SUFFIXES.len();
+            let affix = match game.rng.gen_range(0, count) {
+                i if i < PREFIXES.len() => PREFIXES[i].name,
+                i => SUFFIXES[i - PREFIXES.len()].name,
+            };
+            if imbue(&mut game.inventory[index], affix) {
+                game.messages.add(
+                    tr(
+                        "furniture.the_water_glows_and_the_becomes",
+                        &[("name", &name), ("item", &game.inventory[index].name)],
+                    ),
+                    LIGHT_BLUE,
+                );
+            } else {
+                game.messages
+                    .add(tr("furniture.the_gets_wet", &[("name", &name)]), LIGHT_GREY);
+            }
+        }
+        roll if roll < 20 => {
+            // Anything but coins
+            let item = loop {
+                let item = random_item(game.dungeon_level, &mut game.rng);
+                if item != Item::Gold {
+                    break item;
+                }
+            };
+            let letter = game.inventory[index].letter;
+            game.inventory[index] = make_item(item, 0, 0);
+            game.inventory[index].letter = letter;
+            game.messages.add(
+                tr(
+                    "furniture.the_water_swirls_and_the_turns",
+                    &[("name", &name), ("item", &game.inventory[index].name)],
+                ),
+                LIGHT_BLUE,
+            );
+        }
+        roll if roll < 35 => {
+            game.inventory.remove(index);
+            game.messages.add(
+                tr("furniture.the_current_sweeps_the_away", &[("name", &name)]),
+                LIGHT_RED,
+            );
+        }
+        _ => game
+            .messages
+            .add(tr("furniture.the_gets_wet", &[("name", &name)]), LIGHT_GREY),
+    }
+    dry_up(&mut objects[fountain], game);
+}
+
+fn dry_up(fountain: &mut Object, game: &mut Game) {
+    if game.rng.gen_range(0, 100) < DRY_CHANCE {
+        game.messages
+            .add(tr("furniture.the_fountain_dries_up", &[]), LIGHT_GREY);
+        fountain.furniture = None;
+        fountain.name = "dry fountain".into();
+        fountain.color = DARK_GREY;
+    }
+}
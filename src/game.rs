@@ -1,21 +1,60 @@
 use std::cmp;
+use std::collections::{BTreeMap, HashMap};
+use std::time::{Duration, Instant};
 
 use serde::{Deserialize, Serialize};
 
-use tcod::colors::*;
-use tcod::console::*;
-use tcod::input::{self, Event, Key};
+use crate::colors::*;
 
-use crate::ai::ai_take_turn;
-use crate::equipment::{Equipment, Slot};
-use crate::fighter::{DeathCallback, Fighter};
-use crate::item::{drop_item, pick_item, use_item, Item};
-use crate::object::{player_move_attack, Object};
+use crate::action::{perform_action, Action, DelayedAction};
+use crate::ai::{lose_morale, update_scent, wake_monsters};
+use crate::audio::update_music;
+use crate::backend::{Event, Key, KeyCode};
+use crate::behavior::Behavior;
+use crate::bestiary::{bestiary_screen, load_bestiary, note_monsters, save_bestiary, Bestiary};
+use crate::class::Character;
+use crate::clock::{ClockMode, FrameClock};
+use crate::combat::{in_reach, reach_distance};
+use crate::config::RulesConfig;
+use crate::crafting::{craft_menu, Recipe};
+use crate::dialogue::{talk, Dialogue};
+use crate::dijkstra::DijkstraMaps;
+use crate::encounter::run_phases;
+use crate::errors::log_error;
+use crate::event::{dispatch_events, emit, GameEvent};
+use crate::faction::{hostile, Faction};
+use crate::feeling::level_feeling;
+use crate::fields::Field;
+use crate::food::rot_corpses;
+use crate::furniture::dip_menu;
+use crate::grid::Grid;
+use crate::hireling::{bring_hirelings, give_menu, level_up_hirelings, take_hirelings};
+use crate::item::letter_inventory;
+use crate::item::{aim_item, Item};
+use crate::lang::tr;
+use crate::light::roll_darkness;
+use crate::narrate::narrate_turn;
+use crate::object::{find_handle, Object};
+use crate::options::options_menu;
+use crate::overworld::{make_overworld, Branch, Region};
+use crate::perk::offered_perks;
+use crate::photo::photo_mode;
+use crate::piety::offering_menu;
+use crate::plan::{follow_plan, plan_pickup, start_run, Plan};
 use crate::render::{
-    character_info_box, inventory_menu, menu, render_all, Tcod, LEVEL_SCREEN_WIDTH,
+    character_info_box, confirm, inventory_menu, menu, more_prompt, msgbox, render_all, Context,
+    Layout, View, LEVEL_SCREEN_WIDTH,
 };
-use crate::room::make_map;
-use crate::save::save_game;
+use crate::replay::{save_replay, Replay};
+use crate::respawn::Respawn;
+use crate::rng::GameRng;
+use crate::room::{make_map, uniques_on_level, Rect};
+use crate::save::{delete_save, save_game};
+use crate::spawn::place_spawned;
+use crate::stats::{record_score, show_stats, write_morgue, Stats};
+use crate::system::Systems;
+use crate::theme::Theme;
+use crate::trap::{fall_damage, land_fallen, landing_tile};
 
 pub const MAP_WIDTH: i32 = 80;
 pub const MAP_HEIGHT: i32 = 43;
@@ -25,12 +64,59 @@ pub const PLAYER: usize = 0;
 pub const LEVEL_UP_BASE: i32 = 100;
 pub const LEVEL_UP_FACTOR: i32 = 150;
 
+// Milliseconds between the changes of the real time clock on the panel,
+// racing the clock and not
+const CLOCK_TICK: u64 = 100;
+const SECOND: u64 = 1000;
+
+// Pause between the actions of a replay being watched
+const REPLAY_STEP: Duration = Duration::from_millis(100);
+
 // A tile object
 #[derive(Debug, Clone, Copy, Serialize, Deserialize)]
 pub struct Tile {
     pub blocked: bool,
     pub explored: bool,
     pub block_sight: bool,
+    // Known from magic rather than seen
+    #[serde(default)]
+    pub mapped: bool,
+    #[serde(default)]
+    pub terrain: Terrain,
+}
+
+// What stands on the tile besides the plain rock or floor
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub enum Terrain {
+    #[default]
+    Plain,
+    // Hide what is behind them, but let everyone through
+    Door,
+    Foliage,
+    // Takes twice as long to climb over
+    Rubble,
+    // Can't be walked on, only jumped into to fall to the level below
+    Chasm,
+    // Burns whoever stands in it, spread over the arenas by the bosses
+    Lava,
+}
+
+impl Terrain {
+    pub fn glyph(self) -> Option<(char, Color)> {
+        match self {
+            Terrain::Plain => None,
+            Terrain::Door => Some(('+', LIGHT_AMBER)),
+            Terrain::Foliage => Some(('"', GREEN)),
+            Terrain::Rubble => Some((';', LIGHT_SEPIA)),
+            Terrain::Chasm => Some((':', DARK_GREY)),
+            Terrain::Lava => Some(('~', FLAME)),
+        }
+    }
+
+    // Whether fire burns it down to the floor
+    pub fn flammable(self) -> bool {
+        self == Terrain::Door || self == Terrain::Foliage
+    }
 }
 
 impl Tile {
@@ -39,6 +125,8 @@ impl Tile {
             blocked: false,
             explored: false,
             block_sight: false,
+            mapped: false,
+            terrain: Terrain::Plain,
         }
     }
 
@@ -47,19 +135,73 @@ impl Tile {
             blocked: true,
             explored: false,
             block_sight: true,
+            mapped: false,
+            terrain: Terrain::Plain,
+        }
+    }
+
+    pub fn door() -> Self {
+        Tile {
+            block_sight: true,
+            terrain: Terrain::Door,
+            ..Tile::empty()
+        }
+    }
+
+    pub fn foliage() -> Self {
+        Tile {
+            block_sight: true,
+            terrain: Terrain::Foliage,
+            ..Tile::empty()
         }
     }
+
+    pub fn rubble() -> Self {
+        Tile {
+            terrain: Terrain::Rubble,
+            ..Tile::empty()
+        }
+    }
+
+    pub fn chasm() -> Self {
+        Tile {
+            blocked: true,
+            terrain: Terrain::Chasm,
+            ..Tile::empty()
+        }
+    }
+
+    pub fn lava() -> Self {
+        Tile {
+            terrain: Terrain::Lava,
+            ..Tile::empty()
+        }
+    }
+
+    // Solid rock, rather than something that is only in the way
+    pub fn is_wall(&self) -> bool {
+        self.blocked && self.block_sight
+    }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq)]
 enum PlayerAction {
-    TookTurn,
+    Act(Action),
     DidntTakeTurn,
     Exit,
+    // Leave the game throwing the save away
+    Abandon,
+}
+
+// Where the player's actions come from
+pub enum ActionSource {
+    Player,
+    // Actions of a recorded run, played back in order
+    Replay(std::vec::IntoIter<Action>),
 }
 
 // Alias for the Map type
-pub type Map = Vec<Vec<Tile>>;
+pub type Map = Grid<Tile>;
 
 // A struct to represent the state of the game
 #[derive(Serialize, Deserialize)]
@@ -68,27 +210,200 @@ pub struct Game {
     pub messages: Messages,
     pub inventory: Vec<Object>,
     pub dungeon_level: u32,
+    pub character: Character,
+    pub rng: GameRng,
+    pub replay: Replay,
+    // Real time spent playing, in milliseconds
+    pub play_time: u64,
+    // Handle of the heavy object the player is dragging behind
+    #[serde(default)]
+    pub dragging: Option<u32>,
+    // Handle of the monster aimed at last, offered first the next time
+    #[serde(default)]
+    pub last_target: Option<u32>,
+    // Last handle given to an object
+    #[serde(default)]
+    pub next_handle: u32,
+    // Objects appearing during the turn, the drops of the killed monsters and the summons,
+    // put on the map at its end
+    #[serde(default)]
+    pub spawned: Vec<Object>,
+    // Effects waiting for their turn to come
+    #[serde(default)]
+    pub delayed: Vec<DelayedAction>,
+    // Where the player arrived on the level, and where they placed an anchor on it
+    #[serde(default)]
+    pub entrance: (i32, i32),
+    #[serde(default)]
+    pub anchor: Option<(i32, i32)>,
+    // Quests given by the npcs, and how much each npc likes the player
+    #[serde(default)]
+    pub quests: Vec<String>,
+    #[serde(default)]
+    pub relationships: HashMap<String, i32>,
+    // Where the monsters killed this turn died and which side they were on,
+    // to scare their allies at its end
+    #[serde(default)]
+    pub deaths: Vec<(i32, i32, Option<Faction>)>,
+    // Noises made this turn and how far they carry, waking up the monsters at its end
+    #[serde(default)]
+    pub noises: Vec<(i32, i32, i32)>,
+    // Moving slowly and quietly
+    #[serde(default)]
+    pub sneaking: bool,
+    // Part of a turn the player has spent that the monsters haven't had yet,
+    // in hundredths of a turn
+    #[serde(default)]
+    pub time: i32,
+    // Turns since the player last ate
+    #[serde(default)]
+    pub hunger: i32,
+    #[serde(default)]
+    pub gold: u32,
+    // How pleased every deity the player made offerings to is
+    #[serde(default)]
+    pub piety: BTreeMap<String, i32>,
+    // How fresh the player's scent is on every tile, for the monsters tracking them
+    #[serde(default)]
+    pub scent: Grid<u32>,
+    // Behavior trees of the monster kinds, read from the data file on first use
+    #[serde(skip)]
+    pub behaviors: HashMap<String, Behavior>,
+    // Alchemy recipes, read from the data file on first use, and the ones the player made
+    #[serde(skip)]
+    pub recipes: Vec<Recipe>,
+    // Dialogues of the npcs, read from the data file on first use
+    #[serde(skip)]
+    pub dialogues: Vec<Dialogue>,
+    #[serde(default)]
+    pub known_recipes: Vec<usize>,
+    // The player is about to fall to the level below, and the monsters that already did
+    #[serde(default)]
+    pub falling: bool,
+    // Only the lamps the player holds light the level
+    #[serde(default)]
+    pub dark: bool,
+    #[serde(default)]
+    pub fallen: Vec<Object>,
+    // Gas and fire on the tiles of the level
+    #[serde(default)]
+    pub fields: Vec<Field>,
+    // Tiles that changed since the field of view last saw them
+    #[serde(skip)]
+    pub changed_tiles: Vec<(i32, i32)>,
+    // How dangerous the level felt on arrival, in percent of an ordinary one
+    #[serde(default)]
+    pub danger: i32,
+    // Dungeon the player went down into from the surface, and the regions of the surface
+    // while they're still on it
+    #[serde(default)]
+    pub branch: Branch,
+    #[serde(default)]
+    pub regions: Grid<Region>,
+    // Rules of the game the run was started with
+    #[serde(default)]
+    pub rules: RulesConfig,
+    // Cost maps towards and away from the player and towards the stairs, rebuilt every turn
+    #[serde(skip)]
+    pub dijkstra: DijkstraMaps,
+    // Rooms of the level, where new monsters show up in time,
+    // and the turns spent on it
+    #[serde(default)]
+    pub rooms: Vec<Rect>,
+    #[serde(default)]
+    pub level_turns: u32,
+    // How often the monsters show up, read from the data file on first use
+    #[serde(skip)]
+    pub respawns: Vec<Respawn>,
+    // Unique monsters met this run, never generated again
+    #[serde(default)]
+    pub uniques: Vec<String>,
+    #[serde(default)]
+    pub stats: Stats,
+    // What happened this turn, for the subscribers to react to at its end
+    #[serde(skip)]
+    pub events: Vec<GameEvent>,
+    // What the player keeps doing over the next turns until interrupted
+    #[serde(skip)]
+    pub plan: Option<Plan>,
+    // Monsters met in every run so far, kept in a file of its own
+    #[serde(skip)]
+    pub bestiary: Bestiary,
 }
 
-// Messages log
-#[derive(Serialize, Deserialize)]
+// How much a message matters to the player, telling the color it's shown in
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Severity {
+    Info,
+    Good,
+    Warning,
+    Danger,
+}
+
+impl Severity {
+    pub fn color(self) -> Color {
+        match self {
+            Severity::Info => WHITE,
+            Severity::Good => LIGHT_GREEN,
+            Severity::Warning => YELLOW,
+            Severity::Danger => RED,
+        }
+    }
+}
+
+// Messages log, the same message added again right away is folded into
+// the line before it rather than repeated
+#[derive(Default, Serialize, Deserialize)]
 pub struct Messages {
     pub messages: Vec<(String, Color)>,
+    // The last message as it was added and how many times in a row
+    #[serde(default)]
+    last: String,
+    #[serde(default)]
+    repeats: u32,
+    // Every message added so far, the folded ones too
+    #[serde(default)]
+    pub added: usize,
+    // Where the log ends while the messages of a turn are shown a panel at a time
+    #[serde(skip)]
+    pub shown: Option<usize>,
 }
 
 impl Messages {
     pub fn new() -> Self {
-        Messages { messages: vec![] }
+        Default::default()
     }
 
     // Add a new message
     pub fn add<T: Into<String>>(&mut self, message: T, color: Color) {
-        self.messages.push((message.into(), color));
+        let message = message.into();
+        self.added += 1;
+        match self.messages.last_mut() {
+            Some(last) if self.last == message => {
+                self.repeats += 1;
+                let count = self.repeats;
+                *last = (
+                    tr("game.repeated", &[("message", &message), ("count", &count)]),
+                    color,
+                );
+            }
+            _ => {
+                self.repeats = 1;
+                self.last = message.clone();
+                self.messages.push((message, color));
+            }
+        }
+    }
+
+    // Add a message in the color of how much it matters
+    pub fn report<T: Into<String>>(&mut self, message: T, severity: Severity) {
+        self.add(message, severity.color());
     }
 
-    // Double-ended iterator over the messages
+    // Double-ended iterator over the messages, up to where the log is shown
     pub fn iter(&self) -> impl DoubleEndedIterator<Item = &(String, Color)> {
-        self.messages.iter()
+        let end = self.shown.unwrap_or(self.messages.len());
+        self.messages[..end].iter()
     }
 }
 
@@ -107,7 +422,7 @@ pub fn from_dungeon_level(table: &[Transition], level: u32) -> u32 {
 }
 
 pub fn is_blocked(x: i32, y: i32, map: &Map, objects: &[Object]) -> bool {
-    if map[x as usize][y as usize].blocked {
+    if map[(x, y)].blocked {
         return true;
     }
 
@@ -116,6 +431,26 @@ pub fn is_blocked(x: i32, y: i32, map: &Map, objects: &[Object]) -> bool {
         .any(|object| object.blocks && object.pos() == (x, y))
 }
 
+// Whether stepping that way slips diagonally between two blocked tiles,
+// which is only allowed when the rules say so
+pub fn cuts_corner(x: i32, y: i32, dx: i32, dy: i32, game: &Game) -> bool {
+    !game.rules.corner_cutting
+        && dx != 0
+        && dy != 0
+        && game.map[(x + dx, y)].blocked
+        && game.map[(x, y + dy)].blocked
+}
+
+// Take the object off the map, the last object takes its place,
+// so only the handles of the objects stay right
+pub fn remove_object(id: usize, game: &mut Game, objects: &mut Vec<Object>) -> Object {
+    let object = objects.swap_remove(id);
+    if object.handle != 0 && game.dragging == Some(object.handle) {
+        game.dragging = None;
+    }
+    object
+}
+
 pub fn mut_two<T>(first: usize, second: usize, items: &mut [T]) -> (&mut T, &mut T) {
     assert!(first != second);
     let split_at_index = cmp::max(first, second);
@@ -127,155 +462,451 @@ pub fn mut_two<T>(first: usize, second: usize, items: &mut [T]) -> (&mut T, &mut
     }
 }
 
-pub fn initialize_fov(tcod: &mut Tcod, map: &Map) {
-    for y in 0..MAP_HEIGHT {
-        for x in 0..MAP_WIDTH {
-            tcod.fov.set(
-                x,
-                y,
-                !map[x as usize][y as usize].block_sight,
-                !map[x as usize][y as usize].blocked,
-            );
-        }
+// Change the tile, leaving the player's knowledge of it,
+// the field of view catches up with it on the next redraw
+pub fn set_tile(x: i32, y: i32, tile: Tile, game: &mut Game) {
+    let old = game.map[(x, y)];
+    game.map[(x, y)] = Tile {
+        explored: old.explored,
+        mapped: old.mapped,
+        ..tile
+    };
+    game.changed_tiles.push((x, y));
+}
+
+pub fn initialize_fov(ctx: &mut Context, map: &Map) {
+    for (x, y) in map.iter_coords() {
+        ctx.fov.set_transparent(x, y, !map[(x, y)].block_sight);
     }
-    tcod.con.clear();
 }
 
-pub fn new_game(tcod: &mut Tcod) -> (Game, Vec<Object>) {
+pub fn new_game(ctx: &mut Context, seed: u64, character: &Character) -> (Game, Vec<Object>) {
     // Create player object
     let player = {
-        let mut res = Object::new(0, 0, '@', "player", WHITE, true);
+        let mut res = Object::new(0, 0, '@', &character.name, WHITE, true);
         res.alive = true;
-        res.fighter = Some(Fighter {
-            base_max_hp: 100,
-            hp: 100,
-            base_defense: 0,
-            base_power: 5,
-            xp: 0,
-            on_death: DeathCallback::Player,
-        });
+        res.fighter = Some(character.class.fighter());
+        res.faction = Some(Faction::Player);
         res
     };
 
     // List of game objects
     let mut objects = vec![player];
 
-    const INITIAL_LEVEL: u32 = 1;
+    // The run starts on the surface, above the dungeons
+    const INITIAL_LEVEL: u32 = 0;
+    let mut rng = GameRng::new(seed);
+    let (map, regions) = make_overworld(&mut objects, &mut rng);
     let mut game = Game {
-        map: make_map(&mut objects, INITIAL_LEVEL),
+        map,
         messages: Messages::new(),
-        inventory: vec![],
+        inventory: character.class.starting_inventory(),
         dungeon_level: INITIAL_LEVEL,
+        character: character.clone(),
+        rng,
+        replay: Replay::new(seed, character.clone(), ctx.rules.clone()),
+        play_time: 0,
+        dragging: None,
+        last_target: None,
+        next_handle: 0,
+        spawned: vec![],
+        delayed: vec![],
+        entrance: objects[PLAYER].pos(),
+        anchor: None,
+        quests: vec![],
+        relationships: HashMap::new(),
+        deaths: vec![],
+        noises: vec![],
+        sneaking: false,
+        time: 0,
+        hunger: 0,
+        gold: 0,
+        piety: BTreeMap::new(),
+        scent: Default::default(),
+        behaviors: HashMap::new(),
+        recipes: vec![],
+        dialogues: vec![],
+        known_recipes: vec![],
+        falling: false,
+        dark: false,
+        fallen: vec![],
+        fields: vec![],
+        changed_tiles: vec![],
+        rules: ctx.rules.clone(),
+        danger: 0,
+        branch: Branch::default(),
+        regions,
+        dijkstra: Default::default(),
+        rooms: vec![],
+        level_turns: 0,
+        respawns: vec![],
+        uniques: uniques_on_level(&objects),
+        stats: Stats {
+            deepest_level: INITIAL_LEVEL,
+            ..Default::default()
+        },
+        events: vec![],
+        plan: None,
+        bestiary: Default::default(),
     };
 
-    let dagger = {
-        let mut res = Object::new(0, 0, '-', "dagger", SKY, false);
-        res.item = Some(Item::Sword);
-        res.equipment = Some(Equipment {
-            equipped: true,
-            slot: Slot::LeftHand,
-            max_hp_bonus: 0,
-            power_bonus: 5,
-            defense_bonus: 1,
-        });
-        res
-    };
-    game.inventory.push(dagger);
-
-    initialize_fov(tcod, &game.map);
+    letter_inventory(&mut game.inventory);
+    initialize_fov(ctx, &game.map);
 
     game.messages
-        .add("Prepare yourself to the world of rust and steel", RED);
+        .add(tr("game.prepare_yourself_to_the_world_of", &[]), RED);
 
     (game, objects)
 }
 
-pub fn play_game(tcod: &mut Tcod, game: &mut Game, objects: &mut Vec<Object>) {
-    // Recompute the fov
-    let mut previous_player_position = (-1, -1);
+// Everything the loop of the game keeps from one frame to the next
+struct Session {
+    source: ActionSource,
+    systems: Systems,
+    frame_clock: FrameClock,
+    // When the monsters last moved, for the ultra mode
+    last_turn: Instant,
+    // Real time gathered towards the next step of a replay
+    replay_wait: Duration,
+    // The screen is only drawn again when something on it could have changed:
+    // after an action, after an input event and when the shown clock ticks
+    dirty: bool,
+    shown_time: u64,
+}
 
-    while !tcod.root.window_closed() {
-        // Clear previous frame
-        tcod.con.clear();
+pub fn play_game(
+    ctx: &mut Context,
+    game: &mut Game,
+    objects: &mut Vec<Object>,
+    source: ActionSource,
+) {
+    let mut session = Session {
+        source,
+        systems: Systems::turn(),
+        frame_clock: FrameClock::new(),
+        last_turn: Instant::now(),
+        replay_wait: Duration::ZERO,
+        dirty: true,
+        shown_time: 0,
+    };
 
-        match input::check_for_event(input::MOUSE | input::KEY_PRESS) {
-            Some((_, Event::Mouse(m))) => tcod.mouse = m,
-            Some((_, Event::Key(k))) => tcod.key = k,
-            _ => tcod.key = Default::default(),
+    if let ActionSource::Player = session.source {
+        game.bestiary = load_bestiary().unwrap_or_default();
+    }
+
+    'frames: while !ctx.renderer.window_closed() {
+        // Every event waiting is handled before the frame is drawn, a key per update,
+        // so typing ahead never loses a key
+        let mut keys = vec![];
+        while let Some(event) = ctx.input.check_for_event() {
+            match event {
+                Event::Mouse(m) => {
+                    ctx.mouse = m;
+                    session.dirty = true;
+                }
+                Event::Key(k) => keys.push(k),
+                Event::Resize => session.dirty = true,
+            }
+        }
+        // Without a key there is still the plan to follow, the replay to play back
+        // and the clock of the ultra mode to watch
+        if keys.is_empty() {
+            keys.push(Default::default());
         }
 
-        // render the screen
-        let fov_recompute = previous_player_position != (objects[PLAYER].pos());
-        render_all(tcod, game, &objects, fov_recompute);
+        // Keep the clock running while the player is alive
+        let delta = session.frame_clock.tick();
+        match session.source {
+            ActionSource::Player if objects[PLAYER].alive => {
+                game.play_time += delta.as_millis() as u64
+            }
+            ActionSource::Player => {}
+            ActionSource::Replay(_) => session.replay_wait += delta,
+        }
 
-        tcod.root.flush();
+        for key in keys {
+            ctx.key = key;
+            if !update(ctx, game, objects, &mut session) {
+                break 'frames;
+            }
+        }
+        draw(ctx, game, objects, &mut session);
+        ctx.renderer.flush();
+        update_music(delta, ctx, game, objects);
+    }
+    ctx.audio.stop_music();
+
+    if let ActionSource::Player = session.source {
+        if let Err(e) = save_replay(&game.replay) {
+            log_error(&format!("Failed to save the replay: {}", e));
+        }
+        if let Err(e) = save_bestiary(&game.bestiary) {
+            log_error(&format!("Failed to save the bestiary: {}", e));
+        }
+    }
+}
 
-        // check leveling up
-        level_up(tcod, game, objects);
+// Draw the screen, if anything on it could have changed since it was last drawn
+fn draw(ctx: &mut Context, game: &mut Game, objects: &[Object], session: &mut Session) {
+    let tick = match ctx.clock.mode {
+        ClockMode::Off => SECOND,
+        _ => CLOCK_TICK,
+    };
+    let time = game.play_time / tick;
+    if session.dirty || time != session.shown_time {
+        render_all(ctx, game, objects);
+        note_monsters(ctx, game, objects);
+        narrate_turn(ctx, game, objects);
+        session.shown_time = time;
+        session.dirty = false;
+    }
+}
+
+// Take the player's action for the key, if any, and let the world answer it.
+// Returns false once the player leaves the game
+fn update(
+    ctx: &mut Context,
+    game: &mut Game,
+    objects: &mut Vec<Object>,
+    session: &mut Session,
+) -> bool {
+    // Menus are drawn over the map as it is now, and the monsters act on
+    // the field of view it was drawn with
+    draw(ctx, game, objects, session);
+
+    let idle_expired = objects[PLAYER].alive
+        && matches!(ctx.clock.idle_turn(), Some(turn) if session.last_turn.elapsed() >= turn);
+
+    // check leveling up, then handle keys
+    let player_action = match &mut session.source {
+        ActionSource::Player => match level_up_choice(ctx, objects) {
+            Some(stat) => PlayerAction::Act(Action::LevelUp(stat)),
+            // Any key stops the plan the player follows
+            None if game.plan.is_some() => match follow_plan(ctx, game, objects) {
+                Some(action) => PlayerAction::Act(action),
+                None => PlayerAction::DidntTakeTurn,
+            },
+            None => match handle_keys(ctx, game, objects) {
+                // In the ultra mode the monsters don't wait for the player
+                PlayerAction::DidntTakeTurn if idle_expired => PlayerAction::Act(Action::Idle),
+                player_action => player_action,
+            },
+        },
+        ActionSource::Replay(actions) => replay_step(ctx, actions, &mut session.replay_wait),
+    };
+    // The key might open a menu over the map, which has to be drawn over again
+    if ctx.key.code != KeyCode::NoKey {
+        session.dirty = true;
+    }
+
+    let action = match player_action {
+        PlayerAction::Act(action) => action,
+        PlayerAction::DidntTakeTurn => return true,
+        PlayerAction::Exit => {
+            if let ActionSource::Player = session.source {
+                save_game(game, objects).unwrap();
+            }
+            return false;
+        }
+        PlayerAction::Abandon => {
+            if let Err(e) = delete_save(&game.character.name) {
+                log_error(&format!("Failed to delete the save: {}", e));
+            }
+            return false;
+        }
+    };
+    if let ActionSource::Player = session.source {
+        game.replay.actions.push(action);
+    }
+    session.dirty = true;
+    let turns = action.turns(game, objects);
+    let was_alive = objects[PLAYER].alive;
+    let first_message = game.messages.messages.len();
+    perform_action(action, ctx, game, objects);
+    update_scent(game, objects);
+    if let (ActionSource::Player, Action::Move(..)) = (&session.source, action) {
+        plan_pickup(ctx, game, objects);
+    }
 
-        // handle keys
-        previous_player_position = objects[PLAYER].pos();
-        let player_action = handle_keys(tcod, game, objects);
-        if player_action == PlayerAction::Exit {
-            save_game(game, objects).unwrap();
-            break;
+    // Let monsters tke turn
+    if objects[PLAYER].alive && turns > 0 {
+        session.last_turn = Instant::now();
+        for _ in 0..turns {
+            session.systems.run(ctx, game, objects);
+            if game.falling {
+                break;
+            }
         }
+        rot_corpses(turns, game, objects);
+    }
+    if game.falling && objects[PLAYER].alive {
+        fall(ctx, game, objects);
+    }
+    place_spawned(game, objects);
+    lose_morale(game, objects);
+    wake_monsters(ctx, game, objects);
+    run_phases(ctx, game, objects);
+    dispatch_events(ctx, game);
+    if let ActionSource::Player = session.source {
+        more_prompt(ctx, game, objects, first_message);
+    }
 
-        // Let monsters tke turn
-        if objects[PLAYER].alive && player_action == PlayerAction::TookTurn {
-            for id in 0..objects.len() {
-                if objects[id].ai.is_some() {
-                    ai_take_turn(id, tcod, game, objects);
-                }
+    if was_alive && !objects[PLAYER].alive {
+        if let ActionSource::Player = session.source {
+            render_all(ctx, game, objects);
+            if let Err(e) = write_morgue(game, &objects[PLAYER]) {
+                log_error(&format!("Failed to write the morgue file: {}", e));
+            }
+            if let Err(e) = record_score(game, &objects[PLAYER]) {
+                log_error(&format!("Failed to record the score: {}", e));
             }
+            show_stats(game, ctx);
         }
     }
+    true
 }
 
-// Return the position of the clicked tile, or (None, None) if right clicked
+// Play back the next recorded action once its step has come, Escape stops watching
+fn replay_step(
+    ctx: &mut Context,
+    actions: &mut std::vec::IntoIter<Action>,
+    wait: &mut Duration,
+) -> PlayerAction {
+    if ctx.key.code == KeyCode::Escape {
+        return PlayerAction::Exit;
+    }
+    if *wait < REPLAY_STEP {
+        return PlayerAction::DidntTakeTurn;
+    }
+    *wait = Duration::ZERO;
+    match actions.next() {
+        Some(action) => PlayerAction::Act(action),
+        None => {
+            msgbox(&tr("game.end_of_the_replay", &[]), 24, ctx);
+            PlayerAction::Exit
+        }
+    }
+}
+
+// Ids of the enemies in sight and in range, nearest first
+fn enemies_in_reach(ctx: &Context, objects: &[Object], max_range: Option<f32>) -> Vec<usize> {
+    let player = &objects[PLAYER];
+    let mut enemies = objects
+        .iter()
+        .enumerate()
+        .filter(|(_, object)| {
+            object.alive
+                && hostile(object.faction, player.faction)
+                && ctx.fov.is_in_fov(object.x, object.y)
+                && max_range.is_none_or(|range| player.distance(object.x, object.y) <= range)
+        })
+        .map(|(id, _)| id)
+        .collect::<Vec<_>>();
+    enemies
+        .sort_by_key(|&id| (objects[id].x - player.x).pow(2) + (objects[id].y - player.y).pow(2));
+    enemies
+}
+
+// Return the position of the tile picked, in sight and in range, or None if cancelled.
+// The mouse or the arrows move the cursor and Tab jumps to the next enemy in sight,
+// nearest first; a click or Enter picks the tile, a right click or Escape cancels
 pub fn target_tile(
-    tcod: &mut Tcod,
+    ctx: &mut Context,
     game: &mut Game,
     objects: &[Object],
     max_range: Option<f32>,
 ) -> Option<(i32, i32)> {
-    use tcod::input::KeyCode::Escape;
-    tcod.mouse = Default::default();
-    loop {
-        // render the screen -> erase inventory and show the names under the cursor
-        tcod.root.flush();
+    let player = &objects[PLAYER];
+    let in_reach = |(x, y): (i32, i32), ctx: &Context| {
+        ctx.fov.is_in_fov(x, y) && max_range.is_none_or(|range| player.distance(x, y) <= range)
+    };
+    let enemies = enemies_in_reach(ctx, objects, max_range)
+        .into_iter()
+        .map(|id| objects[id].pos())
+        .collect::<Vec<_>>();
+    // The cursor starts on the monster aimed at last if it's still in reach,
+    // or else on the nearest enemy, or on the player if there's none
+    let last = game
+        .last_target
+        .and_then(|handle| find_handle(handle, objects))
+        .map(|id| objects[id].pos())
+        .filter(|pos| enemies.contains(pos));
+    let mut cursor = last.or(enemies.first().copied()).unwrap_or(player.pos());
+    let mut next_enemy = enemies
+        .iter()
+        .position(|&pos| pos == cursor)
+        .map_or(0, |index| index + 1);
 
-        let event = input::check_for_event(input::KEY_PRESS | input::MOUSE).map(|e| e.1);
-        match event {
-            Some(Event::Mouse(m)) => tcod.mouse = m,
-            Some(Event::Key(k)) => tcod.key = k,
-            None => tcod.key = Default::default(),
+    ctx.mouse = Default::default();
+    loop {
+        let view = View::player(Layout::of(&*ctx.renderer), player.pos());
+        ctx.key = Default::default();
+        match ctx.input.check_for_event() {
+            Some(Event::Mouse(m)) => {
+                ctx.mouse = m;
+                let on_map = view.to_map(m.cx, m.cy);
+                if let Some(tile) = on_map {
+                    cursor = tile;
+                }
+                if m.lbutton_pressed && on_map.is_some() && in_reach(cursor, ctx) {
+                    return Some(cursor);
+                }
+                if m.rbutton_pressed {
+                    return None;
+                }
+            }
+            Some(Event::Key(k)) => ctx.key = k,
+            Some(Event::Resize) | None => {}
         }
-        render_all(tcod, game, objects, false);
-
-        let (x, y) = (tcod.mouse.cx as i32, tcod.mouse.cy as i32);
-
-        // Chech if visible and in range
-        let in_fov = (x < MAP_WIDTH) && (y < MAP_HEIGHT) && tcod.fov.is_in_fov(x, y);
-        let in_range = max_range.map_or(true, |range| objects[PLAYER].distance(x, y) <= range);
-        if tcod.mouse.lbutton_pressed && in_fov && in_range {
-            return Some((x, y));
+        match ctx.key.code {
+            KeyCode::Escape => return None,
+            KeyCode::Enter if in_reach(cursor, ctx) => return Some(cursor),
+            KeyCode::Tab if !enemies.is_empty() => {
+                cursor = enemies[next_enemy % enemies.len()];
+                next_enemy += 1;
+            }
+            code => {
+                if let Some((dx, dy)) = direction(code) {
+                    cursor = (
+                        (cursor.0 + dx).clamp(0, MAP_WIDTH - 1),
+                        (cursor.1 + dy).clamp(0, MAP_HEIGHT - 1),
+                    );
+                }
+            }
         }
-        if tcod.mouse.rbutton_pressed || tcod.key.code == Escape {
-            return None;
+
+        // Highlight the target, the tooltip telling about it
+        let screen = view.to_screen(cursor.0, cursor.1);
+        let (x, y) = screen.unwrap_or((-1, -1));
+        ctx.mouse.cx = x;
+        ctx.mouse.cy = y;
+        render_all(ctx, game, objects);
+        let colors = ctx.options.colors.ui();
+        let color = if in_reach(cursor, ctx) {
+            colors.highlight
+        } else {
+            colors.worse
+        };
+        if screen.is_some() {
+            ctx.renderer.set_background(x, y, color);
         }
+        ctx.renderer.flush();
     }
 }
 
 // Return the id of the clicked monster or None if no selected
 pub fn target_monster(
-    tcod: &mut Tcod,
+    ctx: &mut Context,
     game: &mut Game,
     objects: &[Object],
     max_range: Option<f32>,
 ) -> Option<usize> {
+    // Nothing to choose from with a single enemy in reach
+    if let [only] = enemies_in_reach(ctx, objects, max_range)[..] {
+        return Some(only);
+    }
     loop {
-        match target_tile(tcod, game, objects, max_range) {
+        match target_tile(ctx, game, objects, max_range) {
             Some((x, y)) => {
                 for (id, obj) in objects.iter().enumerate() {
                     if obj.pos() == (x, y) && obj.fighter.is_some() && id != PLAYER {
@@ -288,75 +919,215 @@ pub fn target_monster(
     }
 }
 
-pub fn next_level(tcod: &mut Tcod, game: &mut Game, objects: &mut Vec<Object>) {
-    game.messages.add("You go deeper...", VIOLET);
+pub fn next_level(ctx: &mut Context, game: &mut Game, objects: &mut Vec<Object>) {
+    game.messages.add(tr("game.you_go_deeper", &[]), VIOLET);
     let heal_hp = objects[PLAYER].fighter.map_or(0, |f| f.base_max_hp / 2);
     objects[PLAYER].heal(heal_hp, game);
+    enter_level(None, ctx, game, objects);
+}
 
+// Drop the player to the spot right under them on the level below
+fn fall(ctx: &mut Context, game: &mut Game, objects: &mut Vec<Object>) {
+    game.falling = false;
+    let fell_at = objects[PLAYER].pos();
+    enter_level(Some(fell_at), ctx, game, objects);
+    game.messages
+        .add(tr("game.you_land_hard_on_the_level", &[]), LIGHT_RED);
+    let damage = fall_damage(game);
+    objects[PLAYER].take_damage(damage, false, game);
+}
+
+// Make the next level and put the player in it,
+// where they fell to if they didn't take the stairs
+fn enter_level(
+    fell_at: Option<(i32, i32)>,
+    ctx: &mut Context,
+    game: &mut Game,
+    objects: &mut Vec<Object>,
+) {
     game.dungeon_level += 1;
-    game.map = make_map(objects, game.dungeon_level);
-    initialize_fov(tcod, &game.map);
+    let theme = Theme::at(game.branch, game.dungeon_level);
+    if theme != Theme::at(game.branch, game.dungeon_level - 1) {
+        game.messages.add(
+            tr("game.you_enter_the", &[("theme", &theme.name())]),
+            LIGHT_VIOLET,
+        );
+    }
+    emit(
+        GameEvent::LevelChanged {
+            level: game.dungeon_level,
+        },
+        game,
+    );
+    // Whatever was dragged stays on the level above
+    game.dragging = None;
+    // The hirelings follow the player down
+    let hirelings = take_hirelings(objects);
+    let (map, rooms) = make_map(
+        objects,
+        game.dungeon_level,
+        game.branch,
+        &game.uniques,
+        &mut game.rng,
+    );
+    game.map = map;
+    game.rooms = rooms;
+    game.level_turns = 0;
+    game.uniques.extend(uniques_on_level(objects));
+    if let Some((x, y)) = fell_at.and_then(|(x, y)| landing_tile(x, y, game, objects)) {
+        objects[PLAYER].set_pos(x, y);
+    }
+    land_fallen(game, objects);
+    bring_hirelings(hirelings, game, objects);
+    level_feeling(game, objects);
+    game.entrance = objects[PLAYER].pos();
+    game.anchor = None;
+    game.scent = Default::default();
+    game.fields.clear();
+    game.regions = Default::default();
+    roll_darkness(game);
+    initialize_fov(ctx, &game.map);
 }
 
-fn level_up(tcod: &mut Tcod, game: &mut Game, objects: &mut [Object]) {
+// Let the player choose a stat to raise, if they have enough experience to level up
+fn level_up_choice(ctx: &mut Context, objects: &[Object]) -> Option<usize> {
+    let player = &objects[PLAYER];
+    let level_up_xp = LEVEL_UP_BASE + player.level * LEVEL_UP_FACTOR;
+    let fighter = player.fighter.as_ref()?;
+    if fighter.xp < level_up_xp {
+        return None;
+    }
+
+    let mut options = vec![
+        tr(
+            "game.raise_constitution",
+            &[
+                ("from", &fighter.base_max_hp),
+                ("to", &(fighter.base_max_hp + 20)),
+            ],
+        ),
+        tr(
+            "game.raise_strength",
+            &[
+                ("from", &fighter.base_power),
+                ("to", &(fighter.base_power + 1)),
+            ],
+        ),
+        tr(
+            "game.raise_agility",
+            &[
+                ("from", &fighter.base_defense),
+                ("to", &(fighter.base_defense + 1)),
+            ],
+        ),
+    ];
+    options.extend(
+        offered_perks(player.level, &player.perks)
+            .iter()
+            .map(|perk| format!("{}: {}", perk.name(), perk.description())),
+    );
+
+    let mut choice = None;
+    while choice.is_none() {
+        choice = menu(
+            &tr("game.level_up_choose", &[]),
+            &options,
+            LEVEL_SCREEN_WIDTH,
+            ctx,
+        );
+    }
+    choice
+}
+
+pub fn level_up(game: &mut Game, objects: &mut [Object], stat: usize) {
     let player = &mut objects[PLAYER];
     let level_up_xp = LEVEL_UP_BASE + player.level * LEVEL_UP_FACTOR;
+    let perks = offered_perks(player.level, &player.perks);
+
+    player.level += 1;
+    game.messages
+        .report(tr("game.your_powers_grow_stronger", &[]), Severity::Good);
+    emit(
+        GameEvent::LeveledUp {
+            level: player.level,
+        },
+        game,
+    );
 
-    if player.fighter.as_ref().map_or(0, |f| f.xp) >= level_up_xp {
-        player.level += 1;
-        game.messages.add("Your powers grow stronger", YELLOW);
-
-        let fighter = player.fighter.as_mut().unwrap();
-        let mut choice = None;
-
-        while choice.is_none() {
-            choice = menu(
-                "Level up! Choose a stat to raise:\n",
-                &[
-                    format!(
-                        "Constitution (+20hp {} -> {})",
-                        fighter.base_max_hp,
-                        fighter.base_max_hp + 20
-                    ),
-                    format!(
-                        "Strength (+1 damage {} -> {})",
-                        fighter.base_power,
-                        fighter.base_power + 1
-                    ),
-                    format!(
-                        "Agility (+1 defense {} -> {})",
-                        fighter.base_defense,
-                        fighter.base_defense + 1
-                    ),
-                ],
-                LEVEL_SCREEN_WIDTH,
-                &mut tcod.root,
+    let fighter = player.fighter.as_mut().unwrap();
+    fighter.xp -= level_up_xp;
+    match stat {
+        0 => {
+            fighter.base_max_hp += 20;
+            fighter.hp += 20;
+        }
+        1 => {
+            fighter.base_power += 1;
+        }
+        2 => {
+            fighter.base_defense += 1;
+        }
+        _ => {
+            let perk = perks[stat - 3];
+            game.messages.report(
+                tr("game.you_gain_the_perk", &[("perk", &perk.name())]),
+                Severity::Good,
             );
+            player.perks.push(perk);
         }
-        fighter.xp -= level_up_xp;
-        match choice.unwrap() {
-            0 => {
-                fighter.base_max_hp += 20;
-                fighter.hp += 20;
-            }
-            1 => {
-                fighter.base_power += 1;
-            }
-            2 => {
-                fighter.base_defense += 1;
-            }
-            _ => {
-                unreachable!();
-            }
+    }
+    level_up_hirelings(game, objects);
+}
+
+// Step the arrows and the numpad keys point to
+fn direction(code: KeyCode) -> Option<(i32, i32)> {
+    use KeyCode::*;
+
+    match code {
+        Up | NumPad8 => Some((0, -1)),
+        Down | NumPad2 => Some((0, 1)),
+        Left | NumPad4 => Some((-1, 0)),
+        Right | NumPad6 => Some((1, 0)),
+        NumPad9 => Some((1, -1)),
+        NumPad7 => Some((-1, -1)),
+        NumPad1 => Some((-1, 1)),
+        NumPad3 => Some((1, 1)),
+        _ => None,
+    }
+}
+
+// What Escape brings up while playing, so a stray key press doesn't end the session
+fn pause_menu(ctx: &mut Context) -> PlayerAction {
+    let choices = [
+        tr("game.resume", &[]),
+        tr("game.options", &[]),
+        tr("game.save_and_quit", &[]),
+        tr("game.abandon_run", &[]),
+    ];
+    match menu(&tr("game.paused", &[]), &choices, 24, ctx) {
+        Some(1) => {
+            options_menu(ctx);
+            PlayerAction::DidntTakeTurn
         }
+        Some(2) => PlayerAction::Exit,
+        // There's no coming back from it, so always ask
+        Some(3) if confirm(&tr("game.abandon_the_run", &[]), ctx) => PlayerAction::Abandon,
+        _ => PlayerAction::DidntTakeTurn,
     }
 }
 
-fn handle_keys(tcod: &mut Tcod, mut game: &mut Game, objects: &mut Vec<Object>) -> PlayerAction {
-    use tcod::input::KeyCode::*;
+fn handle_keys(ctx: &mut Context, game: &mut Game, objects: &[Object]) -> PlayerAction {
+    use KeyCode::*;
 
     let player_alive = objects[PLAYER].alive;
-    return match (tcod.key, tcod.key.text(), player_alive) {
+    // Shift and a direction runs that way
+    if let (Some((dx, dy)), true, true) = (direction(ctx.key.code), ctx.key.shift, player_alive) {
+        return match start_run(dx, dy, ctx, game, objects) {
+            Some(action) => PlayerAction::Act(action),
+            None => PlayerAction::DidntTakeTurn,
+        };
+    }
+    let player_action = match (ctx.key, player_alive) {
         (
             Key {
                 code: Enter,
@@ -364,102 +1135,260 @@ fn handle_keys(tcod: &mut Tcod, mut game: &mut Game, objects: &mut Vec<Object>)
                 ..
             },
             _,
-            _,
         ) => {
-            let fullscreen = tcod.root.is_fullscreen();
-            tcod.root.set_fullscreen(!fullscreen);
+            let fullscreen = ctx.renderer.is_fullscreen();
+            ctx.renderer.set_fullscreen(!fullscreen);
             PlayerAction::DidntTakeTurn
         }
-        (Key { code: Escape, .. }, _, _) => PlayerAction::Exit,
+        (Key { code: Escape, .. }, _) => pause_menu(ctx),
 
-        (Key { code: Up, .. }, _, true) | (Key { code: NumPad8, .. }, _, true) => {
-            player_move_attack(0, -1, &mut game, objects);
-            PlayerAction::TookTurn
-        }
-        (Key { code: Down, .. }, _, true) | (Key { code: NumPad2, .. }, _, true) => {
-            player_move_attack(0, 1, &mut game, objects);
-            PlayerAction::TookTurn
-        }
-        (Key { code: Left, .. }, _, true) | (Key { code: NumPad4, .. }, _, true) => {
-            player_move_attack(-1, 0, &mut game, objects);
-            PlayerAction::TookTurn
-        }
-        (Key { code: Right, .. }, _, true) | (Key { code: NumPad6, .. }, _, true) => {
-            player_move_attack(1, 0, &mut game, objects);
-            PlayerAction::TookTurn
-        }
-        (Key { code: NumPad9, .. }, _, true) => {
-            player_move_attack(1, -1, game, objects);
-            PlayerAction::TookTurn
+        (Key { code: Up, .. }, true) | (Key { code: NumPad8, .. }, true) => {
+            PlayerAction::Act(Action::Move(0, -1))
         }
-        (Key { code: NumPad7, .. }, _, true) => {
-            player_move_attack(-1, -1, game, objects);
-            PlayerAction::TookTurn
+        (Key { code: Down, .. }, true) | (Key { code: NumPad2, .. }, true) => {
+            PlayerAction::Act(Action::Move(0, 1))
         }
-        (Key { code: NumPad1, .. }, _, true) => {
-            player_move_attack(-1, 1, game, objects);
-            PlayerAction::TookTurn
+        (Key { code: Left, .. }, true) | (Key { code: NumPad4, .. }, true) => {
+            PlayerAction::Act(Action::Move(-1, 0))
         }
-        (Key { code: NumPad3, .. }, _, true) => {
-            player_move_attack(1, 1, game, objects);
-            PlayerAction::TookTurn
+        (Key { code: Right, .. }, true) | (Key { code: NumPad6, .. }, true) => {
+            PlayerAction::Act(Action::Move(1, 0))
         }
-        (Key { code: NumPad5, .. }, _, true) => {
-            game.messages.add("You rest...", VIOLET);
-            objects[PLAYER].heal(1, game);
-            PlayerAction::TookTurn
-        }
-        (Key { code: Text, .. }, "g", true) => {
-            // Look for an item under the player
-            let item = objects
-                .iter()
-                .position(|o| o.pos() == objects[PLAYER].pos() && o.item.is_some());
-            if let Some(id) = item {
-                pick_item(id, game, objects);
+        (Key { code: NumPad9, .. }, true) => PlayerAction::Act(Action::Move(1, -1)),
+        (Key { code: NumPad7, .. }, true) => PlayerAction::Act(Action::Move(-1, -1)),
+        (Key { code: NumPad1, .. }, true) => PlayerAction::Act(Action::Move(-1, 1)),
+        (Key { code: NumPad3, .. }, true) => PlayerAction::Act(Action::Move(1, 1)),
+        (Key { code: NumPad5, .. }, true) => PlayerAction::Act(Action::Rest),
+        (
+            Key {
+                code: Text,
+                printable: 'a',
+                ..
+            },
+            true,
+        ) => {
+            // Choose whom to hit, over the tile between with a polearm
+            let reach = reach_distance(objects[PLAYER].reach(game));
+            game.messages
+                .add(tr("game.choose_whom_to_hit", &[]), LIGHT_GREY);
+            match target_monster(ctx, game, objects, Some(reach)) {
+                Some(id) if in_reach(PLAYER, id, game, objects) => {
+                    PlayerAction::Act(Action::Strike(objects[id].x, objects[id].y))
+                }
+                Some(_) => {
+                    game.messages
+                        .add(tr("combat.out_of_reach", &[]), LIGHT_GREY);
+                    PlayerAction::DidntTakeTurn
+                }
+                None => PlayerAction::DidntTakeTurn,
             }
-            PlayerAction::TookTurn
         }
-        (Key { code: Text, .. }, "i", true) => {
+        (
+            Key {
+                code: Text,
+                printable: 'g',
+                ..
+            },
+            true,
+        ) => PlayerAction::Act(Action::PickUp),
+        (
+            Key {
+                code: Text,
+                printable: 'G',
+                ..
+            },
+            true,
+        ) => PlayerAction::Act(Action::Drag),
+        (
+            Key {
+                code: Text,
+                printable: 'e',
+                ..
+            },
+            true,
+        ) => PlayerAction::Act(Action::Eat),
+        (
+            Key {
+                code: Text,
+                printable: 'o',
+                ..
+            },
+            true,
+        ) => match offering_menu(ctx, game, objects) {
+            Some(offering) => PlayerAction::Act(Action::Sacrifice(offering)),
+            None => PlayerAction::DidntTakeTurn,
+        },
+        (
+            Key {
+                code: Text,
+                printable: 'q',
+                ..
+            },
+            true,
+        ) => PlayerAction::Act(Action::Drink),
+        (
+            Key {
+                code: Text,
+                printable: 'Q',
+                ..
+            },
+            true,
+        ) => match dip_menu(ctx, game, objects) {
+            Some(index) => PlayerAction::Act(Action::Dip(index)),
+            None => PlayerAction::DidntTakeTurn,
+        },
+        (
+            Key {
+                code: Text,
+                printable: 'h',
+                ..
+            },
+            true,
+        ) => match give_menu(ctx, game, objects) {
+            Some((hireling, item)) => PlayerAction::Act(Action::Give { hireling, item }),
+            None => PlayerAction::DidntTakeTurn,
+        },
+        (
+            Key {
+                code: Text,
+                printable: 's',
+                ..
+            },
+            true,
+        ) => PlayerAction::Act(Action::Sneak),
+        (
+            Key {
+                code: Text,
+                printable: 'i',
+                ..
+            },
+            true,
+        ) => {
             let chosen_item_id = inventory_menu(
                 &game.inventory as &[Object],
-                "Press the key to apply the item\n",
-                &mut tcod.root,
+                &tr("game.press_to_apply", &[]),
+                ctx,
             );
-            if let Some(inventory_index) = chosen_item_id {
-                use_item(inventory_index, tcod, game, objects);
+            match chosen_item_id {
+                // The alchemy kit asks what to mix instead
+                Some(index) if game.inventory[index].item == Some(Item::AlchemyKit) => {
+                    match craft_menu(ctx, game) {
+                        Some(action) => PlayerAction::Act(action),
+                        None => PlayerAction::DidntTakeTurn,
+                    }
+                }
+                Some(index) => {
+                    let target = aim_item(index, ctx, game, objects);
+                    PlayerAction::Act(Action::UseItem { index, target })
+                }
+                None => PlayerAction::DidntTakeTurn,
             }
-            PlayerAction::TookTurn
         }
-        (Key { code: Text, .. }, "d", true) => {
+        (
+            Key {
+                code: Text,
+                printable: 't',
+                ..
+            },
+            _,
+        ) => {
+            ctx.options.threats = !ctx.options.threats;
+            PlayerAction::DidntTakeTurn
+        }
+        (
+            Key {
+                code: Text,
+                printable: 'd',
+                ..
+            },
+            true,
+        ) => {
             let chosen_item_id = inventory_menu(
                 &game.inventory as &[Object],
-                "Press the key to drop the item\n",
-                &mut tcod.root,
+                &tr("game.press_to_drop", &[]),
+                ctx,
             );
-            if let Some(inventory_index) = chosen_item_id {
-                drop_item(inventory_index, game, objects);
+            match chosen_item_id {
+                Some(index) => PlayerAction::Act(Action::Drop(index)),
+                None => PlayerAction::DidntTakeTurn,
             }
-            PlayerAction::TookTurn
         }
-        (Key { code: Text, .. }, "c", true) => {
+        (
+            Key {
+                code: Text,
+                printable: 'c',
+                ..
+            },
+            true,
+        ) => {
             // Show character information
             let player = &objects[PLAYER];
 
-            character_info_box(player, game, &mut tcod.root);
+            character_info_box(player, game, ctx);
 
             PlayerAction::DidntTakeTurn
         }
-        (Key { code: Text, .. }, ">", true) => {
-            // Go down stairs, if the player is on them
-            let on_stairs = objects
-                .iter()
-                .any(|object| object.pos() == objects[PLAYER].pos() && object.name == "stairs");
-            if on_stairs {
-                next_level(tcod, game, objects);
-            }
-            PlayerAction::TookTurn
+        (
+            Key {
+                code: Text,
+                printable: 'b',
+                ..
+            },
+            _,
+        ) => {
+            bestiary_screen(ctx, game);
+            PlayerAction::DidntTakeTurn
         }
+        (
+            Key {
+                code: Text,
+                printable: 'p',
+                ..
+            },
+            _,
+        ) => {
+            // Look around the level with a free camera
+            photo_mode(ctx, game, objects);
+            PlayerAction::DidntTakeTurn
+        }
+        (
+            Key {
+                code: Text,
+                printable: '>',
+                ..
+            },
+            true,
+        ) => PlayerAction::Act(Action::Descend),
 
         _ => PlayerAction::DidntTakeTurn,
     };
+
+    // Ask before stepping onto a trap in plain sight
+    if let PlayerAction::Act(Action::Move(dx, dy)) = player_action {
+        let (x, y) = (objects[PLAYER].x + dx, objects[PLAYER].y + dy);
+        let trap = objects.iter().find(|object| {
+            object.trap.is_some() && object.pos() == (x, y) && ctx.fov.is_in_fov(x, y)
+        });
+        if let Some(trap) = trap {
+            let question = tr("game.step_onto", &[("trap", &trap.name)]);
+            if ctx.options.confirm && !confirm(&question, ctx) {
+                return PlayerAction::DidntTakeTurn;
+            }
+        }
+    }
+
+    // Walking into a friendly npc starts a conversation instead
+    if let PlayerAction::Act(Action::Move(dx, dy)) = player_action {
+        let (x, y) = objects[PLAYER].pos();
+        let npc = objects
+            .iter()
+            .position(|object| object.dialogue.is_some() && object.pos() == (x + dx, y + dy));
+        if let Some(npc) = npc {
+            return match talk(npc, ctx, game, objects) {
+                Some(action) => PlayerAction::Act(action),
+                None => PlayerAction::DidntTakeTurn,
+            };
+        }
+    }
+    player_action
 }
@@ -0,0 +1,145 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::game::Map;
+use crate::object::Object;
+
+// Glyph settings read from the config file
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GlyphConfig {
+    // Draw the walls with box-drawing lines joining their neighbours
+    pub box_walls: bool,
+    // Replace every glyph with its closest ASCII character,
+    // for fonts that lack anything else
+    pub ascii_only: bool,
+    // Glyphs to draw instead of the usual ones, by object name or "wall" and "floor"
+    pub overrides: HashMap<String, char>,
+}
+
+impl GlyphConfig {
+    // Glyph of the object, after the overrides
+    pub fn object(&self, object: &Object) -> char {
+        self.overrides
+            .get(&object.name)
+            .cloned()
+            .unwrap_or(object.char)
+    }
+
+    // Glyph of the map tile, if it has one
+    pub fn tile(&self, map: &Map, x: i32, y: i32) -> Option<char> {
+        let wall = map[(x, y)].is_wall();
+        let name = if wall { "wall" } else { "floor" };
+        match self.overrides.get(name) {
+            Some(&glyph) => Some(glyph),
+            None if wall && self.box_walls => Some(box_wall(map, x, y)),
+            None => None,
+        }
+    }
+}
+
+fn is_wall(map: &Map, x: i32, y: i32) -> bool {
+    map.get(x, y).is_some_and(|tile| tile.is_wall())
+}
+
+// Walls touching the floor, the only ones worth drawing lines for
+fn is_edge(map: &Map, x: i32, y: i32) -> bool {
+    if !is_wall(map, x, y) {
+        return false;
+    }
+    (-1..=1).any(|dx| {
+        (-1..=1).any(|dy| {
+            let (nx, ny) = (x + dx, y + dy);
+            map.in_bounds(nx, ny) && !is_wall(map, nx, ny)
+        })
+    })
+}
+
+// Box-drawing character joining the wall to the walls around it
+pub fn box_wall(map: &Map, x: i32, y: i32) -> char {
+    let north = is_edge(map, x, y - 1);
+    let south = is_edge(map, x, y + 1);
+    let west = is_edge(map, x - 1, y);
+    let east = is_edge(map, x + 1, y);
+
+    match (north, east, south, west) {
+        (false, false, false, false) => '■',
+        (_, false, _, false) => '│',
+        (false, _, false, _) => '─',
+        (true, true, false, false) => '└',
+        (true, false, false, true) => '┘',
+        (false, true, true, false) => '┌',
+        (false, false, true, true) => '┐',
+        (true, true, true, false) => '├',
+        (true, false, true, true) => '┤',
+        (false, true, true, true) => '┬',
+        (true, true, false, true) => '┴',
+        (true, true, true, true) => '┼',
+    }
+}
+
+// Code of the glyph in code page 437, the layout of the libtcod fonts
+pub fn cp437(glyph: char) -> Option<u8> {
+    let code = match glyph {
+        '░' => 176,
+        '▒' => 177,
+        '▓' => 178,
+        '│' => 179,
+        '┤' => 180,
+        '╣' => 185,
+        '║' => 186,
+        '╗' => 187,
+        '╝' => 188,
+        '┐' => 191,
+        '└' => 192,
+        '┴' => 193,
+        '┬' => 194,
+        '├' => 195,
+        '─' => 196,
+        '┼' => 197,
+        '╚' => 200,
+        '╔' => 201,
+        '╩' => 202,
+        '╦' => 203,
+        '╠' => 204,
+        '═' => 205,
+        '╬' => 206,
+        '┘' => 217,
+        '┌' => 218,
+        '█' => 219,
+        '·' => 250,
+        '■' => 254,
+        _ => return None,
+    };
+    Some(code)
+}
+
+// Closest ASCII character to the glyph, for fonts that can't draw it
+pub fn fallback(glyph: char) -> char {
+    match glyph {
+        _ if glyph.is_ascii() => glyph,
+        'À'..='Å' => 'A',
+        'Ç' => 'C',
+        'È'..='Ë' => 'E',
+        'Ì'..='Ï' => 'I',
+        'Ñ' => 'N',
+        'Ò'..='Ö' | 'Ø' => 'O',
+        'Ù'..='Ü' => 'U',
+        'Ý' => 'Y',
+        'à'..='å' => 'a',
+        'ç' => 'c',
+        'è'..='ë' => 'e',
+        'ì'..='ï' => 'i',
+        'ñ' => 'n',
+        'ò'..='ö' | 'ø' => 'o',
+        'ù'..='ü' => 'u',
+        'ý' | 'ÿ' => 'y',
+        '│' | '┃' | '║' => '|',
+        '─' | '━' | '═' => '-',
+        '\u{2500}'..='\u{257f}' => '+',
+        '░' | '▒' | '▓' | '█' | '■' => '#',
+        '·' | '•' => '.',
+        _ => '?',
+    }
+}
@@ -0,0 +1,89 @@
+use std::ops::{Index, IndexMut};
+
+use serde::{Deserialize, Serialize};
+
+// Values laid out over the tiles of a map, column after column in one vector
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Grid<T> {
+    width: i32,
+    height: i32,
+    cells: Vec<T>,
+}
+
+impl<T: Clone> Grid<T> {
+    pub fn new(width: i32, height: i32, value: T) -> Self {
+        Grid {
+            width,
+            height,
+            cells: vec![value; (width * height) as usize],
+        }
+    }
+}
+
+impl<T> Grid<T> {
+    pub fn width(&self) -> i32 {
+        self.width
+    }
+
+    pub fn height(&self) -> i32 {
+        self.height
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cells.is_empty()
+    }
+
+    pub fn in_bounds(&self, x: i32, y: i32) -> bool {
+        x >= 0 && y >= 0 && x < self.width && y < self.height
+    }
+
+    fn offset(&self, x: i32, y: i32) -> usize {
+        (x * self.height + y) as usize
+    }
+
+    // The value at the tile, None off the grid
+    pub fn get(&self, x: i32, y: i32) -> Option<&T> {
+        if self.in_bounds(x, y) {
+            Some(&self.cells[self.offset(x, y)])
+        } else {
+            None
+        }
+    }
+
+    pub fn get_mut(&mut self, x: i32, y: i32) -> Option<&mut T> {
+        if self.in_bounds(x, y) {
+            let offset = self.offset(x, y);
+            Some(&mut self.cells[offset])
+        } else {
+            None
+        }
+    }
+
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = &mut T> {
+        self.cells.iter_mut()
+    }
+
+    // Every tile of the grid, column after column
+    pub fn iter_coords(&self) -> impl Iterator<Item = (i32, i32)> {
+        let height = self.height;
+        (0..self.width).flat_map(move |x| (0..height).map(move |y| (x, y)))
+    }
+}
+
+// Indexing off the grid panics, like indexing past the end of a vector
+impl<T> Index<(i32, i32)> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, (x, y): (i32, i32)) -> &T {
+        assert!(self.in_bounds(x, y), "({}, {}) is off the grid", x, y);
+        &self.cells[self.offset(x, y)]
+    }
+}
+
+impl<T> IndexMut<(i32, i32)> for Grid<T> {
+    fn index_mut(&mut self, (x, y): (i32, i32)) -> &mut T {
+        assert!(self.in_bounds(x, y), "({}, {}) is off the grid", x, y);
+        let offset = self.offset(x, y);
+        &mut self.cells[offset]
+    }
+}
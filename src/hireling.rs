@@ -0,0 +1,242 @@
+use serde::{Deserialize, Serialize};
+
+use crate::colors::*;
+
+use crate::ai::Ai;
+use crate::faction::Faction;
+use crate::fighter::{DeathCallback, Fighter, MAX_MORALE};
+use crate::game::{remove_object, Game, PLAYER};
+use crate::item::add_to_inventory;
+use crate::lang::tr;
+use crate::object::Object;
+use crate::render::{inventory_menu, Context};
+use crate::trap::landing_tile;
+
+// Turns between the paydays of the hirelings
+const WAGE_INTERVAL: u32 = 100;
+// Share of the health in percent below which a hireling runs for their life
+const DESERT_HP_PERCENT: i32 = 20;
+// Stats of a freshly hired mercenary, and what it gains with every level of the player
+const HIRELING_HP: i32 = 40;
+const HIRELING_DEFENSE: i32 = 1;
+const HIRELING_POWER: i32 = 4;
+const LEVEL_HP: i32 = 10;
+const LEVEL_POWER: i32 = 1;
+
+// A fighter paid to follow the player
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Hireling {
+    // Gold taken from the purse every payday
+    pub wage: u32,
+    // Turns left until the next payday
+    pub payday: u32,
+}
+
+// Take the npc into the player's service, if the purse holds the price
+pub fn hire(npc_id: usize, price: u32, wage: u32, game: &mut Game, objects: &mut [Object]) {
+    let npc = &mut objects[npc_id];
+    if game.gold < price {
+        game.messages.add(
+            tr(
+                "hireling.wont_fight_for_less_than_gold",
+                &[("npc", &npc.name), ("price", &price)],
+            ),
+            LIGHT_GREY,
+        );
+        return;
+    }
+    game.gold -= price;
+    game.messages
+        .add(tr("hireling.joins_you", &[("npc", &npc.name)]), LIGHT_GREEN);
+    npc.dialogue = None;
+    npc.faction = Some(Faction::Player);
+    npc.ai = Some(Ai::Tree("ally".into()));
+    npc.fighter = Some(Fighter {
+        base_max_hp: HIRELING_HP,
+        hp: HIRELING_HP,
+        base_defense: HIRELING_DEFENSE,
+        base_power: HIRELING_POWER,
+        xp: 0,
+        on_death: DeathCallback::Monster,
+        morale: MAX_MORALE,
+    });
+    npc.hireling = Some(Hireling {
+        wage,
+        payday: WAGE_INTERVAL,
+    });
+}
+
+// Id of a hireling standing next to the player
+fn adjacent_hireling(objects: &[Object]) -> Option<usize> {
+    objects.iter().position(|object| {
+        object.hireling.is_some() && object.alive && object.distance_to(&objects[PLAYER]) < 2.0
+    })
+}
+
+// Ask for the piece of equipment to hand to the hireling next to the player
+pub fn give_menu(ctx: &mut Context, game: &mut Game, objects: &[Object]) -> Option<(usize, usize)> {
+    let hireling = match adjacent_hireling(objects) {
+        Some(hireling) => hireling,
+        None => {
+            game.messages.add(
+                tr("hireling.none_of_your_hirelings_stands_next", &[]),
+                LIGHT_GREY,
+            );
+            return None;
+        }
+    };
+    let header = tr(
+        "hireling.give_which_item",
+        &[("hireling", &objects[hireling].name)],
+    );
+    let item = inventory_menu(&game.inventory, &header, ctx)?;
+    Some((hireling, item))
+}
+
+// Let the hireling wear the equipment, handing back what it wore in the same slot
+pub fn give(hireling: usize, index: usize, game: &mut Game, objects: &mut [Object]) {
+    if index >= game.inventory.len() || objects[hireling].hireling.is_none() {
+        return;
+    }
+    let slot = match game.inventory[index].equipment {
+        Some(equipment) => equipment.slot,
+        None => {
+            game.messages.add(
+                tr(
+                    "hireling.has_no_use_for_that",
+                    &[("hireling", &objects[hireling].name)],
+                ),
+                LIGHT_GREY,
+            );
+            return;
+        }
+    };
+    let mut item = game.inventory.remove(index);
+    if let Some(equipment) = item.equipment.as_mut() {
+        equipment.equipped = true;
+    }
+    let name = objects[hireling].name.clone();
+    game.messages.add(
+        tr(
+            "hireling.takes_the",
+            &[("hireling", &name), ("item", &item.name)],
+        ),
+        LIGHT_GREY,
+    );
+
+    let inventory = &mut objects[hireling].inventory;
+    if let Some(worn) = inventory.iter().position(|other| {
+        other
+            .equipment
+            .is_some_and(|e| e.equipped && e.slot == slot)
+    }) {
+        let mut old = inventory.remove(worn);
+        if let Some(equipment) = old.equipment.as_mut() {
+            equipment.equipped = false;
+        }
+        game.messages.add(
+            tr(
+                "hireling.hands_you_the",
+                &[("hireling", &name), ("item", &old.name)],
+            ),
+            LIGHT_GREY,
+        );
+        add_to_inventory(old, game);
+    }
+    objects[hireling].inventory.push(item);
+}
+
+// Grow the hirelings along with the player
+pub fn level_up_hirelings(game: &mut Game, objects: &mut [Object]) {
+    for object in objects
+        .iter_mut()
+        .filter(|object| object.hireling.is_some())
+    {
+        if let Some(fighter) = object.fighter.as_mut() {
+            fighter.base_max_hp += LEVEL_HP;
+            fighter.hp += LEVEL_HP;
+            fighter.base_power += LEVEL_POWER;
+            game.messages.add(
+                tr("hireling.grows_stronger", &[("hireling", &object.name)]),
+                LIGHT_GREEN,
+            );
+        }
+    }
+}
+
+// Pay the hirelings on their paydays, the unpaid and the badly hurt ones leave,
+// dropping what they carry
+pub fn pay_hirelings(game: &mut Game, objects: &mut Vec<Object>) {
+    let mut deserters = vec![];
+    for (id, object) in objects.iter_mut().enumerate() {
+        let (hireling, fighter) = match (object.hireling.as_mut(), object.fighter) {
+            (Some(hireling), Some(fighter)) if object.alive => (hireling, fighter),
+            _ => continue,
+        };
+        if fighter.hp * 100 < fighter.base_max_hp * DESERT_HP_PERCENT {
+            game.messages.add(
+                tr(
+                    "hireling.runs_for_their_life_leaving_your",
+                    &[("hireling", &object.name)],
+                ),
+                LIGHT_RED,
+            );
+            deserters.push(id);
+            continue;
+        }
+        hireling.payday -= 1;
+        if hireling.payday > 0 {
+            continue;
+        }
+        if game.gold < hireling.wage {
+            game.messages.add(
+                tr(
+                    "hireling.goes_unpaid_and_deserts_you",
+                    &[("hireling", &object.name)],
+                ),
+                LIGHT_RED,
+            );
+            deserters.push(id);
+            continue;
+        }
+        game.gold -= hireling.wage;
+        hireling.payday = WAGE_INTERVAL;
+        game.messages.add(
+            tr(
+                "hireling.you_pay_gold",
+                &[("hireling", &object.name), ("wage", &hireling.wage)],
+            ),
+            GOLD,
+        );
+    }
+    for id in deserters.into_iter().rev() {
+        let mut deserter = remove_object(id, game, objects);
+        for mut item in deserter.inventory.drain(..) {
+            if let Some(equipment) = item.equipment.as_mut() {
+                equipment.equipped = false;
+            }
+            item.set_pos(deserter.x, deserter.y);
+            objects.push(item);
+        }
+    }
+}
+
+// Take the hirelings off the level the player leaves
+pub fn take_hirelings(objects: &mut Vec<Object>) -> Vec<Object> {
+    let (hirelings, rest) = std::mem::take(objects)
+        .into_iter()
+        .partition(|object| object.hireling.is_some() && object.alive);
+    *objects = rest;
+    hirelings
+}
+
+// Put the hirelings that came along down around the player
+pub fn bring_hirelings(hirelings: Vec<Object>, game: &Game, objects: &mut Vec<Object>) {
+    for mut hireling in hirelings {
+        let (x, y) = objects[PLAYER].pos();
+        if let Some((x, y)) = landing_tile(x, y, game, objects) {
+            hireling.set_pos(x, y);
+            objects.push(hireling);
+        }
+    }
+}
@@ -1,53 +1,215 @@
+use rand::Rng;
 use serde::{Deserialize, Serialize};
 
-use tcod::colors::*;
+use crate::colors::*;
 
-use crate::ai::Ai;
+use crate::action::{Delayed, DelayedAction};
+use crate::ai::{provoke, Ai};
+use crate::cover::misses_in_cover;
 use crate::equipment::Slot;
-use crate::game::{target_monster, target_tile, Game, PLAYER};
-use crate::object::{closest_monster, Object};
+use crate::event::{emit, GameEvent};
+use crate::fields::ignite;
+use crate::fov::line;
+use crate::game::{
+    remove_object, set_tile, target_monster, target_tile, Game, Tile, MAP_HEIGHT, MAP_WIDTH, PLAYER,
+};
+use crate::lang::tr;
+use crate::light::LANTERN_FUEL;
+use crate::object::{closest_monster, handle_of, Object};
 
-use crate::render::Tcod;
+use crate::render::Context;
+use crate::room::make_monster;
+use crate::script::run_script;
+use crate::spawn::summon;
+use crate::status::{apply_effect, Effect, Status};
 
-pub const INVENTORY_SIZE: i32 = 26;
+pub const INVENTORY_SIZE: i32 = 52;
+// Keys of the inventory items, in the order they are handed out
+const INVENTORY_LETTERS: &str = "abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ";
 
 const HEAL_AMOUNT: i32 = 10;
+// Coins in a pile, per dungeon level
+const GOLD_MIN: u32 = 5;
+const GOLD_MAX: u32 = 20;
+const SPEED_DURATION: u32 = 20;
 const LIGHTNING_DAMAGE: i32 = 30;
 const FIRE_DAMAGE: i32 = 15;
 const SPELL_RANGE: i32 = 10;
 const CONFUSION_DURATION: i32 = 5;
+const RETURN_DELAY: u32 = 3;
+// How far the digging wand tunnels
+const DIG_LENGTH: i32 = 8;
+// How far around the chosen tile the clairvoyance shows
+const CLAIRVOYANCE_RADIUS: i32 = 6;
+// Charges the scroll puts into every wand, and the most a wand can hold
+const RECHARGE_CHARGES: u32 = 3;
+const MAX_CHARGES: u32 = 8;
 
 // Item properties
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum Item {
     Heal,
+    // Hastes the player for a while
+    Speed,
     Lightning,
     Fireball,
     Confusion,
+    Return,
+    Anchor,
+    // Calls a spirit wolf fighting for the player
+    Summon,
+    // Wands do what the spell does as long as they have charges left
+    LightningWand,
+    DiggingWand,
+    // Puts charges back into the wands carried
+    Recharge,
+    // Shows the layout of the whole level, or of the place around the chosen tile
+    MagicMapping,
+    Clairvoyance,
+    // Mixed with other items in the alchemy kit
+    Essence,
+    AlchemyKit,
+    // Counted rather than carried, spent at the smith along with the ore
+    Gold,
+    Ore,
     Sword,
+    // Hits harder than the sword, but slowly
+    Greatsword,
+    // Polearms, hitting over the tile between
+    Spear,
+    Halberd,
+    // Digs through the walls the player walks into
+    Pick,
     Shield,
+    // Light up the dark levels while they burn, the lantern can be filled again with oil
+    Torch,
+    Lantern,
+    Oil,
+    // Read for whatever the script of the name does
+    Scripted(String),
+}
+
+// Groups the inventory is sorted into, in the order they are shown
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Category {
+    Weapon,
+    Armor,
+    Potion,
+    Scroll,
+    Wand,
+    Other,
+}
+
+impl Category {
+    pub fn name(self) -> String {
+        let id = match self {
+            Category::Weapon => "item.weapons",
+            Category::Armor => "item.armor",
+            Category::Potion => "item.potions",
+            Category::Scroll => "item.scrolls",
+            Category::Wand => "item.wands",
+            Category::Other => "item.other",
+        };
+        tr(id, &[])
+    }
+}
+
+pub fn category(item: &Object) -> Category {
+    use Item::*;
+    // Whatever is swung is a weapon, in either hand
+    if let Some(equipment) = item.equipment {
+        return if equipment.attack_delay > 0 {
+            Category::Weapon
+        } else {
+            Category::Armor
+        };
+    }
+    match item.item {
+        Some(Heal) | Some(Speed) => Category::Potion,
+        Some(Fireball) | Some(Lightning) | Some(Confusion) | Some(Return) | Some(Summon)
+        | Some(Recharge) | Some(MagicMapping) | Some(Clairvoyance) | Some(Scripted(_)) => {
+            Category::Scroll
+        }
+        Some(LightningWand) | Some(DiggingWand) => Category::Wand,
+        _ => Category::Other,
+    }
+}
+
+// Letter of every inventory item, the first free ones for the items that have none yet
+pub fn inventory_letters(inventory: &[Object]) -> Vec<char> {
+    let mut free = INVENTORY_LETTERS
+        .chars()
+        .filter(|&letter| !inventory.iter().any(|item| item.letter == Some(letter)));
+    inventory
+        .iter()
+        .map(|item| item.letter.or_else(|| free.next()).unwrap_or('?'))
+        .collect()
+}
+
+// Hand out letters to the items that have none, so they keep them from now on
+pub fn letter_inventory(inventory: &mut [Object]) {
+    let letters = inventory_letters(inventory);
+    for (item, letter) in inventory.iter_mut().zip(letters) {
+        item.letter = Some(letter);
+    }
+}
+
+// Put the item in the inventory, under its old letter if it's still free
+pub fn add_to_inventory(mut item: Object, game: &mut Game) {
+    letter_inventory(&mut game.inventory);
+    let taken = |letter: char| {
+        game.inventory
+            .iter()
+            .any(|other| other.letter == Some(letter))
+    };
+    if item.letter.is_none_or(taken) {
+        item.letter = INVENTORY_LETTERS.chars().find(|&letter| !taken(letter));
+    }
+    game.inventory.push(item);
 }
 
 // Enum to represent the outcome of the item being used
+#[derive(Debug, Clone, Copy, PartialEq)]
 enum UseResult {
     UsedUp,
     Cancelled,
     UsedAndKept,
 }
 
+// What happens when the item is used, aimed at the tile if it needs it
+type UseFn = fn(usize, Option<(i32, i32)>, &mut Context, &mut Game, &mut [Object]) -> UseResult;
+
 // Pick up an item to the inventory
 pub fn pick_item(object_id: usize, game: &mut Game, objects: &mut Vec<Object>) {
-    if game.inventory.len() >= INVENTORY_SIZE as usize {
-        game.messages.add("Your inventory is full", DARK_RED);
-    } else {
-        let item = objects.swap_remove(object_id);
+    // The coins go into the purse, how many there are is seen once they're counted
+    if objects[object_id].item == Some(Item::Gold) {
+        remove_object(object_id, game, objects);
+        let amount = game.rng.gen_range(GOLD_MIN, GOLD_MAX) * game.dungeon_level;
+        game.gold += amount;
+        game.messages.add(
+            tr("item.you_pick_up_gold_coins", &[("amount", &amount)]),
+            GOLD,
+        );
+    } else if game.inventory.len() >= INVENTORY_SIZE as usize {
         game.messages
-            .add(format!("You picked up an item: {}", item.name), LIGHT_GREY);
-        game.inventory.push(item);
+            .add(tr("item.your_inventory_is_full", &[]), DARK_RED);
+    } else {
+        let item = remove_object(object_id, game, objects);
+        game.messages.add(
+            tr("item.you_picked_up_an_item", &[("item", &item.name)]),
+            LIGHT_GREY,
+        );
+        emit(
+            GameEvent::ItemPickedUp {
+                name: item.name.clone(),
+            },
+            game,
+        );
+        add_to_inventory(item, game);
     }
 }
 
-fn get_equipped_in_slot(slot: Slot, inventory: &[Object]) -> Option<usize> {
+pub fn get_equipped_in_slot(slot: Slot, inventory: &[Object]) -> Option<usize> {
     for (inventory_id, item) in inventory.iter().enumerate() {
         if item
             .equipment
@@ -60,31 +222,113 @@ fn get_equipped_in_slot(slot: Slot, inventory: &[Object]) -> Option<usize> {
     None
 }
 
-pub fn use_item(inventory_id: usize, tcod: &mut Tcod, game: &mut Game, objects: &mut [Object]) {
+// Ask where to aim the item, for the items that need it
+pub fn aim_item(
+    inventory_id: usize,
+    ctx: &mut Context,
+    game: &mut Game,
+    objects: &[Object],
+) -> Option<(i32, i32)> {
+    match game.inventory[inventory_id].item {
+        Some(Item::Confusion) => {
+            game.messages
+                .add(tr("item.choose_an_enemy_to_confuse", &[]), LIGHT_GREY);
+            target_monster(ctx, game, objects, Some(SPELL_RANGE as f32)).map(|id| objects[id].pos())
+        }
+        Some(Item::Fireball) => {
+            game.messages
+                .add(tr("item.choose_a_tile_to_cast_infernal", &[]), LIGHT_GREY);
+            target_tile(ctx, game, objects, None)
+        }
+        Some(Item::Clairvoyance) => {
+            game.messages
+                .add(tr("item.choose_a_place_to_see_from", &[]), LIGHT_GREY);
+            target_tile(ctx, game, objects, None)
+        }
+        Some(Item::DiggingWand) => {
+            game.messages
+                .add(tr("item.choose_where_to_dig", &[]), LIGHT_GREY);
+            target_tile(ctx, game, objects, None)
+        }
+        _ => None,
+    }
+}
+
+pub fn use_item(
+    inventory_id: usize,
+    target: Option<(i32, i32)>,
+    ctx: &mut Context,
+    game: &mut Game,
+    objects: &mut [Object],
+) {
     use Item::*;
 
     if let Some(item) = &game.inventory[inventory_id].item {
+        // The monster a spell is aimed at, found before the spell might kill it
+        let aimed = target
+            .filter(|_| matches!(item, Confusion | Fireball))
+            .and_then(|(x, y)| {
+                objects.iter().enumerate().position(|(id, object)| {
+                    id != PLAYER
+                        && object.alive
+                        && object.fighter.is_some()
+                        && object.pos() == (x, y)
+                })
+            });
         let on_use = match item {
             Heal => cast_heal,
+            Speed => drink_speed,
             Lightning => cast_lightning,
             Confusion => cast_confusion,
             Fireball => cast_fireball,
+            Return => cast_return,
+            Anchor => place_anchor,
+            Summon => cast_summon,
+            LightningWand => zap_lightning,
+            DiggingWand => zap_digging,
+            Recharge => cast_recharge,
+            MagicMapping => cast_magic_mapping,
+            Clairvoyance => cast_clairvoyance,
+            Essence | AlchemyKit => mix_only,
+            Gold | Ore => for_the_smith,
             Sword => toggle_equipment,
+            Greatsword => toggle_equipment,
+            Spear | Halberd => toggle_equipment,
+            Pick => toggle_equipment,
             Shield => toggle_equipment,
+            Torch | Lantern => toggle_equipment,
+            Oil => fill_lantern,
+            Scripted(_) => read_script,
         };
-        match on_use(inventory_id, tcod, game, objects) {
+        // Known before the item might be used up
+        let used = &game.inventory[inventory_id];
+        let spell = matches!(category(used), Category::Scroll | Category::Wand);
+        let name = used.name.clone();
+        let result = on_use(inventory_id, target, ctx, game, objects);
+        if spell && result != UseResult::Cancelled {
+            emit(GameEvent::SpellCast { name }, game);
+        }
+        // Offered first the next time, once the spell went off at it
+        if let Some(id) = aimed.filter(|_| result != UseResult::Cancelled) {
+            game.last_target = Some(handle_of(id, game, objects));
+        }
+        match result {
             UseResult::UsedUp => {
                 // Destroy the used item
                 game.inventory.remove(inventory_id);
+                game.stats.items_used += 1;
             }
             UseResult::Cancelled => {
-                game.messages.add("Cancelled", WHITE);
+                game.messages.add(tr("item.cancelled", &[]), WHITE);
             }
             UseResult::UsedAndKept => {}
         }
     } else {
         game.messages.add(
-            format!("{} cannot be used", game.inventory[inventory_id].name,),
+            tr(
+                "item.cannot_be_used",
+                &[("item", &game.inventory[inventory_id].name)],
+            ),
             WHITE,
         );
     }
@@ -92,7 +336,8 @@ pub fn use_item(inventory_id: usize, tcod: &mut Tcod, game: &mut Game, objects:
 
 fn toggle_equipment(
     inventory_id: usize,
-    _tcod: &mut Tcod,
+    _target: Option<(i32, i32)>,
+    _ctx: &mut Context,
     game: &mut Game,
     _objects: &mut [Object],
 ) -> UseResult {
@@ -113,18 +358,38 @@ fn toggle_equipment(
     UseResult::UsedAndKept
 }
 
+fn read_script(
+    inventory_id: usize,
+    _target: Option<(i32, i32)>,
+    _ctx: &mut Context,
+    game: &mut Game,
+    objects: &mut [Object],
+) -> UseResult {
+    let name = match &game.inventory[inventory_id].item {
+        Some(Item::Scripted(name)) => name.clone(),
+        _ => return UseResult::Cancelled,
+    };
+    if run_script(&name, PLAYER, None, game, objects) {
+        UseResult::UsedUp
+    } else {
+        UseResult::Cancelled
+    }
+}
+
 fn cast_heal(
     _inventory_id: usize,
-    _tcod: &mut Tcod,
+    _target: Option<(i32, i32)>,
+    _ctx: &mut Context,
     game: &mut Game,
     objects: &mut [Object],
 ) -> UseResult {
     if let Some(fighter) = objects[PLAYER].fighter {
         if fighter.hp == objects[PLAYER].max_hp(game) {
-            game.messages.add("HP is already full", WHITE);
+            game.messages.add(tr("item.hp_is_already_full", &[]), WHITE);
             return UseResult::Cancelled;
         } else {
-            game.messages.add("Your wounds heal", LIGHT_VIOLET);
+            game.messages
+                .add(tr("item.your_wounds_heal", &[]), LIGHT_VIOLET);
             objects[PLAYER].heal(HEAL_AMOUNT, game);
             return UseResult::UsedUp;
         }
@@ -132,44 +397,329 @@ fn cast_heal(
     UseResult::Cancelled
 }
 
+fn drink_speed(
+    _inventory_id: usize,
+    _target: Option<(i32, i32)>,
+    _ctx: &mut Context,
+    game: &mut Game,
+    objects: &mut [Object],
+) -> UseResult {
+    let effect = Effect {
+        status: Status::Haste,
+        turns: SPEED_DURATION,
+    };
+    apply_effect(&mut objects[PLAYER], effect, game);
+    UseResult::UsedUp
+}
+
+fn cast_summon(
+    _inventory_id: usize,
+    _target: Option<(i32, i32)>,
+    _ctx: &mut Context,
+    game: &mut Game,
+    objects: &mut [Object],
+) -> UseResult {
+    let mut wolf = match make_monster("wolf", 0, 0) {
+        Some(wolf) => wolf,
+        None => return UseResult::Cancelled,
+    };
+    wolf.name = "spirit wolf".into();
+    wolf.color = LIGHT_BLUE;
+    wolf.ai = Some(Ai::Tree("ally".into()));
+    if summon(wolf, PLAYER, game, objects) {
+        UseResult::UsedUp
+    } else {
+        game.messages
+            .add(tr("item.there_is_no_room_for_anything", &[]), WHITE);
+        UseResult::Cancelled
+    }
+}
+
 fn cast_lightning(
     _inventory_id: usize,
-    tcod: &mut Tcod,
+    _target: Option<(i32, i32)>,
+    ctx: &mut Context,
     game: &mut Game,
     objects: &mut [Object],
 ) -> UseResult {
-    let monster_id = closest_monster(tcod, objects, SPELL_RANGE);
+    let monster_id = closest_monster(ctx, objects, SPELL_RANGE);
     if let Some(monster_id) = monster_id {
+        let target = objects[monster_id].pos();
+        if misses_in_cover(objects[PLAYER].pos(), target, game, objects) {
+            game.messages.add(
+                tr(
+                    "item.the_lightning_bolt_strikes_the_cover",
+                    &[("target", &objects[monster_id].name)],
+                ),
+                LIGHT_BLUE,
+            );
+            return UseResult::UsedUp;
+        }
         game.messages.add(
-            format!(
-                "A lightning bolt strikes {} for {} damage",
-                objects[monster_id].name, LIGHTNING_DAMAGE
+            tr(
+                "item.a_lightning_bolt_strikes_for_damage",
+                &[
+                    ("target", &objects[monster_id].name),
+                    ("damage", &LIGHTNING_DAMAGE),
+                ],
             ),
             LIGHT_BLUE,
         );
-        if let Some(xp) = objects[monster_id].take_damage(LIGHTNING_DAMAGE, game) {
+        if let Some(xp) = objects[monster_id].take_damage(LIGHTNING_DAMAGE, true, game) {
             objects[PLAYER].fighter.as_mut().unwrap().xp += xp;
         }
         UseResult::UsedUp
     } else {
-        game.messages.add("There is no one to strike", WHITE);
+        game.messages
+            .add(tr("item.there_is_no_one_to_strike", &[]), WHITE);
         UseResult::Cancelled
     }
 }
 
+// The wand does what its spell does, spending a charge instead of being used up
+fn zap(
+    effect: UseFn,
+    inventory_id: usize,
+    target: Option<(i32, i32)>,
+    ctx: &mut Context,
+    game: &mut Game,
+    objects: &mut [Object],
+) -> UseResult {
+    if game.inventory[inventory_id].charges.unwrap_or(0) == 0 {
+        game.messages
+            .add(tr("item.the_wand_is_out_of_charges", &[]), WHITE);
+        return UseResult::Cancelled;
+    }
+    match effect(inventory_id, target, ctx, game, objects) {
+        UseResult::UsedUp => {
+            if let Some(charges) = game.inventory[inventory_id].charges.as_mut() {
+                *charges -= 1;
+            }
+            UseResult::UsedAndKept
+        }
+        result => result,
+    }
+}
+
+fn zap_lightning(
+    inventory_id: usize,
+    target: Option<(i32, i32)>,
+    ctx: &mut Context,
+    game: &mut Game,
+    objects: &mut [Object],
+) -> UseResult {
+    zap(cast_lightning, inventory_id, target, ctx, game, objects)
+}
+
+fn zap_digging(
+    inventory_id: usize,
+    target: Option<(i32, i32)>,
+    ctx: &mut Context,
+    game: &mut Game,
+    objects: &mut [Object],
+) -> UseResult {
+    zap(dig, inventory_id, target, ctx, game, objects)
+}
+
+// Bore a tunnel from the player towards the tile, through the walls but not out of the map
+fn dig(
+    _inventory_id: usize,
+    target: Option<(i32, i32)>,
+    _ctx: &mut Context,
+    game: &mut Game,
+    objects: &mut [Object],
+) -> UseResult {
+    let (player_x, player_y) = objects[PLAYER].pos();
+    let (x, y) = match target {
+        Some(tile_pos) if tile_pos != (player_x, player_y) => tile_pos,
+        _ => return UseResult::Cancelled,
+    };
+
+    // Far enough in that direction for the whole tunnel
+    let far = (
+        player_x + (x - player_x) * DIG_LENGTH,
+        player_y + (y - player_y) * DIG_LENGTH,
+    );
+    let mut dug = 0;
+    for (x, y) in line((player_x, player_y), far)
+        .into_iter()
+        .take(DIG_LENGTH as usize)
+    {
+        if x <= 0 || y <= 0 || x >= MAP_WIDTH - 1 || y >= MAP_HEIGHT - 1 {
+            break;
+        }
+        if diggable(x, y, game) {
+            set_tile(x, y, Tile::empty(), game);
+            dug += 1;
+        }
+    }
+    if dug > 0 {
+        game.messages
+            .add(tr("item.the_wand_bores_a_tunnel_through", &[]), LIGHT_GREY);
+    } else {
+        game.messages
+            .add(tr("item.the_beam_of_the_wand_finds", &[]), LIGHT_GREY);
+    }
+    UseResult::UsedUp
+}
+
+// Whether the tile is rock that can be dug through, the edge of the map can't be
+pub fn diggable(x: i32, y: i32, game: &Game) -> bool {
+    x > 0 && y > 0 && x < MAP_WIDTH - 1 && y < MAP_HEIGHT - 1 && game.map[(x, y)].is_wall()
+}
+
+fn cast_recharge(
+    _inventory_id: usize,
+    _target: Option<(i32, i32)>,
+    _ctx: &mut Context,
+    game: &mut Game,
+    _objects: &mut [Object],
+) -> UseResult {
+    let mut recharged = false;
+    for item in game.inventory.iter_mut() {
+        if let Some(charges) = item.charges.as_mut() {
+            *charges = (*charges + RECHARGE_CHARGES).min(MAX_CHARGES);
+            recharged = true;
+        }
+    }
+    if recharged {
+        game.messages
+            .add(tr("item.your_wands_hum_with_new_power", &[]), LIGHT_BLUE);
+        UseResult::UsedUp
+    } else {
+        game.messages
+            .add(tr("item.you_have_no_wands_to_recharge", &[]), WHITE);
+        UseResult::Cancelled
+    }
+}
+
+fn fill_lantern(
+    _inventory_id: usize,
+    _target: Option<(i32, i32)>,
+    _ctx: &mut Context,
+    game: &mut Game,
+    _objects: &mut [Object],
+) -> UseResult {
+    let lantern = game
+        .inventory
+        .iter_mut()
+        .filter(|item| item.item == Some(Item::Lantern))
+        .min_by_key(|item| item.fuel);
+    match lantern {
+        Some(lantern) => {
+            lantern.fuel = Some(LANTERN_FUEL);
+            game.messages
+                .add(tr("item.you_fill_the_lantern_with_oil", &[]), LIGHT_AMBER);
+            UseResult::UsedUp
+        }
+        None => {
+            game.messages
+                .add(tr("item.you_have_no_lantern_to_fill", &[]), WHITE);
+            UseResult::Cancelled
+        }
+    }
+}
+
+fn for_the_smith(
+    _inventory_id: usize,
+    _target: Option<(i32, i32)>,
+    _ctx: &mut Context,
+    game: &mut Game,
+    _objects: &mut [Object],
+) -> UseResult {
+    game.messages
+        .add(tr("item.a_smith_would_know_what_to", &[]), WHITE);
+    UseResult::Cancelled
+}
+
+fn mix_only(
+    _inventory_id: usize,
+    _target: Option<(i32, i32)>,
+    _ctx: &mut Context,
+    game: &mut Game,
+    _objects: &mut [Object],
+) -> UseResult {
+    game.messages
+        .add(tr("item.its_only_good_for_mixing_with", &[]), WHITE);
+    UseResult::Cancelled
+}
+
+fn cast_magic_mapping(
+    _inventory_id: usize,
+    _target: Option<(i32, i32)>,
+    _ctx: &mut Context,
+    game: &mut Game,
+    _objects: &mut [Object],
+) -> UseResult {
+    game.messages.add(
+        tr("item.the_layout_of_the_level_unfolds", &[]),
+        LIGHT_VIOLET,
+    );
+    map_area(0, 0, MAP_WIDTH - 1, MAP_HEIGHT - 1, game);
+    UseResult::UsedUp
+}
+
+fn cast_clairvoyance(
+    _inventory_id: usize,
+    target: Option<(i32, i32)>,
+    _ctx: &mut Context,
+    game: &mut Game,
+    _objects: &mut [Object],
+) -> UseResult {
+    let (x, y) = match target {
+        Some(tile_pos) => tile_pos,
+        None => return UseResult::Cancelled,
+    };
+    game.messages.add(
+        tr("item.a_distant_place_appears_before_your", &[]),
+        LIGHT_VIOLET,
+    );
+    map_area(
+        x - CLAIRVOYANCE_RADIUS,
+        y - CLAIRVOYANCE_RADIUS,
+        x + CLAIRVOYANCE_RADIUS,
+        y + CLAIRVOYANCE_RADIUS,
+        game,
+    );
+    UseResult::UsedUp
+}
+
+// Learn the floor in the area and the walls around it, leaving out the solid rock
+fn map_area(x1: i32, y1: i32, x2: i32, y2: i32, game: &mut Game) {
+    let (x1, y1) = (x1.max(0), y1.max(0));
+    let (x2, y2) = (x2.min(MAP_WIDTH - 1), y2.min(MAP_HEIGHT - 1));
+    for x in x1..=x2 {
+        for y in y1..=y2 {
+            let near_floor = (x - 1..=x + 1)
+                .flat_map(|nx| (y - 1..=y + 1).map(move |ny| (nx, ny)))
+                .any(|(nx, ny)| game.map.get(nx, ny).is_some_and(|tile| !tile.blocked));
+            if near_floor {
+                game.map[(x, y)].mapped = true;
+            }
+        }
+    }
+}
+
 fn cast_confusion(
     _inventory_id: usize,
-    tcod: &mut Tcod,
+    target: Option<(i32, i32)>,
+    _ctx: &mut Context,
     game: &mut Game,
     objects: &mut [Object],
 ) -> UseResult {
-    // let monster_id = closest_monster(tcod, objects, SPELL_RANGE);
-    game.messages.add("Choose an enemy to confuse", LIGHT_GREY);
-    let monster_id = target_monster(tcod, game, objects, Some(SPELL_RANGE as f32));
+    let monster_id = target.and_then(|(x, y)| {
+        objects
+            .iter()
+            .enumerate()
+            .position(|(id, o)| o.pos() == (x, y) && o.fighter.is_some() && id != PLAYER)
+    });
 
     if let Some(monster_id) = monster_id {
         game.messages.add(
-            format!("{} gets confused", objects[monster_id].name),
+            tr(
+                "item.gets_confused",
+                &[("monster", &objects[monster_id].name)],
+            ),
             LIGHT_BLUE,
         );
         // Fill fail if no ai found
@@ -182,47 +732,104 @@ fn cast_confusion(
         });
         UseResult::UsedUp
     } else {
-        game.messages.add("There is no one to confused", WHITE);
+        game.messages
+            .add(tr("item.there_is_no_one_to_confused", &[]), WHITE);
         UseResult::Cancelled
     }
 }
 
 fn cast_fireball(
     _inventory_id: usize,
-    tcod: &mut Tcod,
+    target: Option<(i32, i32)>,
+    _ctx: &mut Context,
     game: &mut Game,
     objects: &mut [Object],
 ) -> UseResult {
-    // Ask to choose a tile
-    game.messages
-        .add("Choose a tile to cast infernal flames to", LIGHT_GREY);
-    let (x, y) = match target_tile(tcod, game, objects, None) {
+    let (x, y) = match target {
         Some(tile_pos) => tile_pos,
         None => return UseResult::Cancelled,
     };
 
     game.messages.add(
-        "The fireball explodes and burnes everything it can touch",
+        tr("item.the_fireball_explodes_and_burnes_everything", &[]),
         ORANGE,
     );
+    explode(PLAYER, x, y, game, objects);
+    ignite(x, y, SPELL_RANGE / 2, game);
 
+    UseResult::UsedUp
+}
+
+fn cast_return(
+    _inventory_id: usize,
+    _target: Option<(i32, i32)>,
+    _ctx: &mut Context,
+    game: &mut Game,
+    _objects: &mut [Object],
+) -> UseResult {
+    if game
+        .delayed
+        .iter()
+        .any(|delayed| delayed.effect == Delayed::Return)
+    {
+        game.messages
+            .add(tr("item.you_are_already_returning", &[]), WHITE);
+        return UseResult::Cancelled;
+    }
+    game.messages
+        .add(tr("item.the_air_around_you_starts_to", &[]), LIGHT_BLUE);
+    game.delayed.push(DelayedAction {
+        turns: RETURN_DELAY,
+        effect: Delayed::Return,
+        interruptible: true,
+    });
+    UseResult::UsedUp
+}
+
+fn place_anchor(
+    _inventory_id: usize,
+    _target: Option<(i32, i32)>,
+    _ctx: &mut Context,
+    game: &mut Game,
+    objects: &mut [Object],
+) -> UseResult {
+    game.messages
+        .add(tr("item.you_fix_the_anchor_to_the", &[]), LIGHT_BLUE);
+    game.anchor = Some(objects[PLAYER].pos());
+    UseResult::UsedUp
+}
+
+// Burn every fighter around the tile, crediting the caster with the kills
+// and turning the monsters caught in the blast against a monster caster
+pub fn explode(caster_id: usize, x: i32, y: i32, game: &mut Game, objects: &mut [Object]) {
     let mut gained_xp = 0;
+    let mut burnt = vec![];
     for (id, obj) in objects.iter_mut().enumerate() {
         if obj.distance(x, y) <= (SPELL_RANGE / 2) as f32 && obj.fighter.is_some() {
             game.messages.add(
-                format!("{} is burnt by the infernal spell!", obj.name),
+                tr(
+                    "item.is_burnt_by_the_infernal_spell",
+                    &[("target", &obj.name)],
+                ),
                 ORANGE,
             );
-            if let Some(xp) = obj.take_damage(FIRE_DAMAGE, game) {
-                if id != PLAYER {
+            let damage = obj
+                .race(game)
+                .map_or(FIRE_DAMAGE, |race| race.resist_fire(FIRE_DAMAGE));
+            if let Some(xp) = obj.take_damage(damage, caster_id == PLAYER, game) {
+                if id != caster_id {
                     gained_xp += xp;
                 }
             }
+            burnt.push(id);
         }
     }
-    objects[PLAYER].fighter.as_mut().unwrap().xp += gained_xp;
-
-    UseResult::UsedUp
+    if let Some(fighter) = objects[caster_id].fighter.as_mut() {
+        fighter.xp += gained_xp;
+    }
+    for id in burnt {
+        provoke(id, caster_id, game, objects);
+    }
 }
 
 pub fn drop_item(inventory_id: usize, game: &mut Game, objects: &mut Vec<Object>) {
@@ -232,6 +839,6 @@ pub fn drop_item(inventory_id: usize, game: &mut Game, objects: &mut Vec<Object>
     }
     item.set_pos(objects[PLAYER].x, objects[PLAYER].y);
     game.messages
-        .add(format!("Yout dropped {}", item.name), LIGHT_GREY);
+        .add(tr("item.yout_dropped", &[("item", &item.name)]), LIGHT_GREY);
     objects.push(item);
 }
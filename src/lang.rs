@@ -0,0 +1,87 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::fs;
+
+use crate::errors::log_error;
+use crate::mods::read_data;
+use crate::paths::asset;
+
+// Every language has a catalog in there, <language>.json mapping the ids of the texts
+// to the texts themselves, with {name} standing for the values filled in
+const LANG_DIR: &str = "data/lang";
+pub const DEFAULT_LANGUAGE: &str = "en";
+
+// The texts of the language chosen, with the default language standing in
+// for the ones it lacks
+#[derive(Debug, Default)]
+struct Catalog {
+    texts: HashMap<String, String>,
+    fallback: HashMap<String, String>,
+}
+
+fn read_catalog(language: &str) -> HashMap<String, String> {
+    read_data(&format!("{}/{}.json", LANG_DIR, language))
+        .and_then(|data| Ok(serde_json::from_value(data)?))
+        .unwrap_or_else(|e| {
+            log_error(&format!("Failed to load the {} texts: {}", language, e));
+            HashMap::new()
+        })
+}
+
+impl Catalog {
+    fn load(language: &str) -> Self {
+        let fallback = read_catalog(DEFAULT_LANGUAGE);
+        let texts = if language == DEFAULT_LANGUAGE {
+            HashMap::new()
+        } else {
+            read_catalog(language)
+        };
+        Catalog { texts, fallback }
+    }
+}
+
+// Texts are needed just about everywhere, the catalog is kept at hand rather than
+// passed around along with the game
+thread_local! {
+    static CATALOG: RefCell<Catalog> = RefCell::new(Catalog::load(DEFAULT_LANGUAGE));
+}
+
+pub fn set_language(language: &str) {
+    CATALOG.with(|catalog| *catalog.borrow_mut() = Catalog::load(language));
+}
+
+// Languages having a catalog, sorted
+pub fn languages() -> Vec<String> {
+    let mut languages = fs::read_dir(asset(LANG_DIR))
+        .map(|entries| {
+            entries
+                .filter_map(Result::ok)
+                .filter_map(|entry| {
+                    let name = entry.file_name().to_string_lossy().into_owned();
+                    name.strip_suffix(".json").map(String::from)
+                })
+                .collect::<Vec<_>>()
+        })
+        .unwrap_or_default();
+    languages.sort();
+    languages
+}
+
+// The text of the id in the language chosen, the values filled in by name.
+// A text missing from every catalog shows as its id, to be noticed and added
+pub fn tr(id: &str, args: &[(&str, &dyn Display)]) -> String {
+    let mut text = CATALOG.with(|catalog| {
+        let catalog = catalog.borrow();
+        catalog
+            .texts
+            .get(id)
+            .or_else(|| catalog.fallback.get(id))
+            .cloned()
+            .unwrap_or_else(|| id.to_string())
+    });
+    for (name, value) in args {
+        text = text.replace(&format!("{{{}}}", name), &value.to_string());
+    }
+    text
+}
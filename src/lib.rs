@@ -0,0 +1,70 @@
+// The game itself, independent of how it's drawn:
+// frontends provide a Renderer, an InputSource and an AudioSink and run the main menu
+pub mod action;
+pub mod affix;
+pub mod ai;
+pub mod audio;
+pub mod backend;
+pub mod behavior;
+pub mod bestiary;
+pub mod class;
+pub mod clock;
+pub mod colors;
+pub mod combat;
+pub mod config;
+pub mod cover;
+pub mod crafting;
+pub mod daynight;
+pub mod dialogue;
+pub mod dijkstra;
+pub mod encounter;
+pub mod equipment;
+pub mod errors;
+pub mod event;
+pub mod faction;
+pub mod feeling;
+pub mod fields;
+pub mod fighter;
+pub mod fonts;
+pub mod food;
+pub mod fov;
+pub mod furniture;
+pub mod game;
+pub mod glyphs;
+pub mod grid;
+pub mod hireling;
+pub mod item;
+pub mod lang;
+pub mod light;
+pub mod mapgen;
+pub mod mods;
+pub mod narrate;
+pub mod object;
+pub mod options;
+pub mod overworld;
+pub mod paths;
+pub mod perk;
+pub mod photo;
+pub mod piety;
+pub mod plan;
+pub mod props;
+pub mod push;
+pub mod race;
+pub mod reload;
+pub mod render;
+pub mod replay;
+pub mod respawn;
+pub mod rng;
+pub mod room;
+pub mod save;
+pub mod scheme;
+pub mod script;
+pub mod spawn;
+pub mod stats;
+pub mod status;
+pub mod system;
+pub mod theme;
+pub mod threat;
+pub mod tileset;
+pub mod tooltip;
+pub mod trap;
@@ -0,0 +1,88 @@
+use rand::Rng;
+
+use crate::colors::*;
+
+use crate::daynight::{is_night, NIGHT_SIGHT_RADIUS};
+use crate::game::Game;
+use crate::item::Item;
+use crate::lang::tr;
+
+// How far the player sees on the lit levels, and on the dark ones without a light
+const SIGHT_RADIUS: i32 = 10;
+const DARK_SIGHT_RADIUS: i32 = 1;
+// Chance in percent of a level past the first few being dark
+const DARK_LEVEL_CHANCE: i32 = 25;
+const DARK_LEVEL_MIN: u32 = 3;
+// Turns of light the torches come with, and the lanterns hold
+pub const TORCH_FUEL: u32 = 300;
+pub const LANTERN_FUEL: u32 = 600;
+// Turns of fuel left when the player is warned about it
+const LOW_FUEL: u32 = 30;
+
+// Light of the brightest lamp the player holds that still burns
+pub fn carried_light(game: &Game) -> i32 {
+    game.inventory
+        .iter()
+        .filter(|item| item.fuel.is_some_and(|fuel| fuel > 0))
+        .filter_map(|item| item.equipment)
+        .filter(|equipment| equipment.equipped)
+        .map(|equipment| equipment.light_radius)
+        .max()
+        .unwrap_or(0)
+}
+
+// The surface is only lit by day
+pub fn sight_radius(game: &Game) -> i32 {
+    let radius = if game.dark {
+        carried_light(game).max(DARK_SIGHT_RADIUS)
+    } else if game.dungeon_level == 0 && is_night(game) {
+        carried_light(game).max(NIGHT_SIGHT_RADIUS)
+    } else {
+        SIGHT_RADIUS
+    };
+    radius + game.character.race.infravision()
+}
+
+// Maybe plunge the new level into darkness
+pub fn roll_darkness(game: &mut Game) {
+    game.dark =
+        game.dungeon_level >= DARK_LEVEL_MIN && game.rng.gen_range(0, 100) < DARK_LEVEL_CHANCE;
+    if game.dark {
+        game.messages
+            .add(tr("light.it_is_pitch_dark_down_here", &[]), DARK_GREY);
+    }
+}
+
+// Burn a turn of fuel of every lamp in hand, the torches burn out for good
+pub fn burn_fuel(game: &mut Game) {
+    let mut burnt_out = vec![];
+    for (index, item) in game.inventory.iter_mut().enumerate() {
+        let lit = item
+            .equipment
+            .is_some_and(|e| e.equipped && e.light_radius > 0);
+        let fuel = match item.fuel.as_mut() {
+            Some(fuel) if lit && *fuel > 0 => fuel,
+            _ => continue,
+        };
+        *fuel -= 1;
+        match *fuel {
+            0 if item.item == Some(Item::Torch) => burnt_out.push(index),
+            0 => game.messages.add(
+                tr("light.your_goes_out", &[("item", &item.name)]),
+                DARK_GREY,
+            ),
+            LOW_FUEL => game.messages.add(
+                tr("light.your_flickers", &[("item", &item.name)]),
+                LIGHT_GREY,
+            ),
+            _ => {}
+        }
+    }
+    for index in burnt_out.into_iter().rev() {
+        let torch = game.inventory.remove(index);
+        game.messages.add(
+            tr("light.your_burns_out", &[("item", &torch.name)]),
+            DARK_GREY,
+        );
+    }
+}
@@ -1,36 +1,31 @@
-use tcod::console::*;
-use tcod::map::Map as FovMap;
+use roguelike::fov::FovMap;
+use roguelike::narrate::Narrator;
+use roguelike::{config, game, lang, mods, render};
 
-mod ai;
-mod equipment;
-mod fighter;
-mod game;
-mod item;
-mod object;
-mod render;
-mod room;
-mod save;
-
-const FPS_LIMIT: i32 = 60;
+mod frontend;
 
 fn main() {
-    tcod::system::set_fps(FPS_LIMIT);
-
-    let root = Root::initializer()
-        .font("arial10x10.png", FontLayout::Tcod)
-        .font_type(FontType::Greyscale)
-        .size(render::SCREEN_WIDTH, render::SCREEN_HEIGHT)
-        .title("Rust and Steel")
-        .init();
+    let config = config::load_config();
+    mods::report_conflicts();
+    lang::set_language(&config.options.language);
+    // Made before the screen is taken over, for its complaints to be seen
+    let narrator = Narrator::new(&config.narration, !frontend::draws_on_stdout(&config));
+    let (renderer, input, tiles) = frontend::init(&config);
 
-    let mut tcod = render::Tcod {
-        root,
-        con: Offscreen::new(game::MAP_WIDTH, game::MAP_HEIGHT),
-        panel: Offscreen::new(render::SCREEN_WIDTH, render::PANEL_HEIGHT),
+    let mut ctx = render::Context {
+        renderer,
+        input,
         fov: FovMap::new(game::MAP_WIDTH, game::MAP_HEIGHT),
         key: Default::default(),
         mouse: Default::default(),
+        tiles,
+        glyphs: config.glyphs.clone(),
+        clock: config.clock.clone(),
+        rules: config.rules.clone(),
+        options: config.options.clone(),
+        audio: frontend::init_audio(),
+        narrator,
     };
 
-    render::main_menu(&mut tcod);
+    render::main_menu(&mut ctx);
 }
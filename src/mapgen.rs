@@ -0,0 +1,771 @@
+use std::error::Error;
+
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::colors::*;
+
+use crate::dialogue::place_npcs;
+use crate::encounter::load_encounters;
+
+use crate::errors::log_error;
+use crate::furniture::{make_brazier, make_fountain, make_statue};
+use crate::game::{is_blocked, Map, Tile, MAP_HEIGHT, MAP_WIDTH, PLAYER};
+use crate::grid::Grid;
+use crate::mods::read_data;
+use crate::object::Object;
+use crate::overworld::Branch;
+use crate::piety::make_altar;
+use crate::props::make_prop;
+use crate::push::{make_barrel, make_boulder};
+use crate::rng::GameRng;
+use crate::room::{create_h_tunnel, create_room, create_v_tunnel, place_objects, Rect};
+use crate::theme::{Decor, Theme};
+use crate::trap::{make_trap, Trap};
+
+const MAP_BUILDER_FILE: &str = "data/map_builder.json";
+
+// How many times a map is generated again when it fails a stage
+const MAX_ATTEMPTS: u32 = 10;
+
+// Size of the room carved for a boss, and how many places are tried for it
+const VAULT_SIZE: i32 = 9;
+const VAULT_ATTEMPTS: u32 = 50;
+// Where the bosses stand around the stairs in the middle of their room, first free first
+const GUARD_SPOTS: [(i32, i32); 8] = [
+    (0, -1),
+    (0, 1),
+    (-1, 0),
+    (1, 0),
+    (-1, -1),
+    (1, -1),
+    (-1, 1),
+    (1, 1),
+];
+
+// Most barrels, crates and urns put in a single room
+const MAX_PROPS: i32 = 3;
+
+// The level being generated, passed through every stage of the builder
+pub struct MapData<'a> {
+    pub map: Map,
+    pub rooms: Vec<Rect>,
+    pub objects: &'a mut Vec<Object>,
+    pub level: u32,
+    // Look of the depth the level is at, deciding what the rooms are decorated with
+    pub theme: Theme,
+    // Names of the unique monsters already met this run
+    pub uniques: &'a [String],
+}
+
+// A single step of the map generation,
+// failing makes the builder start over with a fresh map
+pub trait MapStage {
+    fn apply(&self, data: &mut MapData, rng: &mut GameRng) -> Result<(), String>;
+
+    // What the stage is called, to tell which one failed
+    fn name(&self) -> &'static str {
+        std::any::type_name::<Self>()
+            .rsplit("::")
+            .next()
+            .unwrap_or_default()
+    }
+}
+
+// Carve rectangular rooms that don't overlap, the player starts in the first one
+pub struct Rooms {
+    pub max_rooms: i32,
+    pub min_size: i32,
+    pub max_size: i32,
+}
+
+impl MapStage for Rooms {
+    fn apply(&self, data: &mut MapData, rng: &mut GameRng) -> Result<(), String> {
+        for _ in 0..self.max_rooms {
+            // Random width and height
+            let w = rng.gen_range(self.min_size, self.max_size + 1);
+            let h = rng.gen_range(self.min_size, self.max_size + 1);
+
+            // Random position of the room with regards to the boundaries
+            let x = rng.gen_range(0, MAP_WIDTH - w);
+            let y = rng.gen_range(0, MAP_HEIGHT - h);
+
+            let new_room = Rect::new(x, y, w, h);
+
+            // Check intersections with existing rooms
+            if data.rooms.iter().any(|room| new_room.intersect(room)) {
+                continue;
+            }
+            create_room(new_room, &mut data.map);
+            data.rooms.push(new_room);
+        }
+
+        let (x, y) = data.rooms.first().ok_or("No room fits the map")?.center();
+        data.objects[PLAYER].set_pos(x, y);
+        Ok(())
+    }
+}
+
+// Connect every room to the previous one with an L-shaped tunnel
+pub struct Corridors;
+
+impl MapStage for Corridors {
+    fn apply(&self, data: &mut MapData, rng: &mut GameRng) -> Result<(), String> {
+        for pair in data.rooms.windows(2) {
+            let (prev_x, prev_y) = pair[0].center();
+            let (new_x, new_y) = pair[1].center();
+
+            if rng.gen() {
+                create_h_tunnel(prev_x, new_x, prev_y, &mut data.map);
+                create_v_tunnel(prev_y, new_y, new_x, &mut data.map);
+            } else {
+                create_v_tunnel(prev_y, new_y, prev_x, &mut data.map);
+                create_h_tunnel(prev_x, new_x, new_y, &mut data.map);
+            }
+        }
+        Ok(())
+    }
+}
+
+// Put doors in the gaps the tunnels made in the walls of the rooms
+pub struct Doors {
+    // Chance in percent of every gap getting a door
+    pub door_chance: i32,
+}
+
+impl MapStage for Doors {
+    fn apply(&self, data: &mut MapData, rng: &mut GameRng) -> Result<(), String> {
+        for room in &data.rooms {
+            let mut gaps = vec![];
+            for x in room.x1 + 1..room.x2 {
+                gaps.push((x, room.y1, (1, 0)));
+                gaps.push((x, room.y2, (1, 0)));
+            }
+            for y in room.y1 + 1..room.y2 {
+                gaps.push((room.x1, y, (0, 1)));
+                gaps.push((room.x2, y, (0, 1)));
+            }
+            for (x, y, (dx, dy)) in gaps {
+                // A single tile wide, with the wall going on at both sides
+                let gap = !data.map[(x, y)].blocked
+                    && data.map[(x - dx, y - dy)].blocked
+                    && data.map[(x + dx, y + dy)].blocked;
+                if gap && rng.gen_range(0, 100) < self.door_chance {
+                    data.map[(x, y)] = Tile::door();
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+// Grow tall grass over the floor of some of the rooms, hiding what is behind it
+pub struct Foliage {
+    // Chance in percent of a room being overgrown, and how much of its floor is
+    pub room_chance: i32,
+    pub density: i32,
+}
+
+impl MapStage for Foliage {
+    fn apply(&self, data: &mut MapData, rng: &mut GameRng) -> Result<(), String> {
+        let room_chance = data.theme.decor_chance(Decor::Foliage, self.room_chance);
+        scatter(Tile::foliage(), room_chance, self.density, data, rng);
+        Ok(())
+    }
+}
+
+// Litter the floor of some of the rooms with rubble, slow to climb over
+pub struct Rubble {
+    // Chance in percent of a room being littered, and how much of its floor is
+    pub room_chance: i32,
+    pub density: i32,
+}
+
+impl MapStage for Rubble {
+    fn apply(&self, data: &mut MapData, rng: &mut GameRng) -> Result<(), String> {
+        scatter(Tile::rubble(), self.room_chance, self.density, data, rng);
+        Ok(())
+    }
+}
+
+// Cover the floor of the rooms with the tile here and there
+fn scatter(tile: Tile, room_chance: i32, density: i32, data: &mut MapData, rng: &mut GameRng) {
+    // The player gets to look around the first room
+    for room in data.rooms.iter().skip(1) {
+        if rng.gen_range(0, 100) >= room_chance {
+            continue;
+        }
+        for x in room.x1 + 1..room.x2 {
+            for y in room.y1 + 1..room.y2 {
+                let floor = &mut data.map[(x, y)];
+                if !floor.blocked && rng.gen_range(0, 100) < density {
+                    *floor = tile;
+                }
+            }
+        }
+    }
+}
+
+// Open chasms in a corner of some of the large rooms, away from the tunnels coming in
+pub struct Chasms {
+    // Chance in percent of every room large enough getting a chasm
+    pub chasm_chance: i32,
+    pub min_size: i32,
+}
+
+impl MapStage for Chasms {
+    fn apply(&self, data: &mut MapData, rng: &mut GameRng) -> Result<(), String> {
+        for room in data.rooms.iter().skip(1) {
+            if room.x2 - room.x1 < self.min_size
+                || room.y2 - room.y1 < self.min_size
+                || rng.gen_range(0, 100) >= self.chasm_chance
+            {
+                continue;
+            }
+            // Between the walls and the middle row and column, keeping a path around
+            let (center_x, center_y) = room.center();
+            let (x1, x2) = if rng.gen() {
+                (room.x1 + 2, center_x - 1)
+            } else {
+                (center_x + 2, room.x2 - 1)
+            };
+            let (y1, y2) = if rng.gen() {
+                (room.y1 + 2, center_y - 1)
+            } else {
+                (center_y + 2, room.y2 - 1)
+            };
+            for x in x1..x2 {
+                for y in y1..y2 {
+                    data.map[(x, y)] = Tile::chasm();
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+// Put pillars in the corners of the rooms large enough to walk around them
+pub struct Pillars {
+    pub min_size: i32,
+}
+
+impl MapStage for Pillars {
+    fn apply(&self, data: &mut MapData, _rng: &mut GameRng) -> Result<(), String> {
+        // Smaller rooms would get pillars in their center, where the tunnels come in
+        let min_size = self.min_size.max(6);
+        for room in &data.rooms {
+            if room.x2 - room.x1 < min_size || room.y2 - room.y1 < min_size {
+                continue;
+            }
+            for &x in &[room.x1 + 2, room.x2 - 2] {
+                for &y in &[room.y1 + 2, room.y2 - 2] {
+                    data.map[(x, y)] = Tile::wall();
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+// Fill the rooms with monsters and items fitting the dungeon level
+pub struct Population;
+
+impl MapStage for Population {
+    fn apply(&self, data: &mut MapData, rng: &mut GameRng) -> Result<(), String> {
+        for &room in data.rooms.iter() {
+            place_objects(
+                room,
+                &data.map,
+                data.objects,
+                data.level,
+                data.theme,
+                data.uniques,
+                rng,
+            );
+        }
+        Ok(())
+    }
+}
+
+// Put the bosses of the level in a vault past the last room, around the stairs,
+// or in the last room itself when there is no space left for the vault
+pub struct Encounters;
+
+impl MapStage for Encounters {
+    fn apply(&self, data: &mut MapData, rng: &mut GameRng) -> Result<(), String> {
+        // No encounters without the data file
+        let encounters = load_encounters().unwrap_or_default();
+        let level = data.level;
+        let bosses = encounters
+            .iter()
+            .filter(|e| e.level == level)
+            .collect::<Vec<_>>();
+        if bosses.is_empty() {
+            return Ok(());
+        }
+
+        let last = *data.rooms.last().ok_or("No room for the boss")?;
+        if let Some(vault) = (0..VAULT_ATTEMPTS)
+            .map(|_| {
+                let x = rng.gen_range(0, MAP_WIDTH - VAULT_SIZE);
+                let y = rng.gen_range(0, MAP_HEIGHT - VAULT_SIZE);
+                Rect::new(x, y, VAULT_SIZE, VAULT_SIZE)
+            })
+            .find(|vault| !data.rooms.iter().any(|room| vault.intersect(room)))
+        {
+            create_room(vault, &mut data.map);
+            let (last_x, last_y) = last.center();
+            let (vault_x, vault_y) = vault.center();
+            create_h_tunnel(last_x, vault_x, last_y, &mut data.map);
+            create_v_tunnel(last_y, vault_y, vault_x, &mut data.map);
+            data.rooms.push(vault);
+        }
+
+        // The stairs go in the middle, the bosses stand around them
+        let (x, y) = data.rooms.last().unwrap().center();
+        for encounter in bosses {
+            let spot = GUARD_SPOTS
+                .iter()
+                .map(|&(dx, dy)| (x + dx, y + dy))
+                .find(|&(x, y)| !is_blocked(x, y, &data.map, data.objects));
+            if let Some((x, y)) = spot {
+                data.objects.push(encounter.spawn(x, y, rng));
+            }
+        }
+        Ok(())
+    }
+}
+
+// Put the friendly npcs of the level in random rooms
+pub struct Npcs;
+
+impl MapStage for Npcs {
+    fn apply(&self, data: &mut MapData, rng: &mut GameRng) -> Result<(), String> {
+        place_npcs(&data.rooms, &data.map, data.objects, data.level, rng);
+        Ok(())
+    }
+}
+
+// Furnish the rooms between the first and the last one
+pub struct Decoration {
+    // Chance in percent of the level having an altar
+    pub altar_chance: i32,
+    // Chances in percent of every room getting a fountain, statues and braziers
+    pub fountain_chance: i32,
+    pub statue_chance: i32,
+    pub brazier_chance: i32,
+    // Chance in percent of every room getting a few barrels, crates and urns to break
+    pub prop_chance: i32,
+}
+
+impl MapStage for Decoration {
+    fn apply(&self, data: &mut MapData, rng: &mut GameRng) -> Result<(), String> {
+        if data.rooms.len() < 3 {
+            return Ok(());
+        }
+        let furnished = data.rooms[1..data.rooms.len() - 1].to_vec();
+        if rng.gen_range(0, 100) < self.altar_chance {
+            let (x, y) = furnished[rng.gen_range(0, furnished.len())].center();
+            place_furniture(make_altar(x, y, rng), data);
+        }
+
+        for room in furnished {
+            if rng.gen_range(0, 100) < self.fountain_chance {
+                let (x, y) = room.center();
+                place_furniture(make_fountain(x, y), data);
+            }
+            // Away from the center, where the tunnels come in
+            if rng.gen_range(0, 100) < data.theme.decor_chance(Decor::Statue, self.statue_chance)
+                && room.x2 - room.x1 >= 6
+                && room.y2 - room.y1 >= 6
+            {
+                for &(x, y) in &[(room.x1 + 2, room.y1 + 2), (room.x2 - 2, room.y2 - 2)] {
+                    place_furniture(make_statue(x, y, rng), data);
+                }
+            }
+            if rng.gen_range(0, 100) < data.theme.decor_chance(Decor::Brazier, self.brazier_chance)
+            {
+                for &(x, y) in &[(room.x1 + 1, room.y2 - 1), (room.x2 - 1, room.y1 + 1)] {
+                    place_furniture(make_brazier(x, y), data);
+                }
+            }
+            if rng.gen_range(0, 100) < data.theme.decor_chance(Decor::Prop, self.prop_chance) {
+                for _ in 0..rng.gen_range(1, MAX_PROPS + 1) {
+                    let x = rng.gen_range(room.x1 + 1, room.x2);
+                    let y = rng.gen_range(room.y1 + 1, room.y2);
+                    let prop = make_prop(rng, x, y);
+                    place_furniture(prop, data);
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+// Put the furniture down unless a wall or something else already takes its place
+fn place_furniture(furniture: Object, data: &mut MapData) {
+    let (x, y) = furniture.pos();
+    if !data.map[(x, y)].blocked && !data.objects.iter().any(|object| object.pos() == (x, y)) {
+        data.objects.push(furniture);
+    }
+}
+
+// Leave boulders and barrels lying around the rooms past the first one
+pub struct Boulders {
+    // Chances in percent of every room getting a boulder and a barrel
+    pub boulder_chance: i32,
+    pub barrel_chance: i32,
+}
+
+impl MapStage for Boulders {
+    fn apply(&self, data: &mut MapData, rng: &mut GameRng) -> Result<(), String> {
+        for room in data.rooms.clone().iter().skip(1) {
+            if rng.gen_range(0, 100) < self.boulder_chance {
+                let x = rng.gen_range(room.x1 + 1, room.x2);
+                let y = rng.gen_range(room.y1 + 1, room.y2);
+                place_furniture(make_boulder(x, y), data);
+            }
+            if rng.gen_range(0, 100) < self.barrel_chance {
+                let x = rng.gen_range(room.x1 + 1, room.x2);
+                let y = rng.gen_range(room.y1 + 1, room.y2);
+                place_furniture(make_barrel(x, y), data);
+            }
+        }
+        Ok(())
+    }
+}
+
+// Hide traps on the floor of the rooms past the first one
+pub struct Traps {
+    // Chance in percent of every room getting a trap
+    pub trap_chance: i32,
+    // Scripts the traps may run, as likely as the gas trap or the pit each
+    pub scripts: Vec<String>,
+}
+
+impl MapStage for Traps {
+    fn apply(&self, data: &mut MapData, rng: &mut GameRng) -> Result<(), String> {
+        for room in data.rooms.iter().skip(1) {
+            if rng.gen_range(0, 100) >= self.trap_chance {
+                continue;
+            }
+            let x = rng.gen_range(room.x1 + 1, room.x2);
+            let y = rng.gen_range(room.y1 + 1, room.y2);
+            let trap = match rng.gen_range(0, self.scripts.len() + 2) {
+                0 => Trap::Gas,
+                1 => Trap::Pit,
+                script => Trap::Scripted(self.scripts[script - 2].clone()),
+            };
+            if !data.map[(x, y)].blocked
+                && !data.objects.iter().any(|object| object.pos() == (x, y))
+            {
+                data.objects.push(make_trap(trap, x, y));
+            }
+        }
+        Ok(())
+    }
+}
+
+// Create stairs at the center of the last room
+pub struct Stairs;
+
+impl MapStage for Stairs {
+    fn apply(&self, data: &mut MapData, _rng: &mut GameRng) -> Result<(), String> {
+        let (x, y) = data.rooms.last().ok_or("No room for the stairs")?.center();
+        let mut stairs = Object::new(x, y, '>', "stairs", WHITE, false);
+        stairs.always_visible = true;
+        data.objects.push(stairs);
+        Ok(())
+    }
+}
+
+// Make sure the stairs can be reached from where the player starts
+pub struct Validation;
+
+impl MapStage for Validation {
+    fn apply(&self, data: &mut MapData, _rng: &mut GameRng) -> Result<(), String> {
+        let stairs = data
+            .objects
+            .iter()
+            .find(|object| object.name == "stairs")
+            .ok_or("The map has no stairs")?
+            .pos();
+
+        // Flood fill the floor starting from the player
+        let mut reached = Grid::new(MAP_WIDTH, MAP_HEIGHT, false);
+        let mut frontier = vec![data.objects[PLAYER].pos()];
+        while let Some((x, y)) = frontier.pop() {
+            if x < 0 || y < 0 || x >= MAP_WIDTH || y >= MAP_HEIGHT {
+                continue;
+            }
+            if reached[(x, y)] || data.map[(x, y)].blocked {
+                continue;
+            }
+            reached[(x, y)] = true;
+            frontier.extend(&[(x + 1, y), (x - 1, y), (x, y + 1), (x, y - 1)]);
+        }
+
+        if reached[(stairs.0, stairs.1)] {
+            Ok(())
+        } else {
+            Err("The stairs can't be reached".into())
+        }
+    }
+}
+
+// Stages as they are written in the data file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "stage")]
+pub enum StageConfig {
+    Rooms {
+        max_rooms: i32,
+        min_size: i32,
+        max_size: i32,
+    },
+    Corridors,
+    Doors {
+        door_chance: i32,
+    },
+    Foliage {
+        room_chance: i32,
+        density: i32,
+    },
+    Rubble {
+        room_chance: i32,
+        density: i32,
+    },
+    Chasms {
+        chasm_chance: i32,
+        min_size: i32,
+    },
+    Pillars {
+        min_size: i32,
+    },
+    Population,
+    Encounters,
+    Npcs,
+    Decoration {
+        altar_chance: i32,
+        fountain_chance: i32,
+        statue_chance: i32,
+        brazier_chance: i32,
+        prop_chance: i32,
+    },
+    Boulders {
+        boulder_chance: i32,
+        barrel_chance: i32,
+    },
+    Traps {
+        trap_chance: i32,
+        #[serde(default)]
+        scripts: Vec<String>,
+    },
+    Stairs,
+    Validation,
+}
+
+impl StageConfig {
+    pub fn to_stage(&self) -> Box<dyn MapStage> {
+        match *self {
+            StageConfig::Rooms {
+                max_rooms,
+                min_size,
+                max_size,
+            } => Box::new(Rooms {
+                max_rooms,
+                min_size,
+                max_size,
+            }),
+            StageConfig::Corridors => Box::new(Corridors),
+            StageConfig::Doors { door_chance } => Box::new(Doors { door_chance }),
+            StageConfig::Foliage {
+                room_chance,
+                density,
+            } => Box::new(Foliage {
+                room_chance,
+                density,
+            }),
+            StageConfig::Rubble {
+                room_chance,
+                density,
+            } => Box::new(Rubble {
+                room_chance,
+                density,
+            }),
+            StageConfig::Chasms {
+                chasm_chance,
+                min_size,
+            } => Box::new(Chasms {
+                chasm_chance,
+                min_size,
+            }),
+            StageConfig::Pillars { min_size } => Box::new(Pillars { min_size }),
+            StageConfig::Population => Box::new(Population),
+            StageConfig::Encounters => Box::new(Encounters),
+            StageConfig::Npcs => Box::new(Npcs),
+            StageConfig::Decoration {
+                altar_chance,
+                fountain_chance,
+                statue_chance,
+                brazier_chance,
+                prop_chance,
+            } => Box::new(Decoration {
+                altar_chance,
+                fountain_chance,
+                statue_chance,
+                brazier_chance,
+                prop_chance,
+            }),
+            StageConfig::Boulders {
+                boulder_chance,
+                barrel_chance,
+            } => Box::new(Boulders {
+                boulder_chance,
+                barrel_chance,
+            }),
+            StageConfig::Traps {
+                trap_chance,
+                ref scripts,
+            } => Box::new(Traps {
+                trap_chance,
+                scripts: scripts.clone(),
+            }),
+            StageConfig::Stairs => Box::new(Stairs),
+            StageConfig::Validation => Box::new(Validation),
+        }
+    }
+}
+
+// A chain of stages run one after another on an empty map
+#[derive(Default)]
+pub struct MapBuilder {
+    stages: Vec<Box<dyn MapStage>>,
+}
+
+impl MapBuilder {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    // Add a stage to the end of the chain
+    pub fn with<S: MapStage + 'static>(mut self, stage: S) -> Self {
+        self.stages.push(Box::new(stage));
+        self
+    }
+
+    pub fn from_config(stages: &[StageConfig]) -> Self {
+        MapBuilder {
+            stages: stages.iter().map(StageConfig::to_stage).collect(),
+        }
+    }
+
+    // Rooms connected by tunnels, like the dungeon always was
+    pub fn standard() -> Self {
+        MapBuilder::new()
+            .with(Rooms {
+                max_rooms: 30,
+                min_size: 6,
+                max_size: 10,
+            })
+            .with(Corridors)
+            .with(Doors { door_chance: 50 })
+            .with(Foliage {
+                room_chance: 15,
+                density: 40,
+            })
+            .with(Rubble {
+                room_chance: 15,
+                density: 20,
+            })
+            .with(Chasms {
+                chasm_chance: 10,
+                min_size: 8,
+            })
+            .with(Population)
+            .with(Encounters)
+            .with(Npcs)
+            .with(Decoration {
+                altar_chance: 30,
+                fountain_chance: 10,
+                statue_chance: 15,
+                brazier_chance: 10,
+                prop_chance: 25,
+            })
+            .with(Boulders {
+                boulder_chance: 10,
+                barrel_chance: 10,
+            })
+            .with(Traps {
+                trap_chance: 10,
+                scripts: vec![],
+            })
+            .with(Stairs)
+            .with(Validation)
+    }
+
+    // Generate a level and its rooms, replacing every object except for the player,
+    // with the standard stages when the ones given never make a good map
+    pub fn build(
+        &self,
+        objects: &mut Vec<Object>,
+        level: u32,
+        branch: Branch,
+        uniques: &[String],
+        rng: &mut GameRng,
+    ) -> (Map, Vec<Rect>) {
+        self.try_build(objects, level, branch, uniques, rng)
+            .or_else(|e| {
+                log_error(&format!("Using the standard map builder, {}", e));
+                MapBuilder::standard().try_build(objects, level, branch, uniques, rng)
+            })
+            .unwrap_or_else(|e| panic!("The standard map builder is broken, {}", e))
+    }
+
+    fn try_build(
+        &self,
+        objects: &mut Vec<Object>,
+        level: u32,
+        branch: Branch,
+        uniques: &[String],
+        rng: &mut GameRng,
+    ) -> Result<(Map, Vec<Rect>), String> {
+        let mut failure = String::new();
+        for _ in 0..MAX_ATTEMPTS {
+            // Remove every object except for the player
+            assert_eq!(&objects[PLAYER] as *const _, &objects[0] as *const _);
+            objects.truncate(1);
+
+            let mut data = MapData {
+                map: Grid::new(MAP_WIDTH, MAP_HEIGHT, Tile::wall()),
+                rooms: vec![],
+                objects,
+                level,
+                theme: Theme::at(branch, level),
+                uniques,
+            };
+            let result = self.stages.iter().try_for_each(|stage| {
+                stage
+                    .apply(&mut data, rng)
+                    .map_err(|e| format!("the {} stage failed: {}", stage.name(), e))
+            });
+            match result {
+                Ok(()) => return Ok((data.map, data.rooms)),
+                Err(e) => failure = e,
+            }
+        }
+        Err(format!(
+            "no map was made in {} attempts, {}",
+            MAX_ATTEMPTS, failure
+        ))
+    }
+}
+
+fn read_map_builder() -> Result<MapBuilder, Box<dyn Error>> {
+    let stages = serde_json::from_value::<Vec<StageConfig>>(read_data(MAP_BUILDER_FILE)?)?;
+    Ok(MapBuilder::from_config(&stages))
+}
+
+// Load the stages from the data file, falling back to the standard ones
+pub fn load_map_builder() -> MapBuilder {
+    read_map_builder().unwrap_or_else(|_| MapBuilder::standard())
+}
@@ -0,0 +1,243 @@
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::fs::{self, File};
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::errors::log_error;
+use crate::lang::tr;
+use crate::paths::{asset, config_file, data_dir};
+use crate::render::{menu, msgbox, Context, MAX_MENU_OPTIONS};
+
+// Every mod is a directory in there, laid out like the game's own files
+const MODS_DIR: &str = "mods";
+// Which mods are on, in the order they're loaded
+const MOD_LIST_FILE: &str = "mods.json";
+// Data files a mod replaces as a whole rather than adds to, the steps of the map builder
+// and the respawn rules making sense only together
+const WHOLE_FILES: &[&str] = &["data/map_builder.json", "data/respawn.json"];
+const MODS_SCREEN_WIDTH: i32 = 40;
+
+// A mod found in the mods directory
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Mod {
+    pub name: String,
+    pub enabled: bool,
+}
+
+// Names of the mods installed, sorted
+fn installed() -> Vec<String> {
+    let mut names = fs::read_dir(data_dir(MODS_DIR))
+        .map(|entries| {
+            entries
+                .filter_map(Result::ok)
+                .filter(|entry| entry.path().is_dir())
+                .map(|entry| entry.file_name().to_string_lossy().into_owned())
+                .collect::<Vec<_>>()
+        })
+        .unwrap_or_default();
+    names.sort();
+    names
+}
+
+fn read_mod_list() -> Result<Vec<Mod>, Box<dyn Error>> {
+    let mut json_mods = String::new();
+    let mut file = File::open(config_file(MOD_LIST_FILE))?;
+    file.read_to_string(&mut json_mods)?;
+    let result = serde_json::from_str::<Vec<Mod>>(&json_mods)?;
+    Ok(result)
+}
+
+// The mods in the order they're loaded, the later ones winning over the earlier:
+// the order of the mod list, then the mods installed since, by name and turned on
+pub fn load_mods() -> Vec<Mod> {
+    let installed = installed();
+    let mut mods = read_mod_list().unwrap_or_default();
+    mods.retain(|m| installed.contains(&m.name));
+    for name in installed {
+        if !mods.iter().any(|m| m.name == name) {
+            mods.push(Mod {
+                name,
+                enabled: true,
+            });
+        }
+    }
+    mods
+}
+
+pub fn save_mods(mods: &[Mod]) -> Result<(), Box<dyn Error>> {
+    let mods_data = serde_json::to_string_pretty(mods)?;
+    let mut file = File::create(config_file(MOD_LIST_FILE))?;
+    file.write_all(mods_data.as_bytes())?;
+    Ok(())
+}
+
+// Directories of the mods turned on, in load order
+fn enabled_mods() -> Vec<(String, PathBuf)> {
+    let dir = data_dir(MODS_DIR);
+    load_mods()
+        .into_iter()
+        .filter(|m| m.enabled)
+        .map(|m| {
+            let path = dir.join(&m.name);
+            (m.name, path)
+        })
+        .collect()
+}
+
+// A file of the game the last mod having it replaces, a script or an image
+pub fn mod_asset(name: &str) -> PathBuf {
+    enabled_mods()
+        .into_iter()
+        .rev()
+        .map(|(_, dir)| dir.join(name))
+        .find(|path| path.exists())
+        .unwrap_or_else(|| asset(name))
+}
+
+fn read_json(path: &Path) -> Result<Value, Box<dyn Error>> {
+    let mut json_data = String::new();
+    let mut file = File::open(path)?;
+    file.read_to_string(&mut json_data)?;
+    Ok(serde_json::from_str(&json_data)?)
+}
+
+// Lay the file of a mod over the data: the entries of a table replace the ones
+// of the same name, a list is added to, unless the file is only replaced whole
+fn overlay(data: &mut Value, layer: Value, whole: bool) {
+    match (data, layer) {
+        (Value::Object(entries), Value::Object(layer)) if !whole => entries.extend(layer),
+        (Value::Array(entries), Value::Array(layer)) if !whole => entries.extend(layer),
+        (data, layer) => *data = layer,
+    }
+}
+
+// A data file of the game with the mods turned on laid over it, in load order.
+// A broken file of a mod is skipped, so one bad mod doesn't take the game down
+pub fn read_data(name: &str) -> Result<Value, Box<dyn Error>> {
+    let mut data = read_json(&asset(name))?;
+    let whole = WHOLE_FILES.contains(&name);
+    for (mod_name, dir) in enabled_mods() {
+        let path = dir.join(name);
+        if !path.exists() {
+            continue;
+        }
+        match read_json(&path) {
+            Ok(layer) => overlay(&mut data, layer, whole),
+            Err(e) => log_error(&format!(
+                "The mod {} has a broken {}: {}",
+                mod_name, name, e
+            )),
+        }
+    }
+    Ok(data)
+}
+
+// Every file under the directory, named relative to it
+pub fn files_under(dir: &Path, prefix: &Path, files: &mut Vec<PathBuf>) {
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(_) => return,
+    };
+    for entry in entries.filter_map(Result::ok) {
+        let path = entry.path();
+        let name = prefix.join(entry.file_name());
+        if path.is_dir() {
+            files_under(&path, &name, files);
+        } else {
+            files.push(name);
+        }
+    }
+}
+
+// What the mods turned on both change, and which of them wins:
+// the same entries of a table, or the same file where it's replaced whole
+pub fn conflicts() -> Vec<String> {
+    // Who changes what, in load order
+    let mut changes: BTreeMap<String, Vec<String>> = BTreeMap::new();
+    for (mod_name, dir) in enabled_mods() {
+        let mut files = vec![];
+        files_under(&dir, Path::new(""), &mut files);
+        for file in files {
+            let name = file.to_string_lossy().replace('\\', "/");
+            let table = name.ends_with(".json") && !WHOLE_FILES.contains(&name.as_str());
+            let changed = match read_json(&dir.join(&file)) {
+                Ok(Value::Object(entries)) if table => entries
+                    .keys()
+                    .map(|key| format!("{} in {}", key, name))
+                    .collect(),
+                // The lists are added to, the mods can't get in each other's way there
+                Ok(Value::Array(_)) if table => vec![],
+                _ => vec![name],
+            };
+            for what in changed {
+                changes.entry(what).or_default().push(mod_name.clone());
+            }
+        }
+    }
+    changes
+        .into_iter()
+        .filter(|(_, mods)| mods.len() > 1)
+        .map(|(what, mods)| {
+            let winner = mods.last().cloned().unwrap_or_default();
+            tr(
+                "mods.is_changed_by",
+                &[
+                    ("what", &what),
+                    ("mods", &mods.join(", ")),
+                    ("winner", &winner),
+                ],
+            )
+        })
+        .collect()
+}
+
+// Tell about the mods getting in each other's way
+pub fn report_conflicts() {
+    for conflict in conflicts() {
+        eprintln!("Mod conflict: {}", conflict);
+    }
+}
+
+// Let the player turn the mods on and off, keeping the choice in the mod list
+pub fn mods_menu(ctx: &mut Context) {
+    let mut mods = load_mods();
+    if mods.is_empty() {
+        let dir = data_dir(MODS_DIR);
+        let text = tr("mods.no_mods_installed", &[("dir", &dir.display())]);
+        msgbox(&text, MODS_SCREEN_WIDTH, ctx);
+        return;
+    }
+    // One line is kept for the conflicts
+    let shown = mods.len().min(MAX_MENU_OPTIONS - 1);
+    loop {
+        let conflicts = conflicts();
+        let mut lines = mods[..shown]
+            .iter()
+            .map(|m| {
+                let id = if m.enabled {
+                    "mods.enabled"
+                } else {
+                    "mods.disabled"
+                };
+                tr(id, &[("name", &m.name)])
+            })
+            .collect::<Vec<_>>();
+        if !conflicts.is_empty() {
+            lines.push(tr("mods.conflicts", &[("count", &conflicts.len())]));
+        }
+        match menu(&tr("mods.header", &[]), &lines, MODS_SCREEN_WIDTH, ctx) {
+            Some(index) if index < shown => {
+                mods[index].enabled = !mods[index].enabled;
+                if let Err(e) = save_mods(&mods) {
+                    log_error(&format!("Failed to save the mod list: {}", e));
+                }
+            }
+            Some(_) => msgbox(&conflicts.join("\n"), MODS_SCREEN_WIDTH, ctx),
+            None => break,
+        }
+    }
+}
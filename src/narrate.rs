@@ -0,0 +1,203 @@
+use std::f32::consts::FRAC_PI_4;
+use std::fs::OpenOptions;
+use std::io::{self, Write};
+
+use serde::{Deserialize, Serialize};
+
+use crate::errors::log_error;
+use crate::game::{Game, PLAYER};
+use crate::lang::tr;
+use crate::object::Object;
+use crate::render::Context;
+
+// Directions the monsters are told to be in, counted clockwise from the east
+// the way the angles on the screen go, y growing downwards
+const DIRECTIONS: [&str; 8] = [
+    "narrate.east",
+    "narrate.south_east",
+    "narrate.south",
+    "narrate.south_west",
+    "narrate.west",
+    "narrate.north_west",
+    "narrate.north",
+    "narrate.north_east",
+];
+
+// Telling what happens in words, line by line, for a screen reader to read out
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct NarrationConfig {
+    pub enabled: bool,
+    // File the lines are written to, a named pipe the screen reader listens on,
+    // the standard output if empty, unless the game is drawn there
+    pub output: String,
+}
+
+pub struct Narrator {
+    out: Option<Box<dyn Write>>,
+    // How many lines of the message log were told already, and how many
+    // messages had been added then, the repeats folded into a line counted too
+    told: usize,
+    added: usize,
+    // The turn described last, for every turn to be described once
+    turn: Option<u32>,
+}
+
+impl Narrator {
+    pub fn new(config: &NarrationConfig, stdout_free: bool) -> Self {
+        let out: Option<Box<dyn Write>> = match config.output.as_str() {
+            _ if !config.enabled => None,
+            "" if stdout_free => Some(Box::new(io::stdout())),
+            "" => {
+                eprintln!("The narration needs a file or a pipe with the terminal backend");
+                None
+            }
+            path => match OpenOptions::new().create(true).append(true).open(path) {
+                Ok(file) => Some(Box::new(file)),
+                Err(e) => {
+                    eprintln!("Failed to open {} for the narration: {}", path, e);
+                    None
+                }
+            },
+        };
+        Narrator {
+            out,
+            told: 0,
+            added: 0,
+            turn: None,
+        }
+    }
+
+    pub fn is_on(&self) -> bool {
+        self.out.is_some()
+    }
+
+    // Tell the line right away, the narration stops once nobody can hear it
+    pub fn say(&mut self, line: &str) {
+        if let Some(out) = self.out.as_mut() {
+            if let Err(e) = writeln!(out, "{}", line).and_then(|_| out.flush()) {
+                log_error(&format!("Failed to narrate: {}", e));
+                self.out = None;
+            }
+        }
+    }
+}
+
+// Compass direction of the offset, in one of eight
+fn direction(dx: i32, dy: i32) -> String {
+    let angle = (dy as f32).atan2(dx as f32);
+    let sector = ((angle / FRAC_PI_4).round() as i32).rem_euclid(8) as usize;
+    tr(DIRECTIONS[sector], &[])
+}
+
+// Tell the messages added to the log since, and once a turn has passed,
+// how the player is doing and what's around them
+pub fn narrate_turn(ctx: &mut Context, game: &mut Game, objects: &[Object]) {
+    if !ctx.narrator.is_on() {
+        return;
+    }
+    // A new game starts the log over
+    if ctx.narrator.told > game.messages.messages.len() {
+        ctx.narrator.told = 0;
+        ctx.narrator.added = 0;
+    }
+    // A repeat folded into the last line tells that line again, with its count
+    let repeated =
+        game.messages.added - ctx.narrator.added > game.messages.messages.len() - ctx.narrator.told;
+    let from = if repeated {
+        ctx.narrator.told.saturating_sub(1)
+    } else {
+        ctx.narrator.told
+    };
+    let mut lines = game.messages.messages[from..]
+        .iter()
+        .map(|(message, _)| message.clone())
+        .collect::<Vec<_>>();
+    ctx.narrator.told = game.messages.messages.len();
+    ctx.narrator.added = game.messages.added;
+
+    let turn = game.stats.turns;
+    if ctx.narrator.turn != Some(turn) {
+        ctx.narrator.turn = Some(turn);
+        let player = &objects[PLAYER];
+        lines.push(tr(
+            "narrate.status",
+            &[
+                ("turn", &turn),
+                ("hp", &player.fighter.map_or(0, |f| f.hp)),
+                ("max_hp", &player.max_hp(game)),
+            ],
+        ));
+
+        // Whoever is in sight, the nearest first
+        let mut seen = objects
+            .iter()
+            .enumerate()
+            .filter(|&(id, object)| {
+                id != PLAYER
+                    && object.alive
+                    && object.fighter.is_some()
+                    && ctx.fov.is_in_fov(object.x, object.y)
+            })
+            .map(|(_, object)| {
+                let (dx, dy) = (object.x - player.x, object.y - player.y);
+                (dx.abs().max(dy.abs()), dx, dy, &object.name)
+            })
+            .collect::<Vec<_>>();
+        seen.sort_by_key(|&(distance, ..)| distance);
+        if seen.is_empty() {
+            lines.push(tr("narrate.nobody_in_sight", &[]));
+        } else {
+            let seen = seen
+                .iter()
+                .map(|&(distance, dx, dy, name)| {
+                    tr(
+                        "narrate.monster",
+                        &[
+                            ("name", name),
+                            ("distance", &distance),
+                            ("direction", &direction(dx, dy)),
+                        ],
+                    )
+                })
+                .collect::<Vec<_>>();
+            lines.push(tr("narrate.in_sight", &[("monsters", &seen.join("; "))]));
+        }
+
+        let items = objects
+            .iter()
+            .filter(|object| object.item.is_some() && object.pos() == player.pos())
+            .map(|object| object.name.as_str())
+            .collect::<Vec<_>>();
+        if !items.is_empty() {
+            lines.push(tr("narrate.items_here", &[("items", &items.join(", "))]));
+        }
+    }
+
+    for line in lines {
+        ctx.narrator.say(&line);
+    }
+}
+
+// Read out the menu, the header and then every option with the letter choosing it
+pub fn announce_menu<T: AsRef<str>>(header: &str, options: &[T], ctx: &mut Context) {
+    if !ctx.narrator.is_on() {
+        return;
+    }
+    if !header.is_empty() {
+        ctx.narrator.say(header);
+    }
+    for (index, option) in options.iter().enumerate() {
+        let letter = (b'a' + index as u8) as char;
+        ctx.narrator.say(&tr(
+            "narrate.option",
+            &[("letter", &letter), ("option", &option.as_ref())],
+        ));
+    }
+    let hint = if options.is_empty() {
+        "narrate.press_any_key"
+    } else {
+        "narrate.choose_a_letter"
+    };
+    ctx.narrator.say(&tr(hint, &[]));
+}
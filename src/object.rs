@@ -1,14 +1,46 @@
 use serde::{Deserialize, Serialize};
 
-use tcod::colors::*;
-use tcod::console::*;
+use crate::colors::*;
 
+use crate::action::TURN;
+use crate::affix::Rarity;
 use crate::ai::Ai;
+use crate::backend::Renderer;
+use crate::combat::{resolve_attack, Brand};
+use crate::encounter::Phase;
 use crate::equipment::Equipment;
-use crate::fighter::Fighter;
-use crate::game::{is_blocked, mut_two, Game, Map, Messages, PLAYER};
-use crate::item::Item;
-use crate::render::Tcod;
+use crate::event::{emit, GameEvent};
+use crate::faction::{hostile, Faction};
+use crate::fighter::{DeathCallback, Fighter};
+use crate::food::Food;
+use crate::furniture::Furniture;
+use crate::game::{
+    cuts_corner, is_blocked, mut_two, set_tile, Game, Messages, Terrain, Tile, PLAYER,
+};
+use crate::glyphs::GlyphConfig;
+use crate::hireling::Hireling;
+use crate::item::{diggable, Item};
+use crate::lang::tr;
+use crate::overworld::Branch;
+use crate::perk::Perk;
+use crate::props::{prop_at, smash, Prop};
+use crate::push::{push, pushable_at, resolve_moves, Pushable};
+use crate::race::Race;
+use crate::render::Context;
+use crate::status::Effect;
+use crate::tileset::TileMapping;
+use crate::trap::Trap;
+
+// How far, in steps, the noises of the player can be heard
+const COMBAT_NOISE: i32 = 10;
+const WALK_NOISE: i32 = 4;
+const SNEAK_NOISE: i32 = 1;
+const DIG_NOISE: i32 = 8;
+
+const THICK_SKIN_DEFENSE: i32 = 2;
+// However nimble or well shielded, some blows land
+const MAX_EVASION: i32 = 50;
+const MAX_BLOCK_CHANCE: i32 = 50;
 
 // A generic object inside the game
 #[derive(Debug, Serialize, Deserialize)]
@@ -18,6 +50,10 @@ pub struct Object {
     pub char: char,
     pub color: Color,
     pub name: String,
+    // What the object was made as, kept when a unique monster gets a name of its own,
+    // empty in older saves
+    #[serde(default)]
+    pub kind: String,
     pub blocks: bool,
     pub alive: bool,
     pub fighter: Option<Fighter>,
@@ -26,6 +62,91 @@ pub struct Object {
     pub item: Option<Item>,
     pub always_visible: bool,
     pub level: i32,
+    // Too heavy to carry, can only be dragged around
+    #[serde(default)]
+    pub heavy: bool,
+    #[serde(default)]
+    pub perks: Vec<Perk>,
+    // Scripted phases of a boss fight still to come
+    #[serde(default)]
+    pub phases: Vec<Phase>,
+    // Name of the conversation of a friendly npc
+    #[serde(default)]
+    pub dialogue: Option<String>,
+    #[serde(default)]
+    pub faction: Option<Faction>,
+    // Direction the object last moved in, what a monster can see depends on it
+    #[serde(default)]
+    pub facing: (i32, i32),
+    // Whether the monster saw the player on its last turn,
+    // and how many more turns the alert over it is shown since it first did
+    #[serde(default)]
+    pub seen_player: bool,
+    #[serde(default)]
+    pub alert: u32,
+    // Where the monster last saw the player,
+    // and for how many more turns it follows the scent after getting there
+    #[serde(default)]
+    pub last_seen: Option<(i32, i32)>,
+    #[serde(default)]
+    pub tracking: u32,
+    // Time the monster's blows took beyond their moves, below zero when they were quick
+    #[serde(default)]
+    pub attack_time: i32,
+    // What a corpse gives to whoever eats it, and how many turns are left until it rots away
+    #[serde(default)]
+    pub food: Option<Food>,
+    #[serde(default)]
+    pub rot: Option<u32>,
+    // Uses left in a wand
+    #[serde(default)]
+    pub charges: Option<u32>,
+    #[serde(default)]
+    pub rarity: Rarity,
+    // Deity the altar is dedicated to
+    #[serde(default)]
+    pub altar: Option<usize>,
+    #[serde(default)]
+    pub furniture: Option<Furniture>,
+    // Radius of the light the object sheds around
+    #[serde(default)]
+    pub light: Option<i32>,
+    #[serde(default)]
+    pub trap: Option<Trap>,
+    #[serde(default)]
+    pub pushable: Option<Pushable>,
+    #[serde(default)]
+    pub prop: Option<Prop>,
+    #[serde(default)]
+    pub hireling: Option<Hireling>,
+    // Branch of the dungeon the entrance on the surface leads to
+    #[serde(default)]
+    pub entrance: Option<Branch>,
+    // Turns the lamp can still burn
+    #[serde(default)]
+    pub fuel: Option<u32>,
+    // Statuses wearing off with the turns, and the one the monster's hits put on whoever they hurt
+    #[serde(default)]
+    pub effects: Vec<Effect>,
+    #[serde(default)]
+    pub on_hit: Option<Effect>,
+    // Items a monster carries, the equipped ones count towards its stats,
+    // the player's are kept in the game
+    #[serde(default)]
+    pub inventory: Vec<Object>,
+    // Shown with a health bar on the panel while in sight
+    #[serde(default)]
+    pub boss: bool,
+    // One of the named monsters met at most once a run
+    #[serde(default)]
+    pub unique: bool,
+    // Key the item is chosen with in the inventory, kept while it's carried
+    #[serde(default)]
+    pub letter: Option<char>,
+    // Refers to the object for good, unlike its index that changes when other objects
+    // are taken off the map, 0 until something needs to remember the object
+    #[serde(default)]
+    pub handle: u32,
 }
 
 impl Object {
@@ -36,6 +157,7 @@ impl Object {
             char: char,
             color: color,
             name: name.into(),
+            kind: name.into(),
             blocks: blocks,
             alive: false,
             fighter: None,
@@ -44,12 +166,61 @@ impl Object {
             item: None,
             always_visible: false,
             level: 1,
+            heavy: false,
+            perks: vec![],
+            phases: vec![],
+            dialogue: None,
+            faction: None,
+            facing: (0, 0),
+            seen_player: false,
+            alert: 0,
+            last_seen: None,
+            tracking: 0,
+            attack_time: 0,
+            food: None,
+            rot: None,
+            charges: None,
+            rarity: Rarity::Common,
+            altar: None,
+            furniture: None,
+            light: None,
+            trap: None,
+            pushable: None,
+            prop: None,
+            hireling: None,
+            entrance: None,
+            fuel: None,
+            effects: vec![],
+            on_hit: None,
+            inventory: vec![],
+            boss: false,
+            unique: false,
+            letter: None,
+            handle: 0,
         }
     }
 
-    pub fn draw(&self, con: &mut dyn Console) {
-        con.set_default_foreground(self.color);
-        con.put_char(self.x, self.y, self.char, BackgroundFlag::None);
+    // Kind of the monster the lookups by kind go by, the name for the older saves
+    pub fn kind(&self) -> &str {
+        if self.kind.is_empty() {
+            &self.name
+        } else {
+            &self.kind
+        }
+    }
+
+    // Draw the object at the given screen position
+    pub fn draw(
+        &self,
+        renderer: &mut dyn Renderer,
+        x: i32,
+        y: i32,
+        glyphs: &GlyphConfig,
+        tiles: Option<&TileMapping>,
+    ) {
+        let glyph = glyphs.object(self);
+        let glyph = tiles.map_or(glyph, |tiles| tiles.tile(glyph));
+        renderer.put_char(x, y, glyph, self.color, None);
     }
 
     pub fn pos(&self) -> (i32, i32) {
@@ -69,15 +240,35 @@ impl Object {
     }
 
     // Get damage
-    pub fn take_damage(&mut self, damage: i32, game: &mut Game) -> Option<i32> {
+    // Returns the xp for the kill, if it was one
+    pub fn take_damage(&mut self, damage: i32, by_player: bool, game: &mut Game) -> Option<i32> {
+        let is_player = self.race(game).is_some();
         if let Some(fighter) = self.fighter.as_mut() {
             if damage > 0 {
                 fighter.hp -= damage;
+                game.noises.push((self.x, self.y, COMBAT_NOISE));
+                emit(
+                    GameEvent::Damaged {
+                        name: self.name.clone(),
+                        damage,
+                        by_player,
+                        to_player: is_player,
+                    },
+                    game,
+                );
             }
         }
 
         if let Some(fighter) = self.fighter {
             if fighter.hp <= 0 {
+                emit(
+                    GameEvent::Died {
+                        name: self.name.clone(),
+                        by_player,
+                        player: is_player,
+                    },
+                    game,
+                );
                 self.alive = false;
                 fighter.on_death.callback(self, game);
                 return Some(fighter.xp);
@@ -87,22 +278,15 @@ impl Object {
     }
 
     pub fn attack(&mut self, target: &mut Object, game: &mut Game) {
-        let damage = self.power(game) - target.defense(game);
+        resolve_attack(self, target, game);
+    }
 
-        if damage > 0 {
-            game.messages.add(
-                format!("{} gets {} damage from {}", target.name, damage, self.name),
-                RED,
-            );
-            if let Some(xp) = target.take_damage(damage, game) {
-                // Reward killer with experience
-                self.fighter.as_mut().unwrap().xp += xp;
-            }
-        } else {
-            game.messages.add(
-                format!("{} failed to scratch {}", self.name, target.name),
-                RED,
-            );
+    // Asleep, or neither seeing the player nor tracking them down
+    pub fn unaware(&self) -> bool {
+        match self.ai {
+            Some(Ai::Sleeping { .. }) => true,
+            Some(_) => !self.seen_player && self.last_seen.is_none() && self.tracking == 0,
+            None => false,
         }
     }
 
@@ -124,7 +308,7 @@ impl Object {
             .map(|e| e.max_hp_bonus)
             .sum::<i32>();
 
-        base_max_hp + bonus
+        base_max_hp + bonus + self.race(game).map_or(0, |race| race.max_hp_bonus())
     }
 
     pub fn power(&self, game: &mut Game) -> i32 {
@@ -135,7 +319,39 @@ impl Object {
             .map(|e| e.power_bonus)
             .sum::<i32>();
 
-        base_power + bonus
+        base_power + bonus + self.race(game).map_or(0, |race| race.power_bonus())
+    }
+
+    pub fn fire_damage(&self, game: &mut Game) -> i32 {
+        self.get_all_equipped(game)
+            .iter()
+            .map(|e| e.fire_damage + e.brand.map_or(0, Brand::fire_damage))
+            .sum()
+    }
+
+    // Whether something equipped digs through walls
+    pub fn digs(&self, game: &mut Game) -> bool {
+        self.get_all_equipped(game).iter().any(|e| e.digging)
+    }
+
+    // Time the slowest weapon in hand takes to swing, the fists take a turn
+    pub fn attack_delay(&self, game: &mut Game) -> i32 {
+        self.get_all_equipped(game)
+            .iter()
+            .map(|e| e.attack_delay)
+            .max()
+            .filter(|&delay| delay > 0)
+            .unwrap_or(TURN)
+    }
+
+    // How many tiles away the blows land, the longest weapon in hand deciding
+    pub fn reach(&self, game: &Game) -> i32 {
+        self.get_all_equipped(game)
+            .iter()
+            .map(|e| e.reach)
+            .max()
+            .unwrap_or(0)
+            .max(1)
     }
 
     pub fn defense(&self, game: &mut Game) -> i32 {
@@ -146,37 +362,91 @@ impl Object {
             .map(|e| e.defense_bonus)
             .sum::<i32>();
 
-        base_defense + bonus
+        let perk_bonus = if self.perks.contains(&Perk::ThickSkin) {
+            THICK_SKIN_DEFENSE
+        } else {
+            0
+        };
+
+        base_defense + bonus + perk_bonus + self.race(game).map_or(0, |race| race.defense_bonus())
     }
 
-    pub fn get_all_equipped(&self, game: &mut Game) -> Vec<Equipment> {
-        if self.name == "player" {
-            game.inventory
-                .iter()
-                .filter(|item| item.equipment.map_or(false, |e| e.equipped))
-                .map(|item| item.equipment.unwrap())
-                .collect()
-        } else {
-            vec![]
+    // Chance in percent of dodging a blow, from the equipment and the race
+    pub fn evasion(&self, game: &mut Game) -> i32 {
+        let bonus = self
+            .get_all_equipped(game)
+            .iter()
+            .map(|e| e.evasion_bonus)
+            .sum::<i32>();
+        (bonus + self.race(game).map_or(0, |race| race.evasion_bonus())).clamp(0, MAX_EVASION)
+    }
+
+    // Chance in percent of stopping a blow with what's in hand
+    pub fn block_chance(&self, game: &mut Game) -> i32 {
+        let chance = self
+            .get_all_equipped(game)
+            .iter()
+            .map(|e| e.block_chance)
+            .sum::<i32>();
+        chance.clamp(0, MAX_BLOCK_CHANCE)
+    }
+
+    // Only the player has a race
+    pub fn race(&self, game: &Game) -> Option<Race> {
+        match self.fighter {
+            Some(Fighter {
+                on_death: DeathCallback::Player,
+                ..
+            }) => Some(game.character.race),
+            _ => None,
         }
     }
 
+    pub fn get_all_equipped(&self, game: &Game) -> Vec<Equipment> {
+        let inventory = if let Some(Fighter {
+            on_death: DeathCallback::Player,
+            ..
+        }) = self.fighter
+        {
+            &game.inventory
+        } else {
+            &self.inventory
+        };
+        inventory
+            .iter()
+            .filter(|item| item.equipment.is_some_and(|e| e.equipped))
+            .map(|item| item.equipment.unwrap())
+            .collect()
+    }
+
     pub fn equip(&mut self, messages: &mut Messages) {
         if self.item.is_none() {
-            messages.add(format!("Can't equip {:?} as it's not an item", self), RED);
+            messages.add(
+                tr(
+                    "object.cant_equip_not_item",
+                    &[("object", &format!("{:?}", self))],
+                ),
+                RED,
+            );
             return;
         }
         if let Some(ref mut equipment) = self.equipment {
             if !equipment.equipped {
                 equipment.equipped = true;
                 messages.add(
-                    format!("Equipped {} on {}", self.name, equipment.slot),
+                    tr(
+                        "object.equipped_on",
+                        &[("item", &self.name), ("slot", &equipment.slot)],
+                    ),
                     LIGHT_GREEN,
                 );
             }
         } else {
             messages.add(
-                format!("Can't equip {:?} as it's not an equipment", self),
+                tr(
+                    "object.cant_equip_not_equipment",
+                    &[("object", &format!("{:?}", self))],
+                ),
                 RED,
             );
         }
@@ -184,19 +454,31 @@ impl Object {
 
     pub fn dequip(&mut self, messages: &mut Messages) {
         if self.item.is_none() {
-            messages.add(format!("Can't dequip {:?} as it's not an item", self), RED);
+            messages.add(
+                tr(
+                    "object.cant_dequip_not_item",
+                    &[("object", &format!("{:?}", self))],
+                ),
+                RED,
+            );
         }
         if let Some(ref mut equipment) = self.equipment {
             if equipment.equipped {
                 equipment.equipped = false;
                 messages.add(
-                    format!("Dequipped {} from {}", self.name, equipment.slot),
+                    tr(
+                        "object.dequipped_from",
+                        &[("item", &self.name), ("slot", &equipment.slot)],
+                    ),
                     LIGHT_YELLOW,
                 );
             }
         } else {
             messages.add(
-                format!("Can't dequip {:?} as it's not an equipment", self),
+                tr(
+                    "object.cant_dequip_not_equipment",
+                    &[("object", &format!("{:?}", self))],
+                ),
                 RED,
             );
         }
@@ -207,17 +489,32 @@ impl Object {
     }
 }
 
-pub fn move_by(id: usize, dx: i32, dy: i32, map: &Map, objects: &mut [Object]) {
+// Handle of the object, given to it the first time it's asked for
+pub fn handle_of(id: usize, game: &mut Game, objects: &mut [Object]) -> u32 {
+    if objects[id].handle == 0 {
+        game.next_handle += 1;
+        objects[id].handle = game.next_handle;
+    }
+    objects[id].handle
+}
+
+// Index of the object with the handle, if it's still on the level
+pub fn find_handle(handle: u32, objects: &[Object]) -> Option<usize> {
+    objects.iter().position(|object| object.handle == handle)
+}
+
+pub fn move_by(id: usize, dx: i32, dy: i32, game: &Game, objects: &mut [Object]) {
     // Get position of object
     let (x, y) = objects[id].pos();
 
     // Chech if the tile is blocked and move the object accordingly
-    if !is_blocked(x + dx, y + dy, &map, objects) {
+    if !is_blocked(x + dx, y + dy, &game.map, objects) && !cuts_corner(x, y, dx, dy, game) {
         objects[id].set_pos(x + dx, y + dy);
+        objects[id].facing = (dx, dy);
     }
 }
 
-pub fn player_move_attack(dx: i32, dy: i32, game: &mut Game, objects: &mut [Object]) {
+pub fn player_move_attack(dx: i32, dy: i32, game: &mut Game, objects: &mut Vec<Object>) {
     // Coordinates of the player's direction
     let x = objects[PLAYER].x + dx;
     let y = objects[PLAYER].y + dy;
@@ -227,20 +524,112 @@ pub fn player_move_attack(dx: i32, dy: i32, game: &mut Game, objects: &mut [Obje
         .iter()
         .position(|object| object.fighter.is_some() && object.pos() == (x, y));
 
+    // The enemies can still be hit past the corners, but nobody squeezes between them
+    let hostile = target_id.is_some_and(|id| objects[id].faction != objects[PLAYER].faction);
+    let digging = diggable(x, y, game) && objects[PLAYER].digs(game);
+    if !hostile && !digging && cuts_corner(objects[PLAYER].x, objects[PLAYER].y, dx, dy, game) {
+        game.messages.add(
+            tr("object.you_cant_squeeze_between_the_corners", &[]),
+            LIGHT_GREY,
+        );
+        return;
+    }
+
     // Attack if there is a target, move otherwise
     match target_id {
+        // Make way for the player instead of getting hit
+        Some(id) if objects[id].faction == objects[PLAYER].faction => {
+            let previous = objects[PLAYER].pos();
+            if resolve_moves(&[(PLAYER, (x, y)), (id, previous)], game, objects) {
+                game.messages.add(
+                    tr(
+                        "object.you_swap_places_with",
+                        &[("object", &objects[id].name)],
+                    ),
+                    LIGHT_GREY,
+                );
+            }
+        }
         Some(id) => {
             // Attack the monster
             let (monster, player) = mut_two(id, PLAYER, objects);
             player.attack(monster, game);
+
+            if objects[PLAYER].perks.contains(&Perk::Cleave) {
+                // And everyone else standing next to the player
+                let (player_x, player_y) = objects[PLAYER].pos();
+                for other in 0..objects.len() {
+                    let (x, y) = objects[other].pos();
+                    let adjacent = (x - player_x).abs() <= 1 && (y - player_y).abs() <= 1;
+                    if other != PLAYER
+                        && other != id
+                        && adjacent
+                        && objects[other].fighter.is_some()
+                        && objects[other].faction != objects[PLAYER].faction
+                    {
+                        let (monster, player) = mut_two(other, PLAYER, objects);
+                        player.attack(monster, game);
+                    }
+                }
+            }
+        }
+        None if game.map[(x, y)].terrain == Terrain::Chasm => {
+            game.messages
+                .add(tr("object.you_leap_into_the_chasm", &[]), LIGHT_RED);
+            game.falling = true;
+        }
+        None if prop_at(x, y, objects).is_some_and(|id| objects[id].pushable.is_none()) => {
+            if let Some(id) = prop_at(x, y, objects) {
+                smash(id, game, objects);
+            }
+        }
+        // Dig into the rock with the pick
+        None if diggable(x, y, game) && objects[PLAYER].digs(game) => {
+            set_tile(x, y, Tile::empty(), game);
+            game.messages
+                .add(tr("object.you_dig_through_the_rock", &[]), LIGHT_GREY);
+            game.noises.push((x, y, DIG_NOISE));
         }
         None => {
-            move_by(PLAYER, dx, dy, &game.map, objects);
+            // Shove whatever heavy thing is in the way first, and smash it if it won't move
+            if let Some(id) = pushable_at(x, y, objects) {
+                if !push(id, dx, dy, game, objects) {
+                    if objects[id].prop.is_some() {
+                        smash(id, game, objects);
+                    }
+                    return;
+                }
+            }
+            // The dragged object follows into the tile the player left
+            let previous = objects[PLAYER].pos();
+            let mut moves = vec![(PLAYER, (x, y))];
+            let mut dragged = game
+                .dragging
+                .and_then(|handle| find_handle(handle, objects));
+            // Whatever got left behind out of arm's reach is let go of
+            if let Some(id) = dragged.filter(|&id| objects[id].distance_to(&objects[PLAYER]) >= 2.0)
+            {
+                game.dragging = None;
+                dragged = None;
+                game.messages.add(
+                    tr("action.you_let_go_of", &[("object", &objects[id].name)]),
+                    LIGHT_GREY,
+                );
+            }
+            moves.extend(dragged.map(|id| (id, previous)));
+            resolve_moves(&moves, game, objects);
+            let loudness = if game.sneaking {
+                SNEAK_NOISE
+            } else {
+                WALK_NOISE
+            };
+            game.noises
+                .push((objects[PLAYER].x, objects[PLAYER].y, loudness));
         }
     }
 }
 
-pub fn move_towards(id: usize, target_x: i32, target_y: i32, map: &Map, objects: &mut [Object]) {
+pub fn move_towards(id: usize, target_x: i32, target_y: i32, game: &Game, objects: &mut [Object]) {
     // vector from current object to the target
     let dx = target_x - objects[id].x;
     let dy = target_y - objects[id].y;
@@ -251,10 +640,10 @@ pub fn move_towards(id: usize, target_x: i32, target_y: i32, map: &Map, objects:
     // round and convert to integer
     let dx = (dx as f64 / distance).round() as i32;
     let dy = (dy as f64 / distance).round() as i32;
-    move_by(id, dx, dy, map, objects);
+    move_by(id, dx, dy, game, objects);
 }
 
-pub fn closest_monster(tcod: &Tcod, objects: &[Object], range: i32) -> Option<usize> {
+pub fn closest_monster(ctx: &Context, objects: &[Object], range: i32) -> Option<usize> {
     let mut closest_enemy = None;
     let mut closest_dist = (range + 1) as f32;
 
@@ -262,7 +651,8 @@ pub fn closest_monster(tcod: &Tcod, objects: &[Object], range: i32) -> Option<us
         if (id != PLAYER)
             && object.fighter.is_some()
             && object.ai.is_some()
-            && tcod.fov.is_in_fov(object.x, object.y)
+            && hostile(object.faction, objects[PLAYER].faction)
+            && ctx.fov.is_in_fov(object.x, object.y)
         {
             let dist = objects[PLAYER].distance_to(&objects[id]);
             if dist < closest_dist {
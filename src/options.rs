@@ -0,0 +1,141 @@
+use crate::audio::MAX_VOLUME;
+use crate::config::save_options;
+use crate::errors::log_error;
+use crate::fonts::{fonts, DEFAULT_FONT, FONT_SCALES};
+use crate::lang::{languages, set_language, tr, DEFAULT_LANGUAGE};
+use crate::render::{menu, Context};
+
+// Frame rates the player can choose between
+const FRAME_RATES: [i32; 3] = [30, 60, 120];
+const OPTIONS_WIDTH: i32 = 36;
+
+// Steps of the volume settings on the way up before wrapping round to silence
+const VOLUME_STEP: u8 = 2;
+
+fn louder(volume: &mut u8) {
+    *volume = if *volume >= MAX_VOLUME {
+        0
+    } else {
+        (*volume + VOLUME_STEP).min(MAX_VOLUME)
+    };
+}
+
+fn percent(volume: u8) -> u32 {
+    u32::from(volume) * 100 / u32::from(MAX_VOLUME)
+}
+
+fn on_off(value: bool) -> String {
+    if value {
+        tr("options.on", &[])
+    } else {
+        tr("options.off", &[])
+    }
+}
+
+// The language after the current one, among the ones having a catalog
+fn next_language(current: &str) -> String {
+    let languages = languages();
+    let next = languages
+        .iter()
+        .position(|language| language == current)
+        .map_or(0, |index| (index + 1) % languages.len());
+    languages
+        .get(next)
+        .cloned()
+        .unwrap_or_else(|| DEFAULT_LANGUAGE.into())
+}
+
+// The font after the current one, among the bundled ones and the player's own
+fn next_font(current: &str) -> String {
+    let fonts = fonts();
+    let next = fonts
+        .iter()
+        .position(|font| font == current)
+        .map_or(0, |index| (index + 1) % fonts.len());
+    fonts
+        .get(next)
+        .cloned()
+        .unwrap_or_else(|| DEFAULT_FONT.into())
+}
+
+// Let the player change the settings, applying them right away and keeping them
+// in the config file once the screen is closed
+pub fn options_menu(ctx: &mut Context) {
+    // Alt+Enter might have changed it since
+    ctx.options.fullscreen = ctx.renderer.is_fullscreen();
+    loop {
+        let options = &ctx.options;
+        let volume = |id, volume| tr(id, &[("volume", &percent(volume))]);
+        let lines = [
+            tr(
+                "options.fullscreen",
+                &[("value", &on_off(options.fullscreen))],
+            ),
+            tr("options.frame_rate", &[("fps", &options.fps)]),
+            tr("options.messages", &[("value", &options.verbosity.name())]),
+            tr(
+                "options.auto_pickup",
+                &[("value", &on_off(options.auto_pickup))],
+            ),
+            tr("options.confirm", &[("value", &on_off(options.confirm))]),
+            tr("options.language", &[("language", &options.language)]),
+            tr("options.colors", &[("scheme", &options.colors.name())]),
+            tr("options.font", &[("font", &options.font)]),
+            tr("options.font_scale", &[("scale", &options.font_scale)]),
+            tr("options.threats", &[("value", &on_off(options.threats))]),
+            volume("options.volume", options.volume.master),
+            volume("options.combat", options.volume.combat),
+            volume("options.items", options.volume.items),
+            volume("options.magic", options.volume.magic),
+            volume("options.character", options.volume.character),
+            volume("options.music", options.volume.music),
+        ];
+        match menu(&tr("options.header", &[]), &lines, OPTIONS_WIDTH, ctx) {
+            Some(0) => {
+                ctx.options.fullscreen = !ctx.options.fullscreen;
+                ctx.renderer.set_fullscreen(ctx.options.fullscreen);
+            }
+            Some(1) => {
+                let next = FRAME_RATES
+                    .iter()
+                    .position(|&fps| fps == ctx.options.fps)
+                    .map_or(0, |index| (index + 1) % FRAME_RATES.len());
+                ctx.options.fps = FRAME_RATES[next];
+                ctx.renderer.set_fps(ctx.options.fps);
+            }
+            Some(2) => ctx.options.verbosity = ctx.options.verbosity.next(),
+            Some(3) => ctx.options.auto_pickup = !ctx.options.auto_pickup,
+            Some(4) => ctx.options.confirm = !ctx.options.confirm,
+            Some(5) => {
+                ctx.options.language = next_language(&ctx.options.language);
+                set_language(&ctx.options.language);
+            }
+            Some(6) => ctx.options.colors = ctx.options.colors.next(),
+            Some(7) => {
+                ctx.options.font = next_font(&ctx.options.font);
+                ctx.renderer
+                    .set_font(&ctx.options.font, ctx.options.font_scale);
+            }
+            Some(8) => {
+                let next = FONT_SCALES
+                    .iter()
+                    .position(|&scale| scale == ctx.options.font_scale)
+                    .map_or(0, |index| (index + 1) % FONT_SCALES.len());
+                ctx.options.font_scale = FONT_SCALES[next];
+                ctx.renderer
+                    .set_font(&ctx.options.font, ctx.options.font_scale);
+            }
+            Some(9) => ctx.options.threats = !ctx.options.threats,
+            Some(10) => louder(&mut ctx.options.volume.master),
+            Some(11) => louder(&mut ctx.options.volume.combat),
+            Some(12) => louder(&mut ctx.options.volume.items),
+            Some(13) => louder(&mut ctx.options.volume.magic),
+            Some(14) => louder(&mut ctx.options.volume.character),
+            Some(15) => louder(&mut ctx.options.volume.music),
+            _ => break,
+        }
+    }
+    if let Err(e) = save_options(&ctx.options) {
+        log_error(&format!("Failed to save the options: {}", e));
+    }
+}
@@ -0,0 +1,203 @@
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::colors::*;
+
+use crate::daynight::is_night;
+use crate::faction::hostile;
+use crate::game::{Game, Map, Tile, MAP_HEIGHT, MAP_WIDTH, PLAYER};
+use crate::grid::Grid;
+use crate::lang::tr;
+use crate::object::Object;
+use crate::rng::GameRng;
+use crate::room::{create_h_tunnel, create_v_tunnel, make_monster};
+use crate::spawn::{free_tile_near, spawn};
+use crate::theme::Theme;
+
+// Turns a single step takes travelling the surface
+pub const TRAVEL_TURNS: i32 = 5;
+// Regions of the surface besides the ones with the dungeon entrances
+const EXTRA_REGIONS: usize = 9;
+// How far from the edges of the map the regions are centered
+const REGION_MARGIN: i32 = 5;
+// Chance in percent of being ambushed every step of the travel, and by how many at most
+const AMBUSH_CHANCE: i32 = 6;
+const MAX_AMBUSHERS: i32 = 3;
+
+// Land covering the surface
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+pub enum Region {
+    #[default]
+    Plains,
+    Forest,
+    Mountains,
+    Ruins,
+}
+
+impl Region {
+    const ALL: [Region; 4] = [
+        Region::Plains,
+        Region::Forest,
+        Region::Mountains,
+        Region::Ruins,
+    ];
+
+    // Tile of the region, the forests grow thick and the mountains are mostly rock
+    fn tile(self, rng: &mut GameRng) -> Tile {
+        let roll = rng.gen_range(0, 100);
+        match self {
+            Region::Plains if roll < 5 => Tile::foliage(),
+            Region::Forest if roll < 35 => Tile::foliage(),
+            Region::Mountains if roll < 60 => Tile::wall(),
+            Region::Mountains if roll < 70 => Tile::rubble(),
+            Region::Ruins if roll < 20 => Tile::wall(),
+            Region::Ruins if roll < 45 => Tile::rubble(),
+            _ => Tile::empty(),
+        }
+    }
+
+    // Kind of monster lying in wait for travellers, the wolves take over the plains at night
+    fn ambusher(self, night: bool) -> &'static str {
+        match self {
+            Region::Plains if night => "wolf",
+            Region::Plains => "goblin",
+            Region::Forest => "wolf",
+            Region::Mountains => "orc",
+            Region::Ruins => "skeleton",
+        }
+    }
+}
+
+// Separate dungeons below the surface, each going through the themes in its own order
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+pub enum Branch {
+    #[default]
+    Crypt,
+    Caverns,
+    Mine,
+}
+
+impl Branch {
+    const ALL: [Branch; 3] = [Branch::Crypt, Branch::Caverns, Branch::Mine];
+
+    pub fn name(self) -> String {
+        match self {
+            Branch::Crypt => tr("overworld.crypt", &[]),
+            Branch::Caverns => tr("overworld.caverns", &[]),
+            Branch::Mine => tr("overworld.mine", &[]),
+        }
+    }
+
+    // Theme of the first levels of the branch
+    pub fn first_theme(self) -> Theme {
+        match self {
+            Branch::Crypt => Theme::Catacombs,
+            Branch::Caverns => Theme::FungalCaverns,
+            Branch::Mine => Theme::Forge,
+        }
+    }
+
+    // Where on the surface the entrance lies
+    fn region(self) -> Region {
+        match self {
+            Branch::Crypt => Region::Ruins,
+            Branch::Caverns => Region::Forest,
+            Branch::Mine => Region::Mountains,
+        }
+    }
+}
+
+fn make_entrance(branch: Branch, x: i32, y: i32) -> Object {
+    let name = tr("overworld.entrance_to_the", &[("branch", &branch.name())]);
+    let mut entrance = Object::new(x, y, '>', &name, WHITE, false);
+    entrance.entrance = Some(branch);
+    entrance.always_visible = true;
+    entrance
+}
+
+// Lay out the regions of the surface around the player in the middle,
+// with trails leading to the entrance of every branch
+pub fn make_overworld(objects: &mut Vec<Object>, rng: &mut GameRng) -> (Map, Grid<Region>) {
+    let random_spot = |rng: &mut GameRng| {
+        (
+            rng.gen_range(REGION_MARGIN, MAP_WIDTH - REGION_MARGIN),
+            rng.gen_range(REGION_MARGIN, MAP_HEIGHT - REGION_MARGIN),
+        )
+    };
+    // The first centers are where the entrances go
+    let mut centers = Branch::ALL
+        .iter()
+        .map(|branch| (random_spot(rng), branch.region()))
+        .collect::<Vec<_>>();
+    for _ in 0..EXTRA_REGIONS {
+        let region = Region::ALL[rng.gen_range(0, Region::ALL.len())];
+        centers.push((random_spot(rng), region));
+    }
+
+    // Every tile belongs to the closest center
+    let mut regions = Grid::new(MAP_WIDTH, MAP_HEIGHT, Region::Plains);
+    let mut map = Grid::new(MAP_WIDTH, MAP_HEIGHT, Tile::empty());
+    for x in 0..MAP_WIDTH {
+        for y in 0..MAP_HEIGHT {
+            let region = centers
+                .iter()
+                .min_by_key(|((cx, cy), _)| (cx - x).pow(2) + (cy - y).pow(2))
+                .map_or(Region::Plains, |&(_, region)| region);
+            regions[(x, y)] = region;
+            let edge = x == 0 || y == 0 || x == MAP_WIDTH - 1 || y == MAP_HEIGHT - 1;
+            map[(x, y)] = if edge { Tile::wall() } else { region.tile(rng) };
+        }
+    }
+
+    let (start_x, start_y) = (MAP_WIDTH / 2, MAP_HEIGHT / 2);
+    objects[PLAYER].set_pos(start_x, start_y);
+    for (&branch, &((x, y), _)) in Branch::ALL.iter().zip(&centers) {
+        create_h_tunnel(start_x, x, start_y, &mut map);
+        create_v_tunnel(start_y, y, x, &mut map);
+        objects.push(make_entrance(branch, x, y));
+    }
+    (map, regions)
+}
+
+// Whether the player is on the surface with nobody to fight, covering ground quickly
+pub fn travelling(game: &Game, objects: &[Object]) -> bool {
+    game.dungeon_level == 0
+        && !objects
+            .iter()
+            .any(|object| object.alive && hostile(object.faction, objects[PLAYER].faction))
+}
+
+// Maybe let the monsters of the region the player travels through jump them
+pub fn roam(game: &mut Game, objects: &[Object]) {
+    if !travelling(game, objects) || game.rng.gen_range(0, 100) >= AMBUSH_CHANCE {
+        return;
+    }
+    let (x, y) = objects[PLAYER].pos();
+    let night = is_night(game);
+    let kind = game.regions[(x, y)].ambusher(night);
+    let mut ambushers = 0;
+    for _ in 0..game.rng.gen_range(1, MAX_AMBUSHERS + 1) {
+        let (ax, ay) = match free_tile_near(x, y, game, objects) {
+            Some(tile) => tile,
+            None => break,
+        };
+        if let Some(monster) = make_monster(kind, ax, ay) {
+            spawn(monster, game);
+            ambushers += 1;
+        }
+    }
+    match ambushers {
+        0 => {}
+        1 => game.messages.add(
+            tr("overworld.a_jumps_out_at_you", &[("kind", &kind)]),
+            LIGHT_RED,
+        ),
+        _ => game.messages.add(
+            tr(
+                "overworld.you_are_ambushed_by_of_them",
+                &[("ambushers", &ambushers)],
+            ),
+            LIGHT_RED,
+        ),
+    }
+}
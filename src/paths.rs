@@ -0,0 +1,80 @@
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+// Name of the directories the game keeps its files in
+const GAME_DIR: &str = "roguelike";
+// Names a directory holding the bundled resources, wherever they were installed
+const ASSETS_VAR: &str = "ROGUELIKE_ASSETS";
+
+// The game's directory under the platform one, made if it's missing,
+// or the working directory on platforms without one
+fn game_dir(base: Option<PathBuf>) -> PathBuf {
+    match base {
+        Some(base) => {
+            let dir = base.join(GAME_DIR);
+            // If it can't be made, opening the file fails with a clearer error
+            let _ = fs::create_dir_all(&dir);
+            dir
+        }
+        None => PathBuf::from("."),
+    }
+}
+
+// Where the saves, the replay, the morgue file and the bestiary are kept
+pub fn data_file(name: &str) -> PathBuf {
+    game_dir(dirs::data_dir()).join(name)
+}
+
+// Where the settings are kept
+pub fn config_file(name: &str) -> PathBuf {
+    game_dir(dirs::config_dir()).join(name)
+}
+
+// Places to look for the bundled resources, in order
+fn asset_dirs() -> Vec<PathBuf> {
+    let mut places = vec![];
+    if let Some(dir) = env::var_os(ASSETS_VAR) {
+        places.push(PathBuf::from(dir));
+    }
+    // Installed next to the executable
+    if let Some(dir) = env::current_exe()
+        .ok()
+        .and_then(|exe| exe.parent().map(Path::to_path_buf))
+    {
+        places.push(dir);
+    }
+    // Run from the working directory, or from anywhere out of the source tree
+    places.push(PathBuf::from("."));
+    places.push(PathBuf::from(env!("CARGO_MANIFEST_DIR")));
+    places
+}
+
+// A font, image or data file shipped with the game, from the first place having it
+pub fn asset(name: &str) -> PathBuf {
+    asset_dirs()
+        .into_iter()
+        .map(|dir| dir.join(name))
+        .find(|path| path.exists())
+        .unwrap_or_else(|| PathBuf::from(name))
+}
+
+// A directory of its own in the data directory, made if it's missing
+pub fn data_dir(name: &str) -> PathBuf {
+    let dir = data_file(name);
+    let _ = fs::create_dir_all(&dir);
+    dir
+}
+
+// A name made safe to be a file name, keeping it readable
+pub fn file_stem(name: &str) -> String {
+    name.chars()
+        .map(|c| {
+            if c.is_alphanumeric() || c == ' ' || c == '-' || c == '_' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
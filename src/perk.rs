@@ -0,0 +1,66 @@
+use serde::{Deserialize, Serialize};
+
+use crate::lang::tr;
+
+// Perks offered on every level up
+const PERKS_OFFERED: usize = 2;
+
+// Lasting gifts the player picks on leveling up instead of a stat
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum Perk {
+    // Attacks also hit the other foes next to the player
+    Cleave,
+    // Extra defense
+    ThickSkin,
+    // Killing heals
+    Bloodlust,
+    // Resting heals more
+    Regeneration,
+}
+
+impl Perk {
+    pub const ALL: [Perk; 4] = [
+        Perk::Cleave,
+        Perk::ThickSkin,
+        Perk::Bloodlust,
+        Perk::Regeneration,
+    ];
+
+    pub fn name(self) -> String {
+        match self {
+            Perk::Cleave => tr("perk.cleave", &[]),
+            Perk::ThickSkin => tr("perk.thick_skin", &[]),
+            Perk::Bloodlust => tr("perk.bloodlust", &[]),
+            Perk::Regeneration => tr("perk.regeneration", &[]),
+        }
+    }
+
+    pub fn description(self) -> String {
+        match self {
+            Perk::Cleave => tr("perk.cleave_description", &[]),
+            Perk::ThickSkin => tr("perk.thick_skin_description", &[]),
+            Perk::Bloodlust => tr("perk.bloodlust_description", &[]),
+            Perk::Regeneration => tr("perk.regeneration_description", &[]),
+        }
+    }
+}
+
+// Perks to choose from at the level, rotating through the ones not taken yet
+pub fn offered_perks(level: i32, taken: &[Perk]) -> Vec<Perk> {
+    let available = Perk::ALL
+        .iter()
+        .cloned()
+        .filter(|perk| !taken.contains(perk))
+        .collect::<Vec<_>>();
+    if available.is_empty() {
+        return available;
+    }
+    let start = level as usize % available.len();
+    available
+        .iter()
+        .cycle()
+        .skip(start)
+        .take(PERKS_OFFERED.min(available.len()))
+        .cloned()
+        .collect()
+}
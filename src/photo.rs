@@ -0,0 +1,111 @@
+use crate::backend::{Key, KeyCode};
+use crate::colors::*;
+use crate::game::{Game, MAP_HEIGHT, MAP_WIDTH, PLAYER};
+use crate::lang::tr;
+use crate::object::Object;
+use crate::render::{draw_map, Context, Layout, View};
+
+// How far the camera moves with shift held
+const FAST_PAN: i32 = 10;
+
+fn on_off(value: bool) -> String {
+    if value {
+        tr("options.on", &[])
+    } else {
+        tr("options.off", &[])
+    }
+}
+
+// Look around the whole level with the camera detached from the player,
+// the game doesn't advance until the mode is left
+pub fn photo_mode(ctx: &mut Context, game: &Game, objects: &[Object]) {
+    let layout = Layout::of(&*ctx.renderer);
+    let mut view = View {
+        fov_shading: false,
+        ..View::player(layout, objects[PLAYER].pos())
+    };
+
+    while !ctx.renderer.window_closed() {
+        ctx.renderer.clear();
+        // The window might have been resized, the camera stays where it is
+        let layout = Layout::of(&*ctx.renderer);
+        let fitted = View::player(layout, objects[PLAYER].pos());
+        view.width = fitted.width;
+        view.height = fitted.height;
+        draw_map(ctx, game, objects, &view);
+
+        let help = [
+            tr(
+                "photo.camera_at",
+                &[("x", &view.camera_x), ("y", &view.camera_y)],
+            ),
+            tr("photo.pan", &[]),
+            tr(
+                "photo.toggles",
+                &[
+                    ("fov", &on_off(view.fov_shading)),
+                    ("decals", &on_off(view.decals)),
+                    ("threats", &on_off(view.threats)),
+                ],
+            ),
+            tr("photo.back", &[]),
+        ];
+        for (index, line) in help.iter().enumerate() {
+            ctx.renderer
+                .print(1, layout.panel_y() + 1 + index as i32, line, LIGHT_GREY);
+        }
+        ctx.renderer.flush();
+
+        let key = ctx.input.wait_for_keypress();
+        let step = if key.shift { FAST_PAN } else { 1 };
+        match key {
+            Key {
+                code: KeyCode::Up, ..
+            } => view.camera_y -= step,
+            Key {
+                code: KeyCode::Down,
+                ..
+            } => view.camera_y += step,
+            Key {
+                code: KeyCode::Left,
+                ..
+            } => view.camera_x -= step,
+            Key {
+                code: KeyCode::Right,
+                ..
+            } => view.camera_x += step,
+            Key {
+                code: KeyCode::Home,
+                ..
+            } => {
+                let home = View::player(Layout::of(&*ctx.renderer), objects[PLAYER].pos());
+                view.camera_x = home.camera_x;
+                view.camera_y = home.camera_y;
+            }
+            Key {
+                code: KeyCode::Text,
+                printable: 'f',
+                ..
+            } => view.fov_shading = !view.fov_shading,
+            Key {
+                code: KeyCode::Text,
+                printable: 'd',
+                ..
+            } => view.decals = !view.decals,
+            Key {
+                code: KeyCode::Text,
+                printable: 't',
+                ..
+            } => view.threats = !view.threats,
+            Key {
+                code: KeyCode::Escape,
+                ..
+            } => break,
+            _ => {}
+        }
+
+        // Keep at least part of the map on the screen
+        view.camera_x = view.camera_x.clamp(1 - MAP_WIDTH, MAP_WIDTH - 1);
+        view.camera_y = view.camera_y.clamp(1 - MAP_HEIGHT, MAP_HEIGHT - 1);
+    }
+}
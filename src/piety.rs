@@ -0,0 +1,237 @@
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::colors::*;
+
+use crate::affix::{enchant, Rarity};
+use crate::game::{remove_object, Game, PLAYER};
+use crate::item::{add_to_inventory, Item};
+use crate::lang::tr;
+use crate::object::Object;
+use crate::render::{inventory_menu, menu, Context};
+use crate::rng::GameRng;
+use crate::room::{make_item, random_item};
+use crate::status::{apply_effect, Effect, Status};
+
+// Piety an offering is worth before the deity's taste, more for the special items
+const CORPSE_PIETY: i32 = 4;
+const ITEM_PIETY: i32 = 2;
+const MAGIC_PIETY: i32 = 4;
+const RARE_PIETY: i32 = 8;
+// Piety a boon takes, and the chance in percent of getting one once there is enough
+const BOON_PIETY: i32 = 10;
+const BOON_CHANCE: i32 = 40;
+// Chance in percent of the angry deity punishing the player, and what the punishment does
+const PUNISHMENT_CHANCE: i32 = 50;
+const SMITE_DAMAGE: i32 = 10;
+const CURSE_TURNS: u32 = 10;
+const OFFERING_WIDTH: i32 = 40;
+// Levels deeper the gifts are rolled for
+const GIFT_LEVELS: u32 = 2;
+
+// What a deity makes of an offering
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Taste {
+    Corpses,
+    Items,
+}
+
+pub struct Deity {
+    pub name: &'static str,
+    pub color: Color,
+    // Offerings worth twice as much, and the ones that anger the deity
+    pub likes: Taste,
+    pub hates: Option<Taste>,
+}
+
+pub const DEITIES: &[Deity] = &[
+    Deity {
+        name: "Ulwar the Bloody",
+        color: LIGHT_RED,
+        likes: Taste::Corpses,
+        hates: None,
+    },
+    Deity {
+        name: "Sella the Gilded",
+        color: GOLD,
+        likes: Taste::Items,
+        hates: Some(Taste::Corpses),
+    },
+    Deity {
+        name: "Morth the Rotting",
+        color: DARK_SEA,
+        likes: Taste::Corpses,
+        hates: Some(Taste::Items),
+    },
+];
+
+// What the player puts on the altar
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum Offering {
+    // Id of the corpse next to the altar
+    Corpse(usize),
+    // Index of the inventory item
+    Item(usize),
+}
+
+// An altar of a random deity
+pub fn make_altar(x: i32, y: i32, rng: &mut GameRng) -> Object {
+    let deity = rng.gen_range(0, DEITIES.len());
+    let name = tr("piety.altar_of", &[("deity", &DEITIES[deity].name)]);
+    let mut altar = Object::new(x, y, '_', &name, DEITIES[deity].color, false);
+    altar.altar = Some(deity);
+    altar.always_visible = true;
+    altar
+}
+
+// Deity of the altar the player stands on
+pub fn altar_under_player(objects: &[Object]) -> Option<usize> {
+    objects
+        .iter()
+        .find(|object| object.pos() == objects[PLAYER].pos() && object.altar.is_some())
+        .and_then(|altar| altar.altar)
+}
+
+// Corpse lying on the altar the player stands on, or next to it
+pub fn corpse_by_altar(objects: &[Object]) -> Option<usize> {
+    objects
+        .iter()
+        .position(|object| object.rot.is_some() && object.distance_to(&objects[PLAYER]) < 2.0)
+}
+
+// Ask what to offer on the altar the player stands on, the corpse by it or an item
+pub fn offering_menu(ctx: &mut Context, game: &mut Game, objects: &[Object]) -> Option<Offering> {
+    let deity = match altar_under_player(objects) {
+        Some(deity) => &DEITIES[deity],
+        None => {
+            game.messages
+                .add(tr("piety.there_is_no_altar_here", &[]), LIGHT_GREY);
+            return None;
+        }
+    };
+    let corpse = corpse_by_altar(objects);
+    let mut options = vec![tr("piety.an_item_from_your_pack", &[])];
+    if let Some(corpse) = corpse {
+        options.push(tr("piety.the_corpse", &[("corpse", &objects[corpse].name)]));
+    }
+    let header = tr("piety.what_do_you_offer", &[("deity", &deity.name)]);
+    match menu(&header, &options, OFFERING_WIDTH, ctx)? {
+        0 => {
+            let header = tr("piety.offer_to", &[("deity", &deity.name)]);
+            inventory_menu(&game.inventory, &header, ctx).map(Offering::Item)
+        }
+        _ => corpse.map(Offering::Corpse),
+    }
+}
+
+// Give the offering to the deity of the altar, which may answer it
+pub fn sacrifice(offering: Offering, game: &mut Game, objects: &mut Vec<Object>) {
+    let deity = match altar_under_player(objects) {
+        Some(deity) => &DEITIES[deity],
+        None => return,
+    };
+    let (name, taste, worth) = match offering {
+        Offering::Corpse(id) if objects.get(id).is_some_and(|o| o.rot.is_some()) => {
+            let corpse = remove_object(id, game, objects);
+            (corpse.name, Taste::Corpses, CORPSE_PIETY)
+        }
+        Offering::Item(index) if index < game.inventory.len() => {
+            let item = game.inventory.remove(index);
+            let worth = match item.rarity {
+                Rarity::Common => ITEM_PIETY,
+                Rarity::Magic => MAGIC_PIETY,
+                Rarity::Rare => RARE_PIETY,
+            };
+            (item.name, Taste::Items, worth)
+        }
+        _ => return,
+    };
+    let worth = if deity.likes == taste {
+        worth * 2
+    } else if deity.hates == Some(taste) {
+        -worth
+    } else {
+        worth
+    };
+
+    let piety = game.piety.entry(deity.name.into()).or_insert(0);
+    *piety += worth;
+    let piety = *piety;
+    if worth > 0 {
+        game.messages.add(
+            tr(
+                "piety.accepts_the",
+                &[("deity", &deity.name), ("name", &name)],
+            ),
+            deity.color,
+        );
+    } else {
+        game.messages.add(
+            tr(
+                "piety.is_offended_by_the",
+                &[("deity", &deity.name), ("name", &name)],
+            ),
+            LIGHT_RED,
+        );
+    }
+
+    if piety >= BOON_PIETY && game.rng.gen_range(0, 100) < BOON_CHANCE {
+        *game.piety.get_mut(deity.name).unwrap() -= BOON_PIETY;
+        grant_boon(deity, game, objects);
+    } else if piety < 0 && game.rng.gen_range(0, 100) < PUNISHMENT_CHANCE {
+        punish(deity, game, objects);
+    }
+}
+
+fn grant_boon(deity: &Deity, game: &mut Game, objects: &mut [Object]) {
+    if game.rng.gen_range(0, 2) == 0 {
+        game.messages.add(
+            tr("piety.mends_your_wounds", &[("deity", &deity.name)]),
+            LIGHT_VIOLET,
+        );
+        let max_hp = objects[PLAYER].max_hp(game);
+        objects[PLAYER].heal(max_hp, game);
+    } else {
+        let level = game.dungeon_level + GIFT_LEVELS;
+        // Anything but coins
+        let item = loop {
+            let item = random_item(level, &mut game.rng);
+            if item != Item::Gold {
+                break item;
+            }
+        };
+        let mut gift = make_item(item, 0, 0);
+        enchant(&mut gift, level, &mut game.rng);
+        game.messages.add(
+            tr(
+                "piety.grants_you_a",
+                &[("deity", &deity.name), ("gift", &gift.name)],
+            ),
+            deity.color,
+        );
+        add_to_inventory(gift, game);
+    }
+}
+
+fn punish(deity: &Deity, game: &mut Game, objects: &mut [Object]) {
+    if game.rng.gen_range(0, 2) == 0 {
+        game.messages.add(
+            tr("piety.smites_you_with_lightning", &[("deity", &deity.name)]),
+            LIGHT_RED,
+        );
+        objects[PLAYER].take_damage(SMITE_DAMAGE, false, game);
+    } else {
+        game.messages.add(
+            tr(
+                "piety.curses_you_with_sluggishness",
+                &[("deity", &deity.name)],
+            ),
+            LIGHT_RED,
+        );
+        let curse = Effect {
+            status: Status::Slow,
+            turns: CURSE_TURNS,
+        };
+        apply_effect(&mut objects[PLAYER], curse, game);
+    }
+}
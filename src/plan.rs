@@ -0,0 +1,130 @@
+use crate::colors::*;
+
+use crate::action::Action;
+use crate::backend::KeyCode;
+use crate::faction::hostile;
+use crate::game::{cuts_corner, is_blocked, Game, PLAYER};
+use crate::lang::tr;
+use crate::object::Object;
+use crate::render::Context;
+
+// Actions spanning many turns, taken a step at a time until something interesting
+// happens or the player presses a key
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Plan {
+    // Keep moving in the direction, remembering how many tiles were open around
+    // the last step and how long the message log was, to notice the corridors
+    // branching off and anything worth telling
+    Run {
+        dx: i32,
+        dy: i32,
+        open: usize,
+        messages: usize,
+    },
+    // Pick up the item the player walked onto
+    PickUp,
+}
+
+// How many of the tiles around can be walked on
+fn open_around(x: i32, y: i32, game: &Game) -> usize {
+    (-1..=1)
+        .flat_map(|dx| (-1..=1).map(move |dy| (x + dx, y + dy)))
+        .filter(|&(nx, ny)| (nx, ny) != (x, y))
+        .filter(|&(nx, ny)| game.map.get(nx, ny).is_some_and(|tile| !tile.blocked))
+        .count()
+}
+
+pub fn enemy_in_sight(ctx: &Context, objects: &[Object]) -> bool {
+    objects.iter().any(|object| {
+        object.alive
+            && hostile(object.faction, objects[PLAYER].faction)
+            && ctx.fov.is_in_fov(object.x, object.y)
+    })
+}
+
+// Whether the step that way leads somewhere the player can walk without a fight
+fn can_step(dx: i32, dy: i32, game: &Game, objects: &[Object]) -> bool {
+    let (x, y) = objects[PLAYER].pos();
+    let (nx, ny) = (x + dx, y + dy);
+    game.map.in_bounds(nx, ny)
+        && !is_blocked(nx, ny, &game.map, objects)
+        && !cuts_corner(x, y, dx, dy, game)
+}
+
+// Take the first step of the run, unless there's a reason not to go anywhere
+pub fn start_run(
+    dx: i32,
+    dy: i32,
+    ctx: &Context,
+    game: &mut Game,
+    objects: &[Object],
+) -> Option<Action> {
+    if enemy_in_sight(ctx, objects) {
+        game.messages
+            .add(tr("plan.you_cant_run_with_enemies_in", &[]), LIGHT_GREY);
+        return None;
+    }
+    if !can_step(dx, dy, game, objects) {
+        return None;
+    }
+    // Judged from the tile the first step leads to
+    let (x, y) = objects[PLAYER].pos();
+    game.plan = Some(Plan::Run {
+        dx,
+        dy,
+        open: open_around(x + dx, y + dy, game),
+        messages: game.messages.added,
+    });
+    Some(Action::Move(dx, dy))
+}
+
+// Next action of the plan the player follows, dropping the plan when a key was pressed
+// or it's time to stop
+pub fn follow_plan(ctx: &Context, game: &mut Game, objects: &[Object]) -> Option<Action> {
+    let plan = game.plan.take()?;
+    if ctx.key.code != KeyCode::NoKey || !objects[PLAYER].alive {
+        return None;
+    }
+    match plan {
+        Plan::Run {
+            dx,
+            dy,
+            open,
+            messages,
+        } => {
+            let (x, y) = objects[PLAYER].pos();
+            let here = objects
+                .iter()
+                .enumerate()
+                .any(|(id, object)| id != PLAYER && object.pos() == (x, y));
+            let now_open = open_around(x, y, game);
+            if here
+                || now_open != open
+                || game.messages.added != messages
+                || enemy_in_sight(ctx, objects)
+                || !can_step(dx, dy, game, objects)
+            {
+                return None;
+            }
+            game.plan = Some(Plan::Run {
+                dx,
+                dy,
+                open: now_open,
+                messages,
+            });
+            Some(Action::Move(dx, dy))
+        }
+        Plan::PickUp => Some(Action::PickUp),
+    }
+}
+
+// Walking onto an item picks it up with the next action, if the player wants that
+pub fn plan_pickup(ctx: &Context, game: &mut Game, objects: &[Object]) {
+    let (x, y) = objects[PLAYER].pos();
+    let item_here = objects
+        .iter()
+        .any(|object| object.item.is_some() && object.pos() == (x, y));
+    if ctx.options.auto_pickup && objects[PLAYER].alive && item_here {
+        game.plan = Some(Plan::PickUp);
+    }
+}
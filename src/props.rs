@@ -0,0 +1,129 @@
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::colors::*;
+
+use crate::fields::{add_field, FieldKind};
+use crate::game::{Game, PLAYER};
+use crate::item::Item;
+use crate::lang::tr;
+use crate::object::Object;
+use crate::push::make_barrel;
+use crate::rng::GameRng;
+use crate::room::{make_item, random_item};
+use crate::spawn::spawn;
+
+// How thick the gas let out of a broken prop is
+const PROP_GAS: u32 = 6;
+
+// Things in the rooms that break when hit, maybe with something inside
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum PropKind {
+    Barrel,
+    Crate,
+    Urn,
+}
+
+impl PropKind {
+    // Damage it takes to break it
+    fn toughness(self) -> i32 {
+        match self {
+            PropKind::Barrel => 10,
+            PropKind::Crate => 6,
+            PropKind::Urn => 1,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Prop {
+    pub kind: PropKind,
+    pub hp: i32,
+}
+
+impl Prop {
+    pub fn new(kind: PropKind) -> Self {
+        Prop {
+            kind,
+            hp: kind.toughness(),
+        }
+    }
+}
+
+pub fn make_crate(x: i32, y: i32) -> Object {
+    let mut object = Object::new(x, y, '=', "crate", DARK_AMBER, true);
+    object.prop = Some(Prop::new(PropKind::Crate));
+    object.always_visible = true;
+    object
+}
+
+pub fn make_urn(x: i32, y: i32) -> Object {
+    let mut object = Object::new(x, y, 'u', "urn", DARK_SEPIA, true);
+    object.prop = Some(Prop::new(PropKind::Urn));
+    object.always_visible = true;
+    object
+}
+
+pub fn make_prop(rng: &mut GameRng, x: i32, y: i32) -> Object {
+    match rng.gen_range(0, 3) {
+        0 => make_barrel(x, y),
+        1 => make_crate(x, y),
+        _ => make_urn(x, y),
+    }
+}
+
+pub fn prop_at(x: i32, y: i32, objects: &[Object]) -> Option<usize> {
+    objects
+        .iter()
+        .position(|object| object.prop.is_some() && object.pos() == (x, y))
+}
+
+// Hit the prop as hard as the player can, breaking it once it took enough
+pub fn smash(id: usize, game: &mut Game, objects: &mut [Object]) {
+    let damage = objects[PLAYER].power(game).max(1);
+    let prop = match objects[id].prop.as_mut() {
+        Some(prop) => prop,
+        None => return,
+    };
+    prop.hp -= damage;
+    if prop.hp > 0 {
+        game.messages.add(
+            tr("props.you_hit_the", &[("object", &objects[id].name)]),
+            LIGHT_GREY,
+        );
+        return;
+    }
+    break_prop(&mut objects[id], game);
+}
+
+// Leave the pieces of the prop behind, along with whatever was inside
+fn break_prop(object: &mut Object, game: &mut Game) {
+    game.messages.add(
+        tr("props.the_breaks_apart", &[("object", &object.name)]),
+        LIGHT_GREY,
+    );
+    let (x, y) = object.pos();
+    match game.rng.gen_range(0, 100) {
+        roll if roll < 25 => spawn(make_item(Item::Gold, x, y), game),
+        roll if roll < 45 => {
+            let item = random_item(game.dungeon_level, &mut game.rng);
+            let item = make_item(item, x, y);
+            game.messages.add(
+                tr("props.something_falls_out", &[("item", &item.name)]),
+                LIGHT_GREY,
+            );
+            spawn(item, game);
+        }
+        roll if roll < 55 => {
+            game.messages
+                .add(tr("props.a_cloud_of_gas_bursts_out", &[]), LIGHT_RED);
+            add_field(x, y, FieldKind::Gas, PROP_GAS, game);
+        }
+        _ => {}
+    }
+    object.name = tr("props.broken", &[("prop", &object.name)]);
+    object.char = ',';
+    object.blocks = false;
+    object.prop = None;
+    object.pushable = None;
+}
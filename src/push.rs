@@ -0,0 +1,116 @@
+use serde::{Deserialize, Serialize};
+
+use crate::colors::*;
+
+use crate::game::{cuts_corner, remove_object, set_tile, Game, Terrain, Tile, PLAYER};
+use crate::lang::tr;
+use crate::object::Object;
+use crate::props::{Prop, PropKind};
+
+// Damage of a boulder rolled onto someone
+const CRUSH_DAMAGE: i32 = 20;
+
+// Heavy things the player can shove out of the way
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum Pushable {
+    // Crushes whoever it is pushed onto and fills the chasms
+    Boulder,
+    // Stops at anyone in the way and falls down the chasms
+    Barrel,
+}
+
+pub fn make_boulder(x: i32, y: i32) -> Object {
+    let mut boulder = Object::new(x, y, 'O', "boulder", GREY, true);
+    boulder.pushable = Some(Pushable::Boulder);
+    boulder.always_visible = true;
+    boulder
+}
+
+pub fn make_barrel(x: i32, y: i32) -> Object {
+    let mut barrel = Object::new(x, y, '0', "barrel", DARKER_ORANGE, true);
+    barrel.pushable = Some(Pushable::Barrel);
+    barrel.prop = Some(Prop::new(PropKind::Barrel));
+    barrel.always_visible = true;
+    barrel
+}
+
+pub fn pushable_at(x: i32, y: i32, objects: &[Object]) -> Option<usize> {
+    objects
+        .iter()
+        .position(|object| object.pushable.is_some() && object.pos() == (x, y))
+}
+
+// Move everyone at once, so that those trading places or following each other
+// don't stand in each other's way, returns false and moves nobody when someone
+// would end up in a wall or on something else that blocks
+pub fn resolve_moves(moves: &[(usize, (i32, i32))], game: &Game, objects: &mut [Object]) -> bool {
+    let moving = |id: usize| moves.iter().any(|&(mover, _)| mover == id);
+    let free = moves.iter().all(|&(_, (x, y))| {
+        !game.map[(x, y)].blocked
+            && !objects
+                .iter()
+                .enumerate()
+                .any(|(id, object)| object.blocks && object.pos() == (x, y) && !moving(id))
+    });
+    if !free {
+        return false;
+    }
+    for &(id, (x, y)) in moves {
+        let (old_x, old_y) = objects[id].pos();
+        objects[id].set_pos(x, y);
+        objects[id].facing = (x - old_x, y - old_y);
+    }
+    true
+}
+
+// Shove the object a step further, crushing whoever stands there or dropping it
+// into the chasm, returns whether the tile it stood on is free now
+pub fn push(id: usize, dx: i32, dy: i32, game: &mut Game, objects: &mut Vec<Object>) -> bool {
+    let (x, y) = objects[id].pos();
+    let (nx, ny) = (x + dx, y + dy);
+    let name = objects[id].name.clone();
+    let kind = objects[id].pushable;
+
+    if game.map[(nx, ny)].terrain == Terrain::Chasm {
+        let object = remove_object(id, game, objects);
+        if kind == Some(Pushable::Boulder) {
+            set_tile(nx, ny, Tile::rubble(), game);
+            game.messages.add(
+                tr("push.the_fills_the_chasm", &[("name", &name)]),
+                LIGHT_GREY,
+            );
+        } else {
+            game.messages.add(
+                tr("push.the_tumbles_into_the_chasm", &[("name", &name)]),
+                LIGHT_GREY,
+            );
+            game.fallen.push(object);
+        }
+        return true;
+    }
+
+    let victim = objects
+        .iter()
+        .position(|object| object.alive && object.fighter.is_some() && object.pos() == (nx, ny));
+    if let (Some(victim), Some(Pushable::Boulder)) = (victim, kind) {
+        game.messages.add(
+            tr(
+                "push.the_rolls_onto",
+                &[("name", &name), ("victim", &objects[victim].name)],
+            ),
+            ORANGE,
+        );
+        if let Some(xp) = objects[victim].take_damage(CRUSH_DAMAGE, true, game) {
+            if let Some(fighter) = objects[PLAYER].fighter.as_mut() {
+                fighter.xp += xp;
+            }
+        }
+    }
+
+    if cuts_corner(x, y, dx, dy, game) || !resolve_moves(&[(id, (nx, ny))], game, objects) {
+        game.messages
+            .add(tr("push.the_wont_budge", &[("name", &name)]), LIGHT_GREY);
+        return false;
+    }
+    true
+}
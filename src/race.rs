@@ -0,0 +1,82 @@
+use serde::{Deserialize, Serialize};
+
+use crate::lang::tr;
+
+// The player's people, adding to the stats of the class
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub enum Race {
+    #[default]
+    Human,
+    Dwarf,
+    Elf,
+}
+
+impl Race {
+    pub const ALL: [Race; 3] = [Race::Human, Race::Dwarf, Race::Elf];
+
+    pub fn name(self) -> String {
+        match self {
+            Race::Human => tr("race.human", &[]),
+            Race::Dwarf => tr("race.dwarf", &[]),
+            Race::Elf => tr("race.elf", &[]),
+        }
+    }
+
+    pub fn description(self) -> String {
+        match self {
+            Race::Human => tr("race.human_description", &[]),
+            Race::Dwarf => tr("race.dwarf_description", &[]),
+            Race::Elf => tr("race.elf_description", &[]),
+        }
+    }
+
+    pub fn max_hp_bonus(self) -> i32 {
+        match self {
+            Race::Human => 10,
+            Race::Dwarf => 20,
+            Race::Elf => -10,
+        }
+    }
+
+    pub fn power_bonus(self) -> i32 {
+        match self {
+            Race::Human => 0,
+            Race::Dwarf => -1,
+            Race::Elf => 1,
+        }
+    }
+
+    pub fn defense_bonus(self) -> i32 {
+        match self {
+            Race::Human => 0,
+            Race::Dwarf => 1,
+            Race::Elf => 0,
+        }
+    }
+
+    // Chance in percent of dodging a blow
+    pub fn evasion_bonus(self) -> i32 {
+        match self {
+            Race::Human => 5,
+            Race::Dwarf => 0,
+            Race::Elf => 15,
+        }
+    }
+
+    // Extra tiles the player sees beyond the torch light
+    pub fn infravision(self) -> i32 {
+        match self {
+            Race::Human => 0,
+            Race::Dwarf => 2,
+            Race::Elf => 4,
+        }
+    }
+
+    // Fire damage left after the innate resistance
+    pub fn resist_fire(self, damage: i32) -> i32 {
+        match self {
+            Race::Dwarf => damage / 2,
+            _ => damage,
+        }
+    }
+}
@@ -0,0 +1,55 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::PathBuf;
+use std::time::SystemTime;
+
+use crate::colors::*;
+
+use crate::game::Game;
+use crate::lang::tr;
+use crate::mods::files_under;
+use crate::object::Object;
+use crate::paths::{asset, data_dir};
+use crate::render::Context;
+use crate::system::System;
+
+// When every data file, of the game and of the mods, was last changed
+fn data_stamps() -> BTreeMap<PathBuf, SystemTime> {
+    let mut files = vec![];
+    for dir in [asset("data"), data_dir("mods")] {
+        files_under(&dir, &dir, &mut files);
+    }
+    files
+        .into_iter()
+        .filter_map(|file| {
+            let modified = fs::metadata(&file).and_then(|m| m.modified()).ok()?;
+            Some((file, modified))
+        })
+        .collect()
+}
+
+// Watches the data files, dropping the catalogs read from them once they change
+// so they're read again when next needed. The rest of the data is read anew
+// for every level already, so the next one generated picks the changes up
+#[derive(Default)]
+pub struct DataWatcher {
+    stamps: Option<BTreeMap<PathBuf, SystemTime>>,
+}
+
+impl System for DataWatcher {
+    fn run(&mut self, _ctx: &mut Context, game: &mut Game, _objects: &mut Vec<Object>) {
+        let stamps = data_stamps();
+        let changed = self.stamps.as_ref().is_some_and(|last| *last != stamps);
+        self.stamps = Some(stamps);
+        if changed {
+            game.behaviors.clear();
+            game.recipes.clear();
+            game.dialogues.clear();
+            game.respawns.clear();
+            game.messages.add(
+                tr("reload.the_data_files_changed_reloading", &[]),
+                LIGHT_GREY,
+            );
+        }
+    }
+}
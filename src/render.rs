@@ -1,178 +1,557 @@
-use tcod::colors::*;
-use tcod::console::*;
-use tcod::input::{Key, Mouse};
-use tcod::map::{FovAlgorithm, Map as FovMap};
+use std::mem;
 
+use crate::action::TURN;
+use crate::backend::{AudioSink, Event, InputSource, Key, KeyCode, Mouse, Renderer};
+use crate::class::create_character;
+use crate::clock::{format_play_time, format_time, ClockConfig};
+use crate::colors::*;
+use crate::combat::Brand;
+use crate::config::{OptionsConfig, RulesConfig};
+use crate::daynight::phase;
+use crate::equipment::Slot;
+use crate::feeling::danger_color;
+use crate::fields::blocks_sight;
+use crate::food::hunger_status;
+use crate::fov::FovMap;
 use crate::game::{
-    initialize_fov, new_game, play_game, Game, LEVEL_UP_BASE, LEVEL_UP_FACTOR, MAP_HEIGHT,
-    MAP_WIDTH, PLAYER,
+    initialize_fov, new_game, play_game, ActionSource, Game, LEVEL_UP_BASE, LEVEL_UP_FACTOR,
+    MAP_HEIGHT, MAP_WIDTH, PLAYER,
 };
-use crate::item::INVENTORY_SIZE;
+use crate::glyphs::GlyphConfig;
+use crate::item::{category, get_equipped_in_slot, inventory_letters};
+use crate::lang::tr;
+use crate::light::sight_radius;
+use crate::mods::mods_menu;
+use crate::narrate::{announce_menu, Narrator};
 use crate::object::Object;
-use crate::save::load_game;
+use crate::options::options_menu;
+use crate::paths::asset;
+use crate::replay::load_replay;
+use crate::rng::random_seed;
+use crate::save::{load_game, saved_games};
+use crate::scheme::UiColors;
+use crate::theme::Theme;
+use crate::threat::threat_map;
+use crate::tileset::{TileMapping, FLOOR_GLYPH, WALL_GLYPH};
+use crate::tooltip::draw_tooltip;
 
+// Size of the screen the window opens with, the window can change it since
 pub const SCREEN_WIDTH: i32 = 80;
 pub const SCREEN_HEIGHT: i32 = 50;
 
 pub const BAR_WIDTH: i32 = 20;
 pub const PANEL_HEIGHT: i32 = 7;
-pub const PANEL_Y: i32 = SCREEN_HEIGHT - PANEL_HEIGHT;
 
 pub const MSG_X: i32 = BAR_WIDTH + 2;
-pub const MSG_WIDTH: i32 = SCREEN_WIDTH - BAR_WIDTH - 2;
+// Column of the panel along the right edge, showing the equipment
+const SIDE_WIDTH: i32 = 18;
 pub const MSG_HEIGHT: usize = PANEL_HEIGHT as usize - 1;
 
 pub const CHARACTER_SCREEN_WIDTH: i32 = 50;
 pub const LEVEL_SCREEN_WIDTH: i32 = 50;
 
 pub const INVENTORY_WIDTH: i32 = 40;
+// Options a menu can have, one for every letter
+pub const MAX_MENU_OPTIONS: usize = 26;
+// Lines of the inventory shown at once
+const INVENTORY_PAGE_LINES: usize = 30;
+// Width of the comparison shown next to the inventory
+const COMPARISON_WIDTH: i32 = 19;
 
-const COLOR_LIGHT_WALL: Color = Color {
-    r: 130,
-    g: 110,
-    b: 150,
-};
-const COLOR_DARK_WALL: Color = Color { r: 0, g: 0, b: 100 };
-const COLOR_LIGHT_GROUND: Color = Color {
-    r: 200,
-    g: 180,
-    b: 150,
-};
-const COLOR_DARK_GROUND: Color = Color {
-    r: 50,
-    g: 50,
-    b: 150,
-};
-
-const TORCH_RADIUS: i32 = 10;
-
-const FOV_ALGO: FovAlgorithm = FovAlgorithm::Basic;
 const FOV_LIGHT_WALLS: bool = true;
 
-pub struct Tcod {
-    pub root: Root,
-    pub con: Offscreen,
-    pub panel: Offscreen,
+// How much of the door or foliage fades into the floor out of sight
+const TERRAIN_DARKEN: f32 = 0.5;
+const FIELD_TINT: f32 = 0.6;
+const THREAT_TINT: f32 = 0.5;
+
+// Everything needed to draw the game and read the player's input
+pub struct Context {
+    pub renderer: Box<dyn Renderer>,
+    pub input: Box<dyn InputSource>,
     pub fov: FovMap,
     pub key: Key,
     pub mouse: Mouse,
+    pub tiles: Option<TileMapping>,
+    pub glyphs: GlyphConfig,
+    pub clock: ClockConfig,
+    pub rules: RulesConfig,
+    pub options: OptionsConfig,
+    pub audio: Box<dyn AudioSink>,
+    pub narrator: Narrator,
+}
+
+// Where everything goes on the screen as big as it is now,
+// the panel along the bottom and the map above it
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Layout {
+    pub width: i32,
+    pub height: i32,
 }
 
-pub fn render_all(tcod: &mut Tcod, game: &mut Game, objects: &[Object], fov_recompute: bool) {
-    if fov_recompute {
-        let player = &objects[PLAYER];
-        tcod.fov
-            .compute_fov(player.x, player.y, TORCH_RADIUS, FOV_LIGHT_WALLS, FOV_ALGO);
+impl Layout {
+    pub fn of(renderer: &dyn Renderer) -> Self {
+        let (width, height) = renderer.size();
+        Layout { width, height }
     }
 
-    for y in 0..MAP_HEIGHT {
-        for x in 0..MAP_WIDTH {
-            let visible = tcod.fov.is_in_fov(x, y);
-            let wall = game.map[x as usize][y as usize].block_sight;
-            let color = match (visible, wall) {
-                (false, true) => COLOR_DARK_WALL,
-                (false, false) => COLOR_DARK_GROUND,
-                (true, true) => COLOR_LIGHT_WALL,
-                (true, false) => COLOR_LIGHT_GROUND,
-            };
-            let explored = &mut game.map[x as usize][y as usize].explored;
-            if visible {
-                *explored = true;
-            }
-            if *explored {
-                tcod.con
-                    .set_char_background(x, y, color, BackgroundFlag::Set);
-            }
+    pub fn panel_y(self) -> i32 {
+        self.height - PANEL_HEIGHT
+    }
+
+    pub fn msg_width(self) -> i32 {
+        self.side_x() - MSG_X - 1
+    }
+
+    pub fn side_x(self) -> i32 {
+        self.width - SIDE_WIDTH - 1
+    }
+}
+
+// Camera position along an axis: the map centered if it fits on the screen,
+// otherwise following the player without showing past the edges
+fn follow(player: i32, map: i32, view: i32) -> i32 {
+    if map <= view {
+        (map - view) / 2
+    } else {
+        (player - view / 2).clamp(0, map - view)
+    }
+}
+
+// Which part of the map is drawn and which layers are shown
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct View {
+    // Map coordinates shown in the top left corner of the screen
+    pub camera_x: i32,
+    pub camera_y: i32,
+    // Size of the part of the screen the map is drawn on
+    pub width: i32,
+    pub height: i32,
+    // Darken what the player can't see and hide what they haven't explored
+    pub fov_shading: bool,
+    // Items, corpses and everything else lying on the floor
+    pub decals: bool,
+    // Tint the tiles monsters can attack
+    pub threats: bool,
+}
+
+impl View {
+    // The map the way the player sees it, around them
+    pub fn player(layout: Layout, (x, y): (i32, i32)) -> Self {
+        let height = layout.panel_y();
+        View {
+            camera_x: follow(x, MAP_WIDTH, layout.width),
+            camera_y: follow(y, MAP_HEIGHT, height),
+            width: layout.width,
+            height,
+            fov_shading: true,
+            decals: true,
+            threats: false,
         }
     }
 
-    // Get objects to draw
-    let mut to_draw: Vec<_> = objects
-        .iter()
-        .filter(|o| {
-            tcod.fov.is_in_fov(o.x, o.y)
-                || (o.always_visible && game.map[o.x as usize][o.y as usize].explored)
-        })
-        .collect();
+    // Screen position of a map tile, if it falls in the map area of the screen
+    pub fn to_screen(self, x: i32, y: i32) -> Option<(i32, i32)> {
+        let (x, y) = (x - self.camera_x, y - self.camera_y);
+        if x >= 0 && y >= 0 && x < self.width && y < self.height {
+            Some((x, y))
+        } else {
+            None
+        }
+    }
 
-    // Show non-blocking on top
-    to_draw.sort_by(|o1, o2| o1.blocks.cmp(&o2.blocks));
+    // Map tile shown at the screen position, if the map is drawn there
+    pub fn to_map(self, x: i32, y: i32) -> Option<(i32, i32)> {
+        let (map_x, map_y) = (x + self.camera_x, y + self.camera_y);
+        let on_view = x >= 0 && y >= 0 && x < self.width && y < self.height;
+        let on_map = map_x >= 0 && map_y >= 0 && map_x < MAP_WIDTH && map_y < MAP_HEIGHT;
+        (on_view && on_map).then_some((map_x, map_y))
+    }
+}
 
-    // Draw
-    for object in &to_draw {
-        object.draw(&mut tcod.con);
+pub fn render_all(ctx: &mut Context, game: &mut Game, objects: &[Object]) {
+    // Only the tiles that changed, rather than the whole map, the field of view
+    // is computed again only if they, the lights or the player's sight did
+    for (x, y) in std::mem::take(&mut game.changed_tiles) {
+        ctx.fov.set_transparent(x, y, !blocks_sight(x, y, game));
+    }
+    let player = &objects[PLAYER];
+    let radius = sight_radius(game);
+    let lights = objects
+        .iter()
+        .filter_map(|object| object.light.map(|light| (object.x, object.y, light)))
+        .collect::<Vec<_>>();
+    ctx.fov.set_lights(&lights);
+    ctx.fov
+        .compute_fov(player.x, player.y, radius, FOV_LIGHT_WALLS);
+    for (x, y) in game.map.iter_coords() {
+        if ctx.fov.is_in_fov(x, y) {
+            game.map[(x, y)].explored = true;
+        }
     }
 
-    blit(
-        &tcod.con,
-        (0, 0),
-        (MAP_WIDTH, MAP_HEIGHT),
-        &mut tcod.root,
-        (0, 0),
-        1.0,
-        1.0,
-    );
+    ctx.renderer.clear();
+    let layout = Layout::of(&*ctx.renderer);
+    let mut view = View::player(layout, objects[PLAYER].pos());
+    view.threats = ctx.options.threats;
+    draw_map(ctx, game, objects, &view);
 
-    tcod.panel.set_default_background(BLACK);
-    tcod.panel.clear();
+    let colors = ctx.options.colors.ui();
+    let panel_y = layout.panel_y();
+
+    // Show who the player is
+    ctx.renderer.print(
+        1,
+        panel_y,
+        &tr(
+            "render.name_level",
+            &[
+                ("name", &game.character.name),
+                ("level", &objects[PLAYER].level),
+            ],
+        ),
+        colors.highlight,
+    );
 
     let hp = objects[PLAYER].fighter.map_or(0, |f| f.hp);
     let base_max_hp = objects[PLAYER].max_hp(game);
     render_bar(
-        &mut tcod.panel,
-        1,
+        &mut *ctx.renderer,
         1,
+        panel_y + 1,
         BAR_WIDTH,
-        "HP",
+        &tr("render.hp", &[]),
         hp,
         base_max_hp,
-        LIGHT_RED,
-        DARKER_RED,
+        colors.health,
+        colors.bar_back,
+        colors.text,
     );
 
-    // Show current dungeon level
-    tcod.panel.print_ex(
+    // Show how far the player is from the next level
+    let xp = objects[PLAYER].fighter.map_or(0, |f| f.xp);
+    let level_up_xp = LEVEL_UP_BASE + objects[PLAYER].level * LEVEL_UP_FACTOR;
+    render_bar(
+        &mut *ctx.renderer,
         1,
-        3,
-        BackgroundFlag::None,
-        TextAlignment::Left,
-        format!("Dungeon level: {}", game.dungeon_level),
+        panel_y + 2,
+        BAR_WIDTH,
+        &tr("render.xp", &[]),
+        xp,
+        level_up_xp,
+        colors.experience,
+        colors.experience_back,
+        colors.text,
     );
 
-    // Display names of objects under the mouse
-    tcod.panel.set_default_foreground(LIGHT_GREY);
-    tcod.panel.print_ex(
+    // Show how hungry the player is, the darkness, and the statuses on them
+    let dark = if game.dark {
+        Some((tr("render.dark", &[]), colors.darkness))
+    } else {
+        None
+    };
+    let statuses = hunger_status(game)
+        .into_iter()
+        .chain(dark)
+        .chain(
+            objects[PLAYER]
+                .effects
+                .iter()
+                .map(|effect| (effect.status.name(), effect.status.color())),
+        )
+        .collect::<Vec<_>>();
+    let mut x = 1;
+    for (status, color) in statuses {
+        ctx.renderer.print(x, panel_y + 3, &status, color);
+        x += status.len() as i32 + 1;
+    }
+
+    // Show current dungeon level and the part of the day
+    let level = match game.dungeon_level {
+        0 => tr("render.surface", &[]),
+        level => tr("render.dungeon_level", &[("level", &level)]),
+    };
+    ctx.renderer
+        .print(1, panel_y + 4, &level, danger_color(game.danger));
+    let phase = phase(game);
+    ctx.renderer.print(
+        level.len() as i32 + 2,
+        panel_y + 4,
+        &phase.name(),
+        phase.color(),
+    );
+
+    // Show the turns taken, in the whole run and on this level, and the real time
+    // the run took so far
+    ctx.renderer.print(
         1,
-        0,
-        BackgroundFlag::None,
-        TextAlignment::Left,
-        names_under_mouse(tcod.mouse, objects, &tcod.fov),
+        panel_y + 6,
+        &tr(
+            "render.turns",
+            &[("turns", &game.stats.turns), ("here", &game.level_turns)],
+        ),
+        colors.text,
+    );
+    ctx.renderer.print(
+        layout.side_x(),
+        panel_y + 6,
+        &tr(
+            "render.time",
+            &[("time", &format_play_time(game.play_time, ctx.clock.mode))],
+        ),
+        colors.text,
     );
 
+    // Show how hurt the boss in sight is
+    let boss = objects
+        .iter()
+        .find(|object| object.boss && object.alive && ctx.fov.is_in_fov(object.x, object.y));
+    if let Some(boss) = boss {
+        let hp = boss.fighter.map_or(0, |f| f.hp);
+        let max_hp = boss.max_hp(game);
+        render_bar(
+            &mut *ctx.renderer,
+            1,
+            panel_y + 5,
+            BAR_WIDTH,
+            &boss.name,
+            hp,
+            max_hp,
+            colors.boss_health,
+            colors.bar_back,
+            colors.text,
+        );
+    }
+
+    draw_equipped(ctx, game, layout.side_x(), panel_y);
+
     let mut y = MSG_HEIGHT as i32;
     for &(ref msg, color) in game.messages.iter().rev() {
-        let msg_height = tcod.panel.get_height_rect(MSG_X, y, MSG_WIDTH, 0, msg);
-        y -= msg_height;
+        let lines = wrap_text(msg, layout.msg_width());
+        y -= lines.len() as i32;
         if y < 0 {
             break;
         }
-        tcod.panel.set_default_foreground(color);
-        tcod.panel.print_rect(MSG_X, y, MSG_WIDTH, 0, msg);
-    }
-
-    blit(
-        &tcod.panel,
-        (0, 0),
-        (SCREEN_WIDTH, PANEL_HEIGHT),
-        &mut tcod.root,
-        (0, PANEL_Y),
-        1.0,
-        1.0,
-    );
+        for (offset, line) in lines.iter().enumerate() {
+            ctx.renderer
+                .print(MSG_X, panel_y + y + offset as i32, line, color);
+        }
+    }
+
+    // Tell about what the mouse points at on the map
+    draw_tooltip(ctx, &view, game, objects);
+}
+
+// Show the messages added since the first one a panel at a time, waiting for a key
+// or a click before the next, so none of them scroll away unread
+pub fn more_prompt(ctx: &mut Context, game: &mut Game, objects: &[Object], first: usize) {
+    let width = Layout::of(&*ctx.renderer).msg_width();
+    let total = game.messages.messages.len();
+    let heights = game.messages.messages[first.min(total)..]
+        .iter()
+        .map(|(msg, _)| wrap_text(msg, width).len())
+        .collect::<Vec<_>>();
+    let mut start = 0;
+    loop {
+        // As many as fit, at least one however long
+        let mut end = start + 1;
+        let mut lines = heights.get(start).copied().unwrap_or(0);
+        while end < heights.len() && lines + heights[end] <= MSG_HEIGHT {
+            lines += heights[end];
+            end += 1;
+        }
+        if end >= heights.len() {
+            break;
+        }
+        game.messages.shown = Some(first + end);
+        render_all(ctx, game, objects);
+        let colors = ctx.options.colors.ui();
+        let panel_y = Layout::of(&*ctx.renderer).panel_y();
+        ctx.renderer.print(
+            MSG_X,
+            panel_y + MSG_HEIGHT as i32,
+            &tr("render.more", &[]),
+            colors.highlight,
+        );
+        ctx.renderer.flush();
+        drop_pending_events(ctx);
+        loop {
+            match wait_for_event(ctx) {
+                Event::Key(_) => break,
+                Event::Mouse(mouse) if mouse.lbutton_pressed || mouse.rbutton_pressed => break,
+                _ => {}
+            }
+        }
+        start = end;
+    }
+    game.messages.shown = None;
+}
+
+// What the player holds and wears, a line for the item and one for its bonuses
+fn draw_equipped(ctx: &mut Context, game: &Game, x: i32, y: i32) {
+    let colors = ctx.options.colors.ui();
+    let slots = [Slot::RightHand, Slot::LeftHand, Slot::Head];
+    for (row, &slot) in slots.iter().enumerate() {
+        let y = y + row as i32 * 2;
+        let item = match get_equipped_in_slot(slot, &game.inventory) {
+            Some(id) => &game.inventory[id],
+            None => {
+                let empty = tr("render.nothing_in_slot", &[("slot", &slot)]);
+                ctx.renderer.print(x, y, &fit(&empty), colors.dim);
+                continue;
+            }
+        };
+        let color = item.rarity.color().unwrap_or(colors.text);
+        ctx.renderer.print(x, y, &fit(&item.name), color);
+        if let Some(equipment) = item.equipment {
+            let bonuses = [
+                ("render.attack_short", equipment.power_bonus),
+                ("render.defense_short", equipment.defense_bonus),
+                ("render.max_hp_short", equipment.max_hp_bonus),
+                ("render.fire_short", equipment.fire_damage),
+                ("render.evasion_short", equipment.evasion_bonus),
+                ("render.block_short", equipment.block_chance),
+            ]
+            .iter()
+            .filter(|&&(_, bonus)| bonus != 0)
+            .map(|&(stat, bonus)| format!("{:+} {}", bonus, tr(stat, &[])))
+            .chain(equipment.brand.map(Brand::name))
+            .chain(
+                (equipment.reach > 1)
+                    .then(|| tr("render.reach_short", &[("reach", &equipment.reach)])),
+            )
+            .collect::<Vec<_>>();
+            ctx.renderer
+                .print(x + 1, y + 1, &fit(&bonuses.join(", ")), colors.dim);
+        }
+    }
+}
+
+// Cut the text to the width of the column on the right of the panel
+fn fit(text: &str) -> String {
+    text.chars().take(SIDE_WIDTH as usize).collect()
+}
+
+// Draw the terrain and the objects through the given view
+pub fn draw_map(ctx: &mut Context, game: &Game, objects: &[Object], view: &View) {
+    let threatened = if view.threats {
+        threat_map(ctx, game, objects)
+    } else {
+        vec![]
+    };
+    let colors = ctx.options.colors.ui();
+    let palette = ctx
+        .options
+        .colors
+        .palette(Theme::at(game.branch, game.dungeon_level).palette());
+    let mut fields = vec![None; (MAP_WIDTH * MAP_HEIGHT) as usize];
+    for field in &game.fields {
+        fields[(field.y * MAP_WIDTH + field.x) as usize] = Some(field.kind.color());
+    }
+
+    for y in 0..MAP_HEIGHT {
+        for x in 0..MAP_WIDTH {
+            let (screen_x, screen_y) = match view.to_screen(x, y) {
+                Some(position) => position,
+                None => continue,
+            };
+            let tile = &game.map[(x, y)];
+            if view.fov_shading && !tile.explored && !tile.mapped {
+                continue;
+            }
+
+            let visible = !view.fov_shading || ctx.fov.is_in_fov(x, y);
+            let wall = tile.is_wall();
+            let mut color = match (visible, wall) {
+                (false, true) if !tile.explored => colors.mapped_wall,
+                (false, false) if !tile.explored => colors.mapped_ground,
+                (false, true) => palette.dark_wall,
+                (false, false) => palette.dark_ground,
+                (true, true) => palette.light_wall,
+                (true, false) => palette.light_ground,
+            };
+            if view.threats && threatened[(y * MAP_WIDTH + x) as usize] {
+                color = lerp(color, colors.threat, THREAT_TINT);
+            }
+            // Gas and fire seen right now
+            match fields[(y * MAP_WIDTH + x) as usize] {
+                Some(field_color) if visible => color = lerp(color, field_color, FIELD_TINT),
+                _ => {}
+            }
+
+            // Doors and foliage are drawn over the floor
+            if let Some((glyph, terrain_color)) = tile.terrain.glyph() {
+                let terrain_color = if visible {
+                    terrain_color
+                } else {
+                    lerp(terrain_color, color, TERRAIN_DARKEN)
+                };
+                let glyph = ctx.tiles.as_ref().map_or(glyph, |tiles| tiles.tile(glyph));
+                ctx.renderer
+                    .put_char(screen_x, screen_y, glyph, terrain_color, Some(color));
+                continue;
+            }
+
+            let glyph = ctx.glyphs.tile(&game.map, x, y);
+            match (&ctx.tiles, glyph) {
+                (Some(tiles), _) => {
+                    let default = if wall { WALL_GLYPH } else { FLOOR_GLYPH };
+                    let glyph = tiles.tile(glyph.unwrap_or(default));
+                    ctx.renderer.put_char(
+                        screen_x,
+                        screen_y,
+                        glyph,
+                        color,
+                        Some(colors.background),
+                    );
+                }
+                (None, Some(glyph)) => {
+                    ctx.renderer.put_char(
+                        screen_x,
+                        screen_y,
+                        glyph,
+                        color,
+                        Some(colors.background),
+                    );
+                }
+                (None, None) => {
+                    ctx.renderer.set_background(screen_x, screen_y, color);
+                }
+            }
+        }
+    }
+
+    // Get objects to draw
+    let mut to_draw: Vec<_> = objects
+        .iter()
+        .filter(|o| view.decals || o.blocks)
+        .filter(|o| {
+            !view.fov_shading
+                || ctx.fov.is_in_fov(o.x, o.y)
+                || (o.always_visible && game.map[(o.x, o.y)].explored)
+        })
+        .collect();
+
+    // Show non-blocking on top
+    to_draw.sort_by_key(|o| o.blocks);
+
+    // Draw
+    for object in &to_draw {
+        if let Some((x, y)) = view.to_screen(object.x, object.y) {
+            object.draw(&mut *ctx.renderer, x, y, &ctx.glyphs, ctx.tiles.as_ref());
+        }
+    }
+
+    // Alert over the monsters that just spotted the player
+    for object in to_draw.iter().filter(|o| o.alert > 0) {
+        if let Some((x, y)) = view.to_screen(object.x, object.y - 1) {
+            ctx.renderer.put_char(x, y, '!', colors.alert, None);
+        }
+    }
 }
 
 pub fn render_bar(
-    panel: &mut Offscreen,
+    renderer: &mut dyn Renderer,
     x: i32,
     y: i32,
     total_width: i32,
@@ -181,210 +560,642 @@ pub fn render_bar(
     maximum: i32,
     bar_color: Color,
     back_color: Color,
+    text_color: Color,
 ) {
     // Get width of the bar (of HP, exp, etc.)
     let bar_width = (value as f32 / maximum as f32 * total_width as f32) as i32;
 
-    // Render the background
-    panel.set_default_background(back_color);
-    panel.rect(x, y, total_width, 1, false, BackgroundFlag::Screen);
-
-    // Render the bar
-    panel.set_default_background(bar_color);
-    if bar_width > 0 {
-        panel.rect(x, y, total_width, 1, false, BackgroundFlag::Screen);
+    // Render the bar over its background
+    for offset in 0..total_width {
+        let color = if offset < bar_width {
+            bar_color
+        } else {
+            back_color
+        };
+        renderer.set_background(x + offset, y, color);
     }
 
     // Centered text with values
-    panel.set_default_foreground(WHITE);
-    panel.print_ex(
+    print_centered(
+        renderer,
         x + total_width / 2,
         y,
-        BackgroundFlag::None,
-        TextAlignment::Center,
         &format!("{}: {}/{}", name, value, maximum),
-    )
+        text_color,
+    );
 }
 
-fn names_under_mouse(mouse: Mouse, object: &[Object], fov_map: &FovMap) -> String {
-    let (x, y) = (mouse.cx as i32, mouse.cy as i32);
+// Print a line of text centered on x
+pub fn print_centered(renderer: &mut dyn Renderer, x: i32, y: i32, text: &str, color: Color) {
+    let width = text.chars().count() as i32;
+    renderer.print(x - width / 2, y, text, color);
+}
 
-    // Create a list with the names of the objects under the mouse's coordinates and in FOV
-    let names = object
-        .iter()
-        .filter(|object| fov_map.is_in_fov(object.x, object.y) && object.pos() == (x, y))
-        .map(|object| object.name.clone())
-        .collect::<Vec<_>>();
+// Split the text into lines of at most the given width, breaking at spaces when possible
+pub fn wrap_text(text: &str, width: i32) -> Vec<String> {
+    let width = width.max(1) as usize;
+    let mut lines = vec![];
 
-    names.join(", ")
+    for paragraph in text.split('\n') {
+        let mut line = String::new();
+        for word in paragraph.split(' ') {
+            let mut word = word.to_string();
+            let line_width = line.chars().count();
+            if line_width > 0 && line_width + 1 + word.chars().count() > width {
+                lines.push(line);
+                line = String::new();
+            }
+            if !line.is_empty() {
+                line.push(' ');
+            }
+            // Words wider than a whole line are cut
+            while word.chars().count() > width {
+                let rest = word.split_off(word.char_indices().nth(width).unwrap().0);
+                lines.push(word);
+                word = rest;
+            }
+            line.push_str(&word);
+        }
+        lines.push(line);
+    }
+    lines
 }
 
 pub fn menu<T: AsRef<str>>(
     header: &str,
     options: &[T],
     width: i32,
-    root: &mut Root,
+    ctx: &mut Context,
 ) -> Option<usize> {
     assert!(
-        options.len() <= INVENTORY_SIZE as usize,
+        options.len() <= MAX_MENU_OPTIONS,
         "Cannot have such a big menu"
     );
 
+    let colors = ctx.options.colors.ui();
+    announce_menu(header, options, ctx);
+    drop_pending_events(ctx);
+
+    // The option under the mouse is highlighted, and chosen by clicking it
+    let mut hovered = None;
+    loop {
+        let lines = options
+            .iter()
+            .enumerate()
+            .map(|(index, option_text)| {
+                let menu_letter = (b'a' + index as u8) as char;
+                let color = if hovered == Some(index) {
+                    colors.highlight
+                } else {
+                    colors.text
+                };
+                (format!("({}) {}", menu_letter, option_text.as_ref()), color)
+            })
+            .collect::<Vec<_>>();
+        let area = draw_menu(header, &lines, width, ctx);
+        ctx.renderer.flush();
+
+        match wait_for_event(ctx) {
+            Event::Key(key) => return letter_index(key, options.len()),
+            Event::Mouse(mouse) if mouse.rbutton_pressed => return None,
+            // A message box closes with a click anywhere
+            Event::Mouse(mouse) if mouse.lbutton_pressed && options.is_empty() => return None,
+            Event::Mouse(mouse) => {
+                hovered = area.line_at(mouse, options.len());
+                if mouse.lbutton_pressed && hovered.is_some() {
+                    return hovered;
+                }
+            }
+            Event::Resize => {}
+        }
+    }
+}
+
+// Index of the option the letter of the key stands for, if there is one
+fn letter_index(key: Key, options: usize) -> Option<usize> {
+    if key.printable.is_ascii_alphabetic() {
+        let index = key.printable.to_ascii_lowercase() as usize - 'a' as usize;
+        (index < options).then_some(index)
+    } else {
+        None
+    }
+}
+
+// Keys pressed before the menu was shown aren't meant for it
+fn drop_pending_events(ctx: &mut Context) {
+    while ctx.input.check_for_event().is_some() {}
+}
+
+// Block until a key is pressed, the mouse moves or clicks, or the window changes,
+// keeping the mouse in the context up to date. Closing the window is Escape
+fn wait_for_event(ctx: &mut Context) -> Event {
+    loop {
+        match ctx.input.check_for_event() {
+            Some(Event::Mouse(mouse)) => {
+                ctx.mouse = mouse;
+                return Event::Mouse(mouse);
+            }
+            Some(event) => return event,
+            None if ctx.renderer.window_closed() => {
+                return Event::Key(Key {
+                    code: KeyCode::Escape,
+                    ..Default::default()
+                })
+            }
+            // Waits for the next frame
+            None => ctx.renderer.flush(),
+        }
+    }
+}
+
+// Where the options of a menu were drawn
+#[derive(Debug, Clone, Copy)]
+struct MenuArea {
+    x: i32,
+    // Line of the first option
+    y: i32,
+    width: i32,
+}
+
+impl MenuArea {
+    // Which of the lines the mouse is over
+    fn line_at(self, mouse: Mouse, lines: usize) -> Option<usize> {
+        let line = mouse.cy - self.y;
+        let inside = mouse.cx >= self.x
+            && mouse.cx < self.x + self.width
+            && line >= 0
+            && (line as usize) < lines;
+        inside.then_some(line as usize)
+    }
+}
+
+// Let the player type a line of text, None if they press Escape
+pub fn text_input(prompt: &str, max_length: usize, ctx: &mut Context) -> Option<String> {
+    let mut text = String::new();
+    let colors = ctx.options.colors.ui();
+    ctx.narrator.say(prompt);
+
+    while !ctx.renderer.window_closed() {
+        ctx.renderer.clear();
+        let layout = Layout::of(&*ctx.renderer);
+        print_centered(
+            &mut *ctx.renderer,
+            layout.width / 2,
+            layout.height / 2 - 2,
+            prompt,
+            colors.text,
+        );
+        print_centered(
+            &mut *ctx.renderer,
+            layout.width / 2,
+            layout.height / 2,
+            &format!("{}_", text),
+            colors.highlight,
+        );
+        ctx.renderer.flush();
+
+        if let Some(Event::Key(key)) = ctx.input.check_for_event() {
+            match key.code {
+                KeyCode::Enter if !text.trim().is_empty() => return Some(text.trim().into()),
+                KeyCode::Escape => return None,
+                KeyCode::Backspace => {
+                    text.pop();
+                }
+                KeyCode::Text
+                    if !key.printable.is_control() && text.chars().count() < max_length =>
+                {
+                    text.push(key.printable)
+                }
+                _ => {}
+            }
+        }
+    }
+    None
+}
+
+// Draw the header wrapped to the width and the lines under it in a box centered on the screen
+fn draw_menu(header: &str, lines: &[(String, Color)], width: i32, ctx: &mut Context) -> MenuArea {
     // Calculate the total height of the header (with auto-wrap) and one line per option
-    let header_height = if header.is_empty() {
-        0
+    let header_lines = if header.is_empty() {
+        vec![]
     } else {
-        root.get_height_rect(0, 0, width, SCREEN_HEIGHT, header)
+        wrap_text(header, width)
     };
-    let height = options.len() as i32 + header_height;
+    let header_height = header_lines.len() as i32;
+    let height = lines.len() as i32 + header_height;
 
-    // Create an offscreen console to represent the menu
-    let mut window = Offscreen::new(width, height);
+    // Center the menu on the screen
+    let colors = ctx.options.colors.ui();
+    let layout = Layout::of(&*ctx.renderer);
+    let x = layout.width / 2 - width / 2;
+    let y = layout.height / 2 - height / 2;
+
+    // Blank the area behind the menu
+    for dy in 0..height {
+        for dx in 0..width {
+            ctx.renderer
+                .put_char(x + dx, y + dy, ' ', colors.text, Some(colors.background));
+        }
+    }
 
     // Print the header
-    window.set_default_foreground(WHITE);
-    window.print_rect_ex(
-        0,
-        0,
-        width,
-        height,
-        BackgroundFlag::None,
-        TextAlignment::Left,
-        header,
-    );
+    for (index, line) in header_lines.iter().enumerate() {
+        ctx.renderer.print(x, y + index as i32, line, colors.text);
+    }
 
     // Print all the options
-    for (index, option_text) in options.iter().enumerate() {
-        let menu_letter = (b'a' + index as u8) as char;
-        let text = format!("({}) {}", menu_letter, option_text.as_ref());
-        window.print_ex(
-            0,
-            header_height + index as i32,
-            BackgroundFlag::None,
-            TextAlignment::Left,
-            text,
-        );
+    for (index, (text, color)) in lines.iter().enumerate() {
+        ctx.renderer
+            .print(x, y + header_height + index as i32, text, *color);
+    }
+    MenuArea {
+        x,
+        y: y + header_height,
+        width,
     }
+}
 
-    // Blit to the root screen
-    let x = SCREEN_WIDTH / 2 - width / 2;
-    let y = SCREEN_HEIGHT / 2 - height / 2;
-    blit(&window, (0, 0), (width, height), root, (x, y), 1.0, 0.7);
+// Inventory grouped by category and sorted by name, a page at a time,
+// with the item under the cursor compared to the equipment it would replace,
+// returns the index of the chosen item
+pub fn inventory_menu(inventory: &[Object], header: &str, ctx: &mut Context) -> Option<usize> {
+    if inventory.is_empty() {
+        menu(
+            header,
+            &[tr("render.inventory_is_empty", &[])],
+            INVENTORY_WIDTH,
+            ctx,
+        );
+        return None;
+    }
 
-    root.flush();
-    let key = root.wait_for_keypress(true);
+    let letters = inventory_letters(inventory);
+    let mut order = (0..inventory.len()).collect::<Vec<_>>();
+    order.sort_by_key(|&index| (category(&inventory[index]), &inventory[index].name));
 
-    // Convert an ASCII key to index
-    if key.printable.is_alphabetic() {
-        let index = key.printable.to_ascii_lowercase() as usize - 'a' as usize;
-        if index < options.len() {
-            Some(index)
-        } else {
-            None
+    // Every category starts with its name, the rest of the lines are items
+    let mut lines = vec![];
+    let mut previous = None;
+    for &index in &order {
+        let item = &inventory[index];
+        if previous != Some(category(item)) {
+            previous = Some(category(item));
+            lines.push((category(item).name(), None));
         }
-    } else {
-        None
+        let name = match item.equipment {
+            Some(equipment) if equipment.equipped => tr(
+                "render.on_slot",
+                &[("item", &item.name), ("slot", &equipment.slot)],
+            ),
+            _ => match item.charges {
+                Some(charges) => tr(
+                    "render.charges",
+                    &[("item", &item.name), ("charges", &charges)],
+                ),
+                None => item.name.clone(),
+            },
+        };
+        let name = match item.equipment {
+            Some(equipment) if equipment.enchantment > 0 => {
+                format!("{} +{}", name, equipment.enchantment)
+            }
+            _ => name,
+        };
+        let name = match item.fuel {
+            Some(fuel) => tr("render.turns_of_light", &[("item", &name), ("fuel", &fuel)]),
+            None => name,
+        };
+        lines.push((format!("({}) {}", letters[index], name), Some(index)));
     }
-}
+    let item_lines = (0..lines.len())
+        .filter(|&line| lines[line].1.is_some())
+        .collect::<Vec<_>>();
+    let pages = lines.len().div_ceil(INVENTORY_PAGE_LINES);
 
-pub fn inventory_menu(inventory: &[Object], header: &str, root: &mut Root) -> Option<usize> {
-    let options = if inventory.len() == 0 {
-        vec!["Inventory is empty".into()]
-    } else {
-        // inventory.iter().map(|item| item.name.clone()).collect()
-        inventory
+    // Position of the cursor among the item lines
+    let mut cursor = 0;
+    let colors = ctx.options.colors.ui();
+    ctx.narrator.say(header);
+    drop_pending_events(ctx);
+    let mut shown_cursor = None;
+    loop {
+        let cursor_moved = shown_cursor != Some(cursor);
+        shown_cursor = Some(cursor);
+        let page = item_lines[cursor] / INVENTORY_PAGE_LINES;
+        let first = page * INVENTORY_PAGE_LINES;
+        let mut shown = lines
             .iter()
-            .map(|item| match item.equipment {
-                Some(equipment) if equipment.equipped => {
-                    format!("{} (on {})", item.name, equipment.slot)
-                }
-                _ => item.name.clone(),
+            .enumerate()
+            .skip(first)
+            .take(INVENTORY_PAGE_LINES)
+            .map(|(line, (text, index))| {
+                let color = match index {
+                    None => colors.dim,
+                    Some(_) if line == item_lines[cursor] => colors.highlight,
+                    // Magic and rare items stand out
+                    Some(index) => inventory[*index].rarity.color().unwrap_or(colors.text),
+                };
+                (text.clone(), color)
             })
-            .collect()
-    };
+            .collect::<Vec<_>>();
+        if pages > 1 {
+            shown.push((
+                tr("render.page", &[("page", &(page + 1)), ("pages", &pages)]),
+                colors.dim,
+            ));
+        }
+        let area = draw_menu(header, &shown, INVENTORY_WIDTH, ctx);
+        // The header is read out once, then the item under the cursor every time it moves
+        if cursor_moved {
+            ctx.narrator.say(&lines[item_lines[cursor]].0);
+        }
+        if let Some(index) = lines[item_lines[cursor]].1 {
+            let comparison = compare_equipment(index, inventory, &colors);
+            let layout = Layout::of(&*ctx.renderer);
+            let x = layout.width / 2 + INVENTORY_WIDTH / 2 + 1;
+            draw_lines(x, layout.height / 2, &comparison, COMPARISON_WIDTH, ctx);
+            if cursor_moved {
+                for (line, _) in &comparison {
+                    ctx.narrator.say(line);
+                }
+            }
+        }
+        ctx.renderer.flush();
 
-    let inventory_index = menu(header, &options, INVENTORY_WIDTH, root);
+        let key = match wait_for_event(ctx) {
+            Event::Key(key) => key,
+            Event::Mouse(mouse) if mouse.rbutton_pressed => return None,
+            // The cursor follows the mouse over the items, a click chooses the item
+            Event::Mouse(mouse) => {
+                let pointed = area
+                    .line_at(mouse, shown.len())
+                    .and_then(|line| item_lines.iter().position(|&item| item == first + line));
+                if let Some(pointed) = pointed {
+                    cursor = pointed;
+                    if mouse.lbutton_pressed {
+                        return lines[item_lines[cursor]].1;
+                    }
+                }
+                continue;
+            }
+            Event::Resize => continue,
+        };
+        // First item on the page, for turning to it
+        let first_on = |page: usize| {
+            item_lines
+                .iter()
+                .position(|&line| line >= page * INVENTORY_PAGE_LINES)
+                .unwrap_or(cursor)
+        };
+        match key.code {
+            KeyCode::Up | KeyCode::NumPad8 => cursor = cursor.saturating_sub(1),
+            KeyCode::Down | KeyCode::NumPad2 => cursor = (cursor + 1).min(item_lines.len() - 1),
+            KeyCode::Right | KeyCode::PageDown if page + 1 < pages => cursor = first_on(page + 1),
+            KeyCode::Left | KeyCode::PageUp if page > 0 => cursor = first_on(page - 1),
+            KeyCode::Right | KeyCode::PageDown | KeyCode::Left | KeyCode::PageUp => {}
+            KeyCode::Enter => return lines[item_lines[cursor]].1,
+            // Any item can be chosen by its letter, whichever page it's on
+            _ => return letters.iter().position(|&letter| letter == key.printable),
+        }
+    }
+}
 
-    // Return the item if it was selected
-    if inventory.len() > 0 {
-        inventory_index
-    } else {
-        None
+// How wearing the item would change the stats, against what is worn in its slot now
+fn compare_equipment(
+    index: usize,
+    inventory: &[Object],
+    colors: &UiColors,
+) -> Vec<(String, Color)> {
+    let equipment = match inventory[index].equipment {
+        Some(equipment) => equipment,
+        None => return vec![],
+    };
+    if equipment.equipped {
+        return vec![(tr("render.worn_now", &[]), colors.dim)];
+    }
+    let current = get_equipped_in_slot(equipment.slot, inventory)
+        .and_then(|current| inventory[current].equipment);
+    let heading = match current {
+        Some(_) => "render.instead_of",
+        None => "render.nothing_on",
+    };
+    let mut lines = vec![(tr(heading, &[("slot", &equipment.slot)]), colors.dim)];
+    let mut stats = vec![
+        (
+            "render.attack",
+            equipment.power_bonus,
+            current.map_or(0, |c| c.power_bonus),
+        ),
+        (
+            "render.defense",
+            equipment.defense_bonus,
+            current.map_or(0, |c| c.defense_bonus),
+        ),
+        (
+            "render.max_hp",
+            equipment.max_hp_bonus,
+            current.map_or(0, |c| c.max_hp_bonus),
+        ),
+    ];
+    let fire = current.map_or(0, |c| c.fire_damage);
+    if equipment.fire_damage > 0 || fire > 0 {
+        stats.push(("render.fire", equipment.fire_damage, fire));
+    }
+    let evasion = current.map_or(0, |c| c.evasion_bonus);
+    if equipment.evasion_bonus > 0 || evasion > 0 {
+        stats.push(("render.evasion", equipment.evasion_bonus, evasion));
+    }
+    let block = current.map_or(0, |c| c.block_chance);
+    if equipment.block_chance > 0 || block > 0 {
+        stats.push(("render.block", equipment.block_chance, block));
+    }
+    // Whatever doesn't say otherwise hits next to the wielder
+    let reach = current.map_or(1, |c| c.reach.max(1));
+    if equipment.reach > 1 || reach > 1 {
+        stats.push(("render.reach", equipment.reach.max(1), reach));
+    }
+    for &(name, new, old) in &stats {
+        let delta = new - old;
+        let color = match delta {
+            _ if delta > 0 => colors.better,
+            _ if delta < 0 => colors.worse,
+            _ => colors.text,
+        };
+        let change = tr(
+            "render.stat_change",
+            &[
+                ("stat", &tr(name, &[])),
+                ("value", &new),
+                ("delta", &format!("{:+}", delta)),
+            ],
+        );
+        lines.push((change, color));
+    }
+    // Slower swings are worse, and whatever isn't a weapon leaves the fists at a turn
+    if equipment.attack_delay > 0 {
+        let old = current
+            .map(|c| c.attack_delay)
+            .filter(|&delay| delay > 0)
+            .unwrap_or(TURN);
+        let delta = equipment.attack_delay - old;
+        let color = match delta {
+            _ if delta < 0 => colors.better,
+            _ if delta > 0 => colors.worse,
+            _ => colors.text,
+        };
+        lines.push((
+            tr(
+                "render.swing",
+                &[
+                    ("delay", &equipment.attack_delay),
+                    ("delta", &format!("{:+}", delta)),
+                ],
+            ),
+            color,
+        ));
+    }
+    lines
+}
+
+// Lines in a blank box with its top left corner at the position
+fn draw_lines(x: i32, y: i32, lines: &[(String, Color)], width: i32, ctx: &mut Context) {
+    let colors = ctx.options.colors.ui();
+    for (dy, (text, color)) in lines.iter().enumerate() {
+        for dx in 0..width {
+            ctx.renderer.put_char(
+                x + dx,
+                y + dy as i32,
+                ' ',
+                colors.text,
+                Some(colors.background),
+            );
+        }
+        ctx.renderer.print(x, y + dy as i32, text, *color);
     }
 }
 
-pub fn msgbox(text: &str, width: i32, root: &mut Root) {
+pub fn msgbox(text: &str, width: i32, ctx: &mut Context) {
     let options: &[&str] = &[];
-    menu(text, options, width, root);
+    menu(text, options, width, ctx);
+}
+
+// Ask the player a yes or no question, anything but yes is a no
+pub fn confirm(question: &str, ctx: &mut Context) -> bool {
+    let choices = [tr("render.yes", &[]), tr("render.no", &[])];
+    menu(question, &choices, 24, ctx) == Some(0)
 }
 
-pub fn character_info_box(player: &Object, game: &mut Game, root: &mut Root) {
+pub fn character_info_box(player: &Object, game: &mut Game, ctx: &mut Context) {
     let level_up_xp = LEVEL_UP_BASE + player.level * LEVEL_UP_FACTOR;
+    let character = game.character.clone();
     if let Some(fighter) = player.fighter.as_ref() {
-        let msg = format!(
-            "Character information:
-Level: {}
-Experience: {}
-Experience to level up: {}
-Maximum HP: {}
-Attack: {}
-Defense: {}",
-            player.level,
-            fighter.xp,
-            level_up_xp,
-            player.max_hp(game),
-            player.power(game),
-            player.defense(game)
+        let mut msg = tr(
+            "render.character_information",
+            &[
+                ("name", &character.name),
+                ("race", &character.race.name()),
+                ("class", &character.class.name()),
+                ("level", &player.level),
+                ("xp", &fighter.xp),
+                ("level_up_xp", &level_up_xp),
+                ("max_hp", &player.max_hp(game)),
+                ("power", &player.power(game)),
+                ("defense", &player.defense(game)),
+                ("evasion", &player.evasion(game)),
+                ("block", &player.block_chance(game)),
+                ("gold", &game.gold),
+                ("turns", &game.stats.turns),
+                ("level_turns", &game.level_turns),
+                ("time", &format_time(game.play_time)),
+            ],
         );
-        msgbox(&msg, CHARACTER_SCREEN_WIDTH, root);
+        for (deity, piety) in &game.piety {
+            msg.push_str(&tr(
+                "render.piety_of",
+                &[("deity", deity), ("piety", piety)],
+            ));
+        }
+        msgbox(&msg, CHARACTER_SCREEN_WIDTH, ctx);
     }
 }
 
-pub fn main_menu(tcod: &mut Tcod) {
-    let img = tcod::image::Image::from_file("menu_background.png")
-        .ok()
-        .expect("Background image not found");
-
-    tcod.root.set_default_foreground(LIGHT_RED);
-    tcod.root.print_ex(
-        SCREEN_WIDTH / 2,
-        SCREEN_HEIGHT / 2 - 4,
-        BackgroundFlag::None,
-        TextAlignment::Center,
-        "World of Rust and Steel",
-    );
-    tcod.root.print_ex(
-        SCREEN_WIDTH / 2,
-        SCREEN_HEIGHT / 2 - 2,
-        BackgroundFlag::None,
-        TextAlignment::Center,
-        "By Eugene Rossokha",
-    );
+pub fn main_menu(ctx: &mut Context) {
+    while !ctx.renderer.window_closed() {
+        ctx.renderer.clear();
+        ctx.renderer
+            .draw_image(&asset("menu_background.png").to_string_lossy());
+        let colors = ctx.options.colors.ui();
+        let layout = Layout::of(&*ctx.renderer);
 
-    while !tcod.root.window_closed() {
-        // Show the image at twice the regular console resolution
-        tcod::image::blit_2x(&img, (0, 0), (-1, -1), &mut tcod.root, (0, 0));
+        print_centered(
+            &mut *ctx.renderer,
+            layout.width / 2,
+            layout.height / 2 - 4,
+            "World of Rust and Steel",
+            colors.title,
+        );
+        print_centered(
+            &mut *ctx.renderer,
+            layout.width / 2,
+            layout.height / 2 - 2,
+            "By Eugene Rossokha",
+            colors.title,
+        );
 
         // Show options and waitt for the player to choose
-        let choices = &["Play a new game", "Continue", "Quit"];
-        let choice = menu("", choices, 24, &mut tcod.root);
+        let choices = [
+            tr("render.play_a_new_game", &[]),
+            tr("render.continue", &[]),
+            tr("render.watch_replay", &[]),
+            tr("render.options", &[]),
+            tr("render.mods", &[]),
+            tr("render.quit", &[]),
+        ];
+        let choice = menu("", &choices, 24, ctx);
 
         match choice {
             Some(0) => {
-                let (mut game, mut objects) = new_game(tcod);
-                play_game(tcod, &mut game, &mut objects);
+                if let Some(character) = create_character(ctx) {
+                    let (mut game, mut objects) = new_game(ctx, random_seed(), &character);
+                    play_game(ctx, &mut game, &mut objects, ActionSource::Player);
+                }
+            }
+            Some(1) => {
+                let mut saves = saved_games();
+                if saves.is_empty() {
+                    msgbox(&tr("render.no_saved_game", &[]), 24, ctx);
+                    continue;
+                }
+                saves.truncate(MAX_MENU_OPTIONS);
+                let slot = match menu(&tr("render.continue_as", &[]), &saves, 24, ctx) {
+                    Some(index) => &saves[index],
+                    None => continue,
+                };
+                match load_game(slot) {
+                    Ok((mut game, mut objects)) => {
+                        initialize_fov(ctx, &game.map);
+                        play_game(ctx, &mut game, &mut objects, ActionSource::Player);
+                    }
+                    Err(_e) => msgbox(&tr("render.saved_game_damaged", &[]), 24, ctx),
+                }
             }
-            Some(1) => match load_game() {
-                Ok((mut game, mut objects)) => {
-                    initialize_fov(tcod, &game.map);
-                    play_game(tcod, &mut game, &mut objects);
+            Some(2) => match load_replay() {
+                Ok(replay) => {
+                    // Re-simulate the run from its seed, with the rules it was played by
+                    let rules = mem::replace(&mut ctx.rules, replay.rules);
+                    let (mut game, mut objects) = new_game(ctx, replay.seed, &replay.character);
+                    let actions = ActionSource::Replay(replay.actions.into_iter());
+                    play_game(ctx, &mut game, &mut objects, actions);
+                    ctx.rules = rules;
                 }
                 Err(_e) => {
-                    msgbox("\nNo saved game to load.\n", 24, &mut tcod.root);
+                    msgbox(&tr("render.no_replay", &[]), 24, ctx);
                     continue;
                 }
             },
-            Some(2) => {
+            Some(3) => options_menu(ctx),
+            Some(4) => mods_menu(ctx),
+            Some(5) => {
                 break;
             }
             _ => {}
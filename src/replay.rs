@@ -0,0 +1,50 @@
+use std::error::Error;
+use std::fs::File;
+
+use std::io::{Read, Write};
+
+use serde::{Deserialize, Serialize};
+
+use crate::action::Action;
+use crate::class::Character;
+use crate::config::RulesConfig;
+use crate::paths::data_file;
+
+const REPLAY_FILE: &str = "replay.json";
+
+// Everything needed to re-simulate a run: the seed, the character and the rules it started with
+// and every action the player took since
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Replay {
+    pub seed: u64,
+    pub character: Character,
+    #[serde(default)]
+    pub rules: RulesConfig,
+    pub actions: Vec<Action>,
+}
+
+impl Replay {
+    pub fn new(seed: u64, character: Character, rules: RulesConfig) -> Self {
+        Replay {
+            seed,
+            character,
+            rules,
+            actions: vec![],
+        }
+    }
+}
+
+pub fn save_replay(replay: &Replay) -> Result<(), Box<dyn Error>> {
+    let replay_data = serde_json::to_string(replay)?;
+    let mut file = File::create(data_file(REPLAY_FILE))?;
+    file.write_all(replay_data.as_bytes())?;
+    Ok(())
+}
+
+pub fn load_replay() -> Result<Replay, Box<dyn Error>> {
+    let mut json_replay = String::new();
+    let mut file = File::open(data_file(REPLAY_FILE))?;
+    file.read_to_string(&mut json_replay)?;
+    let result = serde_json::from_str::<Replay>(&json_replay)?;
+    Ok(result)
+}
@@ -0,0 +1,102 @@
+use std::error::Error;
+
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::daynight::is_night;
+use crate::faction::hostile;
+use crate::game::{is_blocked, Game, PLAYER};
+use crate::mods::read_data;
+use crate::object::Object;
+use crate::overworld::Branch;
+use crate::render::Context;
+use crate::room::{make_monster, random_monster};
+use crate::spawn::spawn;
+use crate::theme::Theme;
+
+const RESPAWN_FILE: &str = "data/respawn.json";
+
+// How often new monsters wander into the levels from the given one down, in the given
+// branch or in all of them when it has none
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Respawn {
+    #[serde(default)]
+    pub branch: Option<Branch>,
+    pub from_level: u32,
+    // Turns between the arrivals
+    pub turns: u32,
+    // Nothing arrives while the level has this many monsters hostile to the player
+    pub max_monsters: usize,
+}
+
+impl Respawn {
+    // Used when the data file is missing
+    pub fn standard() -> Vec<Respawn> {
+        vec![
+            Respawn {
+                branch: None,
+                from_level: 1,
+                turns: 100,
+                max_monsters: 20,
+            },
+            Respawn {
+                branch: None,
+                from_level: 5,
+                turns: 70,
+                max_monsters: 25,
+            },
+        ]
+    }
+}
+
+pub fn load_respawns() -> Result<Vec<Respawn>, Box<dyn Error>> {
+    let result = serde_json::from_value::<Vec<Respawn>>(read_data(RESPAWN_FILE)?)?;
+    Ok(result)
+}
+
+// Count the turn spent on the level, bringing a monster into a room the player doesn't see
+// when it's time for one
+pub fn repopulate(ctx: &Context, game: &mut Game, objects: &[Object]) {
+    game.level_turns += 1;
+    if game.respawns.is_empty() {
+        game.respawns = load_respawns().unwrap_or_else(|_| Respawn::standard());
+    }
+    let level = game.dungeon_level;
+    let branch = game.branch;
+    // The rules of the branch win over the ones for all branches
+    let respawn = match game
+        .respawns
+        .iter()
+        .filter(|respawn| respawn.branch.is_none_or(|b| b == branch) && respawn.from_level <= level)
+        .max_by_key(|respawn| (respawn.branch.is_some(), respawn.from_level))
+    {
+        Some(respawn) => respawn.clone(),
+        None => return,
+    };
+    if respawn.turns == 0
+        || !game.level_turns.is_multiple_of(respawn.turns)
+        || game.rooms.is_empty()
+    {
+        return;
+    }
+    let monsters = objects
+        .iter()
+        .filter(|object| object.alive && hostile(object.faction, objects[PLAYER].faction))
+        .count();
+    if monsters >= respawn.max_monsters {
+        return;
+    }
+
+    let room = game.rooms[game.rng.gen_range(0, game.rooms.len())];
+    let x = game.rng.gen_range(room.x1 + 1, room.x2);
+    let y = game.rng.gen_range(room.y1 + 1, room.y2);
+    let theme = Theme::at(game.branch, level);
+    let night = is_night(game);
+    let kind = random_monster(level, theme, night, &mut game.rng);
+    if ctx.fov.is_in_fov(x, y) || is_blocked(x, y, &game.map, objects) {
+        return;
+    }
+    if let Some(monster) = make_monster(kind, x, y) {
+        spawn(monster, game);
+    }
+}
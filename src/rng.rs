@@ -0,0 +1,43 @@
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+// Random number generator of the game, all the randomness goes through it
+// so that a run can be reproduced from its seed, the state is saved with the game
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GameRng {
+    state: u64,
+}
+
+impl GameRng {
+    pub fn new(seed: u64) -> Self {
+        // Scramble the seed with splitmix64, so that close seeds give unrelated runs
+        let mut state = seed.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        state = (state ^ (state >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        state = (state ^ (state >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        state ^= state >> 31;
+
+        // The state must never be zero
+        GameRng {
+            state: if state == 0 { 1 } else { state },
+        }
+    }
+}
+
+// xorshift64*
+impl Rng for GameRng {
+    fn next_u32(&mut self) -> u32 {
+        (self.next_u64() >> 32) as u32
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.state ^= self.state >> 12;
+        self.state ^= self.state << 25;
+        self.state ^= self.state >> 27;
+        self.state.wrapping_mul(0x2545_F491_4F6C_DD1D)
+    }
+}
+
+// A fresh seed for a new run
+pub fn random_seed() -> u64 {
+    rand::thread_rng().gen()
+}
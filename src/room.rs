@@ -1,25 +1,117 @@
 use std::cmp;
+use std::collections::HashMap;
 
-use tcod::colors::*;
+use crate::colors::*;
 
 use rand::distributions::{IndependentSample, Weighted, WeightedChoice};
 use rand::Rng;
+use serde::{Deserialize, Serialize};
 
+use crate::affix::enchant;
 use crate::ai::Ai;
+use crate::daynight::night_weight;
 use crate::equipment::{Equipment, Slot};
-use crate::fighter::{DeathCallback, Fighter};
-use crate::game::{
-    from_dungeon_level, is_blocked, Map, Tile, Transition, MAP_HEIGHT, MAP_WIDTH, PLAYER,
-};
+use crate::errors::log_error;
+use crate::faction::Faction;
+use crate::fighter::{DeathCallback, Fighter, MAX_MORALE};
+use crate::game::{from_dungeon_level, is_blocked, Map, Tile, Transition};
 use crate::item::Item;
+use crate::lang::tr;
+use crate::light::{LANTERN_FUEL, TORCH_FUEL};
+use crate::mapgen::load_map_builder;
+use crate::mods::read_data;
 use crate::object::Object;
+use crate::overworld::Branch;
+use crate::rng::GameRng;
+use crate::status::{Effect, Status};
+use crate::theme::Theme;
+
+const MONSTERS_FILE: &str = "data/monsters.json";
+
+// What the data tells about a kind of monster, by its name
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MonsterData {
+    // Dungeon level the monster is a fair fight for
+    pub difficulty: i32,
+}
+
+// Chance in percent of a monster being spawned asleep
+const SLEEP_CHANCE: i32 = 70;
+
+// Chance in percent of a monster coming from the table of a deeper level, and how much deeper
+const OUT_OF_DEPTH_CHANCE: i32 = 5;
+const OUT_OF_DEPTH_LEVELS: u32 = 3;
+// Chance in percent of a monster being one of the uniques, if one fits the level
+const UNIQUE_CHANCE: i32 = 3;
+
+// A named monster met at most once a run, a stronger version of its kind
+pub struct Unique {
+    pub name: &'static str,
+    pub kind: &'static str,
+    // Shallowest level it can be found on
+    pub level: u32,
+    pub hp: i32,
+    pub defense: i32,
+    pub power: i32,
+}
 
-const ROOM_MAX_SIZE: i32 = 10;
-const ROOM_MIN_SIZE: i32 = 6;
-const MAX_ROOMS: i32 = 30;
+pub const UNIQUES: &[Unique] = &[
+    Unique {
+        name: "Grik the One-Eyed",
+        kind: "goblin",
+        level: 2,
+        hp: 15,
+        defense: 1,
+        power: 2,
+    },
+    Unique {
+        name: "Old Greymane",
+        kind: "wolf",
+        level: 3,
+        hp: 15,
+        defense: 1,
+        power: 3,
+    },
+    Unique {
+        name: "Ulfang the Black",
+        kind: "orc",
+        level: 5,
+        hp: 25,
+        defense: 2,
+        power: 4,
+    },
+    Unique {
+        name: "Vex the Bonelord",
+        kind: "skeleton",
+        level: 7,
+        hp: 30,
+        defense: 3,
+        power: 5,
+    },
+];
+
+// Chance in percent of a monster carrying something
+const GEAR_CHANCE: i32 = 30;
+
+// Chance in percent of a monster as strong as the level dropping anything
+const LOOT_BASE_CHANCE: i32 = 20;
+const LOOT_CHANCE_PER_LEVEL: i32 = 15;
+// Levels deeper the loot is rolled for, per level the monster is out of depth
+const LOOT_LEVELS_PER_LEVEL: i32 = 2;
+
+// Turns the ghoul's touch slows for
+const GHOUL_SLOW_TURNS: u32 = 6;
+
+// Chance in percent of the shield stopping a blow
+const SHIELD_BLOCK_CHANCE: i32 = 25;
+// Tiles away the polearms hit
+const POLEARM_REACH: i32 = 2;
+
+// Charges a wand is found with
+const WAND_CHARGES: u32 = 4;
 
 // A rectangular object to represent a room
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
 pub struct Rect {
     pub x1: i32,
     pub y1: i32,
@@ -53,26 +145,34 @@ impl Rect {
 
 pub fn create_h_tunnel(x1: i32, x2: i32, y: i32, map: &mut Map) {
     for x in cmp::min(x1, x2)..cmp::max(x1, x2) + 1 {
-        map[x as usize][y as usize] = Tile::empty();
+        map[(x, y)] = Tile::empty();
     }
 }
 
 pub fn create_v_tunnel(y1: i32, y2: i32, x: i32, map: &mut Map) {
     for y in cmp::min(y1, y2)..cmp::max(y1, y2) + 1 {
-        map[x as usize][y as usize] = Tile::empty();
+        map[(x, y)] = Tile::empty();
     }
 }
 
 pub fn create_room(room: Rect, map: &mut Map) {
     for x in (room.x1 + 1)..room.x2 {
         for y in (room.y1 + 1)..room.y2 {
-            map[x as usize][y as usize] = Tile::empty();
+            map[(x, y)] = Tile::empty();
         }
     }
 }
 
 // TODO: rewrite that shit completely
-pub fn place_objects(room: Rect, map: &Map, objects: &mut Vec<Object>, level: u32) {
+pub fn place_objects(
+    room: Rect,
+    map: &Map,
+    objects: &mut Vec<Object>,
+    level: u32,
+    theme: Theme,
+    uniques: &[String],
+    rng: &mut GameRng,
+) {
     // maximum number of monsters in a room
     let max_monsters = from_dungeon_level(
         &[
@@ -84,64 +184,59 @@ pub fn place_objects(room: Rect, map: &Map, objects: &mut Vec<Object>, level: u3
     );
 
     // Random number of monsters in a room
-    let num_monsters = rand::thread_rng().gen_range(0, max_monsters + 1);
-
-    let monster_table = &mut [
-        Weighted {
-            weight: 80,
-            item: "goblin",
-        },
-        Weighted {
-            weight: 20,
-            item: "orc",
-        },
-    ];
+    let num_monsters = rng.gen_range(0, max_monsters + 1);
 
-    let monster_choice = WeightedChoice::new(monster_table);
+    // The level is stocked the same way whatever the hour,
+    // only the monsters wandering in later keep to the clock
+    let table = &mut monster_table(level, theme, false);
+    let monster_choice = WeightedChoice::new(table);
+    let deep_table = &mut monster_table(level + OUT_OF_DEPTH_LEVELS, theme, false);
+    let deep_choice = WeightedChoice::new(deep_table);
 
     for _ in 0..num_monsters {
         // Random spot
-        let x = rand::thread_rng().gen_range(room.x1 + 1, room.x2);
-        let y = rand::thread_rng().gen_range(room.y1 + 1, room.y2);
+        let x = rng.gen_range(room.x1 + 1, room.x2);
+        let y = rng.gen_range(room.y1 + 1, room.y2);
 
         if !is_blocked(x, y, &map, &objects) {
-            let mut monster = match monster_choice.ind_sample(&mut rand::thread_rng()) {
-                "goblin" => {
-                    let mut goblin = Object::new(x, y, 'g', "goblin", DESATURATED_GREEN, true);
-
-                    goblin.fighter = Some(Fighter {
-                        base_max_hp: 10,
-                        hp: 10,
-                        base_defense: 0,
-                        base_power: 3,
-                        xp: 25,
-                        on_death: DeathCallback::Monster,
-                    });
-                    goblin.ai = Some(Ai::Basic);
-
-                    goblin
-                }
-
-                "orc" => {
-                    // Orc
-                    let mut orc = Object::new(x, y, 'o', "orc", DARKER_GREEN, true);
-
-                    orc.fighter = Some(Fighter {
-                        base_max_hp: 15,
-                        hp: 15,
-                        base_defense: 1,
-                        base_power: 5,
-                        xp: 80,
-                        on_death: DeathCallback::Monster,
-                    });
-                    orc.ai = Some(Ai::Basic);
-
-                    orc
+            let eligible = UNIQUES
+                .iter()
+                .filter(|unique| {
+                    level >= unique.level
+                        && !uniques.iter().any(|name| name == unique.name)
+                        && !objects.iter().any(|object| object.name == unique.name)
+                })
+                .collect::<Vec<_>>();
+            let unique = match eligible.len() {
+                0 => None,
+                count => Some(eligible[rng.gen_range(0, count)]),
+            };
+            let monster = match unique {
+                Some(unique) if rng.gen_range(0, 100) < UNIQUE_CHANCE => make_unique(unique, x, y),
+                _ => {
+                    // Now and then something wanders up from below
+                    let kind = if rng.gen_range(0, 100) < OUT_OF_DEPTH_CHANCE {
+                        deep_choice.ind_sample(rng)
+                    } else {
+                        monster_choice.ind_sample(rng)
+                    };
+                    make_monster(kind, x, y).map(|mut monster| {
+                        give_gear(&mut monster, level, rng);
+                        monster
+                    })
                 }
-                _ => unreachable!(),
             };
-            monster.alive = true;
-            objects.push(monster);
+            if let Some(mut monster) = monster {
+                // Most monsters are found asleep
+                if rng.gen_range(0, 100) < SLEEP_CHANCE {
+                    if let Some(ai) = monster.ai.take() {
+                        monster.ai = Some(Ai::Sleeping {
+                            previous_ai: Box::new(ai),
+                        });
+                    }
+                }
+                objects.push(monster);
+            }
         }
     }
 
@@ -155,17 +250,306 @@ pub fn place_objects(room: Rect, map: &Map, objects: &mut Vec<Object>, level: u3
     );
 
     // Random number of iterms in a room
-    let num_items = rand::thread_rng().gen_range(0, max_items + 1);
+    let num_items = rng.gen_range(0, max_items + 1);
+
+    for _ in 0..num_items {
+        // Random spot
+        let x = rng.gen_range(room.x1 + 1, room.x2);
+        let y = rng.gen_range(room.y1 + 1, room.y2);
 
+        // Place if there is some space
+        if !is_blocked(x, y, map, objects) {
+            let mut item = make_item(random_item(level, rng), x, y);
+            enchant(&mut item, level, rng);
+            objects.push(item);
+        }
+    }
+}
+
+// Kind of a monster found on the level, the nocturnal ones more likely at night
+pub fn random_monster(level: u32, theme: Theme, night: bool, rng: &mut GameRng) -> &'static str {
+    WeightedChoice::new(&mut monster_table(level, theme, night)).ind_sample(rng)
+}
+
+// Monsters found on the level, weighted by how common they are
+fn monster_table(level: u32, theme: Theme, night: bool) -> Vec<Weighted<&'static str>> {
+    let table = vec![
+        Weighted {
+            weight: 80,
+            item: "goblin",
+        },
+        Weighted {
+            weight: 20,
+            item: "orc",
+        },
+        Weighted {
+            weight: 15,
+            item: "wolf",
+        },
+        Weighted {
+            weight: from_dungeon_level(
+                &[Transition {
+                    level: 2,
+                    value: 15,
+                }],
+                level,
+            ),
+            item: "goblin archer",
+        },
+        Weighted {
+            weight: from_dungeon_level(
+                &[Transition {
+                    level: 3,
+                    value: 15,
+                }],
+                level,
+            ),
+            item: "skeleton",
+        },
+        Weighted {
+            weight: from_dungeon_level(&[Transition { level: 5, value: 8 }], level),
+            item: "necromancer",
+        },
+        Weighted {
+            weight: from_dungeon_level(
+                &[Transition {
+                    level: 4,
+                    value: 10,
+                }],
+                level,
+            ),
+            item: "ghoul",
+        },
+    ];
+    table
+        .into_iter()
+        .map(|entry| Weighted {
+            weight: night_weight(
+                entry.item,
+                theme.spawn_weight(entry.item, entry.weight),
+                night,
+            ),
+            item: entry.item,
+        })
+        .collect()
+}
+
+// Create a monster of the kind at the given position, None if there is no such kind
+pub fn make_monster(kind: &str, x: i32, y: i32) -> Option<Object> {
+    let mut monster = match kind {
+        "goblin" => {
+            let mut goblin = Object::new(x, y, 'g', "goblin", DESATURATED_GREEN, true);
+
+            goblin.fighter = Some(Fighter {
+                base_max_hp: 10,
+                hp: 10,
+                base_defense: 0,
+                base_power: 3,
+                xp: 25,
+                on_death: DeathCallback::Monster,
+                morale: MAX_MORALE,
+            });
+            goblin.ai = Some(Ai::Tree("goblin".into()));
+            goblin.faction = Some(Faction::Goblins);
+
+            goblin
+        }
+
+        "orc" => {
+            // Orc
+            let mut orc = Object::new(x, y, 'o', "orc", DARKER_GREEN, true);
+
+            orc.fighter = Some(Fighter {
+                base_max_hp: 15,
+                hp: 15,
+                base_defense: 1,
+                base_power: 5,
+                xp: 80,
+                on_death: DeathCallback::Monster,
+                morale: MAX_MORALE,
+            });
+            orc.ai = Some(Ai::Tree("orc".into()));
+            orc.faction = Some(Faction::Goblins);
+
+            orc
+        }
+
+        "goblin archer" => {
+            let mut archer = Object::new(x, y, 'a', "goblin archer", DESATURATED_LIME, true);
+
+            archer.fighter = Some(Fighter {
+                base_max_hp: 8,
+                hp: 8,
+                base_defense: 0,
+                base_power: 3,
+                xp: 40,
+                on_death: DeathCallback::Monster,
+                morale: MAX_MORALE,
+            });
+            archer.ai = Some(Ai::Tree("goblin archer".into()));
+            archer.faction = Some(Faction::Goblins);
+
+            archer
+        }
+
+        "wolf" => {
+            let mut wolf = Object::new(x, y, 'w', "wolf", SEPIA, true);
+
+            wolf.fighter = Some(Fighter {
+                base_max_hp: 8,
+                hp: 8,
+                base_defense: 0,
+                base_power: 4,
+                xp: 20,
+                on_death: DeathCallback::Monster,
+                morale: MAX_MORALE,
+            });
+            wolf.ai = Some(Ai::Tree("wolf".into()));
+            wolf.faction = Some(Faction::Wildlife);
+
+            wolf
+        }
+
+        "skeleton" => {
+            let mut skeleton = Object::new(x, y, 's', "skeleton", LIGHTEST_GREY, true);
+
+            skeleton.fighter = Some(Fighter {
+                base_max_hp: 18,
+                hp: 18,
+                base_defense: 2,
+                base_power: 4,
+                xp: 90,
+                on_death: DeathCallback::Monster,
+                morale: MAX_MORALE,
+            });
+            skeleton.ai = Some(Ai::Tree("skeleton".into()));
+            skeleton.faction = Some(Faction::Undead);
+
+            skeleton
+        }
+        "necromancer" => {
+            let mut necromancer = Object::new(x, y, 'n', "necromancer", DARK_VIOLET, true);
+
+            necromancer.fighter = Some(Fighter {
+                base_max_hp: 16,
+                hp: 16,
+                base_defense: 0,
+                base_power: 3,
+                xp: 120,
+                on_death: DeathCallback::Monster,
+                morale: MAX_MORALE,
+            });
+            necromancer.ai = Some(Ai::Tree("necromancer".into()));
+            necromancer.faction = Some(Faction::Undead);
+
+            necromancer
+        }
+        "ghoul" => {
+            let mut ghoul = Object::new(x, y, 'G', "ghoul", DARK_SEA, true);
+
+            ghoul.fighter = Some(Fighter {
+                base_max_hp: 20,
+                hp: 20,
+                base_defense: 1,
+                base_power: 4,
+                xp: 100,
+                on_death: DeathCallback::Monster,
+                morale: MAX_MORALE,
+            });
+            // Its touch numbs the limbs
+            ghoul.on_hit = Some(Effect {
+                status: Status::Slow,
+                turns: GHOUL_SLOW_TURNS,
+            });
+            ghoul.ai = Some(Ai::Tree("ghoul".into()));
+            ghoul.faction = Some(Faction::Undead);
+
+            ghoul
+        }
+        _ => return None,
+    };
+    monster.alive = true;
+    monster.level = monster_difficulty(kind);
+    Some(monster)
+}
+
+// The unique monster, stronger and worth more than the rest of its kind
+pub fn make_unique(unique: &Unique, x: i32, y: i32) -> Option<Object> {
+    let mut monster = make_monster(unique.kind, x, y)?;
+    monster.name = unique.name.into();
+    monster.color = GOLD;
+    monster.unique = true;
+    monster.level += unique.level as i32;
+    if let Some(fighter) = monster.fighter.as_mut() {
+        fighter.base_max_hp += unique.hp;
+        fighter.hp += unique.hp;
+        fighter.base_defense += unique.defense;
+        fighter.base_power += unique.power;
+        fighter.xp *= 3;
+    }
+    Some(monster)
+}
+
+pub fn uniques_on_level(objects: &[Object]) -> Vec<String> {
+    objects
+        .iter()
+        .filter(|object| object.unique)
+        .map(|object| object.name.clone())
+        .collect()
+}
+
+fn load_monsters() -> HashMap<String, MonsterData> {
+    read_data(MONSTERS_FILE)
+        .and_then(|data| Ok(serde_json::from_value(data)?))
+        .unwrap_or_else(|e| {
+            log_error(&format!("Failed to load the monsters: {}", e));
+            HashMap::new()
+        })
+}
+
+// Monsters are made all over the level generation, the data is read once
+thread_local! {
+    static MONSTERS: HashMap<String, MonsterData> = load_monsters();
+}
+
+// Dungeon level the kind of monster is a fair fight for, the first one
+// for the kinds the data doesn't know
+pub fn monster_difficulty(kind: &str) -> i32 {
+    MONSTERS.with(|monsters| match monsters.get(kind) {
+        Some(data) => data.difficulty,
+        None => {
+            log_error(&format!(
+                "No difficulty for the {} in {}",
+                kind, MONSTERS_FILE
+            ));
+            1
+        }
+    })
+}
+
+// Item found lying around on the level, the deeper the better
+pub fn random_item(level: u32, rng: &mut GameRng) -> Item {
     let item_table = &mut [
         Weighted {
             weight: 70,
             item: Item::Heal,
         },
+        Weighted {
+            weight: from_dungeon_level(&[Transition { level: 2, value: 5 }], level),
+            item: Item::Speed,
+        },
         Weighted {
             weight: 10,
             item: Item::Fireball,
         },
+        Weighted {
+            weight: 20,
+            item: Item::Gold,
+        },
+        Weighted {
+            weight: from_dungeon_level(&[Transition { level: 2, value: 8 }], level),
+            item: Item::Ore,
+        },
         Weighted {
             weight: 10,
             item: Item::Lightning,
@@ -174,10 +558,82 @@ pub fn place_objects(room: Rect, map: &Map, objects: &mut Vec<Object>, level: u3
             weight: 10,
             item: Item::Confusion,
         },
+        Weighted {
+            weight: 5,
+            item: Item::Return,
+        },
+        Weighted {
+            weight: 3,
+            item: Item::Anchor,
+        },
+        Weighted {
+            weight: from_dungeon_level(&[Transition { level: 3, value: 5 }], level),
+            item: Item::Summon,
+        },
+        Weighted {
+            weight: 4,
+            item: Item::Clairvoyance,
+        },
+        Weighted {
+            weight: from_dungeon_level(&[Transition { level: 2, value: 4 }], level),
+            item: Item::MagicMapping,
+        },
+        Weighted {
+            weight: from_dungeon_level(&[Transition { level: 2, value: 3 }], level),
+            item: Item::DiggingWand,
+        },
+        Weighted {
+            weight: from_dungeon_level(&[Transition { level: 2, value: 2 }], level),
+            item: Item::AlchemyKit,
+        },
+        Weighted {
+            weight: from_dungeon_level(&[Transition { level: 3, value: 5 }], level),
+            item: Item::Essence,
+        },
+        Weighted {
+            weight: from_dungeon_level(&[Transition { level: 3, value: 3 }], level),
+            item: Item::LightningWand,
+        },
+        Weighted {
+            weight: from_dungeon_level(&[Transition { level: 4, value: 4 }], level),
+            item: Item::Recharge,
+        },
+        Weighted {
+            weight: from_dungeon_level(&[Transition { level: 3, value: 3 }], level),
+            item: Item::Scripted("frost".into()),
+        },
         Weighted {
             weight: from_dungeon_level(&[Transition { level: 4, value: 5 }], level),
             item: Item::Sword,
         },
+        Weighted {
+            weight: from_dungeon_level(&[Transition { level: 3, value: 2 }], level),
+            item: Item::Pick,
+        },
+        Weighted {
+            weight: from_dungeon_level(&[Transition { level: 2, value: 8 }], level),
+            item: Item::Torch,
+        },
+        Weighted {
+            weight: from_dungeon_level(&[Transition { level: 4, value: 2 }], level),
+            item: Item::Lantern,
+        },
+        Weighted {
+            weight: from_dungeon_level(&[Transition { level: 4, value: 4 }], level),
+            item: Item::Oil,
+        },
+        Weighted {
+            weight: from_dungeon_level(&[Transition { level: 3, value: 3 }], level),
+            item: Item::Spear,
+        },
+        Weighted {
+            weight: from_dungeon_level(&[Transition { level: 6, value: 4 }], level),
+            item: Item::Greatsword,
+        },
+        Weighted {
+            weight: from_dungeon_level(&[Transition { level: 7, value: 3 }], level),
+            item: Item::Halberd,
+        },
         Weighted {
             weight: from_dungeon_level(
                 &[Transition {
@@ -190,120 +646,346 @@ pub fn place_objects(room: Rect, map: &Map, objects: &mut Vec<Object>, level: u3
         },
     ];
 
-    let item_choice = WeightedChoice::new(item_table);
+    WeightedChoice::new(item_table).ind_sample(rng)
+}
 
-    for _ in 0..num_items {
-        // Random spot
-        let x = rand::thread_rng().gen_range(room.x1 + 1, room.x2);
-        let y = rand::thread_rng().gen_range(room.y1 + 1, room.y2);
+// Items the kind of monster may carry, the equipment already equipped,
+// each with the level it's found from, the same as the items lying around
+fn monster_gear(kind: &str) -> Vec<(Item, u32)> {
+    match kind {
+        "goblin" => vec![(Item::Heal, 1), (Item::Spear, 3)],
+        "orc" => vec![(Item::Sword, 1), (Item::Greatsword, 6), (Item::Halberd, 7)],
+        "goblin archer" => vec![(Item::Confusion, 1)],
+        "skeleton" => vec![(Item::Shield, 1)],
+        _ => vec![],
+    }
+}
 
-        // Place if there is some space
-        if !is_blocked(x, y, map, objects) {
-            let item = match item_choice.ind_sample(&mut rand::thread_rng()) {
-                Item::Heal => {
-                    let mut potion = Object::new(x, y, '!', "healing potion", VIOLET, false);
-                    potion.item = Some(Item::Heal);
-                    potion
-                }
-                Item::Fireball => {
-                    let mut scroll = Object::new(x, y, '#', "fireball scroll", ORANGE, false);
-                    scroll.item = Some(Item::Fireball);
-                    scroll
-                }
-                Item::Lightning => {
-                    let mut scroll =
-                        Object::new(x, y, '#', "lightning scroll", LIGHT_YELLOW, false);
-                    scroll.item = Some(Item::Lightning);
-                    scroll
-                }
-                Item::Confusion => {
-                    let mut scroll =
-                        Object::new(x, y, '#', "confusion scroll", LIGHT_YELLOW, false);
-                    scroll.item = Some(Item::Confusion);
-                    scroll
-                }
-                Item::Sword => {
-                    let mut sword = Object::new(x, y, '/', "sword", SKY, false);
-                    sword.item = Some(Item::Sword);
-                    sword.equipment = Some(Equipment {
-                        equipped: false,
-                        slot: Slot::RightHand,
-                        power_bonus: 5,
-                        defense_bonus: 0,
-                        max_hp_bonus: 0,
-                    });
-                    sword
-                }
-                Item::Shield => {
-                    let mut shield = Object::new(x, y, '0', "shield", SKY, false);
-                    shield.item = Some(Item::Shield);
-                    shield.equipment = Some(Equipment {
-                        equipped: false,
-                        slot: Slot::LeftHand,
-                        power_bonus: 0,
-                        defense_bonus: 5,
-                        max_hp_bonus: 4,
-                    });
-                    shield
-                }
-            };
-            objects.push(item);
+// Maybe hand the monster one of the items its kind carries on the level,
+// equipping it if it can be
+pub fn give_gear(monster: &mut Object, level: u32, rng: &mut GameRng) {
+    let gear = monster_gear(monster.kind())
+        .into_iter()
+        .filter(|&(_, from_level)| level >= from_level)
+        .map(|(item, _)| item)
+        .collect::<Vec<_>>();
+    if gear.is_empty() || rng.gen_range(0, 100) >= GEAR_CHANCE {
+        return;
+    }
+    let mut item = make_item(gear[rng.gen_range(0, gear.len())].clone(), 0, 0);
+    if let Some(equipment) = item.equipment.as_mut() {
+        equipment.equipped = true;
+        if let Some(fighter) = monster.fighter.as_mut() {
+            fighter.hp += equipment.max_hp_bonus;
         }
     }
+    monster.inventory.push(item);
 }
 
-pub fn make_map(objects: &mut Vec<Object>, level: u32) -> Map {
-    let mut map = vec![vec![Tile::wall(); MAP_HEIGHT as usize]; MAP_WIDTH as usize];
-
-    // Remove every object except for the player
-    assert_eq!(&objects[PLAYER] as *const _, &objects[0] as *const _);
-    objects.truncate(1);
-
-    let mut rooms = vec![];
-
-    for _ in 0..MAX_ROOMS {
-        // Random width and height
-        let w = rand::thread_rng().gen_range(ROOM_MIN_SIZE, ROOM_MAX_SIZE + 1);
-        let h = rand::thread_rng().gen_range(ROOM_MIN_SIZE, ROOM_MAX_SIZE + 1);
-
-        // Random position of the room with regards to the boundaries
-        let x = rand::thread_rng().gen_range(0, MAP_WIDTH - w);
-        let y = rand::thread_rng().gen_range(0, MAP_HEIGHT - h);
-
-        let new_room = Rect::new(x, y, w, h);
-
-        // Check intersections with existing rooms
-        let failed = rooms.iter().any(|room| new_room.intersect(room));
-
-        if !failed {
-            create_room(new_room, &mut map);
-            place_objects(new_room, &map, objects, level);
-
-            let (new_x, new_y) = new_room.center();
-
-            if rooms.is_empty() {
-                objects[PLAYER].set_pos(new_x, new_y);
-            } else {
-                let (prev_x, prev_y) = rooms[rooms.len() - 1].center();
+// Item dropped by the killed monster, if any:
+// monsters stronger than the level drop more often, and drop what is found deeper
+pub fn monster_loot(monster: &Object, dungeon_level: u32, rng: &mut GameRng) -> Option<Object> {
+    let delta = monster.level - dungeon_level as i32;
+    let drop_chance = (LOOT_BASE_CHANCE + delta * LOOT_CHANCE_PER_LEVEL).clamp(0, 100);
+    if rng.gen_range(0, 100) >= drop_chance {
+        return None;
+    }
 
-                if rand::random() {
-                    create_h_tunnel(prev_x, new_x, prev_y, &mut map);
-                    create_v_tunnel(prev_y, new_y, new_x, &mut map);
-                } else {
-                    create_v_tunnel(prev_y, new_y, prev_x, &mut map);
-                    create_h_tunnel(prev_x, new_x, new_y, &mut map);
-                }
-            }
+    let quality = cmp::max(1, dungeon_level as i32 + delta * LOOT_LEVELS_PER_LEVEL) as u32;
+    let mut item = make_item(random_item(quality, rng), monster.x, monster.y);
+    enchant(&mut item, quality, rng);
+    Some(item)
+}
 
-            rooms.push(new_room);
+// Create an item of the kind lying at the given position
+pub fn make_item(item: Item, x: i32, y: i32) -> Object {
+    match item {
+        Item::Heal => {
+            let mut potion = Object::new(x, y, '!', "healing potion", VIOLET, false);
+            potion.item = Some(Item::Heal);
+            potion
+        }
+        Item::Speed => {
+            let mut potion = Object::new(x, y, '!', "potion of speed", LIGHT_CYAN, false);
+            potion.item = Some(Item::Speed);
+            potion
+        }
+        Item::Fireball => {
+            let mut scroll = Object::new(x, y, '#', "fireball scroll", ORANGE, false);
+            scroll.item = Some(Item::Fireball);
+            scroll
+        }
+        Item::Lightning => {
+            let mut scroll = Object::new(x, y, '#', "lightning scroll", LIGHT_YELLOW, false);
+            scroll.item = Some(Item::Lightning);
+            scroll
+        }
+        Item::Confusion => {
+            let mut scroll = Object::new(x, y, '#', "confusion scroll", LIGHT_YELLOW, false);
+            scroll.item = Some(Item::Confusion);
+            scroll
+        }
+        Item::Return => {
+            let mut scroll = Object::new(x, y, '#', "scroll of return", LIGHT_BLUE, false);
+            scroll.item = Some(Item::Return);
+            scroll
+        }
+        Item::Anchor => {
+            let mut anchor = Object::new(x, y, '&', "teleport anchor", LIGHT_BLUE, false);
+            anchor.item = Some(Item::Anchor);
+            anchor
+        }
+        Item::Summon => {
+            let mut scroll = Object::new(x, y, '#', "scroll of summoning", LIGHT_VIOLET, false);
+            scroll.item = Some(Item::Summon);
+            scroll
+        }
+        Item::LightningWand => {
+            let mut wand = Object::new(x, y, '-', "wand of lightning", LIGHT_BLUE, false);
+            wand.item = Some(Item::LightningWand);
+            wand.charges = Some(WAND_CHARGES);
+            wand
+        }
+        Item::DiggingWand => {
+            let mut wand = Object::new(x, y, '-', "wand of digging", DARK_AMBER, false);
+            wand.item = Some(Item::DiggingWand);
+            wand.charges = Some(WAND_CHARGES);
+            wand
+        }
+        Item::Recharge => {
+            let mut scroll = Object::new(x, y, '#', "scroll of recharging", LIGHT_BLUE, false);
+            scroll.item = Some(Item::Recharge);
+            scroll
+        }
+        Item::MagicMapping => {
+            let mut scroll = Object::new(x, y, '#', "scroll of magic mapping", LIGHT_VIOLET, false);
+            scroll.item = Some(Item::MagicMapping);
+            scroll
+        }
+        Item::Clairvoyance => {
+            let mut scroll = Object::new(x, y, '#', "scroll of clairvoyance", LIGHT_VIOLET, false);
+            scroll.item = Some(Item::Clairvoyance);
+            scroll
+        }
+        Item::Essence => {
+            let mut essence = Object::new(x, y, '*', "arcane essence", LIGHT_MAGENTA, false);
+            essence.item = Some(Item::Essence);
+            essence
+        }
+        Item::AlchemyKit => {
+            let mut kit = Object::new(x, y, '&', "alchemy kit", LIGHT_AMBER, false);
+            kit.item = Some(Item::AlchemyKit);
+            kit
+        }
+        Item::Gold => {
+            let mut gold = Object::new(x, y, '$', "gold coins", GOLD, false);
+            gold.item = Some(Item::Gold);
+            gold
+        }
+        Item::Ore => {
+            let mut ore = Object::new(x, y, '*', "iron ore", DARK_GREY, false);
+            ore.item = Some(Item::Ore);
+            ore
+        }
+        Item::Sword => {
+            let mut sword = Object::new(x, y, '/', "sword", SKY, false);
+            sword.item = Some(Item::Sword);
+            sword.equipment = Some(Equipment {
+                equipped: false,
+                slot: Slot::RightHand,
+                power_bonus: 5,
+                defense_bonus: 0,
+                max_hp_bonus: 0,
+                evasion_bonus: 0,
+                block_chance: 0,
+                attack_delay: 100,
+                reach: 0,
+                fire_damage: 0,
+                on_hit: None,
+                brand: None,
+                enchantment: 0,
+                digging: false,
+                light_radius: 0,
+            });
+            sword
+        }
+        Item::Greatsword => {
+            let mut greatsword = Object::new(x, y, '|', "greatsword", SKY, false);
+            greatsword.item = Some(Item::Greatsword);
+            greatsword.equipment = Some(Equipment {
+                equipped: false,
+                slot: Slot::RightHand,
+                power_bonus: 9,
+                defense_bonus: 0,
+                max_hp_bonus: 0,
+                evasion_bonus: 0,
+                block_chance: 0,
+                attack_delay: 170,
+                reach: 0,
+                fire_damage: 0,
+                on_hit: None,
+                brand: None,
+                enchantment: 0,
+                digging: false,
+                light_radius: 0,
+            });
+            greatsword
+        }
+        Item::Spear => {
+            let mut spear = Object::new(x, y, '\\', "spear", SKY, false);
+            spear.item = Some(Item::Spear);
+            spear.equipment = Some(Equipment {
+                equipped: false,
+                slot: Slot::RightHand,
+                power_bonus: 3,
+                defense_bonus: 0,
+                max_hp_bonus: 0,
+                evasion_bonus: 0,
+                block_chance: 0,
+                attack_delay: 110,
+                reach: POLEARM_REACH,
+                fire_damage: 0,
+                on_hit: None,
+                brand: None,
+                enchantment: 0,
+                digging: false,
+                light_radius: 0,
+            });
+            spear
+        }
+        Item::Halberd => {
+            let mut halberd = Object::new(x, y, '\\', "halberd", SKY, false);
+            halberd.item = Some(Item::Halberd);
+            halberd.equipment = Some(Equipment {
+                equipped: false,
+                slot: Slot::RightHand,
+                power_bonus: 7,
+                defense_bonus: 0,
+                max_hp_bonus: 0,
+                evasion_bonus: 0,
+                block_chance: 0,
+                attack_delay: 160,
+                reach: POLEARM_REACH,
+                fire_damage: 0,
+                on_hit: None,
+                brand: None,
+                enchantment: 0,
+                digging: false,
+                light_radius: 0,
+            });
+            halberd
+        }
+        Item::Pick => {
+            let mut pick = Object::new(x, y, '(', "pick", SKY, false);
+            pick.item = Some(Item::Pick);
+            pick.equipment = Some(Equipment {
+                equipped: false,
+                slot: Slot::RightHand,
+                power_bonus: 2,
+                defense_bonus: 0,
+                max_hp_bonus: 0,
+                evasion_bonus: 0,
+                block_chance: 0,
+                attack_delay: 120,
+                reach: 0,
+                fire_damage: 0,
+                on_hit: None,
+                brand: None,
+                enchantment: 0,
+                digging: true,
+                light_radius: 0,
+            });
+            pick
+        }
+        Item::Torch => {
+            let mut torch = Object::new(x, y, '~', "torch", LIGHT_ORANGE, false);
+            torch.item = Some(Item::Torch);
+            torch.fuel = Some(TORCH_FUEL);
+            torch.equipment = Some(Equipment {
+                equipped: false,
+                slot: Slot::LeftHand,
+                power_bonus: 0,
+                defense_bonus: 0,
+                max_hp_bonus: 0,
+                evasion_bonus: 0,
+                block_chance: 0,
+                attack_delay: 0,
+                reach: 0,
+                fire_damage: 0,
+                on_hit: None,
+                brand: None,
+                enchantment: 0,
+                digging: false,
+                light_radius: 5,
+            });
+            torch
+        }
+        Item::Lantern => {
+            let mut lantern = Object::new(x, y, '~', "lantern", YELLOW, false);
+            lantern.item = Some(Item::Lantern);
+            lantern.fuel = Some(LANTERN_FUEL);
+            lantern.equipment = Some(Equipment {
+                equipped: false,
+                slot: Slot::LeftHand,
+                power_bonus: 0,
+                defense_bonus: 0,
+                max_hp_bonus: 0,
+                evasion_bonus: 0,
+                block_chance: 0,
+                attack_delay: 0,
+                reach: 0,
+                fire_damage: 0,
+                on_hit: None,
+                brand: None,
+                enchantment: 0,
+                digging: false,
+                light_radius: 8,
+            });
+            lantern
+        }
+        Item::Oil => {
+            let mut oil = Object::new(x, y, '!', "flask of oil", AMBER, false);
+            oil.item = Some(Item::Oil);
+            oil
+        }
+        Item::Scripted(name) => {
+            let title = tr("room.scroll_of", &[("name", &name.replace('_', " "))]);
+            let mut scroll = Object::new(x, y, '#', &title, LIGHT_CYAN, false);
+            scroll.item = Some(Item::Scripted(name));
+            scroll
+        }
+        Item::Shield => {
+            let mut shield = Object::new(x, y, '0', "shield", SKY, false);
+            shield.item = Some(Item::Shield);
+            shield.equipment = Some(Equipment {
+                equipped: false,
+                slot: Slot::LeftHand,
+                power_bonus: 0,
+                defense_bonus: 2,
+                max_hp_bonus: 4,
+                evasion_bonus: 0,
+                block_chance: SHIELD_BLOCK_CHANCE,
+                attack_delay: 0,
+                reach: 0,
+                fire_damage: 0,
+                on_hit: None,
+                brand: None,
+                enchantment: 0,
+                digging: false,
+                light_radius: 0,
+            });
+            shield
         }
     }
+}
 
-    // create stairs at the center of the last room
-    let (last_room_x, last_room_y) = rooms[rooms.len() - 1].center();
-    let mut stairs = Object::new(last_room_x, last_room_y, '>', "stairs", WHITE, false);
-    stairs.always_visible = true;
-    objects.push(stairs);
-
-    map
+// Generate a level with the map builder from the data file
+pub fn make_map(
+    objects: &mut Vec<Object>,
+    level: u32,
+    branch: Branch,
+    uniques: &[String],
+    rng: &mut GameRng,
+) -> (Map, Vec<Rect>) {
+    load_map_builder().build(objects, level, branch, uniques, rng)
 }
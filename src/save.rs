@@ -1,22 +1,122 @@
+use std::cmp::Reverse;
 use std::error::Error;
-use std::fs::File;
+use std::fs::{self, File};
+use std::path::{Path, PathBuf};
 
 use std::io::{Read, Write};
 
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+
+use crate::colors::*;
+
+use crate::errors::log_error;
 use crate::game::Game;
+use crate::lang::tr;
 use crate::object::Object;
+use crate::paths::{data_dir, file_stem};
+
+// Every character has a slot of their own in the directory, named after them
+const SAVES_DIR: &str = "saves";
+const SAVE_EXTENSION: &str = "save";
+// The save before the last one, loaded when the last one is damaged
+const BACKUP_EXTENSION: &str = "bak";
+// Where the save is written before it takes the place of the old one
+const TEMP_EXTENSION: &str = "tmp";
+// First bytes of every gzip stream
+const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+
+fn slot_file(slot: &str, extension: &str) -> PathBuf {
+    data_dir(SAVES_DIR).join(format!("{}.{}", slot, extension))
+}
+
+// Slots of the saved characters, the last played first, along with the ones left
+// with only the save before the last after the game died while saving
+pub fn saved_games() -> Vec<String> {
+    let mut saves = fs::read_dir(data_dir(SAVES_DIR))
+        .into_iter()
+        .flatten()
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| {
+            path.extension()
+                .is_some_and(|e| e == SAVE_EXTENSION || e == BACKUP_EXTENSION)
+        })
+        .filter_map(|path| {
+            let modified = path.metadata().and_then(|m| m.modified()).ok()?;
+            Some((modified, path.file_stem()?.to_str()?.to_string()))
+        })
+        .collect::<Vec<_>>();
+    saves.sort_by_key(|&(modified, _)| Reverse(modified));
+    let mut slots: Vec<String> = vec![];
+    for (_, slot) in saves {
+        if !slots.contains(&slot) {
+            slots.push(slot);
+        }
+    }
+    slots
+}
 
+// Write the whole save aside and only then swap it in, so the game dying
+// mid-write leaves the old save as it was
 pub fn save_game(game: &Game, objects: &[Object]) -> Result<(), Box<dyn Error>> {
+    let slot = file_stem(&game.character.name);
+    let (save, backup, temp) = (
+        slot_file(&slot, SAVE_EXTENSION),
+        slot_file(&slot, BACKUP_EXTENSION),
+        slot_file(&slot, TEMP_EXTENSION),
+    );
     let save_data = serde_json::to_string(&(game, objects))?;
-    let mut file = File::create("savegame")?;
-    file.write_all(save_data.as_bytes())?;
+    let mut encoder = GzEncoder::new(File::create(&temp)?, Compression::default());
+    encoder.write_all(save_data.as_bytes())?;
+    encoder.finish()?.sync_all()?;
+    if save.exists() {
+        fs::rename(&save, backup)?;
+    }
+    fs::rename(temp, save)?;
+    Ok(())
+}
+
+// Remove the character's saves for good
+pub fn delete_save(name: &str) -> Result<(), Box<dyn Error>> {
+    let slot = file_stem(name);
+    for extension in &[SAVE_EXTENSION, BACKUP_EXTENSION] {
+        let path = slot_file(&slot, extension);
+        if path.exists() {
+            fs::remove_file(path)?;
+        }
+    }
     Ok(())
 }
 
-pub fn load_game() -> Result<(Game, Vec<Object>), Box<dyn Error>> {
-    let mut json_save_state = String::new();
-    let mut file = File::open("savegame")?;
-    file.read_to_string(&mut json_save_state)?;
-    let result = serde_json::from_str::<(Game, Vec<Object>)>(&json_save_state)?;
+// Load the last save in the slot, falling back to the one before it
+pub fn load_game(slot: &str) -> Result<(Game, Vec<Object>), Box<dyn Error>> {
+    let save = slot_file(slot, SAVE_EXTENSION);
+    read_save(&save).or_else(|e| {
+        let (mut game, objects) = read_save(&slot_file(slot, BACKUP_EXTENSION))?;
+        if save.exists() {
+            log_error(&format!("The save of {} is damaged: {}", slot, e));
+            game.messages
+                .add(tr("save.the_save_was_damaged", &[]), ORANGE);
+        }
+        Ok((game, objects))
+    })
+}
+
+// The gzip stream ends with a checksum of the save, checked once all of it is read
+fn read_save(path: &Path) -> Result<(Game, Vec<Object>), Box<dyn Error>> {
+    let mut data = vec![];
+    let mut file = File::open(path)?;
+    file.read_to_end(&mut data)?;
+    let json_save_state = if data.starts_with(&GZIP_MAGIC) {
+        let mut json = vec![];
+        GzDecoder::new(&data[..]).read_to_end(&mut json)?;
+        json
+    } else {
+        // Saves from before the compression are plain json
+        data
+    };
+    let result = serde_json::from_slice::<(Game, Vec<Object>)>(&json_save_state)?;
     Ok(result)
 }
@@ -0,0 +1,161 @@
+use serde::{Deserialize, Serialize};
+
+use crate::colors::*;
+
+use crate::lang::tr;
+use crate::theme::Palette;
+
+// Colors the screens are drawn in, chosen in the options for the eyes of the player
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub enum ColorScheme {
+    #[default]
+    Standard,
+    // Telling good from bad by blue and orange rather than green and red
+    Deuteranopia,
+    // Same, with the reds that look dark turned to bright orange
+    Protanopia,
+    // Bright text and walls over the black
+    HighContrast,
+}
+
+// Colors of the interface and of what's drawn over the map
+#[derive(Debug, Clone, Copy)]
+pub struct UiColors {
+    pub text: Color,
+    // Headings, hints and what the mouse points at
+    pub dim: Color,
+    // The line under the cursor and the name of the player
+    pub highlight: Color,
+    pub title: Color,
+    pub background: Color,
+    // A change for the better or the worse, in the comparison of the equipment
+    pub better: Color,
+    pub worse: Color,
+    pub health: Color,
+    pub boss_health: Color,
+    pub bar_back: Color,
+    pub experience: Color,
+    pub experience_back: Color,
+    // The mark over the monsters that just spotted the player
+    pub alert: Color,
+    pub threat: Color,
+    pub darkness: Color,
+    // Tiles known only from magic mapping
+    pub mapped_wall: Color,
+    pub mapped_ground: Color,
+}
+
+// The safe colors of Okabe and Ito
+const SKY_BLUE: Color = Color::new(86, 180, 233);
+const BLUISH_GREEN: Color = Color::new(0, 158, 115);
+const OKABE_ORANGE: Color = Color::new(230, 159, 0);
+const VERMILLION: Color = Color::new(213, 94, 0);
+const OKABE_BLUE: Color = Color::new(0, 114, 178);
+const OKABE_YELLOW: Color = Color::new(240, 228, 66);
+
+const STANDARD: UiColors = UiColors {
+    text: WHITE,
+    dim: LIGHT_GREY,
+    highlight: LIGHT_YELLOW,
+    title: LIGHT_RED,
+    background: BLACK,
+    better: LIGHT_GREEN,
+    worse: LIGHT_RED,
+    health: LIGHT_RED,
+    boss_health: ORANGE,
+    bar_back: DARKER_RED,
+    experience: LIGHT_VIOLET,
+    experience_back: DARKER_VIOLET,
+    alert: LIGHT_RED,
+    threat: RED,
+    darkness: DARK_GREY,
+    mapped_wall: Color::new(60, 20, 90),
+    mapped_ground: Color::new(35, 25, 70),
+};
+
+const DEUTERANOPIA: UiColors = UiColors {
+    better: SKY_BLUE,
+    worse: OKABE_ORANGE,
+    health: VERMILLION,
+    boss_health: OKABE_YELLOW,
+    bar_back: Color::new(70, 30, 0),
+    alert: OKABE_ORANGE,
+    threat: OKABE_BLUE,
+    ..STANDARD
+};
+
+const PROTANOPIA: UiColors = UiColors {
+    title: OKABE_ORANGE,
+    better: SKY_BLUE,
+    worse: OKABE_YELLOW,
+    health: OKABE_ORANGE,
+    boss_health: BLUISH_GREEN,
+    bar_back: Color::new(40, 40, 80),
+    alert: OKABE_YELLOW,
+    threat: OKABE_BLUE,
+    ..STANDARD
+};
+
+const HIGH_CONTRAST: UiColors = UiColors {
+    text: WHITE,
+    dim: LIGHTEST_GREY,
+    highlight: YELLOW,
+    title: WHITE,
+    background: BLACK,
+    better: CYAN,
+    worse: Color::new(255, 90, 90),
+    health: RED,
+    boss_health: Color::new(255, 128, 0),
+    bar_back: DARKEST_GREY,
+    experience: LIGHTER_BLUE,
+    experience_back: DARKEST_GREY,
+    alert: YELLOW,
+    threat: MAGENTA,
+    darkness: LIGHT_GREY,
+    mapped_wall: Color::new(120, 80, 200),
+    mapped_ground: Color::new(50, 35, 100),
+};
+
+impl ColorScheme {
+    pub fn name(self) -> String {
+        match self {
+            ColorScheme::Standard => tr("scheme.standard", &[]),
+            ColorScheme::Deuteranopia => tr("scheme.deuteranopia", &[]),
+            ColorScheme::Protanopia => tr("scheme.protanopia", &[]),
+            ColorScheme::HighContrast => tr("scheme.high_contrast", &[]),
+        }
+    }
+
+    pub fn next(self) -> Self {
+        match self {
+            ColorScheme::Standard => ColorScheme::Deuteranopia,
+            ColorScheme::Deuteranopia => ColorScheme::Protanopia,
+            ColorScheme::Protanopia => ColorScheme::HighContrast,
+            ColorScheme::HighContrast => ColorScheme::Standard,
+        }
+    }
+
+    pub fn ui(self) -> UiColors {
+        match self {
+            ColorScheme::Standard => STANDARD,
+            ColorScheme::Deuteranopia => DEUTERANOPIA,
+            ColorScheme::Protanopia => PROTANOPIA,
+            ColorScheme::HighContrast => HIGH_CONTRAST,
+        }
+    }
+
+    // The walls and the floor of the dungeon theme the way the scheme shows them.
+    // The themes keep their look except in high contrast, where the walls stand out
+    // from the floor far more than the themes can tell apart
+    pub fn palette(self, palette: Palette) -> Palette {
+        match self {
+            ColorScheme::HighContrast => Palette {
+                light_wall: WHITE,
+                dark_wall: GREY,
+                light_ground: DARK_GREY,
+                dark_ground: DARKEST_GREY,
+            },
+            _ => palette,
+        }
+    }
+}
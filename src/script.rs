@@ -0,0 +1,196 @@
+use std::cell::RefCell;
+use std::error::Error;
+use std::fs;
+use std::rc::Rc;
+
+use rand::Rng;
+use rhai::{Array, Dynamic, Engine, EvalAltResult, Map, Scope, INT};
+
+use crate::colors::*;
+
+use crate::errors::log_error;
+use crate::fov::line_of_sight;
+use crate::game::{Game, PLAYER};
+use crate::mods::mod_asset;
+use crate::object::Object;
+use crate::room::make_monster;
+use crate::spawn::{free_tile_near, spawn};
+use crate::status::{apply_effect, Effect, Status};
+
+// Scripts are found in the directory as <name>.rhai
+const SCRIPTS_DIR: &str = "data/scripts";
+// How much work a script may do before it's stopped, so a broken one can't hang the game
+const MAX_OPERATIONS: u64 = 100_000;
+
+// What a script asks the game to do, done once the script has finished
+#[derive(Debug, Clone)]
+enum Command {
+    Message(String),
+    Damage {
+        id: usize,
+        amount: i32,
+    },
+    Status {
+        id: usize,
+        status: Status,
+        turns: u32,
+    },
+    Spawn {
+        kind: String,
+        x: i32,
+        y: i32,
+    },
+}
+
+fn status_named(name: &str) -> Result<Status, String> {
+    match name {
+        "haste" => Ok(Status::Haste),
+        "slow" => Ok(Status::Slow),
+        "poison" => Ok(Status::Poison),
+        _ => Err(format!("There is no status {}", name)),
+    }
+}
+
+// What the script sees of an actor
+fn actor(id: usize, seen_from: (i32, i32), game: &mut Game, objects: &[Object]) -> Map {
+    let object = &objects[id];
+    let mut actor = Map::new();
+    actor.insert("id".into(), (id as INT).into());
+    actor.insert("name".into(), object.name.clone().into());
+    actor.insert("x".into(), (object.x as INT).into());
+    actor.insert("y".into(), (object.y as INT).into());
+    actor.insert(
+        "hp".into(),
+        (object.fighter.map_or(0, |f| f.hp) as INT).into(),
+    );
+    actor.insert("max_hp".into(), (object.max_hp(game) as INT).into());
+    actor.insert("player".into(), (id == PLAYER).into());
+    let seen = line_of_sight(&game.map, seen_from, object.pos());
+    actor.insert("in_sight".into(), seen.into());
+    actor
+}
+
+// Run the script of the name for the user, an item's reader, a monster using its ability
+// or whoever stepped on a trap, with the target the user is after if any.
+// Returns what the script ended with, true unless it says otherwise,
+// and false when the script is missing or broken
+pub fn run_script(
+    name: &str,
+    user: usize,
+    target: Option<usize>,
+    game: &mut Game,
+    objects: &mut [Object],
+) -> bool {
+    match eval(name, user, target, game, objects) {
+        Ok(result) => result,
+        Err(e) => {
+            log_error(&format!("The script {} failed: {}", name, e));
+            false
+        }
+    }
+}
+
+fn eval(
+    name: &str,
+    user: usize,
+    target: Option<usize>,
+    game: &mut Game,
+    objects: &mut [Object],
+) -> Result<bool, Box<dyn Error>> {
+    let source = fs::read_to_string(mod_asset(&format!("{}/{}.rhai", SCRIPTS_DIR, name)))?;
+    let commands = Rc::new(RefCell::new(vec![]));
+    // The script rolls the dice of the game, for the runs to stay reproducible
+    let rng = Rc::new(RefCell::new(game.rng.clone()));
+
+    // The only ways a script can touch the game
+    let mut engine = Engine::new();
+    engine.set_max_operations(MAX_OPERATIONS);
+    let queue = commands.clone();
+    engine.register_fn("message", move |text: &str| {
+        queue.borrow_mut().push(Command::Message(text.into()))
+    });
+    let queue = commands.clone();
+    engine.register_fn("damage", move |id: INT, amount: INT| {
+        queue.borrow_mut().push(Command::Damage {
+            id: id as usize,
+            amount: amount as i32,
+        })
+    });
+    let queue = commands.clone();
+    engine.register_fn(
+        "status",
+        move |id: INT, name: &str, turns: INT| -> Result<(), Box<EvalAltResult>> {
+            let status = status_named(name)?;
+            queue.borrow_mut().push(Command::Status {
+                id: id as usize,
+                status,
+                turns: turns.max(0) as u32,
+            });
+            Ok(())
+        },
+    );
+    let queue = commands.clone();
+    engine.register_fn("summon", move |kind: &str, x: INT, y: INT| {
+        queue.borrow_mut().push(Command::Spawn {
+            kind: kind.into(),
+            x: x as i32,
+            y: y as i32,
+        })
+    });
+    let dice = rng.clone();
+    engine.register_fn("roll", move |low: INT, high: INT| {
+        if low >= high {
+            low
+        } else {
+            dice.borrow_mut().gen_range(low, high)
+        }
+    });
+
+    // What the script knows about the level, the actors seen from where the user stands
+    let from = objects[user].pos();
+    let actors = (0..objects.len())
+        .filter(|&id| objects[id].alive && objects[id].fighter.is_some())
+        .map(|id| Dynamic::from(actor(id, from, game, objects)))
+        .collect::<Array>();
+    let mut scope = Scope::new();
+    scope.push_constant("user", actor(user, from, game, objects));
+    let target = match target {
+        Some(target) => Dynamic::from(actor(target, from, game, objects)),
+        None => Dynamic::UNIT,
+    };
+    scope.push_constant("target", target);
+    scope.push_constant("actors", actors);
+    scope.push_constant("level", game.dungeon_level as INT);
+
+    let result = engine.eval_with_scope::<Dynamic>(&mut scope, &source)?;
+    game.rng = rng.borrow().clone();
+    for command in commands.take() {
+        apply(command, user, game, objects);
+    }
+    Ok(result.as_bool().unwrap_or(true))
+}
+
+fn apply(command: Command, user: usize, game: &mut Game, objects: &mut [Object]) {
+    let exists = |id: usize| id < objects.len() && objects[id].alive;
+    match command {
+        Command::Message(text) => game.messages.add(text, WHITE),
+        Command::Damage { id, amount } if exists(id) => {
+            let xp = objects[id].take_damage(amount, user == PLAYER, game);
+            if let (Some(xp), PLAYER) = (xp, user) {
+                objects[PLAYER].fighter.as_mut().unwrap().xp += xp;
+            }
+        }
+        Command::Status { id, status, turns } if exists(id) => {
+            apply_effect(&mut objects[id], Effect { status, turns }, game)
+        }
+        Command::Spawn { kind, x, y } => {
+            let tile = free_tile_near(x, y, game, objects);
+            if let (Some(mut monster), Some((x, y))) = (make_monster(&kind, x, y), tile) {
+                monster.set_pos(x, y);
+                spawn(monster, game);
+            }
+        }
+        // Aimed at someone gone already
+        _ => {}
+    }
+}
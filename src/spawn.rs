@@ -0,0 +1,53 @@
+use crate::colors::*;
+
+use crate::game::{is_blocked, Game};
+use crate::lang::tr;
+use crate::object::Object;
+
+// Objects can't be added to the map in the middle of a turn, while the monsters act,
+// so they wait in the game until the turn is over
+
+// Queue the object to appear on the map at the end of the turn
+pub fn spawn(object: Object, game: &mut Game) {
+    game.spawned.push(object);
+}
+
+// Put everything spawned during the turn on the map
+pub fn place_spawned(game: &mut Game, objects: &mut Vec<Object>) {
+    objects.append(&mut game.spawned);
+}
+
+// Free tile around the spot, not taken by anything on the map or about to appear on it
+pub fn free_tile_near(x: i32, y: i32, game: &Game, objects: &[Object]) -> Option<(i32, i32)> {
+    (-1..=1)
+        .flat_map(|dx| (-1..=1).map(move |dy| (x + dx, y + dy)))
+        .filter(|&(nx, ny)| game.map.in_bounds(nx, ny))
+        .find(|&(nx, ny)| {
+            !is_blocked(nx, ny, &game.map, objects)
+                && !game
+                    .spawned
+                    .iter()
+                    .any(|object| object.blocks && object.pos() == (nx, ny))
+        })
+}
+
+// Call the monster to the side of the caster, fighting for it, returns whether there was room
+pub fn summon(mut monster: Object, caster_id: usize, game: &mut Game, objects: &[Object]) -> bool {
+    let caster = &objects[caster_id];
+    match free_tile_near(caster.x, caster.y, game, objects) {
+        Some((x, y)) => {
+            game.messages.add(
+                tr(
+                    "spawn.summons",
+                    &[("caster", &caster.name), ("monster", &monster.name)],
+                ),
+                LIGHT_VIOLET,
+            );
+            monster.set_pos(x, y);
+            monster.faction = caster.faction;
+            spawn(monster, game);
+            true
+        }
+        None => false,
+    }
+}
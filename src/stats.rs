@@ -0,0 +1,213 @@
+use std::cmp::Reverse;
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::fs::File;
+
+use std::io::{Read, Write};
+
+use serde::{Deserialize, Serialize};
+
+use crate::clock::format_time;
+use crate::event::GameEvent;
+use crate::game::Game;
+use crate::lang::tr;
+use crate::object::Object;
+use crate::paths::{data_dir, data_file, file_stem};
+use crate::render::{msgbox, Context};
+
+// Every character leaves a morgue file of their own in the directory
+const MORGUES_DIR: &str = "morgues";
+const SCORES_FILE: &str = "scores.json";
+// How many of the best runs the high score table keeps
+const MAX_SCORES: usize = 10;
+const STATS_SCREEN_WIDTH: i32 = 40;
+// How many of the last messages go into the morgue file
+const MORGUE_MESSAGES: usize = 10;
+
+// What the player did during the run, shown when it ends
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Stats {
+    pub turns: u32,
+    pub damage_dealt: i32,
+    pub damage_taken: i32,
+    // Monsters the player killed, by name
+    pub kills: BTreeMap<String, u32>,
+    pub uniques_slain: Vec<String>,
+    pub items_used: u32,
+    pub deepest_level: u32,
+    // Turns spent on every dungeon level
+    pub depth_turns: BTreeMap<u32, u32>,
+}
+
+// Count what the player did as it happens
+pub fn record_stats(event: &GameEvent, game: &mut Game) {
+    let stats = &mut game.stats;
+    match *event {
+        GameEvent::Damaged {
+            damage,
+            to_player: true,
+            ..
+        } => stats.damage_taken += damage,
+        GameEvent::Damaged {
+            damage,
+            by_player: true,
+            ..
+        } => stats.damage_dealt += damage,
+        GameEvent::Died {
+            ref name,
+            by_player: true,
+            player: false,
+        } => stats.record_kill(name),
+        GameEvent::LevelChanged { level } => stats.deepest_level = stats.deepest_level.max(level),
+        _ => {}
+    }
+}
+
+impl Stats {
+    pub fn record_kill(&mut self, name: &str) {
+        *self.kills.entry(name.into()).or_insert(0) += 1;
+    }
+
+    // Another turn passed on the level
+    pub fn record_turn(&mut self, level: u32) {
+        self.turns += 1;
+        *self.depth_turns.entry(level).or_insert(0) += 1;
+    }
+
+    pub fn summary(&self, play_time: u64) -> String {
+        let mut summary = tr(
+            "stats.summary",
+            &[
+                ("turns", &self.turns),
+                ("time", &format_time(play_time)),
+                ("deepest_level", &self.deepest_level),
+                ("damage_dealt", &self.damage_dealt),
+                ("damage_taken", &self.damage_taken),
+                ("items_used", &self.items_used),
+                ("kills", &self.kills.values().sum::<u32>()),
+            ],
+        );
+        for (name, count) in &self.kills {
+            summary.push_str(&format!("\n  {} x{}", name, count));
+        }
+        if !self.depth_turns.is_empty() {
+            summary.push_str(&tr("stats.turns_by_depth", &[]));
+        }
+        for (level, turns) in &self.depth_turns {
+            summary.push_str(&tr(
+                "stats.depth_turns",
+                &[("level", level), ("turns", turns)],
+            ));
+        }
+        if !self.uniques_slain.is_empty() {
+            let slain = self.uniques_slain.join(", ");
+            summary.push_str(&tr("stats.slain", &[("slain", &slain)]));
+        }
+        summary
+    }
+}
+
+// A finished run in the high score table
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Score {
+    pub name: String,
+    pub race: String,
+    pub class: String,
+    pub level: i32,
+    pub deepest_level: u32,
+    pub turns: u32,
+    // Real time the run took, in milliseconds
+    #[serde(default)]
+    pub play_time: u64,
+}
+
+pub fn load_scores() -> Result<Vec<Score>, Box<dyn Error>> {
+    let mut json_scores = String::new();
+    let mut file = File::open(data_file(SCORES_FILE))?;
+    file.read_to_string(&mut json_scores)?;
+    let result = serde_json::from_str::<Vec<Score>>(&json_scores)?;
+    Ok(result)
+}
+
+// Put the run into the high score table, the deepest first, then the most
+// experienced, then the quickest in turns and then in real time
+pub fn record_score(game: &Game, player: &Object) -> Result<(), Box<dyn Error>> {
+    let mut scores = load_scores().unwrap_or_default();
+    scores.push(Score {
+        name: game.character.name.clone(),
+        race: game.character.race.name(),
+        class: game.character.class.name(),
+        level: player.level,
+        deepest_level: game.stats.deepest_level,
+        turns: game.stats.turns,
+        play_time: game.play_time,
+    });
+    scores.sort_by_key(|score| {
+        (
+            Reverse(score.deepest_level),
+            Reverse(score.level),
+            score.turns,
+            score.play_time,
+        )
+    });
+    scores.truncate(MAX_SCORES);
+    let scores_data = serde_json::to_string(&scores)?;
+    let mut file = File::create(data_file(SCORES_FILE))?;
+    file.write_all(scores_data.as_bytes())?;
+    Ok(())
+}
+
+pub fn show_stats(game: &Game, ctx: &mut Context) {
+    let mut text = tr(
+        "stats.run_over",
+        &[("summary", &game.stats.summary(game.play_time))],
+    );
+    let scores = load_scores().unwrap_or_default();
+    if !scores.is_empty() {
+        text.push_str(&tr("stats.high_scores", &[]));
+    }
+    for (place, score) in scores.iter().enumerate() {
+        text.push_str(&tr(
+            "stats.score",
+            &[
+                ("place", &format!("{:>2}", place + 1)),
+                ("name", &score.name),
+                ("race", &score.race),
+                ("class", &score.class),
+                ("level", &score.level),
+                ("depth", &score.deepest_level),
+                ("turns", &score.turns),
+                ("time", &format_time(score.play_time)),
+            ],
+        ));
+    }
+    msgbox(&text, STATS_SCREEN_WIDTH, ctx);
+}
+
+// Leave a record of the dead character next to the game
+pub fn write_morgue(game: &Game, player: &Object) -> Result<(), Box<dyn Error>> {
+    let character = &game.character;
+    let mut messages = game
+        .messages
+        .iter()
+        .rev()
+        .take(MORGUE_MESSAGES)
+        .map(|(message, _)| message.as_str())
+        .collect::<Vec<_>>();
+    messages.reverse();
+    let morgue = format!(
+        "{}, the {} {}, level {}\nDied on dungeon level {}\n\n{}\n\nLast messages:\n{}\n",
+        character.name,
+        character.race.name(),
+        character.class.name(),
+        player.level,
+        game.dungeon_level,
+        game.stats.summary(game.play_time),
+        messages.join("\n"),
+    );
+    let morgue_file = data_dir(MORGUES_DIR).join(format!("{}.txt", file_stem(&character.name)));
+    let mut file = File::create(morgue_file)?;
+    file.write_all(morgue.as_bytes())?;
+    Ok(())
+}
@@ -0,0 +1,144 @@
+use serde::{Deserialize, Serialize};
+
+use crate::colors::*;
+
+use crate::game::{Game, PLAYER};
+use crate::lang::tr;
+use crate::object::Object;
+
+// Health the poison takes every turn
+const POISON_DAMAGE: i32 = 1;
+
+// Lasting conditions changing how an actor does things
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Status {
+    // Acting twice as fast
+    Haste,
+    // Acting half as fast
+    Slow,
+    // Losing health every turn
+    Poison,
+}
+
+impl Status {
+    pub fn name(self) -> String {
+        match self {
+            Status::Haste => tr("status.haste", &[]),
+            Status::Slow => tr("status.slow", &[]),
+            Status::Poison => tr("status.poison", &[]),
+        }
+    }
+
+    pub fn color(self) -> Color {
+        match self {
+            Status::Haste => LIGHT_CYAN,
+            Status::Slow => LIGHT_PURPLE,
+            Status::Poison => GREEN,
+        }
+    }
+
+    // The status the effect cancels out, if any
+    fn opposite(self) -> Option<Status> {
+        match self {
+            Status::Haste => Some(Status::Slow),
+            Status::Slow => Some(Status::Haste),
+            Status::Poison => None,
+        }
+    }
+}
+
+// Status put on an actor for some turns
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Effect {
+    pub status: Status,
+    pub turns: u32,
+}
+
+pub fn has_status(object: &Object, status: Status) -> bool {
+    object.effects.iter().any(|effect| effect.status == status)
+}
+
+// Put the effect on the object, curing the opposite one,
+// and renewing it when it's already there
+pub fn apply_effect(object: &mut Object, effect: Effect, game: &mut Game) {
+    let opposite = effect.status.opposite();
+    if let Some(opposite) = opposite.filter(|&opposite| has_status(object, opposite)) {
+        object.effects.retain(|other| other.status != opposite);
+        game.messages.add(
+            tr(
+                "status.is_no_longer",
+                &[
+                    ("object", &object.name),
+                    ("opposite", &opposite.name().to_lowercase()),
+                ],
+            ),
+            LIGHT_GREY,
+        );
+        return;
+    }
+    match object
+        .effects
+        .iter_mut()
+        .find(|other| other.status == effect.status)
+    {
+        Some(other) => other.turns = other.turns.max(effect.turns),
+        None => {
+            game.messages.add(
+                tr(
+                    "status.is",
+                    &[
+                        ("object", &object.name),
+                        ("status", &effect.status.name().to_lowercase()),
+                    ],
+                ),
+                effect.status.color(),
+            );
+            object.effects.push(effect);
+        }
+    }
+}
+
+// Count down the effects on everyone, taking off the ones that wore off,
+// and let the poison do its work
+pub fn tick_effects(game: &mut Game, objects: &mut [Object]) {
+    for (id, object) in objects.iter_mut().enumerate() {
+        if object.alive && has_status(object, Status::Poison) {
+            object.take_damage(POISON_DAMAGE, false, game);
+        }
+        for effect in object.effects.iter_mut() {
+            effect.turns = effect.turns.saturating_sub(1);
+            if effect.turns == 0 && id == PLAYER {
+                game.messages.add(
+                    tr(
+                        "status.you_are_no_longer",
+                        &[("status", &effect.status.name().to_lowercase())],
+                    ),
+                    LIGHT_GREY,
+                );
+            }
+        }
+        object.effects.retain(|effect| effect.turns > 0);
+    }
+}
+
+// Times the monster acts on the turn, the slowed ones sitting out every other turn
+pub fn moves_on_turn(object: &Object, turn: u32) -> u32 {
+    if has_status(object, Status::Haste) {
+        2
+    } else if has_status(object, Status::Slow) {
+        turn % 2
+    } else {
+        1
+    }
+}
+
+// Time the player's action takes with their speed
+pub fn action_time(object: &Object, time: i32) -> i32 {
+    if has_status(object, Status::Haste) {
+        time / 2
+    } else if has_status(object, Status::Slow) {
+        time * 2
+    } else {
+        time
+    }
+}
@@ -0,0 +1,84 @@
+use crate::action::tick_delayed;
+use crate::ai::monsters_take_turns;
+use crate::daynight::tick_clock;
+use crate::dijkstra::update_dijkstra_maps;
+use crate::fields::{burn_in_lava, tick_fields};
+use crate::food::get_hungry;
+use crate::game::Game;
+use crate::hireling::pay_hirelings;
+use crate::light::burn_fuel;
+use crate::object::Object;
+use crate::reload::DataWatcher;
+use crate::render::Context;
+use crate::respawn::repopulate;
+use crate::status::tick_effects;
+use crate::theme::ambient_message;
+use crate::trap::spring_traps;
+
+// A piece of the game logic run every turn of the monsters
+pub trait System {
+    fn run(&mut self, ctx: &mut Context, game: &mut Game, objects: &mut Vec<Object>);
+}
+
+// The systems keeping no state of their own are plain functions
+pub type SystemFn = fn(&mut Context, &mut Game, &mut Vec<Object>);
+
+impl System for SystemFn {
+    fn run(&mut self, ctx: &mut Context, game: &mut Game, objects: &mut Vec<Object>) {
+        self(ctx, game, objects)
+    }
+}
+
+// Systems run one after another, in the order they were added
+#[derive(Default)]
+pub struct Systems {
+    systems: Vec<Box<dyn System>>,
+}
+
+impl Systems {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    pub fn with(self, system: SystemFn) -> Self {
+        self.with_system(system)
+    }
+
+    pub fn with_system(mut self, system: impl System + 'static) -> Self {
+        self.systems.push(Box::new(system));
+        self
+    }
+
+    // What happens every turn, the monsters acting first, the world reacting to them
+    // and the clock moving on last. Debug builds look for changed data files before all
+    pub fn turn() -> Self {
+        let systems = if cfg!(debug_assertions) {
+            Systems::new().with_system(DataWatcher::default())
+        } else {
+            Systems::new()
+        };
+        systems
+            .with(|_, game, objects| update_dijkstra_maps(game, objects))
+            .with(|ctx, game, objects| monsters_take_turns(ctx, game, objects))
+            .with(|_, game, objects| tick_delayed(game, objects))
+            .with(|_, game, objects| tick_effects(game, objects))
+            .with(|_, game, objects| spring_traps(game, objects))
+            .with(|_, game, objects| tick_fields(game, objects))
+            .with(|_, game, objects| burn_in_lava(game, objects))
+            .with(|_, game, objects| get_hungry(game, objects))
+            .with(|_, game, _| burn_fuel(game))
+            .with(|ctx, game, _| ambient_message(ctx, game))
+            .with(|_, game, objects| pay_hirelings(game, objects))
+            .with(|ctx, game, objects| repopulate(ctx, game, objects))
+            .with(|ctx, game, _| {
+                game.stats.record_turn(game.dungeon_level);
+                tick_clock(ctx, game);
+            })
+    }
+
+    pub fn run(&mut self, ctx: &mut Context, game: &mut Game, objects: &mut Vec<Object>) {
+        for system in self.systems.iter_mut() {
+            system.run(ctx, game, objects);
+        }
+    }
+}
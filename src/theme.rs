@@ -0,0 +1,170 @@
+use rand::Rng;
+
+use crate::colors::*;
+
+use crate::config::Verbosity;
+use crate::game::Game;
+use crate::lang::tr;
+use crate::overworld::Branch;
+use crate::render::Context;
+
+// Chance in per mille of hearing the level every turn
+const AMBIENT_CHANCE: i32 = 5;
+// Deepest levels of the first and the second theme of a branch, the third lies below them
+const FIRST_DEPTH: u32 = 3;
+const SECOND_DEPTH: u32 = 6;
+
+// Look and feel of a range of depths
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Theme {
+    Surface,
+    Catacombs,
+    FungalCaverns,
+    Forge,
+}
+
+// Themes below the surface, in the order the crypt goes through them,
+// the other branches start further along and wrap around
+const DEPTHS: [Theme; 3] = [Theme::Catacombs, Theme::FungalCaverns, Theme::Forge];
+
+// Colors of the walls and the floor, in sight and remembered
+#[derive(Debug, Clone, Copy)]
+pub struct Palette {
+    pub light_wall: Color,
+    pub dark_wall: Color,
+    pub light_ground: Color,
+    pub dark_ground: Color,
+}
+
+// What the themes put more of into the rooms
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Decor {
+    Statue,
+    Brazier,
+    Prop,
+    Foliage,
+}
+
+impl Theme {
+    // Theme of the level of the branch, the surface lies above all of them
+    pub fn at(branch: Branch, level: u32) -> Self {
+        let range = match level {
+            0 => return Theme::Surface,
+            level if level <= FIRST_DEPTH => 0,
+            level if level <= SECOND_DEPTH => 1,
+            _ => 2,
+        };
+        let first = DEPTHS
+            .iter()
+            .position(|&theme| theme == branch.first_theme())
+            .unwrap_or(0);
+        DEPTHS[(first + range) % DEPTHS.len()]
+    }
+
+    pub fn name(self) -> String {
+        match self {
+            Theme::Surface => tr("theme.surface", &[]),
+            Theme::Catacombs => tr("theme.catacombs", &[]),
+            Theme::FungalCaverns => tr("theme.fungal_caverns", &[]),
+            Theme::Forge => tr("theme.forge", &[]),
+        }
+    }
+
+    pub fn palette(self) -> Palette {
+        match self {
+            Theme::Surface => Palette {
+                light_wall: Color::new(140, 130, 120),
+                dark_wall: Color::new(50, 45, 40),
+                light_ground: Color::new(120, 170, 90),
+                dark_ground: Color::new(30, 60, 30),
+            },
+            Theme::Catacombs => Palette {
+                light_wall: Color::new(130, 110, 150),
+                dark_wall: Color::new(0, 0, 100),
+                light_ground: Color::new(200, 180, 150),
+                dark_ground: Color::new(50, 50, 150),
+            },
+            Theme::FungalCaverns => Palette {
+                light_wall: Color::new(90, 130, 80),
+                dark_wall: Color::new(10, 50, 30),
+                light_ground: Color::new(150, 170, 120),
+                dark_ground: Color::new(30, 70, 50),
+            },
+            Theme::Forge => Palette {
+                light_wall: Color::new(150, 90, 60),
+                dark_wall: Color::new(70, 20, 10),
+                light_ground: Color::new(190, 150, 110),
+                dark_ground: Color::new(80, 40, 30),
+            },
+        }
+    }
+
+    // Double the chance of the decoration the theme is known for
+    pub fn decor_chance(self, decor: Decor, chance: i32) -> i32 {
+        let favored = match self {
+            Theme::Surface => false,
+            Theme::Catacombs => decor == Decor::Statue || decor == Decor::Prop,
+            Theme::FungalCaverns => decor == Decor::Foliage,
+            Theme::Forge => decor == Decor::Brazier,
+        };
+        if favored {
+            chance * 2
+        } else {
+            chance
+        }
+    }
+
+    // Double the weight of the monsters at home in the theme
+    pub fn spawn_weight(self, monster: &str, weight: u32) -> u32 {
+        let favored = match self {
+            Theme::Surface => false,
+            Theme::Catacombs => ["skeleton", "ghoul", "necromancer"].contains(&monster),
+            Theme::FungalCaverns => ["wolf", "goblin"].contains(&monster),
+            Theme::Forge => ["orc", "goblin archer"].contains(&monster),
+        };
+        if favored {
+            weight * 2
+        } else {
+            weight
+        }
+    }
+
+    // Ids of the texts told now and then on the levels of the theme
+    fn ambience(self) -> &'static [&'static str] {
+        match self {
+            Theme::Surface => &[
+                "theme.surface_ambience_1",
+                "theme.surface_ambience_2",
+                "theme.surface_ambience_3",
+            ],
+            Theme::Catacombs => &[
+                "theme.catacombs_ambience_1",
+                "theme.catacombs_ambience_2",
+                "theme.catacombs_ambience_3",
+            ],
+            Theme::FungalCaverns => &[
+                "theme.fungal_caverns_ambience_1",
+                "theme.fungal_caverns_ambience_2",
+                "theme.fungal_caverns_ambience_3",
+            ],
+            Theme::Forge => &[
+                "theme.forge_ambience_1",
+                "theme.forge_ambience_2",
+                "theme.forge_ambience_3",
+            ],
+        }
+    }
+}
+
+// Now and then tell the player what the level around them sounds like
+pub fn ambient_message(ctx: &Context, game: &mut Game) {
+    if game.rng.gen_range(0, 1000) >= AMBIENT_CHANCE {
+        return;
+    }
+    let messages = Theme::at(game.branch, game.dungeon_level).ambience();
+    // Drawn either way, the brief log only leaves it out
+    let message = messages[game.rng.gen_range(0, messages.len())];
+    if ctx.options.verbosity == Verbosity::Full {
+        game.messages.add(tr(message, &[]), DARK_GREY);
+    }
+}
@@ -0,0 +1,58 @@
+use crate::ai::{moves_this_turn, tree_kind, Ai};
+use crate::behavior::{Behavior, MELEE_REACH};
+use crate::combat::reach_distance;
+use crate::faction::hostile;
+use crate::fov::line_of_sight;
+use crate::game::{Game, MAP_HEIGHT, MAP_WIDTH, PLAYER};
+use crate::object::Object;
+use crate::render::Context;
+
+// How many times the monster acts on the next turn, and how far it hits from
+pub fn threat_range(monster: &Object, game: &Game) -> (u32, f32) {
+    let moves = moves_this_turn(monster, game);
+    let melee = reach_distance(monster.reach(game));
+    let reach = monster
+        .ai
+        .as_ref()
+        .and_then(tree_kind)
+        .and_then(|kind| game.behaviors.get(kind))
+        .map_or_else(|| Behavior::basic().reach(melee), |tree| tree.reach(melee));
+    (moves, reach)
+}
+
+// Tiles the enemies in sight could hit on their next turn, stepping closer first
+// if they're quick enough to act more than once
+pub fn threat_map(ctx: &Context, game: &Game, objects: &[Object]) -> Vec<bool> {
+    let mut threatened = vec![false; (MAP_WIDTH * MAP_HEIGHT) as usize];
+    let enemies = objects.iter().filter(|object| {
+        object.alive
+            && object.fighter.is_some()
+            && hostile(object.faction, objects[PLAYER].faction)
+            && ctx.fov.is_in_fov(object.x, object.y)
+            && !matches!(object.ai, None | Some(Ai::Sleeping { .. }))
+    });
+    for monster in enemies {
+        let (moves, reach) = threat_range(monster, game);
+        if moves == 0 || reach <= 0.0 {
+            continue;
+        }
+        // Every move but the last can be a step, diagonal ones too
+        let steps = moves as i32 - 1;
+        let radius = reach.min(MAP_WIDTH as f32).ceil() as i32 + steps;
+        for y in (monster.y - radius).max(0)..=(monster.y + radius).min(MAP_HEIGHT - 1) {
+            for x in (monster.x - radius).max(0)..=(monster.x + radius).min(MAP_WIDTH - 1) {
+                let dx = ((x - monster.x).abs() - steps).max(0);
+                let dy = ((y - monster.y).abs() - steps).max(0);
+                let distance = ((dx * dx + dy * dy) as f32).sqrt();
+                // Arrows and polearms need a clear line, blows only to be next to the target
+                let hit = distance < reach
+                    && !game.map[(x, y)].is_wall()
+                    && (distance < MELEE_REACH || line_of_sight(&game.map, monster.pos(), (x, y)));
+                if hit {
+                    threatened[(y * MAP_WIDTH + x) as usize] = true;
+                }
+            }
+        }
+    }
+    threatened
+}
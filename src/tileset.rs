@@ -0,0 +1,69 @@
+use std::collections::HashMap;
+
+// Glyphs of the map itself, drawn only when tiles are used instead of background colors
+pub const WALL_GLYPH: char = '\u{b1}';
+pub const FLOOR_GLYPH: char = '.';
+
+// Sprites get their own codes past the text glyphs of the sheet,
+// so messages and menus keep using the regular font
+pub const FIRST_SPRITE_CODE: u32 = 256;
+
+// Registry of glyphs and the sprite sheet cells they are drawn with
+#[derive(Default)]
+pub struct TileMapping {
+    tiles: HashMap<char, char>,
+    sprites: Vec<(char, i32, i32)>,
+}
+
+impl TileMapping {
+    pub fn new() -> Self {
+        TileMapping {
+            tiles: HashMap::new(),
+            sprites: vec![],
+        }
+    }
+
+    // Draw the glyph with the sprite at (column, row) of the sheet
+    pub fn register(&mut self, glyph: char, column: i32, row: i32) {
+        let code = std::char::from_u32(FIRST_SPRITE_CODE + self.sprites.len() as u32)
+            .expect("Too many sprites registered");
+        self.tiles.insert(glyph, code);
+        self.sprites.push((code, column, row));
+    }
+
+    // Get the code to put on the console for the glyph
+    pub fn tile(&self, glyph: char) -> char {
+        self.tiles.get(&glyph).cloned().unwrap_or(glyph)
+    }
+
+    // Codes of the sprites and their (column, row) in the sheet
+    pub fn sprites(&self) -> &[(char, i32, i32)] {
+        &self.sprites
+    }
+}
+
+// Sprites for every glyph the game draws on the map,
+// placed in the rows below the text glyphs of the sheet
+pub fn standard_mapping() -> TileMapping {
+    let mut mapping = TileMapping::new();
+
+    // Terrain
+    mapping.register(WALL_GLYPH, 0, 16);
+    mapping.register(FLOOR_GLYPH, 1, 16);
+    mapping.register('>', 2, 16);
+
+    // Characters
+    mapping.register('@', 0, 17);
+    mapping.register('g', 1, 17);
+    mapping.register('o', 2, 17);
+    mapping.register('%', 3, 17);
+
+    // Items
+    mapping.register('!', 0, 18);
+    mapping.register('#', 1, 18);
+    mapping.register('/', 2, 18);
+    mapping.register('-', 3, 18);
+    mapping.register('0', 4, 18);
+
+    mapping
+}
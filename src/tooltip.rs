@@ -0,0 +1,185 @@
+use crate::colors::Color;
+
+use crate::game::{Game, Terrain, PLAYER};
+use crate::item::category;
+use crate::lang::tr;
+use crate::object::Object;
+use crate::render::{Context, Layout, View};
+use crate::scheme::UiColors;
+
+// How far from the cursor the box is drawn
+const TOOLTIP_OFFSET: i32 = 2;
+
+// Hits it takes to bring the defender down, none if the attacker can't hurt them
+fn hits_to_kill(attacker: &Object, defender: &Object, game: &mut Game) -> Option<i32> {
+    let damage = attacker.power(game) + attacker.fire_damage(game) - defender.defense(game);
+    let hp = defender.fighter.map_or(0, |f| f.hp);
+    (damage > 0).then(|| (hp + damage - 1) / damage)
+}
+
+// How the fight with the monster would likely go, trading blows until one falls
+fn danger(
+    monster: &Object,
+    game: &mut Game,
+    objects: &[Object],
+    colors: &UiColors,
+) -> (String, Color) {
+    let player = &objects[PLAYER];
+    let to_die = hits_to_kill(monster, player, game);
+    let to_win = hits_to_kill(player, monster, game);
+    match (to_die, to_win) {
+        (None, _) => (tr("tooltip.harmless", &[]), colors.better),
+        (Some(_), None) => (tr("tooltip.deadly", &[]), colors.worse),
+        (Some(die), Some(win)) if die <= win => (tr("tooltip.deadly", &[]), colors.worse),
+        (Some(die), Some(win)) if die <= win * 2 => {
+            (tr("tooltip.dangerous", &[]), colors.highlight)
+        }
+        _ => (tr("tooltip.easy", &[]), colors.text),
+    }
+}
+
+fn monster_lines(
+    monster: &Object,
+    game: &mut Game,
+    objects: &[Object],
+    colors: &UiColors,
+) -> Vec<(String, Color)> {
+    let hp = monster.fighter.map_or(0, |f| f.hp);
+    let mut lines = vec![
+        (monster.name.clone(), monster.color),
+        (
+            tr(
+                "tooltip.hp",
+                &[("hp", &hp), ("max_hp", &monster.max_hp(game))],
+            ),
+            colors.text,
+        ),
+    ];
+    let (danger, color) = danger(monster, game, objects, colors);
+    lines.push((tr("tooltip.danger", &[("danger", &danger)]), color));
+    for effect in &monster.effects {
+        lines.push((
+            tr(
+                "tooltip.status",
+                &[("status", &effect.status.name()), ("turns", &effect.turns)],
+            ),
+            effect.status.color(),
+        ));
+    }
+    lines
+}
+
+fn item_lines(item: &Object, colors: &UiColors) -> Vec<(String, Color)> {
+    let color = item.rarity.color().unwrap_or(colors.text);
+    let mut lines = vec![
+        (item.name.clone(), color),
+        (category(item).name(), colors.dim),
+    ];
+    if let Some(equipment) = item.equipment {
+        let bonuses = [
+            ("render.attack", equipment.power_bonus),
+            ("render.defense", equipment.defense_bonus),
+            ("render.max_hp", equipment.max_hp_bonus),
+            ("render.fire", equipment.fire_damage),
+            ("render.evasion", equipment.evasion_bonus),
+            ("render.block", equipment.block_chance),
+        ];
+        for &(stat, bonus) in bonuses.iter().filter(|&&(_, bonus)| bonus != 0) {
+            lines.push((
+                tr(
+                    "tooltip.bonus",
+                    &[("stat", &tr(stat, &[])), ("bonus", &format!("{:+}", bonus))],
+                ),
+                colors.text,
+            ));
+        }
+    }
+    if let Some(brand) = item.equipment.and_then(|e| e.brand) {
+        lines.push((brand.name(), colors.text));
+    }
+    if let Some(reach) = item.equipment.map(|e| e.reach).filter(|&reach| reach > 1) {
+        lines.push((tr("tooltip.reach", &[("reach", &reach)]), colors.text));
+    }
+    if let Some(charges) = item.charges {
+        lines.push((tr("tooltip.charges", &[("charges", &charges)]), colors.text));
+    }
+    if let Some(fuel) = item.fuel {
+        lines.push((tr("tooltip.fuel", &[("fuel", &fuel)]), colors.text));
+    }
+    lines
+}
+
+fn terrain_name(x: i32, y: i32, game: &Game) -> String {
+    let tile = &game.map[(x, y)];
+    let id = match tile.terrain {
+        Terrain::Door => "tooltip.door",
+        Terrain::Foliage => "tooltip.foliage",
+        Terrain::Rubble => "tooltip.rubble",
+        Terrain::Chasm => "tooltip.chasm",
+        Terrain::Lava => "tooltip.lava",
+        Terrain::Plain if tile.is_wall() => "tooltip.wall",
+        Terrain::Plain => "tooltip.floor",
+    };
+    tr(id, &[])
+}
+
+// Box next to the cursor telling about what's on the map under it: how hurt and how
+// dangerous the monsters in sight are, what the items are, and what the tile is
+pub fn draw_tooltip(ctx: &mut Context, view: &View, game: &mut Game, objects: &[Object]) {
+    let (x, y) = match view.to_map(ctx.mouse.cx, ctx.mouse.cy) {
+        Some(position) => position,
+        None => return,
+    };
+    let tile = &game.map[(x, y)];
+    if !tile.explored && !tile.mapped {
+        return;
+    }
+
+    let colors = ctx.options.colors.ui();
+    let mut lines = vec![];
+    if ctx.fov.is_in_fov(x, y) {
+        let here = objects
+            .iter()
+            .enumerate()
+            .filter(|(_, object)| object.pos() == (x, y));
+        for (id, object) in here {
+            if id != PLAYER && object.alive && object.fighter.is_some() {
+                lines.extend(monster_lines(object, game, objects, &colors));
+            } else if object.item.is_some() {
+                lines.extend(item_lines(object, &colors));
+            } else {
+                lines.push((object.name.clone(), object.color));
+            }
+        }
+    }
+    lines.push((terrain_name(x, y, game), colors.dim));
+
+    // Next to the cursor, on the other side of it near the edge of the screen
+    let layout = Layout::of(&*ctx.renderer);
+    let width = lines
+        .iter()
+        .map(|(line, _)| line.chars().count() as i32)
+        .max()
+        .unwrap_or(0)
+        + 2;
+    let height = lines.len() as i32;
+    let mut left = ctx.mouse.cx + TOOLTIP_OFFSET;
+    if left + width > layout.width {
+        left = ctx.mouse.cx - TOOLTIP_OFFSET - width + 1;
+    }
+    let left = left.max(0);
+    let top = (ctx.mouse.cy + 1).min(layout.height - height).max(0);
+
+    for (dy, (line, color)) in lines.iter().enumerate() {
+        for dx in 0..width {
+            ctx.renderer.put_char(
+                left + dx,
+                top + dy as i32,
+                ' ',
+                *color,
+                Some(colors.background),
+            );
+        }
+        ctx.renderer.print(left + 1, top + dy as i32, line, *color);
+    }
+}
@@ -0,0 +1,122 @@
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::colors::*;
+
+use crate::fields::{add_field, FieldKind};
+use crate::game::{is_blocked, remove_object, Game, MAP_HEIGHT, MAP_WIDTH, PLAYER};
+use crate::lang::tr;
+use crate::object::Object;
+use crate::script::run_script;
+
+// How thick the gas a trap lets out is
+const TRAP_GAS: u32 = 9;
+// Damage taken landing on the level below
+const FALL_DAMAGE_MIN: i32 = 3;
+const FALL_DAMAGE_MAX: i32 = 10;
+
+// What happens to whoever steps on the trap
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum Trap {
+    // Lets out a cloud of poison gas, once
+    Gas,
+    // Drops them to the level below
+    Pit,
+    // Runs the script of the name for them, once
+    Scripted(String),
+}
+
+pub fn make_trap(trap: Trap, x: i32, y: i32) -> Object {
+    let mut object = match &trap {
+        Trap::Gas => Object::new(x, y, '^', &tr("trap.gas_trap", &[]), VIOLET, false),
+        Trap::Pit => Object::new(x, y, '^', &tr("trap.pit", &[]), DARK_GREY, false),
+        Trap::Scripted(name) => {
+            let title = tr("trap.scripted_trap", &[("name", &name.replace('_', " "))]);
+            Object::new(x, y, '^', &title, LIGHT_CYAN, false)
+        }
+    };
+    object.trap = Some(trap);
+    object
+}
+
+// Let the traps out under whoever stands on them
+pub fn spring_traps(game: &mut Game, objects: &mut Vec<Object>) {
+    let sprung = objects.iter().position(|trap| {
+        trap.trap.is_some()
+            && objects
+                .iter()
+                .any(|o| o.alive && o.fighter.is_some() && o.pos() == trap.pos())
+    });
+    let id = match sprung {
+        Some(id) => id,
+        None => return,
+    };
+    // Any other trap waits for the next turn
+    let (x, y) = objects[id].pos();
+    let on_player = objects[PLAYER].pos() == (x, y);
+    match objects[id].trap.clone() {
+        Some(Trap::Gas) => {
+            let trap = remove_object(id, game, objects);
+            if on_player {
+                game.messages
+                    .add(tr("trap.you_step_on_a", &[("trap", &trap.name)]), LIGHT_RED);
+            }
+            add_field(x, y, FieldKind::Gas, TRAP_GAS, game);
+        }
+        Some(Trap::Pit) if on_player => {
+            game.messages
+                .add(tr("trap.you_fall_into_the_pit", &[]), LIGHT_RED);
+            game.falling = true;
+        }
+        Some(Trap::Pit) => {
+            if let Some(victim) = objects
+                .iter()
+                .position(|o| o.alive && o.fighter.is_some() && o.pos() == (x, y))
+            {
+                let monster = remove_object(victim, game, objects);
+                game.fallen.push(monster);
+            }
+        }
+        Some(Trap::Scripted(name)) => {
+            let trap = remove_object(id, game, objects);
+            if on_player {
+                game.messages
+                    .add(tr("trap.you_step_on_a", &[("trap", &trap.name)]), LIGHT_RED);
+            }
+            if let Some(victim) = objects
+                .iter()
+                .position(|o| o.alive && o.fighter.is_some() && o.pos() == (x, y))
+            {
+                run_script(&name, victim, None, game, objects);
+            }
+        }
+        None => {}
+    }
+}
+
+// Nearest place to the spot that nothing stands in the way of, searching ever wider
+pub fn landing_tile(x: i32, y: i32, game: &Game, objects: &[Object]) -> Option<(i32, i32)> {
+    (0..MAP_WIDTH.max(MAP_HEIGHT)).find_map(|radius| {
+        (-radius..=radius)
+            .flat_map(|dx| (-radius..=radius).map(move |dy| (x + dx, y + dy)))
+            .filter(|&(nx, ny)| game.map.in_bounds(nx, ny))
+            .find(|&(nx, ny)| !is_blocked(nx, ny, &game.map, objects))
+    })
+}
+
+// Damage of a fall to the level below
+pub fn fall_damage(game: &mut Game) -> i32 {
+    game.rng.gen_range(FALL_DAMAGE_MIN, FALL_DAMAGE_MAX + 1)
+}
+
+// Put the monsters that fell from the level above where they fell to
+pub fn land_fallen(game: &mut Game, objects: &mut Vec<Object>) {
+    for mut monster in std::mem::take(&mut game.fallen) {
+        if let Some((x, y)) = landing_tile(monster.x, monster.y, game, objects) {
+            monster.set_pos(x, y);
+            let damage = fall_damage(game);
+            monster.take_damage(damage, false, game);
+            objects.push(monster);
+        }
+    }
+}